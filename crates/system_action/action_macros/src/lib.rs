@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{ItemFn, parse_macro_input};
+use syn::{ItemFn, LitInt, LitStr, parse_macro_input};
 
 /// # Macro - Generate Action
 ///
@@ -19,6 +19,17 @@ use syn::{ItemFn, parse_macro_input};
 /// }
 /// ```
 ///
+/// If the action needs no argument, the second parameter can be omitted,
+/// the generated `proc_` function then only takes the pool and the context
+///
+/// The following arguments are supported:
+///
+/// - `#[action_gen(local)]`: the action only runs locally
+/// - `#[action_gen(name = "your_action_v2")]`: overrides the action name used on the wire,
+///   the generated struct and `register_` / `proc_` functions keep the function's name
+/// - `#[action_gen(version = 2)]`: protocol version of the action, defaults to 1,
+///   the pool rejects callers sending a different version
+///
 /// > WARNING:
 /// > For Argument and Result types, the `action_gen` macro only supports types that derive serde's Serialize and Deserialize
 ///
@@ -54,27 +65,73 @@ use syn::{ItemFn, parse_macro_input};
 /// ```
 #[proc_macro_attribute]
 pub fn action_gen(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = ActionGenArgs::default();
+    let args_parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with args_parser);
+
     let input_fn = parse_macro_input!(item as ItemFn);
-    let is_local = if attr.is_empty() {
-        false
-    } else {
-        let attr_str = attr.to_string();
-        attr_str == "local" || attr_str.contains("local")
-    };
 
-    generate_action_struct(input_fn, is_local).into()
+    generate_action_struct(input_fn, args)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Arguments accepted by `#[action_gen(...)]`
+#[derive(Default)]
+struct ActionGenArgs {
+    /// `local`: the action only runs locally
+    is_local: bool,
+
+    /// `name = "..."`: overrides the action name used on the wire
+    name: Option<LitStr>,
+
+    /// `version = N`: protocol version of the action
+    version: Option<LitInt>,
+}
+
+impl ActionGenArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("local") {
+            self.is_local = true;
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("version") {
+            let version: LitInt = meta.value()?.parse()?;
+            version.base10_parse::<u32>()?;
+            self.version = Some(version);
+            Ok(())
+        } else {
+            Err(meta.error("Expected `local`, `name = \"...\"` or `version = N` for action_gen"))
+        }
+    }
 }
 
-fn generate_action_struct(input_fn: ItemFn, _is_local: bool) -> proc_macro2::TokenStream {
+fn generate_action_struct(
+    input_fn: ItemFn,
+    args: ActionGenArgs,
+) -> syn::Result<proc_macro2::TokenStream> {
     let fn_vis = &input_fn.vis;
     let fn_sig = &input_fn.sig;
     let fn_name = &fn_sig.ident;
     let fn_block = &input_fn.block;
+    let _is_local = args.is_local;
+
+    validate_function_signature(fn_sig)?;
 
-    validate_function_signature(fn_sig);
+    let (context_param_name, arg_param, return_type) = extract_parameters_and_types(fn_sig)?;
 
-    let (context_param_name, arg_param_name, arg_type, return_type) =
-        extract_parameters_and_types(fn_sig);
+    // Actions without an argument take `()`
+    let (arg_type, process_arg, proc_arg, proc_arg_value) = match arg_param {
+        Some((arg_param_name, arg_type)) => (
+            arg_type.clone(),
+            quote!(#arg_param_name: #arg_type),
+            quote!(, #arg_param_name: #arg_type),
+            arg_param_name,
+        ),
+        None => (quote!(()), quote!(_: ()), quote!(), quote!(())),
+    };
 
     let struct_name = quote::format_ident!("{}", convert_to_pascal_case(&fn_name.to_string()));
 
@@ -83,20 +140,39 @@ fn generate_action_struct(input_fn: ItemFn, _is_local: bool) -> proc_macro2::Tok
     let register_this_action = quote::format_ident!("register_{}", action_name_ident);
     let proc_this_action = quote::format_ident!("proc_{}", action_name_ident);
 
-    quote! {
+    // Name used on the wire, derived from the function name unless overridden
+    let action_name = match &args.name {
+        Some(name) => quote!(#name),
+        None => quote! {
+            Box::leak(string_proc::snake_case!(stringify!(#action_name_ident)).into_boxed_str())
+        },
+    };
+
+    // Version checked by the pool, the trait default is used unless given
+    let version_fn = args.version.as_ref().map(|version| {
+        quote! {
+            fn version() -> u32 {
+                #version
+            }
+        }
+    });
+
+    Ok(quote! {
         #[derive(Debug, Clone, Default)]
         #fn_vis struct #struct_name;
 
         impl action_system::action::Action<#arg_type, #return_type> for #struct_name {
             fn action_name() -> &'static str {
-                Box::leak(string_proc::snake_case!(stringify!(#action_name_ident)).into_boxed_str())
+                #action_name
             }
 
             fn is_remote_action() -> bool {
                 !#_is_local
             }
 
-            async fn process(#context_param_name: action_system::action::ActionContext, #arg_param_name: #arg_type) -> Result<#return_type, tcp_connection::error::TcpTargetError> {
+            #version_fn
+
+            async fn process(#context_param_name: action_system::action::ActionContext, #process_arg) -> Result<#return_type, tcp_connection::error::TcpTargetError> {
                 #fn_block
             }
         }
@@ -107,16 +183,16 @@ fn generate_action_struct(input_fn: ItemFn, _is_local: bool) -> proc_macro2::Tok
 
         #fn_vis async fn #proc_this_action(
             pool: &action_system::action_pool::ActionPool,
-            mut ctx: action_system::action::ActionContext,
-            #arg_param_name: #arg_type
+            mut ctx: action_system::action::ActionContext
+            #proc_arg
         ) -> Result<#return_type, tcp_connection::error::TcpTargetError> {
             ctx.set_is_remote_action(!#_is_local);
-            let args_json = serde_json::to_string(&#arg_param_name)
+            let args_json = serde_json::to_string(&#proc_arg_value)
                 .map_err(|e| {
                     tcp_connection::error::TcpTargetError::Serialization(e.to_string())
                 })?;
             let result_json = pool.process_json(
-                Box::leak(string_proc::snake_case!(stringify!(#action_name_ident)).into_boxed_str()),
+                #action_name,
                 ctx,
                 args_json,
             ).await?;
@@ -141,41 +217,93 @@ fn generate_action_struct(input_fn: ItemFn, _is_local: bool) -> proc_macro2::Tok
         #[doc = "let result = proc_your_func(&pool, ctx, arg).await?;"]
         #[doc = "```"]
         #fn_vis #fn_sig #fn_block
-    }
+    })
 }
 
-fn validate_function_signature(fn_sig: &syn::Signature) {
+fn validate_function_signature(fn_sig: &syn::Signature) -> syn::Result<()> {
     if fn_sig.asyncness.is_none() {
-        panic!("Expected async function for Action, but found synchronous function");
+        return Err(syn::Error::new_spanned(
+            fn_sig,
+            "Expected async function for Action, but found synchronous function",
+        ));
     }
 
-    if fn_sig.inputs.len() != 2 {
-        panic!(
-            "Expected exactly 2 arguments for Action function: ctx: ActionContext and arg: T, but found {} arguments",
-            fn_sig.inputs.len()
-        );
+    if fn_sig.inputs.is_empty() || fn_sig.inputs.len() > 2 {
+        return Err(syn::Error::new_spanned(
+            fn_sig,
+            format!(
+                "Expected 1 or 2 arguments for Action function: ctx: ActionContext and optionally arg: T, but found {} arguments",
+                fn_sig.inputs.len()
+            ),
+        ));
     }
 
     let return_type = match &fn_sig.output {
         syn::ReturnType::Type(_, ty) => ty,
-        _ => panic!(
-            "Expected Action function to return Result<T, TcpTargetError>, but found no return type"
-        ),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                fn_sig,
+                "Expected Action function to return Result<T, TcpTargetError>, but found no return type",
+            ));
+        }
     };
 
     if let syn::Type::Path(type_path) = return_type.as_ref() {
-        if let Some(segment) = type_path.path.segments.last()
-            && segment.ident != "Result"
-        {
-            panic!(
-                "Expected Action function to return Result<T, TcpTargetError>, but found different return type"
-            );
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident != "Result" {
+                return Err(syn::Error::new_spanned(
+                    return_type,
+                    "Expected Action function to return Result<T, TcpTargetError>, but found different return type",
+                ));
+            }
+            validate_result_error_type(segment)?;
         }
     } else {
-        panic!(
-            "Expected Action function to return Result<T, TcpTargetError>, but found no return type"
-        );
+        return Err(syn::Error::new_spanned(
+            return_type,
+            "Expected Action function to return Result<T, TcpTargetError>, but found different return type",
+        ));
     }
+
+    Ok(())
+}
+
+fn validate_result_error_type(segment: &syn::PathSegment) -> syn::Result<()> {
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Err(syn::Error::new_spanned(
+            segment,
+            "Expected Action function to return Result<T, TcpTargetError>, but found Result without type arguments",
+        ));
+    };
+
+    if args.args.len() != 2 {
+        return Err(syn::Error::new_spanned(
+            args,
+            format!(
+                "Expected Action function to return Result<T, TcpTargetError>, but found {} type arguments for Result",
+                args.args.len()
+            ),
+        ));
+    }
+
+    let error_type = &args.args[1];
+    let is_tcp_target_error = match error_type {
+        syn::GenericArgument::Type(syn::Type::Path(type_path)) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "TcpTargetError"),
+        _ => false,
+    };
+
+    if !is_tcp_target_error {
+        return Err(syn::Error::new_spanned(
+            error_type,
+            "Expected the error type of Action function to be TcpTargetError",
+        ));
+    }
+
+    Ok(())
 }
 
 fn convert_to_pascal_case(s: &str) -> String {
@@ -190,14 +318,15 @@ fn convert_to_pascal_case(s: &str) -> String {
         .collect()
 }
 
+type ParamAndType = (proc_macro2::TokenStream, proc_macro2::TokenStream);
+
 fn extract_parameters_and_types(
     fn_sig: &syn::Signature,
-) -> (
-    proc_macro2::TokenStream,
-    proc_macro2::TokenStream,
+) -> syn::Result<(
     proc_macro2::TokenStream,
+    Option<ParamAndType>,
     proc_macro2::TokenStream,
-) {
+)> {
     let mut inputs = fn_sig.inputs.iter();
 
     let context_param = match inputs.next() {
@@ -205,8 +334,11 @@ fn extract_parameters_and_types(
             let pat = &pat_type.pat;
             quote::quote!(#pat)
         }
-        _ => {
-            panic!("Expected the first argument to be a typed parameter, but found something else")
+        other => {
+            return Err(syn::Error::new_spanned(
+                other.map_or(quote::quote!(#fn_sig), |arg| quote::quote!(#arg)),
+                "Expected the first argument to be a typed parameter, but found something else",
+            ));
         }
     };
 
@@ -214,35 +346,54 @@ fn extract_parameters_and_types(
         Some(syn::FnArg::Typed(pat_type)) => {
             let pat = &pat_type.pat;
             let ty = &pat_type.ty;
-            (quote::quote!(#pat), quote::quote!(#ty))
+            Some((quote::quote!(#pat), quote::quote!(#ty)))
         }
-        _ => {
-            panic!("Expected the second argument to be a typed parameter, but found something else")
+        Some(other) => {
+            return Err(syn::Error::new_spanned(
+                other,
+                "Expected the second argument to be a typed parameter, but found something else",
+            ));
         }
+        None => None,
     };
 
-    let (arg_param_name, arg_type) = arg_param;
-
     let return_type = match &fn_sig.output {
         syn::ReturnType::Type(_, ty) => {
-            if let syn::Type::Path(type_path) = ty.as_ref() {
-                if let syn::PathArguments::AngleBracketed(args) =
-                    &type_path.path.segments.last().unwrap().arguments
-                {
-                    if let Some(syn::GenericArgument::Type(ty)) = args.args.first() {
-                        quote::quote!(#ty)
-                    } else {
-                        panic!("Expected to extract the success type of Result, but failed");
-                    }
-                } else {
-                    panic!("Expected Result type to have generic parameters, but found none");
+            let syn::Type::Path(type_path) = ty.as_ref() else {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "Expected return type to be Result, but found different type",
+                ));
+            };
+            let Some(segment) = type_path.path.segments.last() else {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "Expected return type to be Result, but found different type",
+                ));
+            };
+            let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "Expected Result type to have generic parameters, but found none",
+                ));
+            };
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => quote::quote!(#ty),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        args,
+                        "Expected to extract the success type of Result, but failed",
+                    ));
                 }
-            } else {
-                panic!("Expected return type to be Result, but found different type");
             }
         }
-        _ => panic!("Expected function to have return type, but found none"),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                fn_sig,
+                "Expected function to have return type, but found none",
+            ));
+        }
     };
 
-    (context_param, arg_param_name, arg_type, return_type)
+    Ok((context_param, arg_param, return_type))
 }
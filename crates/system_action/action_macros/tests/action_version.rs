@@ -0,0 +1,63 @@
+use action_system::{
+    action::{Action, ActionContext},
+    action_pool::ActionPool,
+    macros::action_gen,
+};
+use serde::{Deserialize, Serialize};
+use tcp_connection::error::TcpTargetError;
+
+#[derive(Serialize, Deserialize)]
+struct RenameArgs {
+    from: String,
+    to: String,
+    keep_history: bool,
+}
+
+#[action_gen(version = 2)]
+async fn rename_action(ctx: ActionContext, arg: RenameArgs) -> Result<String, TcpTargetError> {
+    assert_eq!(ctx.action_version(), 2);
+    Ok(format!("{} -> {} ({})", arg.from, arg.to, arg.keep_history))
+}
+
+#[action_gen]
+async fn ping_action(ctx: ActionContext) -> Result<u32, TcpTargetError> {
+    Ok(ctx.action_version())
+}
+
+#[tokio::test]
+async fn test_action_version_mismatch() -> Result<(), TcpTargetError> {
+    assert_eq!(RenameAction::version(), 2);
+    assert_eq!(PingAction::version(), 1);
+
+    let mut pool = ActionPool::new();
+    register_rename_action(&mut pool);
+    register_ping_action(&mut pool);
+    assert_eq!(pool.action_version("rename_action"), Some(2));
+    assert_eq!(pool.action_version("ping_action"), Some(1));
+
+    // A version 1 client sends the old argument layout, rejected before deserializing
+    let old_args = "[\"a.txt\",\"b.txt\"]".to_string();
+    let result = pool
+        .process_json_versioned("rename_action", 1, ActionContext::local(), old_args)
+        .await;
+    assert!(matches!(result, Err(TcpTargetError::VersionMismatch(_))));
+
+    let args = serde_json::to_string(&RenameArgs {
+        from: "a.txt".to_string(),
+        to: "b.txt".to_string(),
+        keep_history: true,
+    })
+    .unwrap();
+    let result = pool
+        .process_json_versioned("rename_action", 2, ActionContext::local(), args)
+        .await?;
+    assert_eq!(result, "\"a.txt -> b.txt (true)\"");
+
+    // Unversioned actions are version 1
+    let result = pool
+        .process_json_versioned("ping_action", 1, ActionContext::local(), "null".to_string())
+        .await?;
+    assert_eq!(result, "1");
+
+    Ok(())
+}
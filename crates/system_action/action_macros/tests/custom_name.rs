@@ -0,0 +1,57 @@
+use action_system::{
+    action::{Action, ActionContext},
+    action_pool::ActionPool,
+    macros::action_gen,
+};
+use tcp_connection::error::TcpTargetError;
+
+#[action_gen(name = "echo_v2")]
+async fn echo_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(format!("echo: {}", arg))
+}
+
+#[action_gen(local)]
+async fn local_echo_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+#[tokio::test]
+async fn test_custom_action_name() -> Result<(), TcpTargetError> {
+    // Struct and helpers keep the function's name, the wire name is overridden
+    assert_eq!(EchoAction::action_name(), "echo_v2");
+    assert!(EchoAction::is_remote_action());
+
+    let mut pool = ActionPool::new();
+    register_echo_action(&mut pool);
+
+    let result = proc_echo_action(&pool, ActionContext::local(), "hello".to_string()).await?;
+    assert_eq!(result, "echo: hello");
+
+    // The function name is not registered
+    let by_fn_name = pool
+        .process_json(
+            "echo_action",
+            ActionContext::local(),
+            "\"hello\"".to_string(),
+        )
+        .await;
+    assert!(by_fn_name.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_local_action_keeps_default_name() -> Result<(), TcpTargetError> {
+    assert_eq!(LocalEchoAction::action_name(), "local_echo_action");
+    assert!(!LocalEchoAction::is_remote_action());
+
+    let mut pool = ActionPool::new();
+    register_local_echo_action(&mut pool);
+
+    let result = proc_local_echo_action(&pool, ActionContext::local(), "hi".to_string()).await?;
+    assert_eq!(result, "hi");
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use action_system::{
+    action::{Action, ActionContext},
+    action_pool::ActionPool,
+    macros::action_gen,
+};
+use tcp_connection::error::TcpTargetError;
+
+static DELETE_BODY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[action_gen]
+async fn delete_everything_action(
+    ctx: ActionContext,
+    arg: String,
+) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    DELETE_BODY_RUNS.fetch_add(1, Ordering::SeqCst);
+    Ok(format!("deleted: {}", arg))
+}
+
+#[action_gen]
+async fn greet_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(format!("hello, {}", arg))
+}
+
+#[tokio::test]
+async fn test_interceptor_blocks_named_action() -> Result<(), TcpTargetError> {
+    let mut pool = ActionPool::new();
+    register_delete_everything_action(&mut pool);
+    register_greet_action(&mut pool);
+
+    let seen = Arc::new(AtomicUsize::new(0));
+    let seen_by_interceptor = seen.clone();
+    pool.add_interceptor(Box::new(move |_, _| {
+        seen_by_interceptor.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }));
+    pool.add_interceptor(Box::new(|action_name, _| {
+        if action_name == DeleteEverythingAction::action_name() {
+            return Err(TcpTargetError::Authentication(format!(
+                "Action {} is blocked",
+                action_name
+            )));
+        }
+        Ok(())
+    }));
+
+    // The blocked action is rejected before its body runs
+    let blocked =
+        proc_delete_everything_action(&pool, ActionContext::local(), "all".to_string()).await;
+    assert!(matches!(blocked, Err(TcpTargetError::Authentication(_))));
+
+    let blocked_json = pool
+        .process_json(
+            DeleteEverythingAction::action_name(),
+            ActionContext::local(),
+            "\"all\"".to_string(),
+        )
+        .await;
+    assert!(matches!(
+        blocked_json,
+        Err(TcpTargetError::Authentication(_))
+    ));
+    assert_eq!(DELETE_BODY_RUNS.load(Ordering::SeqCst), 0);
+
+    // Other actions pass through every interceptor
+    let result = proc_greet_action(&pool, ActionContext::local(), "vault".to_string()).await?;
+    assert_eq!(result, "hello, vault");
+    assert_eq!(seen.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
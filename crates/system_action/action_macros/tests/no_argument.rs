@@ -0,0 +1,32 @@
+use action_system::{
+    action::{Action, ActionContext},
+    action_pool::ActionPool,
+    macros::action_gen,
+};
+use tcp_connection::error::TcpTargetError;
+
+#[action_gen]
+async fn ping_action(ctx: ActionContext) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok("pong".to_string())
+}
+
+#[tokio::test]
+async fn test_no_argument_action() -> Result<(), TcpTargetError> {
+    assert_eq!(PingAction::action_name(), "ping_action");
+
+    let mut pool = ActionPool::new();
+    register_ping_action(&mut pool);
+
+    // Generated proc function takes no argument
+    let result = proc_ping_action(&pool, ActionContext::local()).await?;
+    assert_eq!(result, "pong");
+
+    // Unit argument goes through the pool as JSON `null`
+    let result_json = pool
+        .process_json("ping_action", ActionContext::local(), "null".to_string())
+        .await?;
+    assert_eq!(result_json, "\"pong\"");
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use action_system::{action::ActionContext, action_pool::ActionPool, macros::action_gen};
+use tcp_connection::error::TcpTargetError;
+
+#[action_gen]
+async fn slow_action(ctx: ActionContext, millis: u64) -> Result<u64, TcpTargetError> {
+    let _ = ctx;
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    Ok(millis)
+}
+
+#[tokio::test]
+async fn test_process_json_with_timeout() -> Result<(), TcpTargetError> {
+    let mut pool = ActionPool::new();
+    register_slow_action(&mut pool);
+
+    let result = pool
+        .process_json_with_timeout(
+            "slow_action",
+            ActionContext::local(),
+            "5000".to_string(),
+            Duration::from_millis(50),
+        )
+        .await;
+    assert!(matches!(result, Err(TcpTargetError::Timeout(_))));
+
+    let result = pool
+        .process_json_with_timeout(
+            "slow_action",
+            ActionContext::local(),
+            "1".to_string(),
+            Duration::from_secs(5),
+        )
+        .await?;
+    assert_eq!(result, "1");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_process_json_default_timeout() -> Result<(), TcpTargetError> {
+    let mut pool = ActionPool::new();
+    register_slow_action(&mut pool);
+    assert_eq!(pool.default_timeout(), None);
+
+    pool.set_default_timeout(Some(Duration::from_millis(50)));
+    let result = pool
+        .process_json("slow_action", ActionContext::local(), "5000".to_string())
+        .await;
+    assert!(matches!(result, Err(TcpTargetError::Timeout(_))));
+
+    pool.set_default_timeout(None);
+    let result = pool
+        .process_json("slow_action", ActionContext::local(), "100".to_string())
+        .await?;
+    assert_eq!(result, "100");
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use action_system::{
+    action::{Action, ActionContext},
+    action_pool::ActionPool,
+    macros::action_gen,
+};
+use tcp_connection::error::TcpTargetError;
+
+#[action_gen]
+async fn list_sheets_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+#[action_gen(name = "fetch_file_v2")]
+async fn fetch_file_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+#[tokio::test]
+async fn test_registered_actions() {
+    let mut pool = ActionPool::new();
+    assert!(pool.registered_actions().is_empty());
+
+    register_list_sheets_action(&mut pool);
+    register_fetch_file_action(&mut pool);
+
+    assert_eq!(
+        pool.registered_actions(),
+        vec![
+            FetchFileAction::action_name(),
+            ListSheetsAction::action_name()
+        ]
+    );
+    assert_eq!(
+        pool.registered_actions(),
+        vec!["fetch_file_v2", "list_sheets_action"]
+    );
+
+    assert!(pool.contains("list_sheets_action"));
+    assert!(pool.contains("fetch_file_v2"));
+    assert!(!pool.contains("fetch_file_action"));
+}
+
+/// Same wire name as `list_sheets_action`
+#[action_gen(name = "list_sheets_action")]
+async fn list_sheets_v2_action(ctx: ActionContext, arg: String) -> Result<String, TcpTargetError> {
+    let _ = ctx;
+    Ok(format!("v2: {}", arg))
+}
+
+#[tokio::test]
+#[should_panic(expected = "Action `list_sheets_action` is already registered")]
+async fn test_duplicate_registration_rejected() {
+    let mut pool = ActionPool::new();
+    register_list_sheets_action(&mut pool);
+    register_list_sheets_v2_action(&mut pool);
+}
+
+#[tokio::test]
+async fn test_register_or_replace() -> Result<(), TcpTargetError> {
+    let mut pool = ActionPool::new();
+    register_list_sheets_action(&mut pool);
+    pool.register_or_replace::<ListSheetsV2Action, String, String>();
+
+    assert_eq!(pool.registered_actions(), vec!["list_sheets_action"]);
+    let result = proc_list_sheets_action(&pool, ActionContext::local(), "main".to_string()).await?;
+    assert_eq!(result, "v2: main");
+
+    Ok(())
+}
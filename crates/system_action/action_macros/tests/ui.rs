@@ -0,0 +1,5 @@
+#[test]
+fn action_gen_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
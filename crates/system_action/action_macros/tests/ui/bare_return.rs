@@ -0,0 +1,9 @@
+use action_system::macros::action_gen;
+
+#[action_gen]
+async fn bare_return_action(ctx: action_system::action::ActionContext, arg: String) -> String {
+    let _ = ctx;
+    arg
+}
+
+fn main() {}
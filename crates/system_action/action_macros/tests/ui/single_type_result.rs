@@ -0,0 +1,11 @@
+use action_system::macros::action_gen;
+
+type Result<T> = std::result::Result<T, tcp_connection::error::TcpTargetError>;
+
+#[action_gen]
+async fn single_type_result_action(ctx: action_system::action::ActionContext, arg: String) -> Result<String> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+fn main() {}
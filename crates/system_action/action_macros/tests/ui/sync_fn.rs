@@ -0,0 +1,9 @@
+use action_system::macros::action_gen;
+
+#[action_gen]
+fn sync_action(ctx: action_system::action::ActionContext, arg: String) -> Result<String, tcp_connection::error::TcpTargetError> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use action_system::macros::action_gen;
+
+#[action_gen]
+async fn three_arguments_action(ctx: action_system::action::ActionContext, arg: String, extra: String) -> Result<String, tcp_connection::error::TcpTargetError> {
+    let _ = ctx;
+    Ok(arg + &extra)
+}
+
+fn main() {}
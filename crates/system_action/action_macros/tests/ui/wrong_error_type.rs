@@ -0,0 +1,9 @@
+use action_system::macros::action_gen;
+
+#[action_gen]
+async fn wrong_error_type_action(ctx: action_system::action::ActionContext, arg: String) -> Result<String, std::io::Error> {
+    let _ = ctx;
+    Ok(arg)
+}
+
+fn main() {}
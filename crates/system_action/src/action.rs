@@ -32,6 +32,9 @@ use tokio::{net::TcpStream, sync::Mutex};
 ///     /// Whether it's a local Action, used to inform the system if it only runs locally
 ///     fn is_remote_action() -> bool;
 ///
+///     /// Protocol version of the arguments and result, checked against the caller's version
+///     fn version() -> u32 { 1 }
+///
 ///     /// Action processing logic
 ///     fn process(
 ///         context: ActionContext,
@@ -48,6 +51,10 @@ where
 
     fn is_remote_action() -> bool;
 
+    fn version() -> u32 {
+        1
+    }
+
     fn process(
         context: ActionContext,
         args: Args,
@@ -87,6 +94,9 @@ pub struct ActionContext {
     /// The name of the action being executed
     action_name: String,
 
+    /// The protocol version of the action being executed
+    action_version: u32,
+
     /// The JSON-serialized arguments for the action
     action_args_json: String,
 
@@ -168,6 +178,11 @@ impl ActionContext {
         &self.action_name
     }
 
+    /// Get the action version from the context
+    pub fn action_version(&self) -> u32 {
+        self.action_version
+    }
+
     /// Get the action arguments from the context
     pub fn action_args_json(&self) -> &String {
         &self.action_args_json
@@ -179,6 +194,12 @@ impl ActionContext {
         self
     }
 
+    /// Set the action version in the context
+    pub fn set_action_version(mut self, action_version: u32) -> Self {
+        self.action_version = action_version;
+        self
+    }
+
     /// Set the action arguments in the context
     pub fn set_action_args(mut self, action_args: String) -> Self {
         self.action_args_json = action_args;
@@ -1,4 +1,4 @@
-use std::pin::Pin;
+use std::{pin::Pin, time::Duration};
 
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json;
@@ -12,6 +12,11 @@ type ProcBeginCallback = for<'a> fn(
 ) -> ProcBeginFuture<'a>;
 type ProcEndCallback = fn() -> ProcEndFuture;
 
+/// Interceptor invoked with the action name and context before an action runs,
+/// returning an error rejects the action
+pub type ActionInterceptor =
+    Box<dyn Fn(&str, &ActionContext) -> Result<(), TcpTargetError> + Send + Sync>;
+
 type ProcBeginFuture<'a> = Pin<Box<dyn Future<Output = Result<(), TcpTargetError>> + Send + 'a>>;
 type ProcEndFuture = Pin<Box<dyn Future<Output = Result<(), TcpTargetError>> + Send>>;
 
@@ -20,7 +25,7 @@ type ProcEndFuture = Pin<Box<dyn Future<Output = Result<(), TcpTargetError>> + S
 /// This struct is used to register and record all accessible and executable actions
 ///
 /// It also registers `on_proc_begin` and `on_proc_end` callback functions
-/// used for action initialization
+/// used for action initialization, and interceptors for checks shared by all actions
 ///
 /// ## Creating and registering actions
 /// ```ignore
@@ -43,6 +48,12 @@ pub struct ActionPool {
 
     /// Callback to execute when process ends
     on_proc_end: Option<ProcEndCallback>,
+
+    /// Interceptors executed in order before each action
+    interceptors: Vec<ActionInterceptor>,
+
+    /// Timeout applied by `process_json`, no timeout if `None`
+    default_timeout: Option<Duration>,
 }
 
 impl Default for ActionPool {
@@ -58,6 +69,8 @@ impl ActionPool {
             actions: std::collections::HashMap::new(),
             on_proc_begin: None,
             on_proc_end: None,
+            interceptors: Vec::new(),
+            default_timeout: None,
         }
     }
 
@@ -71,13 +84,68 @@ impl ActionPool {
         self.on_proc_end = Some(callback);
     }
 
+    /// Adds an interceptor executed before each action
+    ///
+    /// Interceptors run in the order they were added, after `on_proc_begin`,
+    /// the first error rejects the action without running its body
+    ///
+    /// Usage:
+    /// ```ignore
+    /// action_pool.add_interceptor(Box::new(|action_name, ctx| {
+    ///     if action_name == "dangerous_action" && ctx.is_proc_on_remote() {
+    ///         return Err(TcpTargetError::Authentication("Not allowed".to_string()));
+    ///     }
+    ///     Ok(())
+    /// }));
+    /// ```
+    pub fn add_interceptor(&mut self, interceptor: ActionInterceptor) {
+        self.interceptors.push(interceptor);
+    }
+
+    /// Sets the timeout `process_json` applies to every action, `None` to wait forever
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    /// Gets the timeout `process_json` applies to every action
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
     /// Registers an action type with the pool
     ///
     /// Usage:
     /// ```ignore
     /// action_pool.register::<MyAction, MyArgs, MyReturn>();
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an action with the same name is already registered,
+    /// use `register_or_replace` to override it on purpose
     pub fn register<A, Args, Return>(&mut self)
+    where
+        A: Action<Args, Return> + Send + Sync + 'static,
+        Args: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+        Return: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let action_name = A::action_name();
+        if self.actions.contains_key(action_name) {
+            panic!(
+                "Action `{}` is already registered, rename one of the actions or use `register_or_replace`",
+                action_name
+            );
+        }
+        self.register_or_replace::<A, Args, Return>();
+    }
+
+    /// Registers an action type with the pool, replacing any action with the same name
+    ///
+    /// Usage:
+    /// ```ignore
+    /// action_pool.register_or_replace::<MyAction, MyArgs, MyReturn>();
+    /// ```
+    pub fn register_or_replace<A, Args, Return>(&mut self)
     where
         A: Action<Args, Return> + Send + Sync + 'static,
         Args: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
@@ -90,6 +158,25 @@ impl ActionPool {
         );
     }
 
+    /// Returns the names of all registered actions, sorted
+    ///
+    /// These are the names accepted by `process_json`
+    pub fn registered_actions(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.actions.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Checks whether an action with the given name is registered
+    pub fn contains(&self, action_name: &str) -> bool {
+        self.actions.contains_key(action_name)
+    }
+
+    /// Returns the protocol version of a registered action
+    pub fn action_version(&self, action_name: &str) -> Option<u32> {
+        self.actions.get(action_name).map(|action| action.version())
+    }
+
     /// Processes an action by name with given context and arguments
     ///
     /// Usage:
@@ -98,6 +185,8 @@ impl ActionPool {
     /// ```
     /// Processes an action by name with JSON-serialized arguments
     ///
+    /// Fails with `TcpTargetError::Timeout` if the default timeout is set and exceeded
+    ///
     /// Usage:
     /// ```ignore
     /// let result_json = action_pool.process_json("my_action", context, args_json).await?;
@@ -108,13 +197,86 @@ impl ActionPool {
         action_name: &'a str,
         context: ActionContext,
         args_json: String,
+    ) -> Result<String, TcpTargetError> {
+        match self.default_timeout {
+            Some(timeout) => {
+                self.process_json_with_timeout(action_name, context, args_json, timeout)
+                    .await
+            }
+            None => self.dispatch_json(action_name, context, args_json).await,
+        }
+    }
+
+    /// Processes an action by name with JSON-serialized arguments sent by a peer using `version`
+    ///
+    /// Fails with `TcpTargetError::VersionMismatch` before deserializing the arguments
+    /// if the registered action has a different version
+    ///
+    /// Usage:
+    /// ```ignore
+    /// let result_json = action_pool
+    ///     .process_json_versioned(&msg.action_name, msg.action_version, context, args_json)
+    ///     .await?;
+    /// ```
+    pub async fn process_json_versioned<'a>(
+        &'a self,
+        action_name: &'a str,
+        version: u32,
+        context: ActionContext,
+        args_json: String,
+    ) -> Result<String, TcpTargetError> {
+        if let Some(expected) = self.action_version(action_name)
+            && expected != version
+        {
+            return Err(TcpTargetError::VersionMismatch(format!(
+                "Action {} expects version {}, but version {} was sent",
+                action_name, expected, version
+            )));
+        }
+        self.process_json(action_name, context, args_json).await
+    }
+
+    /// Processes an action by name with JSON-serialized arguments,
+    /// failing with `TcpTargetError::Timeout` if it does not complete in time
+    ///
+    /// Usage:
+    /// ```ignore
+    /// let result_json = action_pool
+    ///     .process_json_with_timeout("my_action", context, args_json, Duration::from_secs(30))
+    ///     .await?;
+    /// ```
+    pub async fn process_json_with_timeout<'a>(
+        &'a self,
+        action_name: &'a str,
+        context: ActionContext,
+        args_json: String,
+        timeout: Duration,
+    ) -> Result<String, TcpTargetError> {
+        tokio::time::timeout(timeout, self.dispatch_json(action_name, context, args_json))
+            .await
+            .map_err(|_| {
+                TcpTargetError::Timeout(format!(
+                    "Action {} did not complete within {:?}",
+                    action_name, timeout
+                ))
+            })?
+    }
+
+    /// Dispatches an action by name with JSON-serialized arguments
+    async fn dispatch_json<'a>(
+        &'a self,
+        action_name: &'a str,
+        context: ActionContext,
+        args_json: String,
     ) -> Result<String, TcpTargetError> {
         if let Some(action) = self.actions.get(action_name) {
-            // Set action name and args in context for callbacks
+            // Set action name, version and args in context for callbacks
             let context = context.set_action_name(action_name.to_string());
+            let context = context.set_action_version(action.version());
             let mut context = context.set_action_args(args_json.clone());
 
             self.exec_on_proc_begin(&mut context, &args_json).await?;
+            self.exec_interceptors(action_name, &context)?;
             let result = action.process_json_erased(context, args_json).await?;
             self.exec_on_proc_end().await?;
             Ok(result)
@@ -141,6 +303,7 @@ impl ActionPool {
     {
         if let Some(action) = self.actions.get(action_name) {
             self.exec_on_proc_begin(&mut context, &args).await?;
+            self.exec_interceptors(action_name, &context)?;
             let result = action.process_erased(context, Box::new(args)).await?;
             let result = *result
                 .downcast::<Return>()
@@ -165,6 +328,17 @@ impl ActionPool {
         }
     }
 
+    /// Executes the interceptors in order, stopping at the first error
+    fn exec_interceptors(
+        &self,
+        action_name: &str,
+        context: &ActionContext,
+    ) -> Result<(), TcpTargetError> {
+        self.interceptors
+            .iter()
+            .try_for_each(|interceptor| interceptor(action_name, context))
+    }
+
     /// Executes the process end callback if set
     async fn exec_on_proc_end(&self) -> Result<(), TcpTargetError> {
         if let Some(callback) = &self.on_proc_end {
@@ -186,6 +360,9 @@ type ProcessJsonErasedFuture =
     std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, TcpTargetError>> + Send>>;
 
 trait ActionErased: Send + Sync {
+    /// Protocol version of the action
+    fn version(&self) -> u32;
+
     /// Processes the action with type-erased arguments and returns type-erased result
     fn process_erased(
         &self,
@@ -210,6 +387,10 @@ where
     Args: Serialize + DeserializeOwned + Send + Sync + 'static,
     Return: Serialize + DeserializeOwned + Send + Sync + 'static,
 {
+    fn version(&self) -> u32 {
+        A::version()
+    }
+
     fn process_erased(
         &self,
         context: ActionContext,
@@ -24,8 +24,17 @@ use syn::{Attribute, DeriveInput, Expr, parse_macro_input};
 ///
 /// Other paths: treated as absolute paths
 ///
+/// `$VAR` and `${VAR}` in string literal paths are replaced with environment variables
+/// when `default_path()` is evaluated, an undefined variable is reported as an error
+///
 /// When no path is specified: use the struct name + ".json" as the default filename (e.g., `my_struct.json`)
 ///
+/// ## Format
+///
+/// The serialization format is chosen by the extension of the path:
+/// `.toml`, `.json`, `.yaml` / `.yml` and `.ron` use the matching serde backend,
+/// other extensions are stored as Bincode, and a path without extension is rejected
+///
 /// ## Example
 /// ```ignore
 /// #[derive(ConfigFile)]
@@ -42,12 +51,12 @@ pub fn derive_config_file(input: TokenStream) -> TokenStream {
         Some(PathExpr::StringLiteral(path)) => {
             if let Some(path_str) = path.strip_prefix("./") {
                 quote! {
-                    std::env::current_dir()?.join(#path_str)
+                    std::env::current_dir()?.join(cfg_file::config::expand_env_vars(#path_str)?)
                 }
             } else {
                 // Using Absolute Path
                 quote! {
-                    std::path::PathBuf::from(#path)
+                    std::path::PathBuf::from(cfg_file::config::expand_env_vars(#path)?)
                 }
             }
         }
@@ -121,7 +130,7 @@ fn to_snake_case(s: &str) -> String {
             if i != 0 {
                 snake.push('_');
             }
-            snake.push(c.to_ascii_lowercase());
+            snake.extend(c.to_lowercase());
         } else {
             snake.push(c);
         }
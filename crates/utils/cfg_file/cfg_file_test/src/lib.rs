@@ -2,6 +2,7 @@
 mod test_cfg_file {
     use cfg_file::ConfigFile;
     use cfg_file::config::ConfigFile;
+    use cfg_file::error::ConfigError;
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
 
@@ -92,4 +93,253 @@ mod test_cfg_file {
         assert_eq!(read_cfg.secret["No comments"], secret_no_comments);
         assert_eq!(read_cfg.secret["Peek"], secret_peek);
     }
+
+    #[derive(ConfigFile, Deserialize, Serialize, Default)]
+    #[cfg_file(path = "./.temp/try_read/missing_cfg.toml")]
+    struct MissingConfig {
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_try_read_not_found() {
+        let _ = std::fs::remove_file("./.temp/try_read/missing_cfg.toml");
+
+        let result = MissingConfig::try_read().await;
+        assert!(matches!(result, Err(ConfigError::NotFound)));
+
+        // The convenience read falls back to default
+        let read_cfg = MissingConfig::read_or_default().await;
+        assert_eq!(read_cfg.name, "");
+    }
+
+    #[tokio::test]
+    async fn test_try_read_parse_error() {
+        let path = "./.temp/try_read/corrupt_cfg.toml";
+        std::fs::create_dir_all("./.temp/try_read").unwrap();
+        std::fs::write(path, "name = [this is not toml").unwrap();
+
+        let result = ExampleConfig::try_read_from(path).await;
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+
+        // The io variant keeps reporting corrupt data
+        let result = ExampleConfig::read_from(path).await;
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_read_io_error() {
+        // A directory exists, but cannot be read as a file
+        let path = "./.temp/try_read/dir_cfg.toml";
+        std::fs::create_dir_all(path).unwrap();
+
+        let result = ExampleConfig::try_read_from(path).await;
+        assert!(matches!(result, Err(ConfigError::Io(_))));
+    }
+
+    #[derive(ConfigFile, Deserialize, Serialize, Default, Debug, PartialEq)]
+    struct FormatConfig {
+        name: String,
+        age: i32,
+        hobby: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_format_by_extension() {
+        let example = FormatConfig {
+            name: "Weicao".to_string(),
+            age: 22,
+            hobby: vec!["Programming".to_string(), "Painting".to_string()],
+        };
+
+        let dir = std::path::PathBuf::from("./.temp/format");
+        let cases = [
+            ("format_cfg.toml", "name = \"Weicao\""),
+            ("format_cfg.json", "\"name\":\"Weicao\""),
+            ("format_cfg.yaml", "name: Weicao"),
+        ];
+
+        for (file_name, expected_text) in cases {
+            let path = dir.join(file_name);
+            FormatConfig::write_to(&example, &path).await.unwrap();
+
+            // Each extension is written with its own backend
+            let contents = std::fs::read_to_string(&path).unwrap();
+            assert!(
+                contents.contains(expected_text),
+                "{} should contain `{}`, but got:\n{}",
+                file_name,
+                expected_text,
+                contents
+            );
+
+            let read_cfg = FormatConfig::read_from(&path).await.unwrap();
+            assert_eq!(read_cfg, example);
+        }
+
+        // Default path of a struct without `cfg_file` attribute
+        let default_path = FormatConfig::default_path().unwrap();
+        assert!(default_path.ends_with("format_config.json"));
+    }
+
+    #[tokio::test]
+    async fn test_format_without_extension() {
+        let path = "./.temp/format/no_extension";
+        let _ = std::fs::remove_file(path);
+
+        let result = FormatConfig::write_to(&FormatConfig::default(), path).await;
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::InvalidInput)
+        );
+        assert!(!std::path::Path::new(path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_leaves_no_temp_and_ignores_stale_temp() {
+        let dir = std::path::PathBuf::from("./.temp/atomic");
+        let path = dir.join("atomic_cfg.toml");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let example = FormatConfig {
+            name: "Weicao".to_string(),
+            age: 22,
+            hobby: vec!["Programming".to_string()],
+        };
+        FormatConfig::write_to(&example, &path).await.unwrap();
+
+        // Simulate a writer killed mid-persist
+        let stale_temp = dir.join(".atomic_cfg.toml.tmp");
+        std::fs::write(&stale_temp, "name = \"Trunc").unwrap();
+
+        // The real file is untouched
+        let read_cfg = FormatConfig::read_from(&path).await.unwrap();
+        assert_eq!(read_cfg, example);
+
+        // Later writes still succeed and do not leave their own temp files
+        let updated = FormatConfig { age: 23, ..example };
+        FormatConfig::write_to(&updated, &path).await.unwrap();
+        let read_cfg = FormatConfig::read_from(&path).await.unwrap();
+        assert_eq!(read_cfg, updated);
+
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect();
+        assert_eq!(leftovers, vec![".atomic_cfg.toml.tmp".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_do_not_collide() {
+        let path = std::path::PathBuf::from("./.temp/atomic_concurrent/cfg.json");
+
+        let tasks: Vec<_> = (0..8)
+            .map(|age| {
+                let path = path.clone();
+                tokio::spawn(async move {
+                    let data = FormatConfig {
+                        age,
+                        ..Default::default()
+                    };
+                    FormatConfig::write_to(&data, &path).await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        // The file holds one complete write
+        let read_cfg = FormatConfig::read_from(&path).await.unwrap();
+        assert!((0..8).contains(&read_cfg.age));
+    }
+
+    #[derive(ConfigFile, Deserialize, Serialize, Default)]
+    #[cfg_file(path = "$CFG_FILE_TEST_HOME/vault.toml")]
+    struct EnvHomeConfig {
+        name: String,
+    }
+
+    #[derive(ConfigFile, Deserialize, Serialize, Default)]
+    #[cfg_file(path = "./${CFG_FILE_TEST_SUBDIR}/vault.toml")]
+    struct EnvRelativeConfig {
+        name: String,
+    }
+
+    #[derive(ConfigFile, Deserialize, Serialize, Default)]
+    #[cfg_file(path = "$CFG_FILE_TEST_UNDEFINED/vault.toml")]
+    struct EnvUndefinedConfig {
+        name: String,
+    }
+
+    #[test]
+    fn test_env_var_path() {
+        // SAFETY: Variables are only used by these tests
+        unsafe {
+            std::env::set_var("CFG_FILE_TEST_HOME", "/srv/vcs");
+            std::env::set_var("CFG_FILE_TEST_SUBDIR", "env_subdir");
+        }
+
+        assert_eq!(
+            EnvHomeConfig::default_path().unwrap(),
+            std::path::PathBuf::from("/srv/vcs/vault.toml")
+        );
+        assert_eq!(
+            EnvRelativeConfig::default_path().unwrap(),
+            std::env::current_dir()
+                .unwrap()
+                .join("env_subdir/vault.toml")
+        );
+    }
+
+    #[test]
+    fn test_env_var_path_undefined() {
+        let result = EnvUndefinedConfig::default_path();
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_reload_debounce() {
+        let path = std::path::PathBuf::from("./.temp/watch/watch_cfg.toml");
+        let _ = std::fs::remove_file(&path);
+        FormatConfig::write_to(&FormatConfig::default(), &path)
+            .await
+            .unwrap();
+
+        let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        let watcher = tokio::spawn(FormatConfig::watch_reload_from(
+            path.clone(),
+            move |data: FormatConfig| received_clone.lock().unwrap().push(data.age),
+        ));
+
+        // Let the watcher record the initial state
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        // Two quick writes, like an editor saving
+        let first = FormatConfig {
+            age: 1,
+            ..Default::default()
+        };
+        FormatConfig::write_to(&first, &path).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let second = FormatConfig {
+            name: "Final".to_string(),
+            age: 2,
+            ..Default::default()
+        };
+        FormatConfig::write_to(&second, &path).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+        watcher.abort();
+
+        assert_eq!(*received.lock().unwrap(), vec![2]);
+    }
 }
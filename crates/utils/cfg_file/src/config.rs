@@ -2,13 +2,20 @@ use async_trait::async_trait;
 use bincode2;
 use ron;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
 use std::{
     borrow::Cow,
     env::current_dir,
     io::Error,
     path::{Path, PathBuf},
 };
-use tokio::{fs, io::AsyncReadExt};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+
+use crate::error::ConfigError;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConfigFormat {
@@ -20,6 +27,27 @@ enum ConfigFormat {
 }
 
 impl ConfigFormat {
+    /// Determine the format from the extension of the path
+    ///
+    /// Unknown extensions (e.g. `.st`, `.vf`) are stored as Bincode,
+    /// a path without any extension is rejected since its format cannot be inferred
+    fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let filename = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        if let Some(format) = Self::from_filename(filename) {
+            return Ok(format);
+        }
+        match path.extension() {
+            Some(_) => Ok(Self::Bincode),
+            None => Err(ConfigError::UnsupportedFormat(format!(
+                "Cannot determine config format of \"{}\" without a file extension",
+                path.display()
+            ))),
+        }
+    }
+
     fn from_filename(filename: &str) -> Option<Self> {
         if filename.ends_with(".yaml") || filename.ends_with(".yml") {
             Some(Self::Yaml)
@@ -37,6 +65,131 @@ impl ConfigFormat {
     }
 }
 
+/// Expand `$VAR` and `${VAR}` in the path with environment variables
+///
+/// Returns an error if a referenced variable is not defined
+pub fn expand_env_vars(path: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(path.len());
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        let var_name = if chars.peek() == Some(&'{') {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => name.push(c),
+                    None => {
+                        return Err(Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("Unclosed `${{` in path \"{}\"", path),
+                        ));
+                    }
+                }
+            }
+            name
+        } else {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    name.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            name
+        };
+
+        // A lone `$` is kept as is
+        if var_name.is_empty() {
+            result.push('$');
+            continue;
+        }
+
+        match std::env::var(&var_name) {
+            Ok(value) => result.push_str(&value),
+            Err(_) => {
+                return Err(Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!(
+                        "Environment variable `{}` in path \"{}\" is not defined",
+                        var_name, path
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Interval between checks of a watched file
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Changes of a watched file within this duration are coalesced
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Modification time and size of a file, `None` if it cannot be accessed
+async fn file_stamp(file_path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(file_path).await.ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Read the whole file as text
+async fn read_contents(file_path: &Path) -> Result<String, ConfigError> {
+    let mut file = fs::File::open(file_path).await?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).await?;
+    Ok(contents)
+}
+
+/// Counter making temp file names unique within the process
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Write the file through a sibling temp file and rename it into place,
+/// so an interrupted write never leaves a truncated file behind
+///
+/// Falls back to writing the target directly if the rename fails
+async fn write_atomic(file_path: &Path, contents: &[u8]) -> Result<(), std::io::Error> {
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = file_path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    // Write and sync the temp file
+    let write_result = async {
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await
+    }
+    .await;
+    if let Err(e) = write_result {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e);
+    }
+
+    // Replace the target file
+    if fs::rename(&temp_path, file_path).await.is_err() {
+        let _ = fs::remove_file(&temp_path).await;
+        fs::write(file_path, contents).await?;
+    }
+
+    Ok(())
+}
+
 /// # Trait - ConfigFile
 ///
 /// Used to implement more convenient persistent storage functionality for structs
@@ -100,6 +253,37 @@ pub trait ConfigFile: Serialize + for<'a> Deserialize<'a> + Default {
     /// }
     /// ```
     async fn read_from(path: impl AsRef<Path> + Send) -> Result<Self::DataType, std::io::Error>
+    where
+        Self: Sized + Send + Sync,
+    {
+        Ok(Self::try_read_from(path).await?)
+    }
+
+    /// # Try to read from default path
+    ///
+    /// Read data from the path specified by default_path(),
+    /// distinguishing a missing file from a corrupt one
+    ///
+    /// ```ignore
+    /// match YourData::try_read().await {
+    ///     Ok(data) => { /* Use data */ }
+    ///     Err(ConfigError::NotFound) => { /* First run */ }
+    ///     Err(e) => { /* Corrupt file or disk error */ }
+    /// }
+    /// ```
+    async fn try_read() -> Result<Self::DataType, ConfigError>
+    where
+        Self: Sized + Send + Sync,
+    {
+        let path = Self::default_path()?;
+        Self::try_read_from(path).await
+    }
+
+    /// # Try to read from the given path
+    ///
+    /// Read data from the path specified by the path parameter,
+    /// distinguishing a missing file from a corrupt one
+    async fn try_read_from(path: impl AsRef<Path> + Send) -> Result<Self::DataType, ConfigError>
     where
         Self: Sized + Send + Sync,
     {
@@ -108,61 +292,50 @@ pub trait ConfigFile: Serialize + for<'a> Deserialize<'a> + Default {
         let file_path = cwd.join(path);
 
         // Check if file exists
-        if fs::metadata(&file_path).await.is_err() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Config file not found",
-            ));
-        }
+        fs::metadata(&file_path).await?;
 
         // Determine file format first
-        let format = file_path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .and_then(ConfigFormat::from_filename)
-            .unwrap_or(ConfigFormat::Bincode); // Default to Bincode
+        let format = ConfigFormat::from_path(&file_path)?;
 
         // Deserialize based on format
         let result = match format {
             ConfigFormat::Yaml => {
-                let mut file = fs::File::open(&file_path).await?;
-                let mut contents = String::new();
-                file.read_to_string(&mut contents).await?;
-                serde_yaml::from_str(&contents)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                let contents = read_contents(&file_path).await?;
+                serde_yaml::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))?
             }
             ConfigFormat::Toml => {
-                let mut file = fs::File::open(&file_path).await?;
-                let mut contents = String::new();
-                file.read_to_string(&mut contents).await?;
-                toml::from_str(&contents)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                let contents = read_contents(&file_path).await?;
+                toml::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))?
             }
             ConfigFormat::Ron => {
-                let mut file = fs::File::open(&file_path).await?;
-                let mut contents = String::new();
-                file.read_to_string(&mut contents).await?;
-                ron::from_str(&contents)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                let contents = read_contents(&file_path).await?;
+                ron::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))?
             }
             ConfigFormat::Json => {
-                let mut file = fs::File::open(&file_path).await?;
-                let mut contents = String::new();
-                file.read_to_string(&mut contents).await?;
-                serde_json::from_str(&contents)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                let contents = read_contents(&file_path).await?;
+                serde_json::from_str(&contents).map_err(|e| ConfigError::Parse(e.to_string()))?
             }
             ConfigFormat::Bincode => {
                 // For Bincode, we need to read the file as bytes directly
                 let bytes = fs::read(&file_path).await?;
-                bincode2::deserialize(&bytes)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                bincode2::deserialize(&bytes).map_err(|e| ConfigError::Parse(e.to_string()))?
             }
         };
 
         Ok(result)
     }
 
+    /// # Read from default path, or default
+    ///
+    /// Read data from the path specified by default_path(),
+    /// returns the default value if the file is missing or cannot be read
+    async fn read_or_default() -> Self::DataType
+    where
+        Self: Sized + Send + Sync,
+    {
+        Self::try_read().await.unwrap_or_default()
+    }
+
     /// # Write to default path
     ///
     /// Write data to the path specified by default_path()
@@ -200,6 +373,9 @@ pub trait ConfigFile: Serialize + for<'a> Deserialize<'a> + Default {
     {
         let path = path.as_ref();
 
+        // Determine file format before touching the filesystem
+        let format = ConfigFormat::from_path(path)?;
+
         if let Some(parent) = path.parent()
             && !parent.exists()
         {
@@ -209,23 +385,16 @@ pub trait ConfigFile: Serialize + for<'a> Deserialize<'a> + Default {
         let cwd = current_dir()?;
         let file_path = cwd.join(path);
 
-        // Determine file format
-        let format = file_path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .and_then(ConfigFormat::from_filename)
-            .unwrap_or(ConfigFormat::Bincode); // Default to Bincode
-
         match format {
             ConfigFormat::Yaml => {
                 let contents = serde_yaml::to_string(val)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                fs::write(&file_path, contents).await?
+                write_atomic(&file_path, contents.as_bytes()).await?
             }
             ConfigFormat::Toml => {
                 let contents = toml::to_string(val)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                fs::write(&file_path, contents).await?
+                write_atomic(&file_path, contents.as_bytes()).await?
             }
             ConfigFormat::Ron => {
                 let mut pretty_config = ron::ser::PrettyConfig::new();
@@ -234,22 +403,86 @@ pub trait ConfigFile: Serialize + for<'a> Deserialize<'a> + Default {
 
                 let contents = ron::ser::to_string_pretty(val, pretty_config)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                fs::write(&file_path, contents).await?
+                write_atomic(&file_path, contents.as_bytes()).await?
             }
             ConfigFormat::Json => {
                 let contents = serde_json::to_string(val)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                fs::write(&file_path, contents).await?
+                write_atomic(&file_path, contents.as_bytes()).await?
             }
             ConfigFormat::Bincode => {
                 let bytes = bincode2::serialize(val)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                fs::write(&file_path, bytes).await?
+                write_atomic(&file_path, &bytes).await?
             }
         }
         Ok(())
     }
 
+    /// # Watch default path and reload
+    ///
+    /// Poll the file returned by `default_path()` and invoke `on_change`
+    /// with the freshly parsed data whenever its modification time changes
+    ///
+    /// This future never completes unless the path cannot be resolved,
+    /// spawn it as a task and abort the task to stop watching
+    ///
+    /// ```ignore
+    /// let watcher = tokio::spawn(YourData::watch_reload(|data| {
+    ///     // Use the reloaded data
+    /// }));
+    /// ```
+    async fn watch_reload(
+        on_change: impl FnMut(Self::DataType) + Send + 'static,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized + Send + Sync,
+    {
+        let path = Self::default_path()?;
+        Self::watch_reload_from(path, on_change).await
+    }
+
+    /// # Watch the given path and reload
+    ///
+    /// Same as `watch_reload`, but watches the path specified by the path parameter
+    ///
+    /// Successive writes within `WATCH_DEBOUNCE` are coalesced into a single callback
+    async fn watch_reload_from(
+        path: impl AsRef<Path> + Send,
+        mut on_change: impl FnMut(Self::DataType) + Send + 'static,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized + Send + Sync,
+    {
+        let file_path = current_dir()?.join(path.as_ref());
+        let mut last_stamp = file_stamp(&file_path).await;
+
+        loop {
+            tokio::time::sleep(WATCH_POLL_INTERVAL).await;
+
+            let mut stamp = file_stamp(&file_path).await;
+            if stamp == last_stamp {
+                continue;
+            }
+
+            // Wait until the file stops changing
+            loop {
+                tokio::time::sleep(WATCH_DEBOUNCE).await;
+                let settled = file_stamp(&file_path).await;
+                if settled == stamp {
+                    break;
+                }
+                stamp = settled;
+            }
+            last_stamp = stamp;
+
+            // Missing or unreadable files are skipped until the next change
+            if let Ok(data) = Self::try_read_from(&file_path).await {
+                on_change(data);
+            }
+        }
+    }
+
     /// Check if the file returned by `default_path` exists
     fn exist() -> bool
     where
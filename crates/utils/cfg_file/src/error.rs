@@ -0,0 +1,40 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("Config file not found")]
+    NotFound,
+
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => ConfigError::NotFound,
+            _ => ConfigError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<ConfigError> for std::io::Error {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::NotFound => {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Config file not found")
+            }
+            ConfigError::Parse(msg) => std::io::Error::new(std::io::ErrorKind::InvalidData, msg),
+            ConfigError::Io(msg) => std::io::Error::other(msg),
+            ConfigError::UnsupportedFormat(msg) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+            }
+        }
+    }
+}
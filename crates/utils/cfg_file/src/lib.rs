@@ -5,3 +5,4 @@ extern crate cfg_file_derive;
 pub use cfg_file_derive::*;
 
 pub mod config;
+pub mod error;
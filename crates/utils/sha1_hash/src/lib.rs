@@ -1,68 +1,243 @@
-use sha1::{Digest, Sha1};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, BufReader};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
 use tokio::task;
 
+pub use sha1::Sha1;
+
+/// BLAKE3 hasher, usable as a `HashBackend`
+pub type Blake3 = blake3::Hasher;
+
+/// # Trait - HashBackend
+///
+/// A digest algorithm that can be fed incrementally,
+/// used to make the file hashing functions generic over the algorithm
+pub trait HashBackend: Default + Send + 'static {
+    /// Raw digest bytes produced by `finalize`
+    type Output: AsRef<[u8]>;
+
+    /// Feed a chunk of data into the hasher
+    fn update(&mut self, data: &[u8]);
+
+    /// Consume the hasher and produce the digest
+    fn finalize(self) -> Self::Output;
+}
+
+impl HashBackend for Sha1 {
+    type Output = [u8; 20];
+
+    fn update(&mut self, data: &[u8]) {
+        sha1::Digest::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        sha1::Digest::finalize(self).into()
+    }
+}
+
+impl HashBackend for Blake3 {
+    type Output = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        *blake3::Hasher::finalize(&self).as_bytes()
+    }
+}
+
 /// # Struct - Sha1Result
 ///
-/// Records SHA1 calculation results, including the file path and hash value
+/// Records hash calculation results, including the file path and hash value
 #[derive(Debug, Clone)]
 pub struct Sha1Result {
     pub file_path: PathBuf,
     pub hash: String,
 }
 
-/// Calc SHA1 hash of a string
-pub fn calc_sha1_string<S: AsRef<str>>(input: S) -> String {
-    let mut hasher = Sha1::new();
-    hasher.update(input.as_ref().as_bytes());
-    let hash_result = hasher.finalize();
+/// # Enum - LineEndingMode
+///
+/// Controls how line endings are fed into the hasher
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndingMode {
+    /// Hash bytes as they are, required for binary files
+    #[default]
+    Raw,
+
+    /// Convert CRLF to LF before hashing, so text files hash identically across platforms
+    NormalizeLf,
+}
+
+/// Number of leading bytes inspected by `is_probably_text`
+const TEXT_DETECTION_BYTES: usize = 8000;
 
-    hash_result
+/// Check whether a file looks like text, by scanning its first bytes for a null byte
+///
+/// Binary assets (images, models...) almost always contain null bytes early on,
+/// use this before choosing `LineEndingMode::NormalizeLf` so binary files are hashed as they are
+pub async fn is_probably_text<P: AsRef<Path>>(path: P) -> Result<bool, std::io::Error> {
+    let mut file = File::open(path).await?;
+    let mut buffer = vec![0u8; TEXT_DETECTION_BYTES];
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let n = file.read(&mut buffer[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(!buffer[..filled].contains(&0))
+}
+
+/// Convert digest bytes to a lowercase hex string
+fn to_hex(bytes: impl AsRef<[u8]>) -> String {
+    bytes
+        .as_ref()
         .iter()
         .map(|b| format!("{:02x}", b))
         .collect::<String>()
 }
 
+/// Calc SHA1 hash of a string
+pub fn calc_sha1_string<S: AsRef<str>>(input: S) -> String {
+    calc_hash_string::<Sha1, S>(input)
+}
+
+/// Calc hash of a string with the given backend
+pub fn calc_hash_string<B: HashBackend, S: AsRef<str>>(input: S) -> String {
+    let mut hasher = B::default();
+    hasher.update(input.as_ref().as_bytes());
+    to_hex(hasher.finalize())
+}
+
+/// Read buffer size used by `calc_sha1_default`
+///
+/// Larger buffers mean fewer reads per file, but each concurrent hash holds its own buffer.
+/// 64 KiB keeps syscalls cheap on large files without much memory cost on many small ones
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Calc SHA1 hash of a single file, reading with `DEFAULT_BUFFER_SIZE`
+pub async fn calc_sha1_default<P: AsRef<Path>>(
+    path: P,
+) -> Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>> {
+    calc_sha1(path, DEFAULT_BUFFER_SIZE).await
+}
+
 /// Calc SHA1 hash of a single file
+///
+/// Prefer `calc_sha1_default` unless the buffer size needs tuning
 pub async fn calc_sha1<P: AsRef<Path>>(
     path: P,
     buffer_size: usize,
+) -> Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>> {
+    calc_hash::<Sha1, P>(path, buffer_size).await
+}
+
+/// Calc hash of a single file with the given backend
+pub async fn calc_hash<B: HashBackend, P: AsRef<Path>>(
+    path: P,
+    buffer_size: usize,
+) -> Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>> {
+    calc_hash_with_mode::<B, P>(path, buffer_size, LineEndingMode::Raw).await
+}
+
+/// Calc SHA1 hash of a single file, applying the given line ending mode
+///
+/// Only use `LineEndingMode::NormalizeLf` for files known to be text
+pub async fn calc_sha1_with_mode<P: AsRef<Path>>(
+    path: P,
+    buffer_size: usize,
+    mode: LineEndingMode,
+) -> Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>> {
+    calc_hash_with_mode::<Sha1, P>(path, buffer_size, mode).await
+}
+
+/// Calc hash of a single file with the given backend and line ending mode
+pub async fn calc_hash_with_mode<B: HashBackend, P: AsRef<Path>>(
+    path: P,
+    buffer_size: usize,
+    mode: LineEndingMode,
 ) -> Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>> {
     let file_path = path.as_ref().to_string_lossy().to_string();
 
     // Open file asynchronously
     let file = File::open(&path).await?;
-    let mut reader = BufReader::with_capacity(buffer_size, file);
-    let mut hasher = Sha1::new();
+    let reader = BufReader::with_capacity(buffer_size, file);
+
+    Ok(Sha1Result {
+        file_path: file_path.into(),
+        hash: calc_hash_reader_with_mode::<B, _>(reader, buffer_size, mode).await?,
+    })
+}
+
+/// Calc SHA1 hash of all bytes read from a reader
+pub async fn calc_sha1_reader<R: AsyncRead + Unpin>(
+    reader: R,
+    buffer_size: usize,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    calc_hash_reader::<Sha1, R>(reader, buffer_size).await
+}
+
+/// Calc hash of all bytes read from a reader with the given backend
+pub async fn calc_hash_reader<B: HashBackend, R: AsyncRead + Unpin>(
+    reader: R,
+    buffer_size: usize,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    calc_hash_reader_with_mode::<B, R>(reader, buffer_size, LineEndingMode::Raw).await
+}
+
+/// Calc hash of all bytes read from a reader with the given backend and line ending mode
+pub async fn calc_hash_reader_with_mode<B: HashBackend, R: AsyncRead + Unpin>(
+    mut reader: R,
+    buffer_size: usize,
+    mode: LineEndingMode,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut hasher = B::default();
     let mut buffer = vec![0u8; buffer_size];
 
-    // Read file in chunks and update hash asynchronously
+    // Whether the previous chunk ended with a CR that has not been fed yet
+    let mut pending_cr = false;
+
+    // Read in chunks and update hash asynchronously
     loop {
         let n = reader.read(&mut buffer).await?;
         if n == 0 {
             break;
         }
-        hasher.update(&buffer[..n]);
-    }
 
-    let hash_result = hasher.finalize();
+        match mode {
+            LineEndingMode::Raw => hasher.update(&buffer[..n]),
+            LineEndingMode::NormalizeLf => {
+                let mut normalized = Vec::with_capacity(n + 1);
+                for &byte in &buffer[..n] {
+                    // A CR is only dropped when directly followed by LF
+                    if pending_cr && byte != b'\n' {
+                        normalized.push(b'\r');
+                    }
+                    pending_cr = byte == b'\r';
+                    if !pending_cr {
+                        normalized.push(byte);
+                    }
+                }
+                hasher.update(&normalized);
+            }
+        }
+    }
 
-    // Convert to hex string
-    let hash_hex = hash_result
-        .iter()
-        .map(|b| format!("{:02x}", b))
-        .collect::<String>();
+    // Lone CR at the end of the input
+    if pending_cr {
+        hasher.update(b"\r");
+    }
 
-    Ok(Sha1Result {
-        file_path: file_path.into(),
-        hash: hash_hex,
-    })
+    Ok(to_hex(hasher.finalize()))
 }
 
 /// Calc SHA1 hashes for multiple files using multi-threading
+///
+/// The results are returned in the same order as the input paths,
+/// regardless of which file finishes first
 pub async fn calc_sha1_multi<P, I>(
     paths: I,
     buffer_size: usize,
@@ -70,6 +245,21 @@ pub async fn calc_sha1_multi<P, I>(
 where
     P: AsRef<Path> + Send + Sync + 'static,
     I: IntoIterator<Item = P>,
+{
+    calc_hash_multi::<Sha1, P, I>(paths, buffer_size).await
+}
+
+/// Calc hashes for multiple files with the given backend using multi-threading
+///
+/// The results are returned in the same order as the input paths
+pub async fn calc_hash_multi<B, P, I>(
+    paths: I,
+    buffer_size: usize,
+) -> Result<Vec<Sha1Result>, Box<dyn std::error::Error + Send + Sync>>
+where
+    B: HashBackend,
+    P: AsRef<Path> + Send + Sync + 'static,
+    I: IntoIterator<Item = P>,
 {
     let buffer_size = Arc::new(buffer_size);
 
@@ -85,11 +275,11 @@ where
         .into_iter()
         .map(|path| {
             let buffer_size = Arc::clone(&buffer_size);
-            task::spawn(async move { calc_sha1(path, *buffer_size).await })
+            task::spawn(async move { calc_hash::<B, P>(path, *buffer_size).await })
         })
         .collect();
 
-    // Execute tasks with concurrency limit using join_all
+    // join_all yields the results in task order, which is the input order
     let results: Vec<Result<Sha1Result, Box<dyn std::error::Error + Send + Sync>>> =
         futures::future::join_all(tasks)
             .await
@@ -113,6 +303,121 @@ where
     Ok(successful_results)
 }
 
+/// Calc SHA1 hashes for multiple files using multi-threading,
+/// keeping the result of each file instead of aborting on the first failure
+///
+/// The results are returned in the same order as the input paths,
+/// failed files are reported as `(path, error message)`
+pub async fn calc_sha1_multi_partial<P, I>(
+    paths: I,
+    buffer_size: usize,
+) -> Vec<Result<Sha1Result, (PathBuf, String)>>
+where
+    P: AsRef<Path> + Send + Sync + 'static,
+    I: IntoIterator<Item = P>,
+{
+    calc_hash_multi_partial::<Sha1, P, I>(paths, buffer_size).await
+}
+
+/// Calc hashes for multiple files with the given backend,
+/// keeping the result of each file instead of aborting on the first failure
+///
+/// The results are returned in the same order as the input paths
+pub async fn calc_hash_multi_partial<B, P, I>(
+    paths: I,
+    buffer_size: usize,
+) -> Vec<Result<Sha1Result, (PathBuf, String)>>
+where
+    B: HashBackend,
+    P: AsRef<Path> + Send + Sync + 'static,
+    I: IntoIterator<Item = P>,
+{
+    let buffer_size = Arc::new(buffer_size);
+
+    // Create tasks for each file, remembering the path for error reporting
+    let tasks: Vec<_> = paths
+        .into_iter()
+        .map(|path| {
+            let file_path = path.as_ref().to_path_buf();
+            let buffer_size = Arc::clone(&buffer_size);
+            let task = task::spawn(async move {
+                calc_hash::<B, P>(path, *buffer_size)
+                    .await
+                    .map_err(|e| e.to_string())
+            });
+            (file_path, task)
+        })
+        .collect();
+
+    let (file_paths, tasks): (Vec<PathBuf>, Vec<_>) = tasks.into_iter().unzip();
+
+    futures::future::join_all(tasks)
+        .await
+        .into_iter()
+        .zip(file_paths)
+        .map(|(task_result, file_path)| match task_result {
+            Ok(Ok(calc_result)) => Ok(calc_result),
+            Ok(Err(e)) => Err((file_path, e)),
+            Err(e) => Err((file_path, e.to_string())),
+        })
+        .collect()
+}
+
+/// Calc SHA1 hashes for multiple files, hashing at most `max_concurrency` files at a time
+///
+/// Behaves like `calc_sha1_multi_partial`, but never keeps more than `max_concurrency`
+/// files open, use it for large batches
+pub async fn calc_sha1_multi_bounded<P, I>(
+    paths: I,
+    buffer_size: usize,
+    max_concurrency: usize,
+) -> Vec<Result<Sha1Result, (PathBuf, String)>>
+where
+    P: AsRef<Path> + Send + Sync + 'static,
+    I: IntoIterator<Item = P>,
+{
+    calc_hash_multi_bounded::<Sha1, P, I>(paths, buffer_size, max_concurrency).await
+}
+
+/// Calc hashes for multiple files with the given backend, hashing at most `max_concurrency` files at a time
+///
+/// The results are returned in the same order as the input paths
+pub async fn calc_hash_multi_bounded<B, P, I>(
+    paths: I,
+    buffer_size: usize,
+    max_concurrency: usize,
+) -> Vec<Result<Sha1Result, (PathBuf, String)>>
+where
+    B: HashBackend,
+    P: AsRef<Path> + Send + Sync + 'static,
+    I: IntoIterator<Item = P>,
+{
+    use futures::StreamExt;
+
+    let tasks = paths.into_iter().map(|path| {
+        let file_path = path.as_ref().to_path_buf();
+        let task = task::spawn(async move {
+            calc_hash::<B, P>(path, buffer_size)
+                .await
+                .map_err(|e| e.to_string())
+        });
+        async move {
+            match task.await {
+                Ok(Ok(calc_result)) => Ok(calc_result),
+                Ok(Err(e)) => Err((file_path, e)),
+                Err(e) => Err((file_path, e.to_string())),
+            }
+        }
+    });
+
+    // `buffered` only pulls the next file, spawning its task, once a slot is free,
+    // and yields the results in input order
+    futures::stream::iter(tasks)
+        .buffered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +489,21 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_sha1_default_buffer_size() {
+        let test_file_path = "res/story.txt";
+        let default = calc_sha1_default(test_file_path)
+            .await
+            .expect("Failed to calculate SHA1");
+
+        for buffer_size in [1, 2048, 8192, DEFAULT_BUFFER_SIZE] {
+            let explicit = calc_sha1(test_file_path, buffer_size)
+                .await
+                .expect("Failed to calculate SHA1");
+            assert_eq!(default.hash, explicit.hash);
+        }
+    }
+
     #[tokio::test]
     async fn test_sha1_empty_file() {
         // Create a temporary empty file for testing
@@ -254,4 +574,271 @@ mod tests {
             "SHA1 hash mismatch in multi-file test"
         );
     }
+
+    #[tokio::test]
+    async fn test_sha1_multi_partial_with_missing_file() {
+        let test_files = vec![
+            "res/story.txt",
+            "res/story_lf.sha1",
+            "res/not_exist.txt",
+            "res/story_crlf.sha1",
+        ];
+
+        let results = calc_sha1_multi_partial(test_files, 8192).await;
+
+        assert_eq!(results.len(), 4, "Should have a result for every file");
+
+        // Valid files are still hashed
+        let expected_story_hash = calc_sha1("res/story.txt", 8192)
+            .await
+            .expect("Failed to calculate SHA1")
+            .hash;
+        assert_eq!(
+            results[0].as_ref().expect("story.txt should succeed").hash,
+            expected_story_hash
+        );
+        assert!(results[1].is_ok(), "story_lf.sha1 should succeed");
+        assert!(results[3].is_ok(), "story_crlf.sha1 should succeed");
+
+        // Missing file is reported with its path
+        let (failed_path, message) = results[2].as_ref().expect_err("Missing file should fail");
+        assert_eq!(failed_path, &PathBuf::from("res/not_exist.txt"));
+        assert!(!message.is_empty(), "Error message should not be empty");
+    }
+
+    #[tokio::test]
+    async fn test_sha1_multi_keeps_input_order() {
+        // The large files finish last, the output must still follow the input
+        let files = [
+            ("test_order_large_a.bin", 8 * 1024 * 1024),
+            ("test_order_small_a.bin", 1),
+            ("test_order_large_b.bin", 4 * 1024 * 1024),
+            ("test_order_empty.bin", 0),
+            ("test_order_small_b.bin", 16),
+        ];
+        for (i, (name, size)) in files.iter().enumerate() {
+            fs::write(name, vec![i as u8; *size]).expect("Failed to create test file");
+        }
+
+        let results = calc_sha1_multi(files.iter().map(|(name, _)| *name), 1024).await;
+        let mut expected = Vec::new();
+        for (name, _) in files.iter() {
+            expected.push(
+                calc_sha1(name, 1024)
+                    .await
+                    .expect("Failed to calculate SHA1")
+                    .hash,
+            );
+        }
+        let partial = calc_sha1_multi_partial(files.iter().map(|(name, _)| *name), 1024).await;
+
+        // Clean up
+        for (name, _) in files.iter() {
+            fs::remove_file(name).expect("Failed to remove temporary test file");
+        }
+
+        let results = results.expect("Failed to calculate SHA1 for multiple files");
+        let paths: Vec<PathBuf> = results.iter().map(|r| r.file_path.clone()).collect();
+        let expected_paths: Vec<PathBuf> = files.iter().map(|(name, _)| name.into()).collect();
+        assert_eq!(paths, expected_paths);
+        let hashes: Vec<String> = results.into_iter().map(|r| r.hash).collect();
+        assert_eq!(hashes, expected);
+
+        let partial_hashes: Vec<String> = partial
+            .into_iter()
+            .map(|r| r.expect("Failed to calculate SHA1").hash)
+            .collect();
+        assert_eq!(partial_hashes, expected);
+    }
+
+    #[tokio::test]
+    async fn test_sha1_multi_bounded_matches_partial() {
+        let test_files = vec![
+            "res/story.txt",
+            "res/not_exist.txt",
+            "res/story_lf.sha1",
+            "res/story_crlf.sha1",
+        ];
+
+        let partial = calc_sha1_multi_partial(test_files.clone(), 8192).await;
+        for max_concurrency in [0, 1, 2, 16] {
+            let bounded = calc_sha1_multi_bounded(test_files.clone(), 8192, max_concurrency).await;
+            assert_eq!(bounded.len(), partial.len());
+            for (bounded, partial) in bounded.iter().zip(partial.iter()) {
+                match (bounded, partial) {
+                    (Ok(bounded), Ok(partial)) => {
+                        assert_eq!(bounded.file_path, partial.file_path);
+                        assert_eq!(bounded.hash, partial.hash);
+                    }
+                    (Err((bounded, _)), Err((partial, _))) => assert_eq!(bounded, partial),
+                    other => panic!("Results differ: {:?}", other),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sha1_multi_strict_with_missing_file() {
+        let test_files = vec!["res/story.txt", "res/not_exist.txt"];
+
+        let result = calc_sha1_multi(test_files, 8192).await;
+
+        assert!(result.is_err(), "Strict variant should fail as a whole");
+    }
+
+    #[tokio::test]
+    async fn test_blake3_accuracy() {
+        let expected_hash_path = if cfg!(windows) {
+            "res/story_crlf.blake3"
+        } else {
+            "res/story_lf.blake3"
+        };
+
+        let result = calc_hash::<Blake3, _>("res/story.txt", 8192)
+            .await
+            .expect("Failed to calculate BLAKE3");
+
+        let expected_hash = fs::read_to_string(expected_hash_path)
+            .expect("Failed to read expected hash file")
+            .trim()
+            .to_string();
+
+        assert_eq!(
+            result.hash, expected_hash,
+            "BLAKE3 hash mismatch for test file"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sha1_backend_matches_wrapper() {
+        let generic = calc_hash::<Sha1, _>("res/story.txt", 8192)
+            .await
+            .expect("Failed to calculate SHA1");
+        let wrapper = calc_sha1("res/story.txt", 8192)
+            .await
+            .expect("Failed to calculate SHA1");
+
+        assert_eq!(generic.hash, wrapper.hash);
+    }
+
+    #[test]
+    fn test_blake3_string_empty() {
+        // BLAKE3 of empty string
+        let expected_empty_hash =
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+        assert_eq!(calc_hash_string::<Blake3, _>(""), expected_empty_hash);
+    }
+
+    #[tokio::test]
+    async fn test_sha1_reader_cursor() {
+        let reader = std::io::Cursor::new(b"Hello, SHA1!".to_vec());
+
+        let hash = calc_sha1_reader(reader, 4)
+            .await
+            .expect("Failed to calculate SHA1 from reader");
+
+        assert_eq!(hash, "de1c3daadc6f0f1626f4cf56c03e05a1e5d7b187");
+    }
+
+    #[tokio::test]
+    async fn test_sha1_normalize_lf_matches_across_line_endings() {
+        let lf_content = fs::read("res/story.txt").expect("Failed to read test file");
+        let lf_content: Vec<u8> = lf_content.into_iter().filter(|b| *b != b'\r').collect();
+        let crlf_content = String::from_utf8(lf_content.clone())
+            .expect("Test file should be UTF-8")
+            .replace('\n', "\r\n")
+            .into_bytes();
+
+        let lf_file = "test_line_ending_lf.txt";
+        let crlf_file = "test_line_ending_crlf.txt";
+        fs::write(lf_file, &lf_content).expect("Failed to create LF test file");
+        fs::write(crlf_file, &crlf_content).expect("Failed to create CRLF test file");
+
+        let lf_hash = calc_sha1_with_mode(lf_file, 8192, LineEndingMode::NormalizeLf)
+            .await
+            .expect("Failed to calculate SHA1 for LF file");
+        let crlf_hash = calc_sha1_with_mode(crlf_file, 8192, LineEndingMode::NormalizeLf)
+            .await
+            .expect("Failed to calculate SHA1 for CRLF file");
+        let crlf_raw_hash = calc_sha1_with_mode(crlf_file, 8192, LineEndingMode::Raw)
+            .await
+            .expect("Failed to calculate SHA1 for CRLF file");
+
+        // Clean up
+        fs::remove_file(lf_file).expect("Failed to remove temporary test file");
+        fs::remove_file(crlf_file).expect("Failed to remove temporary test file");
+
+        let expected_hash = fs::read_to_string("res/story_lf.sha1")
+            .expect("Failed to read expected hash file")
+            .trim()
+            .to_string();
+        let expected_crlf_hash = fs::read_to_string("res/story_crlf.sha1")
+            .expect("Failed to read expected hash file")
+            .trim()
+            .to_string();
+
+        assert_eq!(lf_hash.hash, expected_hash);
+        assert_eq!(crlf_hash.hash, expected_hash);
+        assert_eq!(crlf_raw_hash.hash, expected_crlf_hash);
+    }
+
+    #[tokio::test]
+    async fn test_is_probably_text() {
+        let text_file = "test_probably_text.txt";
+        let binary_file = "test_probably_binary.bin";
+        fs::write(text_file, "Grüße\r\nline two\n").expect("Failed to create text file");
+        fs::write(
+            binary_file,
+            [0x89, b'P', b'N', b'G', 0x00, 0x00, 0x0d, 0x0a],
+        )
+        .expect("Failed to create binary file");
+
+        let text = is_probably_text(text_file).await;
+        let binary = is_probably_text(binary_file).await;
+        let story = is_probably_text("res/story.txt").await;
+        let missing = is_probably_text("res/missing_file.txt").await;
+
+        // Clean up
+        fs::remove_file(text_file).expect("Failed to remove temporary test file");
+        fs::remove_file(binary_file).expect("Failed to remove temporary test file");
+
+        assert!(text.expect("Failed to inspect text file"));
+        assert!(!binary.expect("Failed to inspect binary file"));
+        assert!(story.expect("Failed to inspect story file"));
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sha1_normalize_lf_across_chunk_boundary() {
+        // Buffer size of 1 splits every CRLF pair across reads
+        let crlf = std::io::Cursor::new(b"a\r\nb\r\n".to_vec());
+        let lf = std::io::Cursor::new(b"a\nb\n".to_vec());
+
+        let crlf_hash = calc_hash_reader_with_mode::<Sha1, _>(crlf, 1, LineEndingMode::NormalizeLf)
+            .await
+            .expect("Failed to calculate SHA1");
+        let lf_hash = calc_sha1_reader(lf, 1)
+            .await
+            .expect("Failed to calculate SHA1");
+
+        assert_eq!(crlf_hash, lf_hash);
+    }
+
+    #[tokio::test]
+    async fn test_sha1_normalize_lf_keeps_lone_cr() {
+        let data = b"a\rb\r".to_vec();
+
+        let normalized = calc_hash_reader_with_mode::<Sha1, _>(
+            std::io::Cursor::new(data.clone()),
+            8192,
+            LineEndingMode::NormalizeLf,
+        )
+        .await
+        .expect("Failed to calculate SHA1");
+        let raw = calc_sha1_reader(std::io::Cursor::new(data), 8192)
+            .await
+            .expect("Failed to calculate SHA1");
+
+        assert_eq!(normalized, raw);
+    }
 }
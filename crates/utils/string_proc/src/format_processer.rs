@@ -1,42 +1,53 @@
+/// Characters `FormatProcesser::from` treats as word boundaries
+pub const DEFAULT_DELIMITERS: &[char] = &['_', ',', '.', '-', ' '];
+
 pub struct FormatProcesser {
     content: Vec<String>,
 }
 
 impl From<String> for FormatProcesser {
     fn from(value: String) -> Self {
-        Self {
-            content: Self::process_string(value),
-        }
+        Self::with_delimiters(value, DEFAULT_DELIMITERS)
     }
 }
 
 impl From<&str> for FormatProcesser {
     fn from(value: &str) -> Self {
-        Self {
-            content: Self::process_string(value.to_string()),
-        }
+        Self::with_delimiters(value, DEFAULT_DELIMITERS)
     }
 }
 
 impl FormatProcesser {
+    /// Create a FormatProcesser that breaks words at the given delimiters
+    ///
+    /// Default delimiters left out of the set are kept inside words
+    /// (e.g. omit '.' to keep file extensions), whitespace always breaks words
+    pub fn with_delimiters(input: impl Into<String>, delimiters: &[char]) -> Self {
+        Self {
+            content: Self::process_string(input.into(), delimiters),
+        }
+    }
+
     /// Process the string into an intermediate format
-    fn process_string(input: String) -> Vec<String> {
+    ///
+    /// Letters and digits of any script are kept, words break at delimiters,
+    /// at lower-to-upper case changes and between cased and caseless letters (e.g. CJK)
+    fn process_string(input: String, delimiters: &[char]) -> Vec<String> {
         let mut result = String::new();
         let mut prev_space = false;
 
         for c in input.chars() {
-            match c {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {
-                    result.push(c);
-                    prev_space = false;
+            if c.is_alphanumeric() {
+                result.push(c);
+                prev_space = false;
+            } else if delimiters.contains(&c) {
+                if !prev_space {
+                    result.push(' ');
+                    prev_space = true;
                 }
-                '_' | ',' | '.' | '-' | ' ' => {
-                    if !prev_space {
-                        result.push(' ');
-                        prev_space = true;
-                    }
-                }
-                _ => {}
+            } else if DEFAULT_DELIMITERS.contains(&c) {
+                result.push(c);
+                prev_space = false;
             }
         }
 
@@ -46,8 +57,9 @@ impl FormatProcesser {
         while let Some(c) = chars.next() {
             processed.push(c);
             if let Some(&next) = chars.peek()
-                && c.is_lowercase()
-                && next.is_uppercase()
+                && ((c.is_lowercase() && next.is_uppercase())
+                    || (is_caseless_letter(c) && is_cased(next))
+                    || (is_cased(c) && is_caseless_letter(next)))
             {
                 processed.push(' ');
             }
@@ -100,6 +112,11 @@ impl FormatProcesser {
         self.content.join("_").to_lowercase()
     }
 
+    /// Convert to SCREAMING_SNAKE_CASE format (BREW_COFFEE)
+    pub fn to_screaming_snake_case(&self) -> String {
+        self.content.join("_").to_uppercase()
+    }
+
     /// Convert to dot.case format (brew.coffee)
     pub fn to_dot_case(&self) -> String {
         self.content.join(".").to_lowercase()
@@ -120,6 +137,21 @@ impl FormatProcesser {
         result
     }
 
+    /// Convert to Train-Case format (Brew-Coffee)
+    pub fn to_train_case(&self) -> String {
+        let mut result = String::new();
+        for word in &self.content {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.push_str(&first.to_uppercase().collect::<String>());
+                result.push_str(&chars.collect::<String>().to_lowercase());
+            }
+            result.push('-');
+        }
+        result.pop();
+        result
+    }
+
     /// Convert to lower case format (brew coffee)
     pub fn to_lower_case(&self) -> String {
         self.content.join(" ").to_lowercase()
@@ -130,3 +162,13 @@ impl FormatProcesser {
         self.content.join(" ").to_uppercase()
     }
 }
+
+/// Whether the character has an upper / lower case form
+fn is_cased(c: char) -> bool {
+    c.is_lowercase() || c.is_uppercase()
+}
+
+/// Whether the character is a letter without case, such as CJK ideographs
+fn is_caseless_letter(c: char) -> bool {
+    c.is_alphabetic() && !is_cased(c)
+}
@@ -5,7 +5,7 @@ pub mod simple_processer;
 
 #[cfg(test)]
 mod tests {
-    use crate::format_processer::FormatProcesser;
+    use crate::format_processer::{DEFAULT_DELIMITERS, FormatProcesser};
 
     #[test]
     fn test_processer() {
@@ -46,5 +46,117 @@ mod tests {
         assert_eq!(processor.to_kebab_case(), "brew-coffee");
         assert_eq!(processor.to_pascal_case(), "BrewCoffee");
         assert_eq!(processor.to_camel_case(), "brewCoffee");
+        assert_eq!(processor.to_screaming_snake_case(), "BREW_COFFEE");
+        assert_eq!(processor.to_train_case(), "Brew-Coffee");
+    }
+
+    #[test]
+    fn test_screaming_snake_and_train_case() {
+        let test_cases = vec![
+            ("brew_coffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("brew, coffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("brew-coffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("Brew.Coffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("b&rewCoffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("BrewCoffee", "BREW_COFFEE", "Brew-Coffee"),
+            ("BREW COFFEE", "BREW_COFFEE", "Brew-Coffee"),
+            (
+                "server_file_vault",
+                "SERVER_FILE_VAULT",
+                "Server-File-Vault",
+            ),
+            ("bRewCofFee", "B_REW_COF_FEE", "B-Rew-Cof-Fee"),
+        ];
+
+        for (input, screaming_snake, train) in test_cases {
+            let processor = FormatProcesser::from(input);
+            assert_eq!(
+                processor.to_screaming_snake_case(),
+                screaming_snake,
+                "Failed for input: '{}'",
+                input
+            );
+            assert_eq!(
+                processor.to_train_case(),
+                train,
+                "Failed for input: '{}'",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_unicode_conversions() {
+        let test_cases = vec![
+            ("café_data", "café_data", "caféData"),
+            ("naïveCase", "naïve_case", "naïveCase"),
+            ("Ärger-Über", "ärger_über", "ärgerÜber"),
+            ("ÉCOLE NORMALE", "école_normale", "écoleNormale"),
+            ("数据Value", "数据_value", "数据Value"),
+            ("用户_配置", "用户_配置", "用户配置"),
+        ];
+
+        for (input, snake, camel) in test_cases {
+            let processor = FormatProcesser::from(input);
+            assert_eq!(
+                processor.to_snake_case(),
+                snake,
+                "Failed for input: '{}'",
+                input
+            );
+            assert_eq!(
+                processor.to_camel_case(),
+                camel,
+                "Failed for input: '{}'",
+                input
+            );
+        }
+
+        let processor = FormatProcesser::from("naïveCase");
+        assert_eq!(processor.to_pascal_case(), "NaïveCase");
+        assert_eq!(processor.to_screaming_snake_case(), "NAÏVE_CASE");
+        assert_eq!(processor.to_train_case(), "Naïve-Case");
+    }
+
+    #[test]
+    fn test_custom_delimiters() {
+        let path_delimiters = ['/', '\\', '_', '-', ' '];
+        let test_cases = vec![
+            (
+                "assets/textures/hero_idle.png",
+                "assets_textures_hero_idle.png",
+            ),
+            (
+                "assets\\textures\\hero-idle.png",
+                "assets_textures_hero_idle.png",
+            ),
+            (
+                "/assets//Textures/HeroIdle.png",
+                "assets_textures_hero_idle.png",
+            ),
+            ("docs/v1.2/read me.md", "docs_v1.2_read_me.md"),
+        ];
+
+        for (input, expected) in test_cases {
+            let processor = FormatProcesser::with_delimiters(input, &path_delimiters);
+            assert_eq!(
+                processor.to_snake_case(),
+                expected,
+                "Failed for input: '{}'",
+                input
+            );
+        }
+
+        let processor = FormatProcesser::with_delimiters("src/vault/virtual_file.rs", &['/']);
+        assert_eq!(processor.to_pascal_case(), "SrcVaultVirtual_file.rs");
+        assert_eq!(processor.to_kebab_case(), "src-vault-virtual_file.rs");
+
+        // Default delimiters match `from`, path separators are dropped
+        let processor = FormatProcesser::with_delimiters("brew/coffee.beans", DEFAULT_DELIMITERS);
+        assert_eq!(
+            processor.to_snake_case(),
+            FormatProcesser::from("brew/coffee.beans").to_snake_case()
+        );
+        assert_eq!(processor.to_snake_case(), "brewcoffee_beans");
     }
 }
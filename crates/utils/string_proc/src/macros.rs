@@ -61,3 +61,19 @@ macro_rules! pascal_case {
         FormatProcesser::from($input).to_pascal_case()
     }};
 }
+
+#[macro_export]
+macro_rules! screaming_snake_case {
+    ($input:expr) => {{
+        use string_proc::format_processer::FormatProcesser;
+        FormatProcesser::from($input).to_screaming_snake_case()
+    }};
+}
+
+#[macro_export]
+macro_rules! train_case {
+    ($input:expr) => {{
+        use string_proc::format_processer::FormatProcesser;
+        FormatProcesser::from($input).to_train_case()
+    }};
+}
@@ -1,15 +1,78 @@
-/// Sanitizes a file path by replacing special characters with underscores.
+/// Device names reserved by Windows, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Sanitizes a file path into a single, safe, relative file name.
 ///
-/// This function takes a file path as input and returns a sanitized version
-/// where characters that are not allowed in file paths (such as path separators
-/// and other reserved characters) are replaced with underscores.
+/// - Drive prefixes (`C:`), empty, `.` and `..` components are dropped,
+///   so the result can never leave the directory it is joined to
+/// - The remaining components are joined with underscores, and characters
+///   not allowed in file names are replaced with underscores
+/// - Windows reserved device names (`CON`, `NUL`, `COM1`...) are prefixed with an underscore
+/// - Trailing dots and spaces are removed, an empty result becomes `_`
 pub fn sanitize_file_path<P: AsRef<str>>(path: P) -> String {
     let path_str = path.as_ref();
-    path_str
+    let mut name = path_str
+        .split(['/', '\\'])
+        .enumerate()
+        .filter(|(i, component)| {
+            let is_drive = *i == 0 && component.len() == 2 && component.ends_with(':');
+            !is_drive && !matches!(*component, "" | "." | "..")
+        })
+        .map(|(_, component)| component)
+        .collect::<Vec<_>>()
+        .join("_")
         .chars()
         .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
             _ => c,
         })
-        .collect()
+        .collect::<String>();
+
+    let trimmed_len = name.trim_end_matches(['.', ' ']).len();
+    name.truncate(trimmed_len);
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem.trim_end()))
+    {
+        name.insert(0, '_');
+    }
+
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        return "_".to_string();
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_path() {
+        assert_eq!(sanitize_file_path("../../etc/passwd"), "etc_passwd");
+        assert_eq!(sanitize_file_path("..\\..\\secret.txt"), "secret.txt");
+        assert_eq!(sanitize_file_path("/etc/passwd"), "etc_passwd");
+        assert_eq!(sanitize_file_path("C:\\Windows"), "Windows");
+        assert_eq!(
+            sanitize_file_path("C:/Windows/System32"),
+            "Windows_System32"
+        );
+        assert_eq!(sanitize_file_path("CON.txt"), "_CON.txt");
+        assert_eq!(sanitize_file_path("nul"), "_nul");
+        assert_eq!(sanitize_file_path("Com1.tar.gz"), "_Com1.tar.gz");
+        assert_eq!(sanitize_file_path("console.txt"), "console.txt");
+        assert_eq!(sanitize_file_path("what?.txt"), "what_.txt");
+        assert_eq!(sanitize_file_path("report. "), "report");
+        assert_eq!(sanitize_file_path(".."), "_");
+        assert_eq!(sanitize_file_path("./"), "_");
+        assert_eq!(sanitize_file_path(""), "_");
+        assert_eq!(sanitize_file_path("..."), "_");
+        assert_eq!(sanitize_file_path(".gitignore"), ".gitignore");
+    }
 }
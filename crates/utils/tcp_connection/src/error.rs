@@ -9,6 +9,9 @@ pub enum TcpTargetError {
     #[error("Reference sheet not allowed: {0}")]
     ReferenceSheetNotAllowed(String),
 
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     #[error("Cryptographic error: {0}")]
     Crypto(String),
 
@@ -53,11 +56,28 @@ pub enum TcpTargetError {
 
     #[error("Unsupported operation: {0}")]
     Unsupported(String),
+
+    #[error("Version mismatch: {0}")]
+    VersionMismatch(String),
+}
+
+impl TcpTargetError {
+    /// Whether the error comes from the connection itself rather than the protocol or logic,
+    /// such errors may succeed on a new connection
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            TcpTargetError::Io(_) | TcpTargetError::Network(_) | TcpTargetError::Timeout(_)
+        )
+    }
 }
 
 impl From<io::Error> for TcpTargetError {
     fn from(error: io::Error) -> Self {
-        TcpTargetError::Io(error.to_string())
+        match error.kind() {
+            io::ErrorKind::TimedOut => TcpTargetError::Timeout(error.to_string()),
+            _ => TcpTargetError::Io(error.to_string()),
+        }
     }
 }
 
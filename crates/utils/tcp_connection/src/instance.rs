@@ -6,24 +6,229 @@ use tokio::{
     io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
     net::TcpStream,
 };
+use tokio_util::sync::CancellationToken;
 
 use ring::signature::{self};
 
-use crate::error::TcpTargetError;
+use crate::{
+    error::TcpTargetError,
+    instance_buffer::BufferedStream,
+    instance_heartbeat::HeartbeatStream,
+    instance_throttle::Throttle,
+    instance_tls::{ConnectionStream, TlsConfig},
+};
 
 const DEFAULT_CHUNK_SIZE: usize = 4096;
 const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_MESSAGE_LEN: usize = 256 * 1024 * 1024;
 
 const ECDSA_P256_SHA256_ASN1_SIGNING: &signature::EcdsaSigningAlgorithm =
     &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
 const ECDSA_P384_SHA384_ASN1_SIGNING: &signature::EcdsaSigningAlgorithm =
     &signature::ECDSA_P384_SHA384_ASN1_SIGNING;
 
+/// Serialization format used by `write` / `read`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// MessagePack, consistent with the rest of the protocol
+    #[default]
+    MsgPack,
+
+    /// JSON text, human-debuggable
+    Json,
+}
+
+/// Width of the length prefix framing msgpack and text messages
+///
+/// Both sides of the connection must use the same width,
+/// agree on it with `ConnectionInstance::negotiate_length_prefix` when connecting
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// 4-byte big-endian length, compatible with older peers (protocol version 1)
+    #[default]
+    U32,
+
+    /// 8-byte big-endian length, allows messages over 4 GiB (protocol version 2)
+    U64,
+}
+
+impl LengthPrefix {
+    /// Size of the prefix in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            LengthPrefix::U32 => 4,
+            LengthPrefix::U64 => 8,
+        }
+    }
+
+    /// Protocol version announced by `ConnectionInstance::negotiate_length_prefix`
+    pub fn version(&self) -> u8 {
+        match self {
+            LengthPrefix::U32 => 1,
+            LengthPrefix::U64 => 2,
+        }
+    }
+
+    /// Width of a protocol version, newer versions keep the widest known width
+    pub fn from_version(version: u8) -> Result<Self, TcpTargetError> {
+        match version {
+            0 => Err(TcpTargetError::Protocol(
+                "Invalid length prefix version: 0".to_string(),
+            )),
+            1 => Ok(LengthPrefix::U32),
+            _ => Ok(LengthPrefix::U64),
+        }
+    }
+
+    /// Encode a length, failing if it does not fit in the prefix
+    pub fn encode(&self, len: u64) -> Result<Vec<u8>, TcpTargetError> {
+        match self {
+            LengthPrefix::U32 => match u32::try_from(len) {
+                Ok(len) => Ok(len.to_be_bytes().to_vec()),
+                Err(_) => Err(TcpTargetError::Protocol(format!(
+                    "Message of {} bytes exceeds the 4-byte length prefix, use LengthPrefix::U64",
+                    len
+                ))),
+            },
+            LengthPrefix::U64 => Ok(len.to_be_bytes().to_vec()),
+        }
+    }
+
+    /// Decode a length from exactly `size()` bytes
+    pub fn decode(&self, bytes: &[u8]) -> Result<u64, TcpTargetError> {
+        match (self, bytes.len()) {
+            (LengthPrefix::U32, 4) => {
+                Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64)
+            }
+            (LengthPrefix::U64, 8) => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(bytes);
+                Ok(u64::from_be_bytes(buf))
+            }
+            _ => Err(TcpTargetError::Protocol(format!(
+                "Invalid length prefix of {} bytes",
+                bytes.len()
+            ))),
+        }
+    }
+}
+
+/// Compression applied to file content by `write_file`
+///
+/// Each chunk is compressed on its own, the receiver detects the compression from the file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Flag carried in the file header
+    fn flag(&self) -> u8 {
+        match self {
+            Compression::Gzip => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    /// Parse the flag carried in the file header, `0` means no compression
+    fn from_flag(flag: u8) -> Result<Option<Self>, TcpTargetError> {
+        match flag {
+            0 => Ok(None),
+            1 => Ok(Some(Compression::Gzip)),
+            2 => Ok(Some(Compression::Zstd)),
+            _ => Err(TcpTargetError::Protocol(format!(
+                "Unsupported compression flag: {}",
+                flag
+            ))),
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, TcpTargetError> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                std::io::Write::write_all(&mut encoder, data)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::Zstd => Ok(zstd::stream::encode_all(data, 0)?),
+        }
+    }
+
+    /// Largest size `compress` produces for `len` bytes
+    fn compress_bound(&self, len: usize) -> usize {
+        match self {
+            // zlib's `compressBound` plus the gzip header and trailer
+            Compression::Gzip => len + (len >> 12) + (len >> 14) + (len >> 25) + 13 + 18,
+            Compression::Zstd => zstd::zstd_safe::compress_bound(len),
+        }
+    }
+
+    /// Decompress at most `limit` bytes, so a chunk inflating beyond it stops there
+    fn decompress(&self, data: &[u8], limit: u64) -> Result<Vec<u8>, TcpTargetError> {
+        let mut decoded = Vec::new();
+        match self {
+            Compression::Gzip => {
+                let decoder = flate2::read::GzDecoder::new(data);
+                std::io::Read::read_to_end(&mut std::io::Read::take(decoder, limit), &mut decoded)?;
+            }
+            Compression::Zstd => {
+                let decoder = zstd::stream::read::Decoder::new(data)?;
+                std::io::Read::read_to_end(&mut std::io::Read::take(decoder, limit), &mut decoded)?;
+            }
+        }
+        Ok(decoded)
+    }
+}
+
+/// File transfer header version without compression flag
+const FILE_TRANSFER_VERSION: u64 = 1;
+
+/// File transfer header version carrying a compression flag
+const FILE_TRANSFER_VERSION_COMPRESSED: u64 = 2;
+
 #[derive(Debug, Clone)]
 pub struct ConnectionConfig {
     pub chunk_size: usize,
     pub timeout_secs: u64,
     pub enable_crc_validation: bool,
+    pub message_format: MessageFormat,
+    pub length_prefix: LengthPrefix,
+    pub tls: Option<TlsConfig>,
+    pub compression: Option<Compression>,
+
+    /// Cap on the average throughput of `write_file` and `write_large_msgpack`, unlimited if `None`
+    pub max_bytes_per_sec: Option<u64>,
+
+    /// Ping the peer at this interval and fail with `TcpTargetError::Timeout`
+    /// if a ping is not answered before the next one, disabled if `None`
+    ///
+    /// Both sides of the connection must use the same setting
+    pub heartbeat_interval: Option<Duration>,
+
+    /// Disable Nagle's algorithm on the socket
+    ///
+    /// Helps the action protocol, which exchanges many small frames and waits for each answer
+    pub tcp_nodelay: bool,
+
+    /// Size of the socket send buffer, the system default if `None`
+    pub send_buffer_size: Option<usize>,
+
+    /// Size of the socket receive buffer, the system default if `None`
+    pub recv_buffer_size: Option<usize>,
+
+    /// Largest file `read_file` accepts, unlimited if `None`
+    ///
+    /// A larger announced size fails with `TcpTargetError::Protocol` before anything is written,
+    /// the connection must be dropped afterwards since the file content is still in flight
+    pub max_file_size: Option<u64>,
+
+    /// Largest length-prefixed message (msgpack, JSON, text) accepted from the peer,
+    /// unlimited if `None`
+    ///
+    /// Checked against the length prefix before anything is allocated
+    pub max_message_len: Option<usize>,
 }
 
 impl Default for ConnectionConfig {
@@ -32,28 +237,116 @@ impl Default for ConnectionConfig {
             chunk_size: DEFAULT_CHUNK_SIZE,
             timeout_secs: DEFAULT_TIMEOUT_SECS,
             enable_crc_validation: false,
+            message_format: MessageFormat::default(),
+            length_prefix: LengthPrefix::default(),
+            tls: None,
+            compression: None,
+            max_bytes_per_sec: None,
+            heartbeat_interval: None,
+            tcp_nodelay: false,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+            max_file_size: None,
+            max_message_len: Some(DEFAULT_MAX_MESSAGE_LEN),
         }
     }
 }
 
+/// Run a protocol message inside a span, only when the `tracing` feature is enabled
+///
+/// Each span carries the direction (`write` / `read`) and the frame tag,
+/// the payload size is recorded with `trace_message_size`
+pub(crate) async fn trace_message<F: Future>(
+    direction: &'static str,
+    tag: &'static str,
+    message: F,
+) -> F::Output {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+        let span = tracing::trace_span!(
+            "protocol_message",
+            direction,
+            tag,
+            size = tracing::field::Empty
+        );
+        message.instrument(span).await
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (direction, tag);
+        message.await
+    }
+}
+
+/// Record the payload size on the span of the protocol message being run
+pub(crate) fn trace_message_size(size: u64) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("size", size);
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = size;
+}
+
 pub struct ConnectionInstance {
-    pub(crate) stream: TcpStream,
+    pub(crate) stream: BufferedStream,
     config: ConnectionConfig,
+    pub(crate) throttle: Option<Throttle>,
+    cancel: Option<CancellationToken>,
 }
 
 impl From<TcpStream> for ConnectionInstance {
     fn from(stream: TcpStream) -> Self {
+        let config = ConnectionConfig::default();
+        apply_socket_options(&stream, &config);
         Self {
-            stream,
-            config: ConnectionConfig::default(),
+            stream: BufferedStream::new(ConnectionStream::Plain(stream)),
+            config,
+            throttle: None,
+            cancel: None,
         }
     }
 }
 
+/// Apply the socket options of `ConnectionConfig` to the stream
+///
+/// Options the platform rejects are left at their system defaults
+pub(crate) fn apply_socket_options(stream: &TcpStream, config: &ConnectionConfig) {
+    let _ = stream.set_nodelay(config.tcp_nodelay);
+
+    let socket = socket2::SockRef::from(stream);
+    if let Some(size) = config.send_buffer_size {
+        let _ = socket.set_send_buffer_size(size);
+    }
+    if let Some(size) = config.recv_buffer_size {
+        let _ = socket.set_recv_buffer_size(size);
+    }
+}
+
 impl ConnectionInstance {
     /// Create a new ConnectionInstance with custom configuration
     pub fn with_config(stream: TcpStream, config: ConnectionConfig) -> Self {
-        Self { stream, config }
+        apply_socket_options(&stream, &config);
+        Self::with_stream(ConnectionStream::Plain(stream), config)
+    }
+
+    /// Create a new ConnectionInstance over an already established stream
+    ///
+    /// Starts the heartbeat if `ConnectionConfig::heartbeat_interval` is set
+    pub(crate) fn with_stream(stream: ConnectionStream, config: ConnectionConfig) -> Self {
+        let stream = match config.heartbeat_interval {
+            Some(interval) => {
+                ConnectionStream::Heartbeat(Box::new(HeartbeatStream::spawn(stream, interval)))
+            }
+            None => stream,
+        };
+        Self {
+            stream: BufferedStream::new(stream),
+            config,
+            throttle: None,
+            cancel: None,
+        }
     }
 
     /// Get a reference to the current configuration
@@ -65,10 +358,49 @@ impl ConnectionInstance {
     pub fn config_mut(&mut self) -> &mut ConnectionConfig {
         &mut self.config
     }
+
+    /// Set the token used to abort file transfers on this connection
+    ///
+    /// Once the token is cancelled, transfers stop after the current chunk
+    /// with `TcpTargetError::Cancelled`. The peer is left mid-transfer,
+    /// so the connection should not be used for anything else afterwards
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancel = token;
+    }
+
+    /// Get the token used to abort file transfers on this connection
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+
+    /// Fail with `TcpTargetError::Cancelled` if the cancellation token was cancelled
+    pub fn check_cancelled(&self) -> Result<(), TcpTargetError> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => {
+                Err(TcpTargetError::Cancelled("Transfer cancelled".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Serialize data and write to the target machine
+    ///
+    /// The format is decided by `ConnectionConfig::message_format`,
+    /// both sides of the connection must use the same format
     pub async fn write<Data>(&mut self, data: Data) -> Result<(), TcpTargetError>
     where
         Data: Default + Serialize,
+    {
+        match self.config.message_format {
+            MessageFormat::MsgPack => self.write_msgpack(data).await,
+            MessageFormat::Json => self.write_json(data).await,
+        }
+    }
+
+    /// Serialize data to JSON and write to the target machine
+    pub async fn write_json<Data>(&mut self, data: Data) -> Result<(), TcpTargetError>
+    where
+        Data: Serialize,
     {
         let Ok(json_text) = serde_json::to_string(&data) else {
             return Err(TcpTargetError::Serialization(
@@ -79,39 +411,150 @@ impl ConnectionInstance {
         Ok(())
     }
 
+    /// Read exactly `buf.len()` bytes from the target machine
+    ///
+    /// Fails with `TcpTargetError::Timeout` if the peer stalls for longer than
+    /// `ConnectionConfig::timeout_secs`
+    pub(crate) async fn read_exact_timeout(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<(), TcpTargetError> {
+        let timeout_secs = self.config.timeout_secs;
+        tokio::time::timeout(
+            Duration::from_secs(timeout_secs),
+            self.stream.read_exact(buf),
+        )
+        .await
+        .map_err(|_| {
+            TcpTargetError::Timeout(format!("No data received within {} seconds", timeout_secs))
+        })??;
+        Ok(())
+    }
+
+    /// Reject an announced file size above `ConnectionConfig::max_file_size`
+    pub(crate) fn check_file_size(&self, file_size: u64) -> Result<(), TcpTargetError> {
+        match self.config.max_file_size {
+            Some(max) if file_size > max => Err(TcpTargetError::Protocol(format!(
+                "Announced file size {} exceeds the limit of {} bytes",
+                file_size, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Agree on the length prefix width with the peer
+    ///
+    /// Both sides announce the version of their configured `LengthPrefix` in a single byte,
+    /// then use the older of the two, so a wider prefix is only used when the peer supports it.
+    /// Must be called by both sides right after connecting, before any length-prefixed message
+    pub async fn negotiate_length_prefix(&mut self) -> Result<LengthPrefix, TcpTargetError> {
+        let version = self.config.length_prefix.version();
+        self.stream.write_all(&[version]).await?;
+        self.stream.flush().await?;
+
+        let mut peer_version = [0u8; 1];
+        self.read_exact_timeout(&mut peer_version).await?;
+        let length_prefix = LengthPrefix::from_version(version.min(peer_version[0]))?;
+        self.config.length_prefix = length_prefix;
+        Ok(length_prefix)
+    }
+
+    /// Write a length prefix with the width configured by `ConnectionConfig::length_prefix`
+    async fn write_length(&mut self, len: u64) -> Result<(), TcpTargetError> {
+        let prefix = self.config.length_prefix.encode(len)?;
+        self.stream.write_all(&prefix).await?;
+        Ok(())
+    }
+
+    /// Read a length prefix with the width configured by `ConnectionConfig::length_prefix`
+    async fn read_length(&mut self) -> Result<usize, TcpTargetError> {
+        let mut len_buf = vec![0u8; self.config.length_prefix.size()];
+        self.read_exact_timeout(&mut len_buf).await?;
+        let len = self.config.length_prefix.decode(&len_buf)?;
+        let too_large =
+            || TcpTargetError::Protocol(format!("Message of {} bytes is too large", len));
+        let len = usize::try_from(len).map_err(|_| too_large())?;
+        if self.config.max_message_len.is_some_and(|max| len > max) {
+            return Err(too_large());
+        }
+        Ok(len)
+    }
+
     /// Serialize data to MessagePack and write to the target machine
+    ///
+    /// When `ConnectionConfig::enable_crc_validation` is set,
+    /// a CRC32 of the body is appended after it
     pub async fn write_msgpack<Data>(&mut self, data: Data) -> Result<(), TcpTargetError>
     where
         Data: Serialize,
     {
-        let msgpack_data = rmp_serde::to_vec(&data)?;
-        let len = msgpack_data.len() as u32;
-
-        self.stream.write_all(&len.to_be_bytes()).await?;
-        self.stream.write_all(&msgpack_data).await?;
-        Ok(())
+        trace_message("write", "msgpack", async {
+            let msgpack_data = rmp_serde::to_vec(&data)?;
+            let len = msgpack_data.len() as u64;
+
+            self.write_length(len).await?;
+            self.stream.write_all(&msgpack_data).await?;
+            if self.config.enable_crc_validation {
+                let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&msgpack_data);
+                self.stream.write_all(&crc.to_be_bytes()).await?;
+            }
+            trace_message_size(len);
+            Ok(())
+        })
+        .await
     }
 
     /// Read data from target machine and deserialize from MessagePack
+    ///
+    /// When `ConnectionConfig::enable_crc_validation` is set,
+    /// the trailing CRC32 is verified before deserializing
     pub async fn read_msgpack<Data>(&mut self) -> Result<Data, TcpTargetError>
     where
         Data: serde::de::DeserializeOwned,
     {
-        let mut len_buf = [0u8; 4];
-        self.stream.read_exact(&mut len_buf).await?;
-        let len = u32::from_be_bytes(len_buf) as usize;
-
-        let mut buffer = vec![0; len];
-        self.stream.read_exact(&mut buffer).await?;
+        trace_message("read", "msgpack", async {
+            let len = self.read_length().await?;
+
+            let mut buffer = vec![0; len];
+            self.read_exact_timeout(&mut buffer).await?;
+            if self.config.enable_crc_validation {
+                let mut crc_buf = [0u8; 4];
+                self.read_exact_timeout(&mut crc_buf).await?;
+                let expected_crc = u32::from_be_bytes(crc_buf);
+                let actual_crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&buffer);
+                if actual_crc != expected_crc {
+                    return Err(TcpTargetError::Protocol(format!(
+                        "Message CRC validation failed: expected {:08x}, got {:08x}",
+                        expected_crc, actual_crc
+                    )));
+                }
+            }
+            trace_message_size(len as u64);
 
-        let data = rmp_serde::from_slice(&buffer)?;
-        Ok(data)
+            let data = rmp_serde::from_slice(&buffer)?;
+            Ok(data)
+        })
+        .await
     }
 
     /// Read data from target machine and deserialize
+    ///
+    /// The format is decided by `ConnectionConfig::message_format`,
+    /// both sides of the connection must use the same format
     pub async fn read<Data>(&mut self) -> Result<Data, TcpTargetError>
     where
         Data: Default + serde::de::DeserializeOwned,
+    {
+        match self.config.message_format {
+            MessageFormat::MsgPack => self.read_msgpack().await,
+            MessageFormat::Json => self.read_json().await,
+        }
+    }
+
+    /// Read data from target machine and deserialize from JSON
+    pub async fn read_json<Data>(&mut self) -> Result<Data, TcpTargetError>
+    where
+        Data: serde::de::DeserializeOwned,
     {
         let Ok(json_text) = Self::read_text(self).await else {
             return Err(TcpTargetError::Io("Read failed.".to_string()));
@@ -159,54 +602,73 @@ impl ConnectionInstance {
 
     /// Write text to the target machine
     pub async fn write_text(&mut self, text: impl Into<String>) -> Result<(), TcpTargetError> {
-        let text = text.into();
-        let bytes = text.as_bytes();
-        let len = bytes.len() as u32;
-
-        self.stream.write_all(&len.to_be_bytes()).await?;
-        match self.stream.write_all(bytes).await {
-            Ok(_) => Ok(()),
-            Err(err) => Err(TcpTargetError::Io(err.to_string())),
-        }
+        trace_message("write", "text", async {
+            let text = text.into();
+            let bytes = text.as_bytes();
+            let len = bytes.len() as u64;
+
+            self.write_length(len).await?;
+            match self.stream.write_all(bytes).await {
+                Ok(_) => {
+                    trace_message_size(len);
+                    Ok(())
+                }
+                Err(err) => Err(TcpTargetError::Io(err.to_string())),
+            }
+        })
+        .await
     }
 
     /// Read text from the target machine
     pub async fn read_text(&mut self) -> Result<String, TcpTargetError> {
-        let mut len_buf = [0u8; 4];
-        self.stream.read_exact(&mut len_buf).await?;
-        let len = u32::from_be_bytes(len_buf) as usize;
-
-        let mut buffer = vec![0; len];
-        self.stream.read_exact(&mut buffer).await?;
-
-        match String::from_utf8(buffer) {
-            Ok(text) => Ok(text),
-            Err(err) => Err(TcpTargetError::Serialization(format!(
-                "Invalid UTF-8 sequence: {}",
-                err
-            ))),
-        }
+        trace_message("read", "text", async {
+            let len = self.read_length().await?;
+
+            let mut buffer = vec![0; len];
+            self.read_exact_timeout(&mut buffer).await?;
+            trace_message_size(len as u64);
+
+            match String::from_utf8(buffer) {
+                Ok(text) => Ok(text),
+                Err(err) => Err(TcpTargetError::Serialization(format!(
+                    "Invalid UTF-8 sequence: {}",
+                    err
+                ))),
+            }
+        })
+        .await
     }
 
     /// Write large text to the target machine (chunked)
+    ///
+    /// The text is prefixed with its length, so it can be followed by other messages
     pub async fn write_large_text(
         &mut self,
         text: impl Into<String>,
     ) -> Result<(), TcpTargetError> {
-        let text = text.into();
-        let bytes = text.as_bytes();
-        let mut offset = 0;
-
-        while offset < bytes.len() {
-            let chunk = &bytes[offset..];
-            let written = match self.stream.write(chunk).await {
-                Ok(n) => n,
-                Err(err) => return Err(TcpTargetError::Io(err.to_string())),
-            };
-            offset += written;
-        }
+        trace_message("write", "large_text", async {
+            let text = text.into();
+            let bytes = text.as_bytes();
+            let len = bytes.len() as u64;
+
+            // Write total length first
+            self.write_length(len).await?;
+
+            // Write data in chunks
+            let mut offset = 0;
+            while offset < bytes.len() {
+                let end = std::cmp::min(offset + self.config.chunk_size, bytes.len());
+                let chunk = &bytes[offset..end];
+                match self.stream.write(chunk).await {
+                    Ok(n) => offset += n,
+                    Err(err) => return Err(TcpTargetError::Io(err.to_string())),
+                }
+            }
+            trace_message_size(len);
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// Read large text from the target machine (chunked)
@@ -214,21 +676,30 @@ impl ConnectionInstance {
         &mut self,
         chunk_size: impl Into<u32>,
     ) -> Result<String, TcpTargetError> {
-        let chunk_size = chunk_size.into() as usize;
-        let mut buffer = Vec::new();
-        let mut chunk_buf = vec![0; chunk_size];
-
-        loop {
-            match self.stream.read(&mut chunk_buf).await {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    buffer.extend_from_slice(&chunk_buf[..n]);
-                }
-                Err(err) => return Err(TcpTargetError::Io(err.to_string())),
+        trace_message("read", "large_text", async {
+            let chunk_size = chunk_size.into() as usize;
+
+            // Read total length first
+            let total_len = self.read_length().await?;
+
+            // Read data in chunks
+            let mut buffer = Vec::with_capacity(total_len);
+            let mut remaining = total_len;
+            let mut chunk_buf = vec![0; chunk_size];
+
+            while remaining > 0 {
+                let read_size = std::cmp::min(chunk_size, remaining);
+                let chunk = &mut chunk_buf[..read_size];
+
+                self.read_exact_timeout(chunk).await?;
+                buffer.extend_from_slice(chunk);
+                remaining -= read_size;
             }
-        }
+            trace_message_size(total_len as u64);
 
-        Ok(String::from_utf8_lossy(&buffer).to_string())
+            Ok(String::from_utf8_lossy(&buffer).to_string())
+        })
+        .await
     }
 
     /// Write large MessagePack data to the target machine (chunked)
@@ -240,25 +711,30 @@ impl ConnectionInstance {
     where
         Data: Serialize,
     {
-        let msgpack_data = rmp_serde::to_vec(&data)?;
-        let chunk_size = chunk_size.into() as usize;
-        let len = msgpack_data.len() as u32;
-
-        // Write total length first
-        self.stream.write_all(&len.to_be_bytes()).await?;
-
-        // Write data in chunks
-        let mut offset = 0;
-        while offset < msgpack_data.len() {
-            let end = std::cmp::min(offset + chunk_size, msgpack_data.len());
-            let chunk = &msgpack_data[offset..end];
-            match self.stream.write(chunk).await {
-                Ok(n) => offset += n,
-                Err(err) => return Err(TcpTargetError::Io(err.to_string())),
+        trace_message("write", "large_msgpack", async {
+            let msgpack_data = rmp_serde::to_vec(&data)?;
+            let chunk_size = chunk_size.into() as usize;
+            let len = msgpack_data.len() as u64;
+
+            // Write total length first
+            self.write_length(len).await?;
+
+            // Write data in chunks
+            let mut offset = 0;
+            while offset < msgpack_data.len() {
+                let end = std::cmp::min(offset + chunk_size, msgpack_data.len());
+                let chunk = &msgpack_data[offset..end];
+                self.throttle_write(chunk.len()).await;
+                match self.stream.write_all(chunk).await {
+                    Ok(_) => offset = end,
+                    Err(err) => return Err(TcpTargetError::Io(err.to_string())),
+                }
             }
-        }
+            trace_message_size(len);
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// Read large MessagePack data from the target machine (chunked)
@@ -269,274 +745,361 @@ impl ConnectionInstance {
     where
         Data: serde::de::DeserializeOwned,
     {
-        let chunk_size = chunk_size.into() as usize;
+        trace_message("read", "large_msgpack", async {
+            let chunk_size = chunk_size.into() as usize;
 
-        // Read total length first
-        let mut len_buf = [0u8; 4];
-        self.stream.read_exact(&mut len_buf).await?;
-        let total_len = u32::from_be_bytes(len_buf) as usize;
+            // Read total length first
+            let total_len = self.read_length().await?;
 
-        // Read data in chunks
-        let mut buffer = Vec::with_capacity(total_len);
-        let mut remaining = total_len;
-        let mut chunk_buf = vec![0; chunk_size];
+            // Read data in chunks
+            let mut buffer = Vec::with_capacity(total_len);
+            let mut remaining = total_len;
+            let mut chunk_buf = vec![0; chunk_size];
 
-        while remaining > 0 {
-            let read_size = std::cmp::min(chunk_size, remaining);
-            let chunk = &mut chunk_buf[..read_size];
+            while remaining > 0 {
+                let read_size = std::cmp::min(chunk_size, remaining);
+                let chunk = &mut chunk_buf[..read_size];
 
-            match self.stream.read_exact(chunk).await {
-                Ok(_) => {
-                    buffer.extend_from_slice(chunk);
-                    remaining -= read_size;
-                }
-                Err(err) => return Err(TcpTargetError::Io(err.to_string())),
+                self.read_exact_timeout(chunk).await?;
+                buffer.extend_from_slice(chunk);
+                remaining -= read_size;
             }
-        }
+            trace_message_size(total_len as u64);
 
-        let data = rmp_serde::from_slice(&buffer)?;
-        Ok(data)
+            let data = rmp_serde::from_slice(&buffer)?;
+            Ok(data)
+        })
+        .await
     }
 
     /// Write file to target machine.
     pub async fn write_file(&mut self, file_path: impl AsRef<Path>) -> Result<(), TcpTargetError> {
-        let path = file_path.as_ref();
-
-        // Validate file
-        if !path.exists() {
-            return Err(TcpTargetError::File(format!(
-                "File not found: {}",
-                path.display()
-            )));
-        }
-        if path.is_dir() {
-            return Err(TcpTargetError::File(format!(
-                "Path is directory: {}",
-                path.display()
-            )));
-        }
+        self.write_file_with_progress(file_path, &mut |_, _| {})
+            .await
+    }
 
-        // Open file and get metadata
-        let mut file = File::open(path).await?;
-        let file_size = file.metadata().await?.len();
-
-        // Send file header (version + size + crc)
-        self.stream.write_all(&1u64.to_be_bytes()).await?;
-        self.stream.write_all(&file_size.to_be_bytes()).await?;
-
-        // Calculate and send CRC32 if enabled
-        let file_crc = if self.config.enable_crc_validation {
-            let crc32 = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
-            let mut crc_calculator = crc32.digest();
-
-            let mut temp_reader =
-                BufReader::with_capacity(self.config.chunk_size, File::open(path).await?);
-            let mut temp_buffer = vec![0u8; self.config.chunk_size];
-            let mut temp_bytes_read = 0;
-
-            while temp_bytes_read < file_size {
-                let bytes_to_read =
-                    (file_size - temp_bytes_read).min(self.config.chunk_size as u64) as usize;
-                temp_reader
-                    .read_exact(&mut temp_buffer[..bytes_to_read])
-                    .await?;
-                crc_calculator.update(&temp_buffer[..bytes_to_read]);
-                temp_bytes_read += bytes_to_read as u64;
-            }
+    /// Write file to target machine, reporting `(bytes_done, total)` after each chunk
+    pub async fn write_file_with_progress(
+        &mut self,
+        file_path: impl AsRef<Path>,
+        progress: &mut impl FnMut(u64, u64),
+    ) -> Result<(), TcpTargetError> {
+        trace_message("write", "file", async {
+            let path = file_path.as_ref();
 
-            crc_calculator.finalize()
-        } else {
-            0
-        };
+            // Validate file
+            if !path.exists() {
+                return Err(TcpTargetError::File(format!(
+                    "File not found: {}",
+                    path.display()
+                )));
+            }
+            if path.is_dir() {
+                return Err(TcpTargetError::File(format!(
+                    "Path is directory: {}",
+                    path.display()
+                )));
+            }
 
-        self.stream.write_all(&file_crc.to_be_bytes()).await?;
+            // Open file and get metadata
+            let mut file = File::open(path).await?;
+            let file_size = file.metadata().await?.len();
 
-        // If file size is 0, skip content transfer
-        if file_size == 0 {
-            self.stream.flush().await?;
+            // Send file header (version + size + crc [+ compression])
+            let compression = self.config.compression;
+            let version = match compression {
+                Some(_) => FILE_TRANSFER_VERSION_COMPRESSED,
+                None => FILE_TRANSFER_VERSION,
+            };
+            self.stream.write_all(&version.to_be_bytes()).await?;
+            self.stream.write_all(&file_size.to_be_bytes()).await?;
+
+            // Calculate and send CRC32 if enabled
+            let file_crc = if self.config.enable_crc_validation {
+                let crc32 = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+                let mut crc_calculator = crc32.digest();
+
+                let mut temp_reader =
+                    BufReader::with_capacity(self.config.chunk_size, File::open(path).await?);
+                let mut temp_buffer = vec![0u8; self.config.chunk_size];
+                let mut temp_bytes_read = 0;
+
+                while temp_bytes_read < file_size {
+                    let bytes_to_read =
+                        (file_size - temp_bytes_read).min(self.config.chunk_size as u64) as usize;
+                    temp_reader
+                        .read_exact(&mut temp_buffer[..bytes_to_read])
+                        .await?;
+                    crc_calculator.update(&temp_buffer[..bytes_to_read]);
+                    temp_bytes_read += bytes_to_read as u64;
+                }
 
-            // Wait for receiver confirmation
-            let mut ack = [0u8; 1];
-            tokio::time::timeout(
-                Duration::from_secs(self.config.timeout_secs),
-                self.stream.read_exact(&mut ack),
-            )
-            .await
-            .map_err(|_| TcpTargetError::Timeout("Ack timeout".to_string()))??;
+                crc_calculator.finalize()
+            } else {
+                0
+            };
 
-            if ack[0] != 1 {
-                return Err(TcpTargetError::Protocol(
-                    "Receiver verification failed".to_string(),
-                ));
+            self.stream.write_all(&file_crc.to_be_bytes()).await?;
+            if let Some(compression) = compression {
+                self.stream.write_all(&[compression.flag()]).await?;
             }
 
-            return Ok(());
-        }
+            // If file size is 0, skip content transfer
+            if file_size == 0 {
+                self.stream.flush().await?;
 
-        // Transfer file content
-        let mut reader = BufReader::with_capacity(self.config.chunk_size, &mut file);
-        let mut bytes_sent = 0;
+                // Wait for receiver confirmation
+                let mut ack = [0u8; 1];
+                self.read_exact_timeout(&mut ack).await?;
 
-        while bytes_sent < file_size {
-            let buffer = reader.fill_buf().await?;
-            if buffer.is_empty() {
-                break;
+                if ack[0] != 1 {
+                    return Err(TcpTargetError::Protocol(
+                        "Receiver verification failed".to_string(),
+                    ));
+                }
+                progress(0, 0);
+                trace_message_size(0);
+
+                return Ok(());
             }
 
-            let chunk_size = buffer.len().min((file_size - bytes_sent) as usize);
-            self.stream.write_all(&buffer[..chunk_size]).await?;
-            reader.consume(chunk_size);
+            // Transfer file content
+            let mut reader = BufReader::with_capacity(self.config.chunk_size, &mut file);
+            let mut bytes_sent = 0;
 
-            bytes_sent += chunk_size as u64;
-        }
+            while bytes_sent < file_size {
+                self.check_cancelled()?;
+                let buffer = reader.fill_buf().await?;
+                if buffer.is_empty() {
+                    break;
+                }
 
-        // Verify transfer completion
-        if bytes_sent != file_size {
-            return Err(TcpTargetError::File(format!(
-                "Transfer incomplete: expected {} bytes, sent {} bytes",
-                file_size, bytes_sent
-            )));
-        }
+                let chunk_size = buffer.len().min((file_size - bytes_sent) as usize);
+                match compression {
+                    // Compressed chunks are framed with their length
+                    Some(compression) => {
+                        let compressed = compression.compress(&buffer[..chunk_size])?;
+                        self.throttle_write(compressed.len() + 4).await;
+                        self.stream
+                            .write_all(&(compressed.len() as u32).to_be_bytes())
+                            .await?;
+                        self.stream.write_all(&compressed).await?;
+                    }
+                    None => {
+                        self.throttle_write(chunk_size).await;
+                        self.stream.write_all(&buffer[..chunk_size]).await?
+                    }
+                }
+                reader.consume(chunk_size);
 
-        self.stream.flush().await?;
+                bytes_sent += chunk_size as u64;
+                progress(bytes_sent, file_size);
+            }
 
-        // Wait for receiver confirmation
-        let mut ack = [0u8; 1];
-        tokio::time::timeout(
-            Duration::from_secs(self.config.timeout_secs),
-            self.stream.read_exact(&mut ack),
-        )
-        .await
-        .map_err(|_| TcpTargetError::Timeout("Ack timeout".to_string()))??;
+            // Verify transfer completion
+            if bytes_sent != file_size {
+                return Err(TcpTargetError::File(format!(
+                    "Transfer incomplete: expected {} bytes, sent {} bytes",
+                    file_size, bytes_sent
+                )));
+            }
 
-        if ack[0] != 1 {
-            return Err(TcpTargetError::Protocol(
-                "Receiver verification failed".to_string(),
-            ));
-        }
+            self.stream.flush().await?;
 
-        Ok(())
+            // Wait for receiver confirmation
+            let mut ack = [0u8; 1];
+            self.read_exact_timeout(&mut ack).await?;
+
+            if ack[0] != 1 {
+                return Err(TcpTargetError::Protocol(
+                    "Receiver verification failed".to_string(),
+                ));
+            }
+            trace_message_size(file_size);
+
+            Ok(())
+        })
+        .await
     }
 
     /// Read file from target machine
     pub async fn read_file(&mut self, save_path: impl AsRef<Path>) -> Result<(), TcpTargetError> {
-        let path = save_path.as_ref();
-        // Create CRC instance at function scope to ensure proper lifetime
-        let crc_instance = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
-
-        // Make sure parent directory exists
-        if let Some(parent) = path.parent()
-            && !parent.exists()
-        {
-            tokio::fs::create_dir_all(parent).await?;
-        }
+        self.read_file_with_progress(save_path, &mut |_, _| {})
+            .await
+    }
 
-        // Read file header (version + size + crc)
-        let mut version_buf = [0u8; 8];
-        self.stream.read_exact(&mut version_buf).await?;
-        let version = u64::from_be_bytes(version_buf);
-        if version != 1 {
-            return Err(TcpTargetError::Protocol(
-                "Unsupported transfer version".to_string(),
-            ));
-        }
+    /// Read file from target machine, reporting `(bytes_done, total)` after each chunk
+    pub async fn read_file_with_progress(
+        &mut self,
+        save_path: impl AsRef<Path>,
+        progress: &mut impl FnMut(u64, u64),
+    ) -> Result<(), TcpTargetError> {
+        trace_message("read", "file", async {
+            let path = save_path.as_ref();
+            // Create CRC instance at function scope to ensure proper lifetime
+            let crc_instance = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+            // Read file header (version + size + crc)
+            let mut version_buf = [0u8; 8];
+            self.read_exact_timeout(&mut version_buf).await?;
+            let version = u64::from_be_bytes(version_buf);
+            if version != FILE_TRANSFER_VERSION && version != FILE_TRANSFER_VERSION_COMPRESSED {
+                return Err(TcpTargetError::Protocol(
+                    "Unsupported transfer version".to_string(),
+                ));
+            }
+
+            let mut size_buf = [0u8; 8];
+            self.read_exact_timeout(&mut size_buf).await?;
+            let file_size = u64::from_be_bytes(size_buf);
+            self.check_file_size(file_size)?;
+
+            let mut expected_crc_buf = [0u8; 4];
+            self.read_exact_timeout(&mut expected_crc_buf).await?;
+            let expected_crc = u32::from_be_bytes(expected_crc_buf);
+
+            let compression = if version == FILE_TRANSFER_VERSION_COMPRESSED {
+                let mut flag_buf = [0u8; 1];
+                self.read_exact_timeout(&mut flag_buf).await?;
+                Compression::from_flag(flag_buf[0])?
+            } else {
+                None
+            };
+
+            // Make sure parent directory exists
+            if let Some(parent) = path.parent()
+                && !parent.exists()
+            {
+                tokio::fs::create_dir_all(parent).await?;
+            }
 
-        let mut size_buf = [0u8; 8];
-        self.stream.read_exact(&mut size_buf).await?;
-        let file_size = u64::from_be_bytes(size_buf);
+            if file_size == 0 {
+                // Create empty file and return early
+                let _file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(path)
+                    .await?;
+                // Send confirmation
+                self.stream.write_all(&[1u8]).await?;
+                self.stream.flush().await?;
+                progress(0, 0);
+                trace_message_size(0);
+                return Ok(());
+            }
 
-        let mut expected_crc_buf = [0u8; 4];
-        self.stream.read_exact(&mut expected_crc_buf).await?;
-        let expected_crc = u32::from_be_bytes(expected_crc_buf);
-        if file_size == 0 {
-            // Create empty file and return early
-            let _file = OpenOptions::new()
+            // Prepare output file
+            let file = OpenOptions::new()
                 .write(true)
                 .create(true)
                 .truncate(true)
                 .open(path)
                 .await?;
-            // Send confirmation
-            self.stream.write_all(&[1u8]).await?;
-            self.stream.flush().await?;
-            return Ok(());
-        }
-
-        // Prepare output file
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(path)
-            .await?;
-        let mut writer = BufWriter::with_capacity(self.config.chunk_size, file);
-
-        // Receive file content with CRC calculation if enabled
-        let mut bytes_received = 0;
-        let mut buffer = vec![0u8; self.config.chunk_size];
-        let mut crc_calculator = if self.config.enable_crc_validation {
-            Some(crc_instance.digest())
-        } else {
-            None
-        };
-
-        while bytes_received < file_size {
-            let bytes_to_read =
-                (file_size - bytes_received).min(self.config.chunk_size as u64) as usize;
-            let chunk = &mut buffer[..bytes_to_read];
-
-            self.stream.read_exact(chunk).await?;
+            let mut writer = BufWriter::with_capacity(self.config.chunk_size, file);
+
+            // Receive file content with CRC calculation if enabled
+            let mut bytes_received = 0;
+            let mut buffer = vec![0u8; self.config.chunk_size];
+            let mut crc_calculator = if self.config.enable_crc_validation {
+                Some(crc_instance.digest())
+            } else {
+                None
+            };
 
-            writer.write_all(chunk).await?;
+            while bytes_received < file_size {
+                self.check_cancelled()?;
+                let chunk: &[u8] = match compression {
+                    Some(compression) => {
+                        let mut len_buf = [0u8; 4];
+                        self.read_exact_timeout(&mut len_buf).await?;
+
+                        // A chunk holds at most `chunk_size` bytes before compression
+                        let len = u32::from_be_bytes(len_buf) as usize;
+                        if len > compression.compress_bound(self.config.chunk_size) {
+                            return Err(TcpTargetError::Protocol(format!(
+                                "Compressed chunk of {} bytes exceeds the chunk size {}",
+                                len, self.config.chunk_size
+                            )));
+                        }
+                        let mut compressed = vec![0u8; len];
+                        self.read_exact_timeout(&mut compressed).await?;
+
+                        // One byte past the rest is enough to detect an overflow
+                        let remaining = file_size - bytes_received;
+                        buffer = compression.decompress(&compressed, remaining + 1)?;
+                        if buffer.len() as u64 > remaining {
+                            return Err(TcpTargetError::File(format!(
+                                "Transfer overflow: expected {} bytes",
+                                file_size
+                            )));
+                        }
+                        &buffer
+                    }
+                    None => {
+                        let bytes_to_read = (file_size - bytes_received)
+                            .min(self.config.chunk_size as u64)
+                            as usize;
+                        let chunk = &mut buffer[..bytes_to_read];
+                        self.read_exact_timeout(chunk).await?;
+                        chunk
+                    }
+                };
+
+                writer.write_all(chunk).await?;
+
+                // Update CRC over the original bytes if validation is enabled
+                if let Some(ref mut crc) = crc_calculator {
+                    crc.update(chunk);
+                }
 
-            // Update CRC if validation is enabled
-            if let Some(ref mut crc) = crc_calculator {
-                crc.update(chunk);
+                bytes_received += chunk.len() as u64;
+                progress(bytes_received, file_size);
             }
 
-            bytes_received += bytes_to_read as u64;
-        }
+            // Verify transfer completion
+            if bytes_received != file_size {
+                return Err(TcpTargetError::File(format!(
+                    "Transfer incomplete: expected {} bytes, received {} bytes",
+                    file_size, bytes_received
+                )));
+            }
 
-        // Verify transfer completion
-        if bytes_received != file_size {
-            return Err(TcpTargetError::File(format!(
-                "Transfer incomplete: expected {} bytes, received {} bytes",
-                file_size, bytes_received
-            )));
-        }
+            writer.flush().await?;
+
+            // Validate CRC if enabled
+            if self.config.enable_crc_validation
+                && let Some(crc_calculator) = crc_calculator
+            {
+                let actual_crc = crc_calculator.finalize();
+                if actual_crc != expected_crc && expected_crc != 0 {
+                    return Err(TcpTargetError::File(format!(
+                        "CRC validation failed: expected {:08x}, got {:08x}",
+                        expected_crc, actual_crc
+                    )));
+                }
+            }
 
-        writer.flush().await?;
+            // Final flush and sync
+            writer.flush().await?;
+            writer.into_inner().sync_all().await?;
 
-        // Validate CRC if enabled
-        if self.config.enable_crc_validation
-            && let Some(crc_calculator) = crc_calculator
-        {
-            let actual_crc = crc_calculator.finalize();
-            if actual_crc != expected_crc && expected_crc != 0 {
+            // Verify completion
+            if bytes_received != file_size {
+                let _ = tokio::fs::remove_file(path).await;
                 return Err(TcpTargetError::File(format!(
-                    "CRC validation failed: expected {:08x}, got {:08x}",
-                    expected_crc, actual_crc
+                    "Transfer incomplete: expected {} bytes, received {} bytes",
+                    file_size, bytes_received
                 )));
             }
-        }
 
-        // Final flush and sync
-        writer.flush().await?;
-        writer.into_inner().sync_all().await?;
-
-        // Verify completion
-        if bytes_received != file_size {
-            let _ = tokio::fs::remove_file(path).await;
-            return Err(TcpTargetError::File(format!(
-                "Transfer incomplete: expected {} bytes, received {} bytes",
-                file_size, bytes_received
-            )));
-        }
-
-        // Send confirmation
-        self.stream.write_all(&[1u8]).await?;
-        self.stream.flush().await?;
+            // Send confirmation
+            self.stream.write_all(&[1u8]).await?;
+            self.stream.flush().await?;
+            trace_message_size(file_size);
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 }
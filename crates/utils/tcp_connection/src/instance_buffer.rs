@@ -0,0 +1,181 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{error::TcpTargetError, instance::ConnectionInstance, instance_tls::ConnectionStream};
+
+/// Writes smaller than this are coalesced while write buffering is enabled
+const WRITE_BUFFER_CAPACITY: usize = 8192;
+
+/// Stream of a `ConnectionInstance`, optionally coalescing small writes
+///
+/// Buffered bytes are sent on flush, before every read,
+/// or once the buffer would overflow
+pub(crate) struct BufferedStream {
+    inner: ConnectionStream,
+    buffer: Option<Vec<u8>>,
+    written: usize,
+}
+
+impl BufferedStream {
+    pub(crate) fn new(inner: ConnectionStream) -> Self {
+        Self {
+            inner,
+            buffer: None,
+            written: 0,
+        }
+    }
+
+    /// Get a reference to the underlying stream
+    pub(crate) fn get_ref(&self) -> &ConnectionStream {
+        &self.inner
+    }
+
+    fn has_pending(&self) -> bool {
+        self.buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.len() > self.written)
+    }
+
+    /// Write out all buffered bytes, without flushing the underlying stream
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let Some(buffer) = self.buffer.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        while self.written < buffer.len() {
+            match Pin::new(&mut self.inner).poll_write(cx, &buffer[self.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "Failed to write buffered data",
+                    )));
+                }
+                Poll::Ready(Ok(n)) => self.written += n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        buffer.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for BufferedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        // The peer may be waiting for the buffered request before it answers
+        if this.has_pending() {
+            std::task::ready!(this.poll_drain(cx))?;
+            std::task::ready!(Pin::new(&mut this.inner).poll_flush(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for BufferedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let Some(buffered) = this.buffer.as_ref() else {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        };
+
+        if buffered.len() + buf.len() > WRITE_BUFFER_CAPACITY {
+            std::task::ready!(this.poll_drain(cx))?;
+        }
+
+        // Large writes gain nothing from coalescing
+        if buf.len() >= WRITE_BUFFER_CAPACITY {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+
+        if let Some(buffer) = this.buffer.as_mut() {
+            buffer.extend_from_slice(buf);
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        std::task::ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        std::task::ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+impl ConnectionInstance {
+    /// Enable or disable coalescing of small writes
+    ///
+    /// While enabled, small messages are collected and sent together on `flush`,
+    /// before the next read, or once the buffer fills up.
+    /// Disabling sends whatever is still buffered
+    pub async fn set_write_buffering(&mut self, enabled: bool) -> Result<(), TcpTargetError> {
+        if enabled {
+            if self.stream.buffer.is_none() {
+                self.stream.buffer = Some(Vec::with_capacity(WRITE_BUFFER_CAPACITY));
+            }
+        } else {
+            self.flush().await?;
+            self.stream.buffer = None;
+        }
+        Ok(())
+    }
+
+    /// Check whether small writes are currently coalesced
+    pub fn is_write_buffering(&self) -> bool {
+        self.stream.buffer.is_some()
+    }
+
+    /// Send all buffered writes to the target machine
+    pub async fn flush(&mut self) -> Result<(), TcpTargetError> {
+        use tokio::io::AsyncWriteExt;
+
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Check whether `TCP_NODELAY` is set on the underlying socket
+    pub fn nodelay(&self) -> Result<bool, TcpTargetError> {
+        match self.stream.get_ref() {
+            ConnectionStream::Plain(stream) => Ok(stream.nodelay()?),
+            ConnectionStream::Tls(stream) => Ok(stream.get_ref().0.nodelay()?),
+            ConnectionStream::Heartbeat(_) => Err(TcpTargetError::Unsupported(
+                "TCP_NODELAY cannot be read on a heartbeat connection".to_string(),
+            )),
+        }
+    }
+
+    /// Set `TCP_NODELAY` on the underlying socket
+    ///
+    /// Not supported on heartbeat connections, whose socket is owned by the heartbeat task
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<(), TcpTargetError> {
+        match self.stream.get_ref() {
+            ConnectionStream::Plain(stream) => stream.set_nodelay(nodelay)?,
+            ConnectionStream::Tls(stream) => stream.get_ref().0.set_nodelay(nodelay)?,
+            ConnectionStream::Heartbeat(_) => {
+                return Err(TcpTargetError::Unsupported(
+                    "TCP_NODELAY cannot be set on a heartbeat connection".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -4,15 +4,19 @@ use rand::TryRngCore;
 use rsa::{
     RsaPrivateKey, RsaPublicKey,
     pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{
+        AlgorithmIdentifierRef, DecodePrivateKey, DecodePublicKey, ObjectIdentifier,
+        PrivateKeyInfo, SubjectPublicKeyInfoRef,
+    },
     sha2,
+    traits::PublicKeyParts,
 };
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use ring::rand::SystemRandom;
 use ring::signature::{
-    self, ECDSA_P256_SHA256_ASN1, ECDSA_P384_SHA384_ASN1, EcdsaKeyPair, RSA_PKCS1_2048_8192_SHA256,
-    UnparsedPublicKey,
+    self, ECDSA_P256_SHA256_ASN1, ECDSA_P384_SHA384_ASN1, EcdsaKeyPair, UnparsedPublicKey,
 };
 
 use crate::{error::TcpTargetError, instance::ConnectionInstance};
@@ -22,9 +26,191 @@ const ECDSA_P256_SHA256_ASN1_SIGNING: &signature::EcdsaSigningAlgorithm =
 const ECDSA_P384_SHA384_ASN1_SIGNING: &signature::EcdsaSigningAlgorithm =
     &signature::ECDSA_P384_SHA384_ASN1_SIGNING;
 
+/// Object identifiers of the supported key types
+const OID_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const OID_ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+const OID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const OID_P256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const OID_P384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+
+/// Size of an uncompressed EC point of each curve
+const P256_POINT_LEN: usize = 65;
+const P384_POINT_LEN: usize = 97;
+
+/// Signature algorithm of a challenge key
+///
+/// The challenge response carries the algorithm as a one-byte tag,
+/// the verifier only accepts it if it matches the type of the public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyAlgorithm {
+    /// RSA PKCS#1 v1.5 with SHA-256
+    Rsa,
+
+    /// Ed25519
+    Ed25519,
+
+    /// ECDSA on P-256 with SHA-256
+    EcdsaP256,
+
+    /// ECDSA on P-384 with SHA-384
+    EcdsaP384,
+}
+
+impl KeyAlgorithm {
+    /// Tag of the algorithm in the challenge response
+    pub fn tag(self) -> u8 {
+        match self {
+            KeyAlgorithm::Rsa => 1,
+            KeyAlgorithm::Ed25519 => 2,
+            KeyAlgorithm::EcdsaP256 => 3,
+            KeyAlgorithm::EcdsaP384 => 4,
+        }
+    }
+
+    /// Get the algorithm of a tag, None if the tag is unknown
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(KeyAlgorithm::Rsa),
+            2 => Some(KeyAlgorithm::Ed25519),
+            3 => Some(KeyAlgorithm::EcdsaP256),
+            4 => Some(KeyAlgorithm::EcdsaP384),
+            _ => None,
+        }
+    }
+
+    /// Detect the algorithm of a public key in PEM format
+    pub fn of_public_key(pem: &str) -> Option<Self> {
+        let pem = pem::parse(pem).ok()?;
+        match pem.tag() {
+            "RSA PUBLIC KEY" => Some(KeyAlgorithm::Rsa),
+            "PUBLIC KEY" => {
+                let spki = SubjectPublicKeyInfoRef::try_from(pem.contents()).ok()?;
+                Self::of_algorithm_identifier(&spki.algorithm)
+            }
+            _ => None,
+        }
+    }
+
+    /// Detect the algorithm of a private key in PEM format
+    pub fn of_private_key(pem: &str) -> Option<Self> {
+        let pem = pem::parse(pem).ok()?;
+        match pem.tag() {
+            "RSA PRIVATE KEY" => Some(KeyAlgorithm::Rsa),
+            "PRIVATE KEY" => {
+                let info = PrivateKeyInfo::try_from(pem.contents()).ok()?;
+                Self::of_algorithm_identifier(&info.algorithm)
+            }
+            _ => None,
+        }
+    }
+
+    /// Detect the algorithm from the AlgorithmIdentifier of a PKCS#8 or SubjectPublicKeyInfo document
+    fn of_algorithm_identifier(algorithm: &AlgorithmIdentifierRef<'_>) -> Option<Self> {
+        if algorithm.oid == OID_RSA {
+            Some(KeyAlgorithm::Rsa)
+        } else if algorithm.oid == OID_ED25519 {
+            Some(KeyAlgorithm::Ed25519)
+        } else if algorithm.oid == OID_EC_PUBLIC_KEY {
+            // The curve is the parameter of the identifier
+            let curve = algorithm.parameters_oid().ok()?;
+            if curve == OID_P256 {
+                Some(KeyAlgorithm::EcdsaP256)
+            } else if curve == OID_P384 {
+                Some(KeyAlgorithm::EcdsaP384)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// List the algorithms of the public keys in `public_key_dir`, sorted and without duplicates
+///
+/// Files that are not `.pem` public keys of a supported type are ignored
+pub async fn supported_key_algorithms(
+    public_key_dir: impl AsRef<Path>,
+) -> Result<Vec<KeyAlgorithm>, TcpTargetError> {
+    let mut algorithms = Vec::new();
+    let mut entries = tokio::fs::read_dir(public_key_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "pem") || !path.is_file() {
+            continue;
+        }
+        let pem = tokio::fs::read_to_string(&path).await?;
+        if let Some(algorithm) = KeyAlgorithm::of_public_key(&pem) {
+            algorithms.push(algorithm);
+        }
+    }
+    algorithms.sort();
+    algorithms.dedup();
+    Ok(algorithms)
+}
+
+/// Outcome of `ConnectionInstance::challenge_outcome`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeOutcome {
+    /// The signature was verified with the public key of `key_id`
+    Verified { key_id: String },
+
+    /// The signature does not match the public key of `key_id`
+    Rejected { key_id: String },
+
+    /// No usable public key named `key_id` was found
+    UnknownKey { key_id: String },
+
+    /// The signature cannot belong to the type of the public key of `key_id`,
+    /// or its algorithm tag is unknown
+    MalformedSignature { key_id: String },
+
+    /// The advertised algorithm does not match the type of the public key of `key_id`
+    AlgorithmMismatch { key_id: String },
+
+    /// The public key directory does not exist
+    NoKeys,
+}
+
+impl ChallengeOutcome {
+    /// Check if the challenge was verified
+    pub fn is_verified(&self) -> bool {
+        matches!(self, ChallengeOutcome::Verified { .. })
+    }
+
+    /// Get the key identifier sent by the target machine, if the outcome carries it
+    pub fn key_id(&self) -> Option<&str> {
+        match self {
+            ChallengeOutcome::Verified { key_id }
+            | ChallengeOutcome::Rejected { key_id }
+            | ChallengeOutcome::UnknownKey { key_id }
+            | ChallengeOutcome::MalformedSignature { key_id }
+            | ChallengeOutcome::AlgorithmMismatch { key_id } => Some(key_id),
+            ChallengeOutcome::NoKeys => None,
+        }
+    }
+}
+
 impl ConnectionInstance {
     /// Initiates a challenge to the target machine to verify connection security
     ///
+    /// Same as `challenge_outcome`, but collapses every failure into `false`
+    ///
+    /// # Returns
+    /// * `Ok((true, "KeyId"))` - Challenge verification successful
+    /// * `Ok((false, "KeyId"))` - Challenge verification failed
+    /// * `Err(TcpTargetError)` - Error during challenge process
+    pub async fn challenge(
+        &mut self,
+        public_key_dir: impl AsRef<Path>,
+    ) -> Result<(bool, String), TcpTargetError> {
+        let outcome = self.challenge_outcome(public_key_dir).await?;
+        let key_id = outcome.key_id().unwrap_or_default().to_string();
+        Ok((outcome.is_verified(), key_id))
+    }
+
+    /// Initiates a challenge to the target machine and reports why it passed or failed
+    ///
     /// This method performs a cryptographic challenge-response authentication:
     /// 1. Generates a random 32-byte challenge
     /// 2. Sends the challenge to the target machine
@@ -35,13 +221,12 @@ impl ConnectionInstance {
     /// * `public_key_dir` - Directory containing public key files for verification
     ///
     /// # Returns
-    /// * `Ok((true, "KeyId"))` - Challenge verification successful
-    /// * `Ok((false, "KeyId"))` - Challenge verification failed
+    /// * `Ok(ChallengeOutcome)` - The challenge completed, see `ChallengeOutcome` for the result
     /// * `Err(TcpTargetError)` - Error during challenge process
-    pub async fn challenge(
+    pub async fn challenge_outcome(
         &mut self,
         public_key_dir: impl AsRef<Path>,
-    ) -> Result<(bool, String), TcpTargetError> {
+    ) -> Result<ChallengeOutcome, TcpTargetError> {
         // Generate random challenge
         let mut challenge = [0u8; 32];
         rand::rngs::OsRng
@@ -54,56 +239,59 @@ impl ConnectionInstance {
         self.stream.write_all(&challenge).await?;
         self.stream.flush().await?;
 
-        // Read signature from target
+        // Read algorithm tag and signature from target
+        let mut tag_buf = [0u8; 1];
+        self.read_exact_timeout(&mut tag_buf).await?;
+
         let mut signature = Vec::new();
         let mut signature_len_buf = [0u8; 4];
-        self.stream.read_exact(&mut signature_len_buf).await?;
+        self.read_exact_timeout(&mut signature_len_buf).await?;
 
         let signature_len = u32::from_be_bytes(signature_len_buf) as usize;
         signature.resize(signature_len, 0);
-        self.stream.read_exact(&mut signature).await?;
+        self.read_exact_timeout(&mut signature).await?;
 
         // Read key identifier from target to identify which public key to use
         let mut key_id_len_buf = [0u8; 4];
-        self.stream.read_exact(&mut key_id_len_buf).await?;
+        self.read_exact_timeout(&mut key_id_len_buf).await?;
         let key_id_len = u32::from_be_bytes(key_id_len_buf) as usize;
 
         let mut key_id_buf = vec![0u8; key_id_len];
-        self.stream.read_exact(&mut key_id_buf).await?;
+        self.read_exact_timeout(&mut key_id_buf).await?;
         let key_id = String::from_utf8(key_id_buf)
             .map_err(|e| TcpTargetError::Crypto(format!("Invalid key identifier: {}", e)))?;
 
         // Load appropriate public key
-        let public_key_path = public_key_dir.as_ref().join(format!("{}.pem", key_id));
+        let public_key_dir = public_key_dir.as_ref();
+        if !public_key_dir.is_dir() {
+            return Ok(ChallengeOutcome::NoKeys);
+        }
+        let public_key_path = public_key_dir.join(format!("{}.pem", key_id));
         if !public_key_path.exists() {
-            return Ok((false, key_id));
+            return Ok(ChallengeOutcome::UnknownKey { key_id });
         }
 
         let public_key_pem = tokio::fs::read_to_string(&public_key_path).await?;
-
-        // Try to verify with different key types
-        let verified = if let Ok(rsa_key) = RsaPublicKey::from_pkcs1_pem(&public_key_pem) {
-            let padding = rsa::pkcs1v15::Pkcs1v15Sign::new::<sha2::Sha256>();
-            rsa_key.verify(padding, &challenge, &signature).is_ok()
-        } else if let Ok(ed25519_key) =
-            VerifyingKey::from_bytes(&parse_ed25519_public_key(&public_key_pem))
-        {
-            if signature.len() == 64 {
-                let sig_bytes: [u8; 64] = signature.as_slice().try_into().map_err(|_| {
-                    TcpTargetError::Crypto("Invalid signature length for Ed25519".to_string())
-                })?;
-                let sig = Signature::from_bytes(&sig_bytes);
-                ed25519_key.verify(&challenge, &sig).is_ok()
-            } else {
-                false
-            }
-        } else if let Ok(dsa_key_info) = parse_dsa_public_key(&public_key_pem) {
-            verify_dsa_signature(&dsa_key_info, &challenge, &signature)
-        } else {
-            false
+        let Some(algorithm) = KeyAlgorithm::of_public_key(&public_key_pem) else {
+            // The key file exists but is not a supported public key
+            return Ok(ChallengeOutcome::UnknownKey { key_id });
         };
+        let Some(advertised) = KeyAlgorithm::from_tag(tag_buf[0]) else {
+            return Ok(ChallengeOutcome::MalformedSignature { key_id });
+        };
+        if advertised != algorithm {
+            return Ok(ChallengeOutcome::AlgorithmMismatch { key_id });
+        }
 
-        Ok((verified, key_id))
+        // Verify with the algorithm of the key
+        Ok(
+            match verify_signature(algorithm, &public_key_pem, &challenge, &signature) {
+                SignatureCheck::Valid => ChallengeOutcome::Verified { key_id },
+                SignatureCheck::Invalid => ChallengeOutcome::Rejected { key_id },
+                SignatureCheck::Malformed => ChallengeOutcome::MalformedSignature { key_id },
+                SignatureCheck::UnusableKey => ChallengeOutcome::UnknownKey { key_id },
+            },
+        )
     }
 
     /// Accepts a challenge from the target machine to verify connection security
@@ -111,7 +299,7 @@ impl ConnectionInstance {
     /// This method performs a cryptographic challenge-response authentication:
     /// 1. Receives a random 32-byte challenge from the target machine
     /// 2. Signs the challenge using the appropriate private key
-    /// 3. Sends the digital signature back to the target machine
+    /// 3. Sends the algorithm tag and the digital signature back to the target machine
     /// 4. Sends the key identifier for public key verification
     ///
     /// # Arguments
@@ -129,7 +317,7 @@ impl ConnectionInstance {
     ) -> Result<bool, TcpTargetError> {
         // Read challenge from initiator
         let mut challenge = [0u8; 32];
-        self.stream.read_exact(&mut challenge).await?;
+        self.read_exact_timeout(&mut challenge).await?;
 
         // Load private key
         let private_key_pem = tokio::fs::read_to_string(&private_key_file)
@@ -148,17 +336,14 @@ impl ConnectionInstance {
                 ))
             })?;
 
-        // Sign the challenge with supported key types
-        let signature = if let Ok(rsa_key) = RsaPrivateKey::from_pkcs1_pem(&private_key_pem) {
-            let padding = rsa::pkcs1v15::Pkcs1v15Sign::new::<sha2::Sha256>();
-            rsa_key.sign(padding, &challenge)?
-        } else if let Ok(ed25519_key) = parse_ed25519_private_key(&private_key_pem) {
-            ed25519_key.sign(&challenge).to_bytes().to_vec()
-        } else if let Ok(dsa_key_info) = parse_dsa_private_key(&private_key_pem) {
-            sign_with_dsa(&dsa_key_info, &challenge)?
-        } else {
+        // Sign the challenge with the algorithm of the key
+        let Some(algorithm) = KeyAlgorithm::of_private_key(&private_key_pem) else {
             return Ok(false);
         };
+        let signature = sign_challenge(algorithm, &private_key_pem, &challenge)?;
+
+        // Send algorithm tag
+        self.stream.write_all(&[algorithm.tag()]).await?;
 
         // Send signature length and signature
         let signature_len = signature.len() as u32;
@@ -179,133 +364,130 @@ impl ConnectionInstance {
     }
 }
 
-/// Parse Ed25519 public key from PEM format
-fn parse_ed25519_public_key(pem: &str) -> [u8; 32] {
-    // Robust parsing for Ed25519 public key using pem crate
-    let mut key_bytes = [0u8; 32];
-
-    if let Ok(pem_data) = pem::parse(pem)
-        && pem_data.tag() == "PUBLIC KEY"
-        && pem_data.contents().len() >= 32
-    {
-        let contents = pem_data.contents();
-        key_bytes.copy_from_slice(&contents[contents.len() - 32..]);
-    }
-    key_bytes
-}
-
-/// Parse Ed25519 private key from PEM format
-fn parse_ed25519_private_key(pem: &str) -> Result<SigningKey, TcpTargetError> {
-    if let Ok(pem_data) = pem::parse(pem)
-        && pem_data.tag() == "PRIVATE KEY"
-        && pem_data.contents().len() >= 32
-    {
-        let contents = pem_data.contents();
-        let mut seed = [0u8; 32];
-        seed.copy_from_slice(&contents[contents.len() - 32..]);
-        return Ok(SigningKey::from_bytes(&seed));
-    }
-    Err(TcpTargetError::Crypto(
-        "Invalid Ed25519 private key format".to_string(),
-    ))
+/// Result of checking a signature against a public key
+enum SignatureCheck {
+    Valid,
+    Invalid,
+    Malformed,
+    UnusableKey,
 }
 
-/// Parse DSA public key information from PEM
-fn parse_dsa_public_key(
-    pem: &str,
-) -> Result<(&'static dyn signature::VerificationAlgorithm, Vec<u8>), TcpTargetError> {
-    if let Ok(pem_data) = pem::parse(pem) {
-        let contents = pem_data.contents().to_vec();
-
-        // Try different DSA algorithms based on PEM tag
-        match pem_data.tag() {
-            "EC PUBLIC KEY" | "PUBLIC KEY" if pem.contains("ECDSA") || pem.contains("ecdsa") => {
-                if pem.contains("P-256") {
-                    return Ok((&ECDSA_P256_SHA256_ASN1, contents));
-                } else if pem.contains("P-384") {
-                    return Ok((&ECDSA_P384_SHA384_ASN1, contents));
-                }
+/// Verify a signature of `message` with a public key of the given algorithm
+fn verify_signature(
+    algorithm: KeyAlgorithm,
+    public_key_pem: &str,
+    message: &[u8],
+    signature: &[u8],
+) -> SignatureCheck {
+    let check = |valid: bool| {
+        if valid {
+            SignatureCheck::Valid
+        } else {
+            SignatureCheck::Invalid
+        }
+    };
+
+    match algorithm {
+        KeyAlgorithm::Rsa => {
+            let Ok(key) = RsaPublicKey::from_pkcs1_pem(public_key_pem)
+                .or_else(|_| RsaPublicKey::from_public_key_pem(public_key_pem))
+            else {
+                return SignatureCheck::UnusableKey;
+            };
+            if signature.len() != key.size() {
+                return SignatureCheck::Malformed;
+            }
+            let padding = rsa::pkcs1v15::Pkcs1v15Sign::new::<sha2::Sha256>();
+            check(key.verify(padding, message, signature).is_ok())
+        }
+        KeyAlgorithm::Ed25519 => {
+            let Some(key_bytes) = pem_tail::<32>(public_key_pem) else {
+                return SignatureCheck::UnusableKey;
+            };
+            let Ok(key) = VerifyingKey::from_bytes(&key_bytes) else {
+                return SignatureCheck::UnusableKey;
+            };
+            let Ok(sig_bytes) = <[u8; 64]>::try_from(signature) else {
+                return SignatureCheck::Malformed;
+            };
+            check(
+                key.verify(message, &Signature::from_bytes(&sig_bytes))
+                    .is_ok(),
+            )
+        }
+        KeyAlgorithm::EcdsaP256 | KeyAlgorithm::EcdsaP384 => {
+            let (verification, point_len) = match algorithm {
+                KeyAlgorithm::EcdsaP256 => (&ECDSA_P256_SHA256_ASN1, P256_POINT_LEN),
+                _ => (&ECDSA_P384_SHA384_ASN1, P384_POINT_LEN),
+            };
+            let Ok(pem) = pem::parse(public_key_pem) else {
+                return SignatureCheck::UnusableKey;
+            };
+            let contents = pem.contents();
+            if contents.len() < point_len {
+                return SignatureCheck::UnusableKey;
             }
-            "RSA PUBLIC KEY" | "PUBLIC KEY" => {
-                return Ok((&RSA_PKCS1_2048_8192_SHA256, contents));
+            if signature.is_empty() {
+                return SignatureCheck::Malformed;
             }
-            _ => {}
+            let point = &contents[contents.len() - point_len..];
+            check(
+                UnparsedPublicKey::new(verification, point)
+                    .verify(message, signature)
+                    .is_ok(),
+            )
         }
-
-        // Default to RSA for unknown types
-        return Ok((&RSA_PKCS1_2048_8192_SHA256, contents));
     }
-    Err(TcpTargetError::Crypto(
-        "Invalid DSA public key format".to_string(),
-    ))
-}
-
-/// Parse DSA private key information from PEM
-fn parse_dsa_private_key(
-    pem: &str,
-) -> Result<(&'static dyn signature::VerificationAlgorithm, Vec<u8>), TcpTargetError> {
-    // For DSA, private key verification uses the same algorithm as public key
-    parse_dsa_public_key(pem)
 }
 
-/// Verify DSA signature
-fn verify_dsa_signature(
-    algorithm_and_key: &(&'static dyn signature::VerificationAlgorithm, Vec<u8>),
-    message: &[u8],
-    signature: &[u8],
-) -> bool {
-    let (algorithm, key_bytes) = algorithm_and_key;
-    let public_key = UnparsedPublicKey::new(*algorithm, key_bytes);
-    public_key.verify(message, signature).is_ok()
-}
-
-/// Sign with DSA
-fn sign_with_dsa(
-    algorithm_and_key: &(&'static dyn signature::VerificationAlgorithm, Vec<u8>),
+/// Sign `message` with a private key of the given algorithm
+fn sign_challenge(
+    algorithm: KeyAlgorithm,
+    private_key_pem: &str,
     message: &[u8],
 ) -> Result<Vec<u8>, TcpTargetError> {
-    let (algorithm, key_bytes) = algorithm_and_key;
-
-    // Handle different DSA/ECDSA algorithms by comparing algorithm identifiers
-    // Since we can't directly compare trait objects, we use pointer comparison
-    let algorithm_ptr = algorithm as *const _ as *const ();
-    let ecdsa_p256_ptr = &ECDSA_P256_SHA256_ASN1 as *const _ as *const ();
-    let ecdsa_p384_ptr = &ECDSA_P384_SHA384_ASN1 as *const _ as *const ();
-
-    if algorithm_ptr == ecdsa_p256_ptr {
-        let key_pair = EcdsaKeyPair::from_pkcs8(
-            ECDSA_P256_SHA256_ASN1_SIGNING,
-            key_bytes,
-            &SystemRandom::new(),
-        )
-        .map_err(|e| {
-            TcpTargetError::Crypto(format!("Failed to create ECDSA P-256 key pair: {}", e))
-        })?;
-
-        let signature = key_pair
-            .sign(&SystemRandom::new(), message)
-            .map_err(|e| TcpTargetError::Crypto(format!("ECDSA P-256 signing failed: {}", e)))?;
-
-        Ok(signature.as_ref().to_vec())
-    } else if algorithm_ptr == ecdsa_p384_ptr {
-        let key_pair = EcdsaKeyPair::from_pkcs8(
-            ECDSA_P384_SHA384_ASN1_SIGNING,
-            key_bytes,
-            &SystemRandom::new(),
-        )
-        .map_err(|e| {
-            TcpTargetError::Crypto(format!("Failed to create ECDSA P-384 key pair: {}", e))
-        })?;
-
-        let signature = key_pair
-            .sign(&SystemRandom::new(), message)
-            .map_err(|e| TcpTargetError::Crypto(format!("ECDSA P-384 signing failed: {}", e)))?;
-
-        Ok(signature.as_ref().to_vec())
-    } else {
-        // RSA or unsupported algorithm
-        Err(TcpTargetError::Unsupported(
-            "DSA/ECDSA signing not supported for this algorithm type".to_string(),
-        ))
+    match algorithm {
+        KeyAlgorithm::Rsa => {
+            let key = RsaPrivateKey::from_pkcs1_pem(private_key_pem)
+                .or_else(|_| RsaPrivateKey::from_pkcs8_pem(private_key_pem))
+                .map_err(|e| TcpTargetError::Crypto(format!("Invalid RSA private key: {}", e)))?;
+            let padding = rsa::pkcs1v15::Pkcs1v15Sign::new::<sha2::Sha256>();
+            Ok(key.sign(padding, message)?)
+        }
+        KeyAlgorithm::Ed25519 => {
+            let Some(seed) = pem_tail::<32>(private_key_pem) else {
+                return Err(TcpTargetError::Crypto(
+                    "Invalid Ed25519 private key format".to_string(),
+                ));
+            };
+            Ok(SigningKey::from_bytes(&seed)
+                .sign(message)
+                .to_bytes()
+                .to_vec())
+        }
+        KeyAlgorithm::EcdsaP256 | KeyAlgorithm::EcdsaP384 => {
+            let signing = match algorithm {
+                KeyAlgorithm::EcdsaP256 => ECDSA_P256_SHA256_ASN1_SIGNING,
+                _ => ECDSA_P384_SHA384_ASN1_SIGNING,
+            };
+            let pem = pem::parse(private_key_pem)?;
+            let rng = SystemRandom::new();
+            let key_pair = EcdsaKeyPair::from_pkcs8(signing, pem.contents(), &rng)
+                .map_err(|e| TcpTargetError::Crypto(format!("Invalid ECDSA private key: {}", e)))?;
+            let signature = key_pair
+                .sign(&rng, message)
+                .map_err(|e| TcpTargetError::Crypto(format!("ECDSA signing failed: {}", e)))?;
+            Ok(signature.as_ref().to_vec())
+        }
     }
 }
+
+/// Take the last `N` bytes of a PEM document, where Ed25519 keys keep their raw key
+fn pem_tail<const N: usize>(pem: &str) -> Option<[u8; N]> {
+    let pem = pem::parse(pem).ok()?;
+    let contents = pem.contents();
+    contents
+        .get(contents.len().checked_sub(N)?..)?
+        .try_into()
+        .ok()
+}
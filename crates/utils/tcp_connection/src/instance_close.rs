@@ -0,0 +1,63 @@
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    error::TcpTargetError,
+    instance::{ConnectionInstance, trace_message, trace_message_size},
+};
+
+/// Control byte sent by `close` to mark the clean end of a session
+const GOODBYE_FRAME: u8 = 0xFF;
+
+impl ConnectionInstance {
+    /// Close the connection cleanly
+    ///
+    /// Flushes pending data, sends a one-byte goodbye frame and shuts down the stream,
+    /// a peer waiting in `wait_close` can then tell the clean close from a crash
+    pub async fn close(mut self) -> Result<(), TcpTargetError> {
+        trace_message("write", "goodbye", async {
+            self.stream.write_all(&[GOODBYE_FRAME]).await?;
+            trace_message_size(1);
+            self.shutdown().await
+        })
+        .await
+    }
+
+    /// Close the connection without the goodbye frame
+    ///
+    /// Flushes pending data and shuts down the stream, for peers that don't call `wait_close`
+    pub async fn shutdown(mut self) -> Result<(), TcpTargetError> {
+        self.stream.flush().await?;
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Wait for the peer to close the connection with `close`
+    ///
+    /// Fails with `TcpTargetError::Network` if the connection ends without the goodbye frame,
+    /// and with `TcpTargetError::Protocol` if other data arrives first
+    pub async fn wait_close(&mut self) -> Result<(), TcpTargetError> {
+        trace_message("read", "goodbye", async {
+            let mut frame = [0u8; 1];
+            match self.read_exact_timeout(&mut frame).await {
+                Ok(()) => {}
+                Err(TcpTargetError::Io(e)) => {
+                    return Err(TcpTargetError::Network(format!(
+                        "Connection closed without goodbye: {}",
+                        e
+                    )));
+                }
+                Err(e) => return Err(e),
+            }
+
+            if frame[0] != GOODBYE_FRAME {
+                return Err(TcpTargetError::Protocol(format!(
+                    "Expected goodbye frame, got {:#04x}",
+                    frame[0]
+                )));
+            }
+            trace_message_size(1);
+            Ok(())
+        })
+        .await
+    }
+}
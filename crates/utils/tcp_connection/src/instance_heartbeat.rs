@@ -0,0 +1,313 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::{
+        AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf, ReadHalf,
+        WriteHalf,
+    },
+    sync::{
+        Mutex, Notify, Semaphore,
+        mpsc::{self, UnboundedReceiver},
+    },
+    time::MissedTickBehavior,
+};
+
+use crate::instance_tls::ConnectionStream;
+
+/// Size of the in-memory pipe between the instance and the heartbeat tasks
+const HEARTBEAT_PIPE_SIZE: usize = 64 * 1024;
+
+/// Bytes of data a side may send before the peer forwarded them to its instance
+const HEARTBEAT_WINDOW_SIZE: usize = 1024 * 1024;
+
+/// Control frame carrying protocol bytes, followed by a 4-byte length and the bytes
+const FRAME_DATA: u8 = 0;
+
+/// Control frame asking the peer to prove it is alive
+const FRAME_PING: u8 = 1;
+
+/// Control frame answering a ping
+const FRAME_PONG: u8 = 2;
+
+/// Control frame granting the peer room for more data, followed by a 4-byte byte count
+const FRAME_CREDIT: u8 = 3;
+
+type SharedWriter = Arc<Mutex<WriteHalf<ConnectionStream>>>;
+
+/// # Struct - HeartbeatStream
+///
+/// Stream of a `ConnectionInstance` with `ConnectionConfig::heartbeat_interval` set
+///
+/// The instance reads and writes an in-memory pipe, two background tasks carry the
+/// pipe over the connection inside data frames and interleave ping / pong frames,
+/// so heartbeats never split a message. Both sides must enable the heartbeat
+///
+/// Data is only sent while the peer has room for it (`HEARTBEAT_WINDOW_SIZE`),
+/// so an instance that does not read never stops its side from answering pings
+///
+/// The tasks end once the instance is dropped and the pending bytes are sent
+pub(crate) struct HeartbeatStream {
+    pipe: DuplexStream,
+    expired: Arc<AtomicBool>,
+}
+
+impl HeartbeatStream {
+    /// Start the heartbeat tasks over the given stream
+    pub(crate) fn spawn(stream: ConnectionStream, interval: Duration) -> Self {
+        let (pipe, task_pipe) = tokio::io::duplex(HEARTBEAT_PIPE_SIZE);
+        let (pipe_reader, pipe_writer) = tokio::io::split(task_pipe);
+        let (wire_reader, wire_writer) = tokio::io::split(stream);
+        let wire_writer = Arc::new(Mutex::new(wire_writer));
+
+        let expired = Arc::new(AtomicBool::new(false));
+        let awaiting_pong = Arc::new(AtomicBool::new(false));
+        let credit = Arc::new(Semaphore::new(HEARTBEAT_WINDOW_SIZE));
+        let peer_closed = Arc::new(Notify::new());
+        let sender_stopped = Arc::new(Notify::new());
+
+        tokio::spawn(receive_frames(
+            wire_reader,
+            pipe_writer,
+            wire_writer.clone(),
+            credit.clone(),
+            awaiting_pong.clone(),
+            peer_closed.clone(),
+            sender_stopped.clone(),
+        ));
+        tokio::spawn(send_frames(
+            pipe_reader,
+            wire_writer,
+            interval,
+            credit,
+            awaiting_pong,
+            expired.clone(),
+            peer_closed,
+            sender_stopped,
+        ));
+
+        Self { pipe, expired }
+    }
+
+    fn expired_error() -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, "Peer did not answer the heartbeat")
+    }
+}
+
+impl AsyncRead for HeartbeatStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.expired.load(Ordering::Acquire) {
+            return Poll::Ready(Err(Self::expired_error()));
+        }
+
+        let filled = buf.filled().len();
+        match Pin::new(&mut this.pipe).poll_read(cx, buf) {
+            // The pipe reports end of stream when the heartbeat gave up
+            Poll::Ready(Ok(())) if buf.filled().len() == filled => {
+                if this.expired.load(Ordering::Acquire) {
+                    Poll::Ready(Err(Self::expired_error()))
+                } else {
+                    Poll::Ready(Ok(()))
+                }
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl AsyncWrite for HeartbeatStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.expired.load(Ordering::Acquire) {
+            return Poll::Ready(Err(Self::expired_error()));
+        }
+        Pin::new(&mut this.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().pipe).poll_shutdown(cx)
+    }
+}
+
+/// Write a control frame to the connection
+async fn write_frame(writer: &SharedWriter, frame: &[u8]) -> io::Result<()> {
+    let mut writer = writer.lock().await;
+    writer.write_all(frame).await?;
+    writer.flush().await
+}
+
+/// Read frames from the connection, queue data for the pipe and answer pings
+///
+/// Never waits on the pipe, so control frames are handled while the instance is not reading
+async fn receive_frames(
+    mut wire: ReadHalf<ConnectionStream>,
+    pipe: WriteHalf<DuplexStream>,
+    wire_writer: SharedWriter,
+    credit: Arc<Semaphore>,
+    awaiting_pong: Arc<AtomicBool>,
+    peer_closed: Arc<Notify>,
+    sender_stopped: Arc<Notify>,
+) {
+    let (queue, queued) = mpsc::unbounded_channel();
+    let queued_bytes = Arc::new(AtomicUsize::new(0));
+    let forwarder = tokio::spawn(forward_data(
+        queued,
+        pipe,
+        wire_writer.clone(),
+        queued_bytes.clone(),
+    ));
+
+    loop {
+        let kind = tokio::select! {
+            kind = wire.read_u8() => kind,
+            _ = sender_stopped.notified() => break,
+        };
+        let Ok(kind) = kind else {
+            break;
+        };
+        let result = match kind {
+            FRAME_DATA => {
+                let Ok(len) = wire.read_u32().await else {
+                    break;
+                };
+
+                // The peer must not send more than the window
+                let len = len as usize;
+                if queued_bytes.fetch_add(len, Ordering::AcqRel) + len > HEARTBEAT_WINDOW_SIZE {
+                    break;
+                }
+                let mut data = vec![0u8; len];
+                match wire.read_exact(&mut data).await {
+                    Ok(_) => queue
+                        .send(data)
+                        .map_err(|_| io::ErrorKind::BrokenPipe.into()),
+                    Err(err) => Err(err),
+                }
+            }
+            FRAME_PING => write_frame(&wire_writer, &[FRAME_PONG]).await,
+            FRAME_PONG => {
+                awaiting_pong.store(false, Ordering::Release);
+                Ok(())
+            }
+            FRAME_CREDIT => match wire.read_u32().await {
+                Ok(granted) => {
+                    credit.add_permits(granted as usize);
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            },
+            _ => break,
+        };
+        if result.is_err() {
+            break;
+        }
+    }
+
+    // Let the instance read the queued data, then see the end of stream
+    drop(queue);
+    let _ = forwarder.await;
+    peer_closed.notify_one();
+}
+
+/// Write the queued data to the pipe, granting the peer room for as many bytes again
+async fn forward_data(
+    mut queued: UnboundedReceiver<Vec<u8>>,
+    mut pipe: WriteHalf<DuplexStream>,
+    wire_writer: SharedWriter,
+    queued_bytes: Arc<AtomicUsize>,
+) {
+    while let Some(data) = queued.recv().await {
+        if pipe.write_all(&data).await.is_err() {
+            break;
+        }
+        queued_bytes.fetch_sub(data.len(), Ordering::AcqRel);
+
+        let mut frame = Vec::with_capacity(5);
+        frame.push(FRAME_CREDIT);
+        frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        if write_frame(&wire_writer, &frame).await.is_err() {
+            break;
+        }
+    }
+    let _ = pipe.shutdown().await;
+}
+
+/// Forward the pipe to the connection in data frames and send a ping every interval
+///
+/// Data is only read from the pipe while the peer granted room for it.
+/// Gives up when a ping is still unanswered one interval later
+#[allow(clippy::too_many_arguments)]
+async fn send_frames(
+    mut pipe: ReadHalf<DuplexStream>,
+    wire_writer: SharedWriter,
+    interval: Duration,
+    credit: Arc<Semaphore>,
+    awaiting_pong: Arc<AtomicBool>,
+    expired: Arc<AtomicBool>,
+    peer_closed: Arc<Notify>,
+    sender_stopped: Arc<Notify>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut buffer = vec![0u8; HEARTBEAT_PIPE_SIZE];
+    loop {
+        tokio::select! {
+            read = async {
+                // Wait until the peer has room for more data
+                drop(credit.acquire().await);
+                let room = credit.available_permits().min(buffer.len());
+                pipe.read(&mut buffer[..room]).await
+            } => {
+                let n = match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                if let Ok(used) = credit.acquire_many(n as u32).await {
+                    used.forget();
+                }
+                let mut frame = Vec::with_capacity(n + 5);
+                frame.push(FRAME_DATA);
+                frame.extend_from_slice(&(n as u32).to_be_bytes());
+                frame.extend_from_slice(&buffer[..n]);
+                if write_frame(&wire_writer, &frame).await.is_err() {
+                    break;
+                }
+            }
+            _ = ticker.tick() => {
+                if awaiting_pong.swap(true, Ordering::AcqRel) {
+                    expired.store(true, Ordering::Release);
+                    break;
+                }
+                if write_frame(&wire_writer, &[FRAME_PING]).await.is_err() {
+                    break;
+                }
+            }
+            _ = peer_closed.notified() => return,
+        }
+    }
+
+    // Close the connection and stop the receiving task
+    let _ = wire_writer.lock().await.shutdown().await;
+    sender_stopped.notify_one();
+}
@@ -0,0 +1,127 @@
+use std::{net::SocketAddr, time::Duration};
+
+use tokio::net::TcpStream;
+
+use crate::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+    instance_tls::TlsSide,
+};
+
+/// # Struct - ReconnectPolicy
+///
+/// How often and how patiently `Reconnector` retries after a connection-level error
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+
+    /// Wait before the second attempt, doubled after every failure
+    pub initial_backoff: Duration,
+
+    /// Upper bound of the wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Wait after the given failed attempt (starting at 0)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// # Struct - Reconnector
+///
+/// Connects to a stored upstream address, re-establishing the connection
+/// with exponential backoff when it fails at the connection level
+///
+/// Only wrap idempotent phases such as the connect / authenticate handshake,
+/// a file transfer or any request that changes the upstream must not be retried
+#[derive(Debug, Clone)]
+pub struct Reconnector {
+    addr: SocketAddr,
+    config: ConnectionConfig,
+    policy: ReconnectPolicy,
+}
+
+impl Reconnector {
+    /// Create a reconnector for the upstream address
+    pub fn new(addr: SocketAddr, config: ConnectionConfig, policy: ReconnectPolicy) -> Self {
+        Self {
+            addr,
+            config,
+            policy,
+        }
+    }
+
+    /// Get the upstream address
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the retry policy
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+
+    /// Connect to the upstream address, retrying on connection-level errors
+    pub async fn connect(&self) -> Result<ConnectionInstance, TcpTargetError> {
+        self.connect_with(async |_| Ok(()))
+            .await
+            .map(|(instance, _)| instance)
+    }
+
+    /// Connect to the upstream address and run the handshake on the new connection
+    ///
+    /// If connecting or the handshake fails with a connection-level error,
+    /// a new connection is made and the handshake runs again, other errors are returned as is
+    pub async fn connect_with<T, F>(
+        &self,
+        mut handshake: F,
+    ) -> Result<(ConnectionInstance, T), TcpTargetError>
+    where
+        F: AsyncFnMut(&mut ConnectionInstance) -> Result<T, TcpTargetError>,
+    {
+        let mut attempt = 0;
+        loop {
+            let err = match self.open().await {
+                Ok(mut instance) => match handshake(&mut instance).await {
+                    Ok(value) => return Ok((instance, value)),
+                    Err(err) => err,
+                },
+                Err(err) => err,
+            };
+
+            attempt += 1;
+            if !err.is_connection_error() || attempt >= self.policy.max_attempts {
+                return Err(err);
+            }
+            tokio::time::sleep(self.policy.backoff(attempt - 1)).await;
+        }
+    }
+
+    /// Open a single connection, over TLS if `ConnectionConfig::tls` is set
+    async fn open(&self) -> Result<ConnectionInstance, TcpTargetError> {
+        let stream = TcpStream::connect(self.addr).await.map_err(|e| {
+            TcpTargetError::Network(format!("Connect to {} failed: {}", self.addr, e))
+        })?;
+        match self.config.tls {
+            Some(_) => {
+                ConnectionInstance::with_tls(stream, self.config.clone(), TlsSide::Client).await
+            }
+            None => Ok(ConnectionInstance::with_config(stream, self.config.clone())),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::path::{Path, PathBuf};
+
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter},
+};
+
+use crate::{
+    error::TcpTargetError,
+    instance::{ConnectionInstance, trace_message, trace_message_size},
+};
+
+/// File transfer header version of resumable transfers
+const FILE_TRANSFER_VERSION_RESUMABLE: u64 = 3;
+
+impl ConnectionInstance {
+    /// Write file to target machine, resuming from what the receiver already has
+    ///
+    /// The receiver must call `read_file_resumable`,
+    /// if the connection drops, calling both sides again only sends the remaining bytes
+    pub async fn write_file_resumable(
+        &mut self,
+        file_path: impl AsRef<Path>,
+    ) -> Result<(), TcpTargetError> {
+        trace_message("write", "file_resumable", async {
+            let path = file_path.as_ref();
+
+            if !path.is_file() {
+                return Err(TcpTargetError::File(format!(
+                    "File not found: {}",
+                    path.display()
+                )));
+            }
+
+            let file_size = tokio::fs::metadata(path).await?.len();
+            let file_hash = hash_file(path, self.config().chunk_size).await?;
+
+            // Send file header (version + size + hash)
+            self.stream
+                .write_all(&FILE_TRANSFER_VERSION_RESUMABLE.to_be_bytes())
+                .await?;
+            self.stream.write_all(&file_size.to_be_bytes()).await?;
+            self.stream.write_all(file_hash.as_bytes()).await?;
+            self.stream.flush().await?;
+
+            // Receiver reports how many bytes it already has
+            let mut offset_buf = [0u8; 8];
+            self.read_exact_timeout(&mut offset_buf).await?;
+            let offset = u64::from_be_bytes(offset_buf);
+            if offset > file_size {
+                return Err(TcpTargetError::Protocol(format!(
+                    "Resume offset {} exceeds file size {}",
+                    offset, file_size
+                )));
+            }
+
+            // Stream the remainder
+            let mut file = File::open(path).await?;
+            file.seek(std::io::SeekFrom::Start(offset)).await?;
+            let mut reader = BufReader::with_capacity(self.config().chunk_size, file);
+            let mut buffer = vec![0u8; self.config().chunk_size];
+            let mut bytes_sent = offset;
+
+            while bytes_sent < file_size {
+                self.check_cancelled()?;
+                let bytes_to_read =
+                    (file_size - bytes_sent).min(self.config().chunk_size as u64) as usize;
+                reader.read_exact(&mut buffer[..bytes_to_read]).await?;
+                self.throttle_write(bytes_to_read).await;
+                self.stream.write_all(&buffer[..bytes_to_read]).await?;
+                bytes_sent += bytes_to_read as u64;
+            }
+            self.stream.flush().await?;
+
+            // Wait for receiver confirmation
+            let mut ack = [0u8; 1];
+            self.read_exact_timeout(&mut ack).await?;
+
+            if ack[0] != 1 {
+                return Err(TcpTargetError::Protocol(
+                    "Receiver verification failed".to_string(),
+                ));
+            }
+            trace_message_size(file_size - offset);
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Read file from target machine, keeping a `.part` file to resume an interrupted transfer
+    ///
+    /// The partial file is only reused if it belongs to the same file size and content hash,
+    /// otherwise it is discarded and the transfer starts from zero
+    pub async fn read_file_resumable(
+        &mut self,
+        save_path: impl AsRef<Path>,
+    ) -> Result<(), TcpTargetError> {
+        trace_message("read", "file_resumable", async {
+            let path = save_path.as_ref();
+            let part_path = append_extension(path, "part");
+            let meta_path = append_extension(path, "part.meta");
+
+            if let Some(parent) = path.parent()
+                && !parent.exists()
+            {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            // Read file header (version + size + hash)
+            let mut version_buf = [0u8; 8];
+            self.read_exact_timeout(&mut version_buf).await?;
+            if u64::from_be_bytes(version_buf) != FILE_TRANSFER_VERSION_RESUMABLE {
+                return Err(TcpTargetError::Protocol(
+                    "Unsupported transfer version".to_string(),
+                ));
+            }
+
+            let mut size_buf = [0u8; 8];
+            self.read_exact_timeout(&mut size_buf).await?;
+            let file_size = u64::from_be_bytes(size_buf);
+            self.check_file_size(file_size)?;
+
+            let mut hash_buf = [0u8; 32];
+            self.read_exact_timeout(&mut hash_buf).await?;
+            let expected_hash = blake3::Hash::from_bytes(hash_buf);
+
+            // Reuse the partial file only if it belongs to the same content
+            let part_meta = format!("{} {}", file_size, expected_hash.to_hex());
+            let existing_meta = tokio::fs::read_to_string(&meta_path).await.ok();
+            let part_len = tokio::fs::metadata(&part_path)
+                .await
+                .map(|m| m.len())
+                .unwrap_or(0);
+            let offset =
+                if existing_meta.as_deref() == Some(part_meta.as_str()) && part_len <= file_size {
+                    part_len
+                } else {
+                    tokio::fs::write(&meta_path, &part_meta).await?;
+                    0
+                };
+
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(offset == 0)
+                .open(&part_path)
+                .await?;
+            let mut writer = BufWriter::with_capacity(self.config().chunk_size, file);
+            if offset > 0 {
+                writer.seek(std::io::SeekFrom::Start(offset)).await?;
+            }
+
+            // Report the resume offset
+            self.stream.write_all(&offset.to_be_bytes()).await?;
+            self.stream.flush().await?;
+
+            // Receive the remainder, keeping what arrived if the connection drops
+            let mut buffer = vec![0u8; self.config().chunk_size];
+            let mut bytes_received = offset;
+            while bytes_received < file_size {
+                // Keep what arrived, the next call resumes from it
+                if let Err(e) = self.check_cancelled() {
+                    writer.flush().await?;
+                    return Err(e);
+                }
+                let bytes_to_read =
+                    (file_size - bytes_received).min(self.config().chunk_size as u64) as usize;
+                let timeout_secs = self.config().timeout_secs;
+                let read = tokio::time::timeout(
+                    std::time::Duration::from_secs(timeout_secs),
+                    self.stream.read(&mut buffer[..bytes_to_read]),
+                )
+                .await;
+                let n = match read {
+                    Err(_) => {
+                        writer.flush().await?;
+                        return Err(TcpTargetError::Timeout(format!(
+                            "No data received within {} seconds",
+                            timeout_secs
+                        )));
+                    }
+                    Ok(Ok(0)) => {
+                        writer.flush().await?;
+                        return Err(TcpTargetError::Network(format!(
+                            "Connection closed after {} of {} bytes",
+                            bytes_received, file_size
+                        )));
+                    }
+                    Ok(Ok(n)) => n,
+                    Ok(Err(err)) => {
+                        writer.flush().await?;
+                        return Err(err.into());
+                    }
+                };
+                writer.write_all(&buffer[..n]).await?;
+                bytes_received += n as u64;
+            }
+            writer.flush().await?;
+            writer.into_inner().sync_all().await?;
+
+            // Verify the assembled file before moving it into place
+            let actual_hash = hash_file(&part_path, self.config().chunk_size).await?;
+            if actual_hash != expected_hash {
+                let _ = tokio::fs::remove_file(&part_path).await;
+                let _ = tokio::fs::remove_file(&meta_path).await;
+                self.stream.write_all(&[0u8]).await?;
+                self.stream.flush().await?;
+                return Err(TcpTargetError::File(format!(
+                    "Hash mismatch: expected {}, got {}",
+                    expected_hash.to_hex(),
+                    actual_hash.to_hex()
+                )));
+            }
+
+            tokio::fs::rename(&part_path, path).await?;
+            let _ = tokio::fs::remove_file(&meta_path).await;
+
+            // Send confirmation
+            self.stream.write_all(&[1u8]).await?;
+            self.stream.flush().await?;
+            trace_message_size(file_size - offset);
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// Append an extension to the full file name, e.g. `a.png` -> `a.png.part`
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Calc BLAKE3 hash of a file
+async fn hash_file(path: &Path, chunk_size: usize) -> Result<blake3::Hash, TcpTargetError> {
+    let mut reader = BufReader::with_capacity(chunk_size, File::open(path).await?);
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; chunk_size];
+    loop {
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize())
+}
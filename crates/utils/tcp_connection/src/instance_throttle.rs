@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use crate::instance::ConnectionInstance;
+
+/// Share of a second the throttle may burst after being idle
+const BURST_SECS: f64 = 0.1;
+
+/// # Struct - Throttle
+///
+/// Token bucket keeping the average write throughput under `max_bytes_per_sec`
+///
+/// Tokens refill continuously up to a short burst, a write larger than the
+/// available tokens waits until the deficit has refilled
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    /// Create an empty bucket for the given rate
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take tokens for the given bytes, returns how long to wait before writing them
+    fn acquire(&mut self, bytes: usize) -> Option<Duration> {
+        let rate = self.rate as f64;
+        let now = Instant::now();
+        let refill = now.duration_since(self.last_refill).as_secs_f64() * rate;
+        self.tokens = (self.tokens + refill).min(rate * BURST_SECS);
+        self.last_refill = now;
+
+        self.tokens -= bytes as f64;
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / rate))
+    }
+}
+
+impl ConnectionInstance {
+    /// Wait until the given bytes may be written under `ConnectionConfig::max_bytes_per_sec`
+    ///
+    /// Returns immediately when no cap is configured
+    pub(crate) async fn throttle_write(&mut self, bytes: usize) {
+        let Some(rate) = self.config().max_bytes_per_sec.filter(|rate| *rate > 0) else {
+            self.throttle = None;
+            return;
+        };
+
+        let throttle = match &mut self.throttle {
+            Some(throttle) if throttle.rate == rate => throttle,
+            throttle => throttle.insert(Throttle::new(rate)),
+        };
+        if let Some(wait) = throttle.acquire(bytes) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    TlsAcceptor, TlsConnector, TlsStream,
+    rustls::{
+        ClientConfig, RootCertStore, ServerConfig,
+        crypto::{CryptoProvider, ring},
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName, pem::PemObject},
+    },
+};
+
+use crate::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance, apply_socket_options},
+    instance_heartbeat::HeartbeatStream,
+};
+
+/// # Struct - TlsConfig
+///
+/// Certificates used by `ConnectionInstance::with_tls`
+///
+/// The server side needs `cert_path` and `key_path`,
+/// the client side needs `ca_path` and `server_name`
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// Certificate chain presented by the server (PEM)
+    pub cert_path: Option<PathBuf>,
+
+    /// Private key of the server certificate (PEM)
+    pub key_path: Option<PathBuf>,
+
+    /// Root certificates trusted by the client (PEM)
+    pub ca_path: Option<PathBuf>,
+
+    /// Name the client expects in the server certificate
+    pub server_name: String,
+}
+
+/// Which side of the TLS handshake this instance performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsSide {
+    /// Accept the handshake as the server
+    Server,
+
+    /// Initiate the handshake as the client
+    Client,
+}
+
+/// Underlying stream of a `ConnectionInstance`, either plain TCP or TLS over TCP,
+/// optionally carried by a heartbeat
+pub(crate) enum ConnectionStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+    Heartbeat(Box<HeartbeatStream>),
+}
+
+impl AsyncRead for ConnectionStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            ConnectionStream::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            ConnectionStream::Heartbeat(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ConnectionStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            ConnectionStream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            ConnectionStream::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            ConnectionStream::Heartbeat(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            ConnectionStream::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            ConnectionStream::Heartbeat(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ConnectionStream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            ConnectionStream::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            ConnectionStream::Heartbeat(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+impl ConnectionInstance {
+    /// Create a ConnectionInstance that communicates over TLS
+    ///
+    /// Performs the handshake on the given side using `ConnectionConfig::tls`,
+    /// all read / write methods then work the same as on a plain connection
+    pub async fn with_tls(
+        stream: TcpStream,
+        config: ConnectionConfig,
+        side: TlsSide,
+    ) -> Result<Self, TcpTargetError> {
+        let Some(tls) = &config.tls else {
+            return Err(TcpTargetError::Config(
+                "TLS configuration is missing".to_string(),
+            ));
+        };
+
+        apply_socket_options(&stream, &config);
+        let tls_stream = match side {
+            TlsSide::Server => {
+                let acceptor = TlsAcceptor::from(Arc::new(build_server_config(tls)?));
+                TlsStream::Server(acceptor.accept(stream).await?)
+            }
+            TlsSide::Client => {
+                let connector = TlsConnector::from(Arc::new(build_client_config(tls)?));
+                let server_name = ServerName::try_from(tls.server_name.clone())
+                    .map_err(|e| TcpTargetError::Config(format!("Invalid server name: {}", e)))?;
+                TlsStream::Client(connector.connect(server_name, stream).await?)
+            }
+        };
+
+        Ok(Self::with_stream(
+            ConnectionStream::Tls(Box::new(tls_stream)),
+            config,
+        ))
+    }
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn build_server_config(tls: &TlsConfig) -> Result<ServerConfig, TcpTargetError> {
+    let (Some(cert_path), Some(key_path)) = (&tls.cert_path, &tls.key_path) else {
+        return Err(TcpTargetError::Config(
+            "TLS server requires cert_path and key_path".to_string(),
+        ));
+    };
+
+    let certs = load_certs(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| TcpTargetError::Crypto(format!("Failed to load TLS key: {}", e)))?;
+
+    ServerConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| TcpTargetError::Crypto(e.to_string()))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| TcpTargetError::Crypto(e.to_string()))
+}
+
+fn build_client_config(tls: &TlsConfig) -> Result<ClientConfig, TcpTargetError> {
+    let Some(ca_path) = &tls.ca_path else {
+        return Err(TcpTargetError::Config(
+            "TLS client requires ca_path".to_string(),
+        ));
+    };
+
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(ca_path)? {
+        roots
+            .add(cert)
+            .map_err(|e| TcpTargetError::Crypto(e.to_string()))?;
+    }
+
+    Ok(ClientConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| TcpTargetError::Crypto(e.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TcpTargetError> {
+    CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            TcpTargetError::Crypto(format!(
+                "Failed to load TLS certificates from {}: {}",
+                path.display(),
+                e
+            ))
+        })
+}
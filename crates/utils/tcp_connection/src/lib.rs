@@ -1,6 +1,20 @@
 #[allow(dead_code)]
 pub mod instance;
 
+pub mod instance_buffer;
+
 pub mod instance_challenge;
 
+pub mod instance_close;
+
+pub mod instance_heartbeat;
+
+pub mod instance_reconnect;
+
+pub mod instance_resume;
+
+pub mod instance_throttle;
+
+pub mod instance_tls;
+
 pub mod error;
@@ -13,5 +13,62 @@ pub mod test_file_transfer;
 #[cfg(test)]
 pub mod test_msgpack;
 
+#[cfg(test)]
+pub mod test_protocol_tracing;
+
+#[cfg(test)]
+pub mod test_large_text;
+
+#[cfg(test)]
+pub mod test_length_prefix;
+
+#[cfg(test)]
+pub mod test_tls;
+
+#[cfg(test)]
+pub mod test_compression;
+
+#[cfg(test)]
+pub mod test_resume_transfer;
+
+#[cfg(test)]
+pub mod test_transfer_progress;
+
+#[cfg(test)]
+pub mod test_msgpack_crc;
+
+#[cfg(test)]
+pub mod test_read_timeout;
+
+#[cfg(test)]
+pub mod test_transfer_throttle;
+
+#[cfg(test)]
+pub mod test_heartbeat;
+
+#[cfg(test)]
+pub mod test_reconnect;
+
+#[cfg(test)]
+pub mod test_close;
+
+#[cfg(test)]
+pub mod test_challenge_outcome;
+
+#[cfg(test)]
+pub mod test_write_buffering;
+
+#[cfg(test)]
+pub mod test_socket_options;
+
+#[cfg(test)]
+pub mod test_max_file_size;
+
+#[cfg(test)]
+pub mod test_max_message_len;
+
+#[cfg(test)]
+pub mod test_cancellation;
+
 pub mod test_utils;
 pub use test_utils::*;
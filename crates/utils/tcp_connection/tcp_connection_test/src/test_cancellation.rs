@@ -0,0 +1,70 @@
+use std::{env::current_dir, time::Duration};
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_file_transfer_cancellation() -> Result<(), std::io::Error> {
+    let host = "localhost:5064";
+    let temp_dir = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("cancellation");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("texture.png");
+    std::fs::write(&source, vec![5u8; 256 * 1024])?;
+    let target = temp_dir.join("texture_received.png");
+
+    // Throttled, so the transfer would take several seconds without cancellation
+    let config = ConnectionConfig {
+        chunk_size: 4 * 1024,
+        max_bytes_per_sec: Some(32 * 1024),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let receiver_config = config.clone();
+    let receiver = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, receiver_config);
+        instance.read_file(target).await
+    };
+
+    let token = CancellationToken::new();
+    let sender_token = token.clone();
+    let sender = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        instance.set_cancellation_token(Some(sender_token));
+        let result = instance.write_file(source).await;
+
+        // Dropping the connection lets the receiver fail instead of waiting
+        drop(instance);
+        result
+    };
+
+    let canceller = async move {
+        sleep(Duration::from_millis(200)).await;
+        token.cancel();
+    };
+
+    let (received, sent, _) = timeout(Duration::from_secs(5), async {
+        join!(receiver, sender, canceller)
+    })
+    .await
+    .expect("cancelled transfer did not stop in time");
+
+    assert!(matches!(sent, Err(TcpTargetError::Cancelled(_))));
+    assert!(received.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use std::{env::current_dir, time::Duration};
+
+use tcp_connection::{
+    instance::ConnectionInstance,
+    instance_challenge::{ChallengeOutcome, KeyAlgorithm, supported_key_algorithms},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+/// Answer a challenge with a crafted algorithm tag and signature
+async fn answer_crafted(stream: &mut TcpStream, tag: u8, signature: &[u8], key_id: &str) {
+    let mut challenge = [0u8; 32];
+    stream.read_exact(&mut challenge).await.unwrap();
+    stream.write_all(&[tag]).await.unwrap();
+    stream
+        .write_all(&(signature.len() as u32).to_be_bytes())
+        .await
+        .unwrap();
+    stream.write_all(signature).await.unwrap();
+    stream
+        .write_all(&(key_id.len() as u32).to_be_bytes())
+        .await
+        .unwrap();
+    stream.write_all(key_id.as_bytes()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_challenge_outcomes() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5052").await?;
+    let addr = listener.local_addr()?;
+    let key_dir = current_dir()?.join("res").join("key");
+    let private_key = key_dir.join("test_key_private.pem");
+
+    // One connection per case, the last one without a public key directory
+    let server = async move {
+        let mut outcomes = Vec::new();
+        for dir in [
+            key_dir.clone(),
+            key_dir.clone(),
+            key_dir.clone(),
+            key_dir.clone(),
+            key_dir.join("missing_dir"),
+        ] {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut instance = ConnectionInstance::from(stream);
+            outcomes.push(instance.challenge_outcome(dir).await.unwrap());
+        }
+        outcomes
+    };
+
+    let client = async move {
+        // Correct signature
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        assert!(
+            instance
+                .accept_challenge(&private_key, "test_key")
+                .await
+                .unwrap()
+        );
+
+        // Signature of the right size for the 4096-bit RSA key, but wrong
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        answer_crafted(
+            &mut stream,
+            KeyAlgorithm::Rsa.tag(),
+            &[0u8; 512],
+            "test_key",
+        )
+        .await;
+
+        // Key id without public key
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        assert!(
+            instance
+                .accept_challenge(&private_key, "unknown_key")
+                .await
+                .unwrap()
+        );
+
+        // Signature too short for the RSA key
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        answer_crafted(&mut stream, KeyAlgorithm::Rsa.tag(), &[1, 2, 3], "test_key").await;
+
+        // Server without public keys
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        assert!(
+            instance
+                .accept_challenge(&private_key, "test_key")
+                .await
+                .unwrap()
+        );
+    };
+
+    let (outcomes, ()) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    let key_id = "test_key".to_string();
+    assert_eq!(
+        outcomes,
+        vec![
+            ChallengeOutcome::Verified {
+                key_id: key_id.clone()
+            },
+            ChallengeOutcome::Rejected {
+                key_id: key_id.clone()
+            },
+            ChallengeOutcome::UnknownKey {
+                key_id: "unknown_key".to_string()
+            },
+            ChallengeOutcome::MalformedSignature { key_id },
+            ChallengeOutcome::NoKeys,
+        ]
+    );
+    assert!(outcomes[0].is_verified());
+    assert!(outcomes[1..].iter().all(|o| !o.is_verified()));
+    assert_eq!(outcomes[4].key_id(), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_challenge_key_algorithms() -> Result<(), std::io::Error> {
+    let key_dir = current_dir()?.join("res").join("key");
+    assert_eq!(
+        supported_key_algorithms(&key_dir).await.unwrap(),
+        vec![
+            KeyAlgorithm::Rsa,
+            KeyAlgorithm::Ed25519,
+            KeyAlgorithm::EcdsaP256
+        ]
+    );
+
+    let listener = TcpListener::bind("localhost:5053").await?;
+    let addr = listener.local_addr()?;
+
+    let server = {
+        let key_dir = key_dir.clone();
+        async move {
+            let mut outcomes = Vec::new();
+            for _ in 0..5 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut instance = ConnectionInstance::from(stream);
+                outcomes.push(instance.challenge_outcome(&key_dir).await.unwrap());
+            }
+            outcomes
+        }
+    };
+
+    let client = async move {
+        // Valid Ed25519 and ECDSA signatures
+        for (private_key, key_id) in [
+            ("ed25519_key_private.pem", "ed25519_key"),
+            ("ecdsa_key_private.pem", "ecdsa_key"),
+        ] {
+            let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+            assert!(
+                instance
+                    .accept_challenge(key_dir.join(private_key), key_id)
+                    .await
+                    .unwrap()
+            );
+        }
+
+        // Signed with RSA, claiming the Ed25519 key
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        assert!(
+            instance
+                .accept_challenge(key_dir.join("test_key_private.pem"), "ed25519_key")
+                .await
+                .unwrap()
+        );
+
+        // Tagged as Ed25519, but not an Ed25519 signature of the challenge
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        answer_crafted(
+            &mut stream,
+            KeyAlgorithm::Ed25519.tag(),
+            &[0u8; 64],
+            "ed25519_key",
+        )
+        .await;
+
+        // Unknown tag
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        answer_crafted(&mut stream, 0xEE, &[0u8; 64], "ed25519_key").await;
+    };
+
+    let (outcomes, ()) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    let key_id = "ed25519_key".to_string();
+    assert_eq!(
+        outcomes,
+        vec![
+            ChallengeOutcome::Verified {
+                key_id: key_id.clone()
+            },
+            ChallengeOutcome::Verified {
+                key_id: "ecdsa_key".to_string()
+            },
+            ChallengeOutcome::AlgorithmMismatch {
+                key_id: key_id.clone()
+            },
+            ChallengeOutcome::Rejected {
+                key_id: key_id.clone()
+            },
+            ChallengeOutcome::MalformedSignature { key_id },
+        ]
+    );
+
+    Ok(())
+}
+
+/// A P-256 public key whose point happens to contain the DER encoded Ed25519 identifier
+const P256_KEY_WITH_ED25519_BYTES: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEBEQd7T5ShOj4ZqNnwQYDK2VwgXn9
+meVVHTXuarRGq36eWSle72lE9y5KPH67GeSEcqIgMcNDYO1YsSbieyzqxQ==
+-----END PUBLIC KEY-----
+";
+
+#[tokio::test]
+async fn test_key_algorithm_from_algorithm_identifier() -> Result<(), std::io::Error> {
+    let key_dir = current_dir()?.join("res").join("key");
+    for (key, algorithm) in [
+        ("test_key", KeyAlgorithm::Rsa),
+        ("ed25519_key", KeyAlgorithm::Ed25519),
+        ("ecdsa_key", KeyAlgorithm::EcdsaP256),
+    ] {
+        let public_key = tokio::fs::read_to_string(key_dir.join(format!("{}.pem", key))).await?;
+        assert_eq!(KeyAlgorithm::of_public_key(&public_key), Some(algorithm));
+        let private_key =
+            tokio::fs::read_to_string(key_dir.join(format!("{}_private.pem", key))).await?;
+        assert_eq!(KeyAlgorithm::of_private_key(&private_key), Some(algorithm));
+    }
+
+    // Only the identifier decides, not identifier bytes elsewhere in the key
+    assert_eq!(
+        KeyAlgorithm::of_public_key(P256_KEY_WITH_ED25519_BYTES),
+        Some(KeyAlgorithm::EcdsaP256)
+    );
+
+    // Identifier bytes alone are not a key
+    let only_identifier = "-----BEGIN PUBLIC KEY-----\nBgMrZXA=\n-----END PUBLIC KEY-----\n";
+    assert_eq!(KeyAlgorithm::of_public_key(only_identifier), None);
+
+    Ok(())
+}
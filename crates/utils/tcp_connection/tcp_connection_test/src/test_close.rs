@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use tcp_connection::{error::TcpTargetError, instance::ConnectionInstance};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_close_is_observed_by_peer() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5050").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.write_msgpack("last message").await.unwrap();
+        instance.close().await
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        let message: String = instance.read_msgpack().await.unwrap();
+        (message, instance.wait_close().await)
+    };
+
+    let (closed, (message, observed)) =
+        timeout(Duration::from_secs(10), async { join!(server, client) })
+            .await
+            .unwrap();
+
+    closed.unwrap();
+    assert_eq!(message, "last message");
+    observed.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drop_is_not_a_clean_close() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5051").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        drop(ConnectionInstance::from(stream));
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        instance.wait_close().await
+    };
+
+    let (_, observed) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    assert!(matches!(observed, Err(TcpTargetError::Network(_))));
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::{env::current_dir, path::PathBuf, time::Duration};
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{Compression, ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, copy_bidirectional},
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+/// Transfer a file through a counting proxy, returns the bytes sent by the sender
+async fn transfer_through_proxy(
+    receiver_host: &'static str,
+    proxy_host: &'static str,
+    source: PathBuf,
+    target: PathBuf,
+    compression: Option<Compression>,
+) -> u64 {
+    let receiver_listener = TcpListener::bind(receiver_host).await.unwrap();
+    let proxy_listener = TcpListener::bind(proxy_host).await.unwrap();
+
+    let receiver = async move {
+        let (stream, _) = receiver_listener.accept().await.unwrap();
+        let config = ConnectionConfig {
+            enable_crc_validation: true,
+            ..Default::default()
+        };
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        instance.read_file(target).await.unwrap();
+    };
+
+    let proxy = async move {
+        let (mut inbound, _) = proxy_listener.accept().await.unwrap();
+        let mut outbound = TcpStream::connect(receiver_host).await.unwrap();
+        let (sent, _) = copy_bidirectional(&mut inbound, &mut outbound)
+            .await
+            .unwrap();
+        sent
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(proxy_host).await.unwrap();
+        let config = ConnectionConfig {
+            enable_crc_validation: true,
+            compression,
+            ..Default::default()
+        };
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        instance.write_file(source).await.unwrap();
+    };
+
+    let (_, sent, _) = timeout(Duration::from_secs(10), async {
+        join!(receiver, proxy, sender)
+    })
+    .await
+    .unwrap();
+    sent
+}
+
+#[tokio::test]
+async fn test_compressed_file_transfer() -> Result<(), std::io::Error> {
+    let temp_dir = current_dir()?.join("res").join(".temp").join("compression");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    // Highly compressible content, like a JSON scene
+    let source = temp_dir.join("scene.json");
+    let content = "{\"entity\": \"tree\", \"position\": [0, 0, 0]},\n".repeat(20_000);
+    std::fs::write(&source, &content)?;
+    let file_size = content.len() as u64;
+
+    let cases = [
+        ("localhost:5024", "localhost:5025", Compression::Gzip),
+        ("localhost:5026", "localhost:5027", Compression::Zstd),
+    ];
+    for (receiver_host, proxy_host, compression) in cases {
+        let target = temp_dir.join(format!("scene_{:?}.json", compression));
+        let sent = transfer_through_proxy(
+            receiver_host,
+            proxy_host,
+            source.clone(),
+            target.clone(),
+            Some(compression),
+        )
+        .await;
+
+        assert!(
+            sent < file_size / 2,
+            "{:?} sent {} bytes for a {} bytes file",
+            compression,
+            sent,
+            file_size
+        );
+        assert_eq!(std::fs::read(&target)?, content.as_bytes());
+    }
+
+    // Uncompressed transfers still send the raw content
+    let target = temp_dir.join("scene_raw.json");
+    let sent = transfer_through_proxy(
+        "localhost:5028",
+        "localhost:5029",
+        source.clone(),
+        target.clone(),
+        None,
+    )
+    .await;
+    assert!(sent > file_size);
+    assert_eq!(std::fs::read(&target)?, content.as_bytes());
+
+    Ok(())
+}
+
+/// Send a compressed file header announcing `file_size` bytes, followed by one raw chunk
+async fn send_compressed_chunk(
+    host: &str,
+    file_size: u64,
+    chunk_len: u32,
+    chunk: &[u8],
+) -> Result<(), std::io::Error> {
+    let mut stream = TcpStream::connect(host).await?;
+    stream.write_all(&2u64.to_be_bytes()).await?; // Version with compression flag
+    stream.write_all(&file_size.to_be_bytes()).await?;
+    stream.write_all(&0u32.to_be_bytes()).await?; // No CRC
+    stream.write_all(&[2u8]).await?; // Zstd
+    stream.write_all(&chunk_len.to_be_bytes()).await?;
+    stream.write_all(chunk).await?;
+    stream.flush().await?;
+
+    // Keep the connection open until the receiver gives up
+    let mut rest = Vec::new();
+    let _ = stream.read_to_end(&mut rest).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_compressed_chunk_bounds() -> Result<(), std::io::Error> {
+    let host = "localhost:5075";
+    let temp_dir = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("compression_bounds");
+    std::fs::create_dir_all(&temp_dir)?;
+    let target = temp_dir.join("received.bin");
+    let listener = TcpListener::bind(host).await?;
+
+    let receive = async || {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.read_file(&target).await
+    };
+
+    // A chunk length far beyond what a chunk can compress to is rejected before reading it
+    let (result, sent) = timeout(Duration::from_secs(10), async {
+        join!(receive(), send_compressed_chunk(host, 16, u32::MAX, &[]))
+    })
+    .await
+    .unwrap();
+    sent?;
+    assert!(matches!(result, Err(TcpTargetError::Protocol(_))));
+
+    // A small chunk inflating far beyond the announced size stops right past it
+    let bomb = zstd::stream::encode_all(&vec![0u8; 64 * 1024 * 1024][..], 19)?;
+    let (result, sent) = timeout(Duration::from_secs(10), async {
+        join!(
+            receive(),
+            send_compressed_chunk(host, 16, bomb.len() as u32, &bomb)
+        )
+    })
+    .await
+    .unwrap();
+    sent?;
+    assert!(matches!(result, Err(TcpTargetError::File(_))));
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::{
+    env::current_dir,
+    time::{Duration, Instant},
+};
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    io::AsyncReadExt,
+    join,
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+
+#[tokio::test]
+async fn test_heartbeat_detects_silent_peer() -> Result<(), std::io::Error> {
+    let host = "localhost:5046";
+    let interval = Duration::from_millis(200);
+    let config = ConnectionConfig {
+        heartbeat_interval: Some(interval),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        let started = Instant::now();
+        let result = instance.read_msgpack::<String>().await;
+        (result, started.elapsed())
+    };
+
+    // The peer keeps the socket open but never answers
+    let silent_peer = async move {
+        let mut stream = TcpStream::connect(host).await.unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).await.unwrap();
+        received
+    };
+
+    let ((result, elapsed), received) =
+        timeout(Duration::from_secs(5), async { join!(server, silent_peer) })
+            .await
+            .unwrap();
+
+    assert!(matches!(result, Err(TcpTargetError::Timeout(_))));
+    assert!(
+        elapsed < interval * 2,
+        "heartbeat took {:?} to detect the silent peer",
+        elapsed
+    );
+
+    // The peer only saw pings before the connection was closed
+    assert!(!received.is_empty());
+    assert!(received.iter().all(|byte| *byte == 1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_heartbeat_keeps_idle_connection() -> Result<(), std::io::Error> {
+    let host = "localhost:5047";
+    let temp_dir = current_dir()?.join("res").join(".temp").join("heartbeat");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("level.map");
+    let content: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&source, &content)?;
+    let target = temp_dir.join("level_received.map");
+
+    let interval = Duration::from_millis(100);
+    let config = ConnectionConfig {
+        heartbeat_interval: Some(interval),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let server_config = config.clone();
+    let receive_target = target.clone();
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, server_config);
+        let message: String = instance.read_msgpack().await.unwrap();
+        instance.read_file(receive_target).await.unwrap();
+        instance
+            .write_msgpack(message.to_uppercase())
+            .await
+            .unwrap();
+    };
+
+    let client = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+
+        // Stay idle for several intervals, pings keep the connection alive
+        sleep(interval * 5).await;
+        instance.write_msgpack("ping pong").await.unwrap();
+        instance.write_file(source).await.unwrap();
+        instance.read_msgpack::<String>().await.unwrap()
+    };
+
+    let (_, reply) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    assert_eq!(reply, "PING PONG");
+    assert_eq!(std::fs::read(&target)?, content);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_heartbeat_keeps_slow_reader() -> Result<(), std::io::Error> {
+    let host = "localhost:5074";
+    let temp_dir = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("heartbeat_slow");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    // Far more than the pipes and socket buffers hold
+    let source = temp_dir.join("large.bin");
+    let content: Vec<u8> = (0..8 * 1024 * 1024u32).map(|i| (i % 253) as u8).collect();
+    std::fs::write(&source, &content)?;
+    let target = temp_dir.join("large_received.bin");
+
+    let interval = Duration::from_millis(100);
+    let config = ConnectionConfig {
+        heartbeat_interval: Some(interval),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let server_config = config.clone();
+    let receive_target = target.clone();
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, server_config);
+
+        // Busy for several intervals while the data is backed up
+        sleep(interval * 5).await;
+        instance.read_file(receive_target).await.unwrap();
+        instance.write_msgpack(true).await.unwrap();
+    };
+
+    let client = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        instance.write_file(source).await.unwrap();
+        instance.read_msgpack::<bool>().await.unwrap()
+    };
+
+    // A missed heartbeat fails the transfer itself, this bound only catches a hang
+    let (_, received) = timeout(Duration::from_secs(60), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    assert!(received);
+    assert_eq!(std::fs::read(&target)?, content);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+
+use crate::test_utils::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+
+fn large_text(seed: char) -> String {
+    std::iter::repeat_n(seed, 256 * 1024).collect()
+}
+
+pub(crate) struct LargeTextClientHandle;
+
+impl ClientHandle<LargeTextServerHandle> for LargeTextClientHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        // Both texts arrive intact on the same connection
+        let first = instance.read_large_text(4096u32).await.unwrap();
+        assert_eq!(first, large_text('a'));
+
+        let second = instance.read_large_text(1000u32).await.unwrap();
+        assert_eq!(second, large_text('b'));
+
+        // Following messages are not consumed by the large text reads
+        let done = instance.read_text().await.unwrap();
+        assert_eq!(done, "done");
+    }
+}
+
+pub(crate) struct LargeTextServerHandle;
+
+impl ServerHandle<LargeTextClientHandle> for LargeTextServerHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        instance.write_large_text(large_text('a')).await.unwrap();
+        instance.write_large_text(large_text('b')).await.unwrap();
+        instance.write_text("done").await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_large_text_back_to_back() -> Result<(), std::io::Error> {
+    let host = "localhost:5020";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<LargeTextClientHandle, LargeTextServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<LargeTextClientHandle, LargeTextServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(10);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+use tcp_connection::instance::{ConnectionInstance, LengthPrefix};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+
+use crate::test_utils::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+
+#[test]
+fn test_length_prefix_over_u32() {
+    // A crafted length just past the 4-byte framing boundary
+    let len = u32::MAX as u64 + 1;
+
+    let wide = LengthPrefix::U64.encode(len).unwrap();
+    assert_eq!(wide.len(), LengthPrefix::U64.size());
+    assert_eq!(LengthPrefix::U64.decode(&wide).unwrap(), len);
+
+    // The 4-byte prefix refuses instead of truncating
+    assert!(LengthPrefix::U32.encode(len).is_err());
+
+    let narrow = LengthPrefix::U32.encode(u32::MAX as u64).unwrap();
+    assert_eq!(narrow.len(), LengthPrefix::U32.size());
+    assert_eq!(LengthPrefix::U32.decode(&narrow).unwrap(), u32::MAX as u64);
+
+    // Mismatched prefix width is rejected
+    assert!(LengthPrefix::U32.decode(&wide).is_err());
+}
+
+fn test_vec() -> Vec<u64> {
+    (0..100_000).collect()
+}
+
+pub(crate) struct WidePrefixClientHandle;
+
+impl ClientHandle<WidePrefixServerHandle> for WidePrefixClientHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        instance.config_mut().length_prefix = LengthPrefix::U64;
+        let length_prefix = instance.negotiate_length_prefix().await.unwrap();
+        assert_eq!(length_prefix, LengthPrefix::U64);
+
+        let data: Vec<u64> = instance.read_msgpack().await.unwrap();
+        assert_eq!(data, test_vec());
+
+        let data: Vec<u64> = instance.read_large_msgpack(4096u32).await.unwrap();
+        assert_eq!(data, test_vec());
+
+        let text = instance.read_text().await.unwrap();
+        assert_eq!(text, "wide");
+    }
+}
+
+pub(crate) struct WidePrefixServerHandle;
+
+impl ServerHandle<WidePrefixClientHandle> for WidePrefixServerHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        instance.config_mut().length_prefix = LengthPrefix::U64;
+        let length_prefix = instance.negotiate_length_prefix().await.unwrap();
+        assert_eq!(length_prefix, LengthPrefix::U64);
+
+        instance.write_msgpack(test_vec()).await.unwrap();
+        instance
+            .write_large_msgpack(test_vec(), 4096u32)
+            .await
+            .unwrap();
+        instance.write_text("wide").await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_wide_length_prefix_round_trip() -> Result<(), std::io::Error> {
+    let host = "localhost:5021";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<WidePrefixClientHandle, WidePrefixServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<WidePrefixClientHandle, WidePrefixServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(10);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_length_prefix_negotiation_mismatch() -> Result<(), std::io::Error> {
+    let host = "localhost:5077";
+    let listener = TcpListener::bind(host).await?;
+    let (client, accepted) = join!(TcpStream::connect(host), listener.accept());
+    let mut client = ConnectionInstance::from(client?);
+    let mut server = ConnectionInstance::from(accepted?.0);
+
+    // A peer announcing the wide prefix falls back to the 4-byte prefix of the older peer
+    client.config_mut().length_prefix = LengthPrefix::U64;
+    let (client_prefix, server_prefix) = timeout(Duration::from_secs(10), async {
+        join!(
+            client.negotiate_length_prefix(),
+            server.negotiate_length_prefix()
+        )
+    })
+    .await?;
+    assert_eq!(client_prefix.unwrap(), LengthPrefix::U32);
+    assert_eq!(server_prefix.unwrap(), LengthPrefix::U32);
+    assert_eq!(client.config().length_prefix, LengthPrefix::U32);
+
+    let (written, read) = join!(
+        client.write_msgpack(test_vec()),
+        server.read_msgpack::<Vec<u64>>()
+    );
+    written.unwrap();
+    assert_eq!(read.unwrap(), test_vec());
+
+    // An invalid version is rejected
+    assert!(LengthPrefix::from_version(0).is_err());
+    assert_eq!(LengthPrefix::from_version(3).unwrap(), LengthPrefix::U64);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::{env::current_dir, time::Duration};
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_oversized_file_is_rejected() -> Result<(), std::io::Error> {
+    let temp_dir = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("max_file_size");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(&temp_dir)?;
+    }
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let small = temp_dir.join("small.bin");
+    let large = temp_dir.join("large.bin");
+    std::fs::write(&small, vec![7u8; 1024])?;
+    std::fs::write(&large, vec![7u8; 1024 * 1024])?;
+
+    let listener = TcpListener::bind("localhost:5060").await?;
+    let addr = listener.local_addr()?;
+
+    let received_small = temp_dir.join("received").join("small.bin");
+    let received_large = temp_dir.join("received").join("large.bin");
+    let server = {
+        let (received_small, received_large) = (received_small.clone(), received_large.clone());
+        async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let config = ConnectionConfig {
+                max_file_size: Some(4096),
+                ..Default::default()
+            };
+            let mut instance = ConnectionInstance::with_config(stream, config);
+            instance.read_file(&received_small).await.unwrap();
+            instance.read_file(&received_large).await
+        }
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        instance.write_file(&small).await.unwrap();
+        instance.write_file(&large).await
+    };
+
+    let (rejected, sent) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    // Files within the limit still arrive
+    assert_eq!(std::fs::read(&received_small)?, vec![7u8; 1024]);
+
+    // The oversized file is rejected from its header, nothing is written
+    assert!(matches!(rejected, Err(TcpTargetError::Protocol(_))));
+    assert!(!received_large.exists());
+    assert!(sent.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    io::AsyncWriteExt,
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_oversized_length_prefix_is_rejected() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5061").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        let huge = instance.read_msgpack::<Vec<u8>>().await;
+        let huge_large = instance.read_large_msgpack::<Vec<u8>>(1024u16).await;
+
+        // A smaller configured limit applies to every length-prefixed read
+        instance.config_mut().max_message_len = Some(16);
+        let limited = instance.read_text().await;
+        (huge, huge_large, limited)
+    };
+
+    let client = async move {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        // Announce 4 GiB twice, without ever sending the content
+        stream.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+        stream.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+        stream.write_all(&100u32.to_be_bytes()).await.unwrap();
+        stream.write_all(&[b'x'; 100]).await.unwrap();
+        stream
+    };
+
+    let ((huge, huge_large, limited), _stream) =
+        timeout(Duration::from_secs(10), async { join!(server, client) })
+            .await
+            .unwrap();
+
+    assert!(matches!(huge, Err(TcpTargetError::Protocol(_))));
+    assert!(matches!(huge_large, Err(TcpTargetError::Protocol(_))));
+    assert!(matches!(limited, Err(TcpTargetError::Protocol(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_message_within_limit_is_read() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5062").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let config = ConnectionConfig {
+            max_message_len: Some(64),
+            ..Default::default()
+        };
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        instance.read_msgpack::<String>().await
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        instance.write_msgpack("small enough").await.unwrap();
+        instance
+    };
+
+    let (message, _instance) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+    assert_eq!(message.unwrap(), "small enough");
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Default)]
+struct SheetRecord {
+    holder: String,
+    mapping: Vec<(String, u32)>,
+}
+
+fn sample_record() -> SheetRecord {
+    SheetRecord {
+        holder: "alice".to_string(),
+        mapping: vec![
+            ("Assets/hero.png".to_string(), 3),
+            ("Assets/tree.fbx".to_string(), 7),
+        ],
+    }
+}
+
+fn crc_config() -> ConnectionConfig {
+    ConnectionConfig {
+        enable_crc_validation: true,
+        ..Default::default()
+    }
+}
+
+/// Send a record through a proxy, optionally flipping one byte of the body
+async fn send_record(
+    receiver_host: &'static str,
+    proxy_host: &'static str,
+    corrupt: bool,
+) -> Result<SheetRecord, TcpTargetError> {
+    let receiver_listener = TcpListener::bind(receiver_host).await.unwrap();
+    let proxy_listener = TcpListener::bind(proxy_host).await.unwrap();
+
+    let receiver = async move {
+        let (stream, _) = receiver_listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, crc_config());
+        instance.read_msgpack::<SheetRecord>().await
+    };
+
+    let proxy = async move {
+        let (mut inbound, _) = proxy_listener.accept().await.unwrap();
+        let mut outbound = TcpStream::connect(receiver_host).await.unwrap();
+        let mut bytes = Vec::new();
+        inbound.read_to_end(&mut bytes).await.unwrap();
+        if corrupt {
+            // Skip the 4 byte length prefix and flip a bit inside the body
+            bytes[10] ^= 0x01;
+        }
+        outbound.write_all(&bytes).await.unwrap();
+        outbound.shutdown().await.unwrap();
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(proxy_host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, crc_config());
+        instance.write_msgpack(sample_record()).await.unwrap();
+    };
+
+    let (result, _, _) = timeout(Duration::from_secs(10), async {
+        join!(receiver, proxy, sender)
+    })
+    .await
+    .unwrap();
+    result
+}
+
+#[tokio::test]
+async fn test_msgpack_crc_valid() {
+    let record = send_record("localhost:5035", "localhost:5036", false)
+        .await
+        .unwrap();
+    assert_eq!(record, sample_record());
+}
+
+#[tokio::test]
+async fn test_msgpack_crc_detects_corruption() {
+    let result = send_record("localhost:5037", "localhost:5038", true).await;
+    assert!(matches!(result, Err(TcpTargetError::Protocol(_))));
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fmt::Debug,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_core::span::Current;
+
+use crate::test_utils::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+
+/// Recorded span: (direction, tag, size)
+type SpanRecord = (String, String, u64);
+
+/// Subscriber that collects every `protocol_message` span by id, and how often it was entered
+#[derive(Default)]
+struct CollectingSubscriber {
+    next_id: AtomicU64,
+    spans: Arc<Mutex<HashMap<u64, (SpanRecord, usize)>>>,
+
+    /// Metadata of the collected spans
+    metadata: Mutex<HashMap<u64, &'static Metadata<'static>>>,
+
+    /// Entered spans, the last one is the current span
+    entered: Mutex<Vec<(Id, &'static Metadata<'static>)>>,
+}
+
+#[derive(Default)]
+struct SpanVisitor {
+    direction: String,
+    tag: String,
+    size: u64,
+}
+
+impl Visit for SpanVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "direction" => self.direction = value.to_string(),
+            "tag" => self.tag = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "size" {
+            self.size = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+}
+
+impl Subscriber for CollectingSubscriber {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        if span.metadata().name() == "protocol_message" {
+            let mut visitor = SpanVisitor::default();
+            span.record(&mut visitor);
+            self.spans
+                .lock()
+                .unwrap()
+                .insert(id, ((visitor.direction, visitor.tag, visitor.size), 0));
+            self.metadata.lock().unwrap().insert(id, span.metadata());
+        }
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        // The size is recorded once the message is done
+        if let Some(((_, _, size), _)) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            let mut visitor = SpanVisitor::default();
+            values.record(&mut visitor);
+            *size = visitor.size;
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        if let Some((_, entered)) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            *entered += 1;
+            self.entered.lock().unwrap().push((
+                span.clone(),
+                self.metadata.lock().unwrap()[&span.into_u64()],
+            ));
+        }
+    }
+
+    fn exit(&self, span: &Id) {
+        let mut entered = self.entered.lock().unwrap();
+        if entered.last().is_some_and(|(id, _)| id == span) {
+            entered.pop();
+        }
+    }
+
+    fn current_span(&self) -> Current {
+        match self.entered.lock().unwrap().last() {
+            Some((id, metadata)) => Current::new(id.clone(), metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+fn transfer_image_path() -> std::path::PathBuf {
+    current_dir()
+        .unwrap()
+        .join("res")
+        .join("image")
+        .join("test_transfer.png")
+}
+
+pub(crate) struct TracingClientHandle;
+
+impl ClientHandle<TracingServerHandle> for TracingClientHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        instance.write_file(transfer_image_path()).await.unwrap();
+    }
+}
+
+pub(crate) struct TracingServerHandle;
+
+impl ServerHandle<TracingClientHandle> for TracingServerHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        let save_path = current_dir()
+            .unwrap()
+            .join("res")
+            .join(".temp")
+            .join("image")
+            .join("test_tracing_transfer.png");
+        instance.read_file(save_path).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_file_transfer_protocol_spans() -> Result<(), std::io::Error> {
+    let host = "localhost:5017";
+
+    // Collect the spans of this test thread only
+    let subscriber = CollectingSubscriber::default();
+    let spans = subscriber.spans.clone();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<TracingClientHandle, TracingServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<TracingClientHandle, TracingServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(10);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    // Each transfer ran inside its span
+    let size = std::fs::metadata(transfer_image_path())?.len();
+    let mut spans: Vec<(SpanRecord, usize)> = spans.lock().unwrap().values().cloned().collect();
+    spans.sort();
+    assert_eq!(
+        spans
+            .iter()
+            .map(|(record, _)| record.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            ("read".to_string(), "file".to_string(), size),
+            ("write".to_string(), "file".to_string(), size),
+        ]
+    );
+    assert!(spans.iter().all(|(_, entered)| *entered > 0));
+
+    Ok(())
+}
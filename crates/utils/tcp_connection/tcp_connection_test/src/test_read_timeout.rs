@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+
+#[tokio::test]
+async fn test_read_times_out_on_silent_peer() {
+    let host = "localhost:5039";
+    let listener = TcpListener::bind(host).await.unwrap();
+
+    // Accept the connection but never send anything
+    let silent_peer = async move {
+        let (_stream, _) = listener.accept().await.unwrap();
+        sleep(Duration::from_secs(3)).await;
+    };
+
+    let reader = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let config = ConnectionConfig {
+            timeout_secs: 1,
+            ..Default::default()
+        };
+        let mut instance = ConnectionInstance::with_config(stream, config);
+
+        let msgpack = instance.read_msgpack::<String>().await;
+        assert!(matches!(msgpack, Err(TcpTargetError::Timeout(_))));
+
+        let text = instance.read_text().await;
+        assert!(matches!(text, Err(TcpTargetError::Timeout(_))));
+    };
+
+    timeout(Duration::from_secs(10), async {
+        join!(silent_peer, reader)
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use tcp_connection::{
+    error::TcpTargetError,
+    instance::{ConnectionConfig, ConnectionInstance},
+    instance_reconnect::{ReconnectPolicy, Reconnector},
+};
+use tokio::{join, net::TcpListener, time::timeout};
+
+fn test_policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(100),
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_after_dropped_connection() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5048").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        // Drop the first connection before the handshake
+        let (first, _) = listener.accept().await.unwrap();
+        drop(first);
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.write_msgpack("welcome").await.unwrap();
+        let reply: String = instance.read_msgpack().await.unwrap();
+        reply
+    };
+
+    let client = async move {
+        let reconnector = Reconnector::new(addr, ConnectionConfig::default(), test_policy());
+        let mut attempts = 0;
+        let (mut instance, greeting) = reconnector
+            .connect_with(async |instance| {
+                attempts += 1;
+                instance.read_msgpack::<String>().await
+            })
+            .await
+            .unwrap();
+        instance.write_msgpack("thanks").await.unwrap();
+        (attempts, greeting)
+    };
+
+    let (reply, (attempts, greeting)) =
+        timeout(Duration::from_secs(10), async { join!(server, client) })
+            .await
+            .unwrap();
+
+    assert_eq!(attempts, 2);
+    assert_eq!(greeting, "welcome");
+    assert_eq!(reply, "thanks");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconnect_skips_protocol_errors() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5049").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.write_msgpack("rejected").await.unwrap();
+    };
+
+    let client = async move {
+        let reconnector = Reconnector::new(addr, ConnectionConfig::default(), test_policy());
+        let mut attempts = 0;
+        let result = reconnector
+            .connect_with(async |instance| {
+                attempts += 1;
+                let answer: String = instance.read_msgpack().await?;
+                Err::<(), _>(TcpTargetError::Authentication(answer))
+            })
+            .await;
+        (attempts, result.map(|_| ()))
+    };
+
+    let (_, (attempts, result)) = timeout(Duration::from_secs(10), async { join!(server, client) })
+        .await
+        .unwrap();
+
+    assert_eq!(attempts, 1);
+    assert!(matches!(result, Err(TcpTargetError::Authentication(_))));
+
+    // Nothing listens any more, every attempt fails and the last error is returned
+    let reconnector = Reconnector::new(addr, ConnectionConfig::default(), test_policy());
+    assert!(matches!(
+        reconnector.connect().await,
+        Err(TcpTargetError::Network(_))
+    ));
+
+    let policy = test_policy();
+    assert_eq!(policy.backoff(0), Duration::from_millis(20));
+    assert_eq!(policy.backoff(1), Duration::from_millis(40));
+    assert_eq!(policy.backoff(5), Duration::from_millis(100));
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::{env::current_dir, path::PathBuf, time::Duration};
+
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, copy},
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+/// Transfer a file through a proxy that drops the connection after `limit` bytes
+async fn interrupted_transfer(
+    receiver_host: &'static str,
+    proxy_host: &'static str,
+    source: PathBuf,
+    target: PathBuf,
+    limit: u64,
+) {
+    let receiver_listener = TcpListener::bind(receiver_host).await.unwrap();
+    let proxy_listener = TcpListener::bind(proxy_host).await.unwrap();
+
+    let receiver = async move {
+        let (stream, _) = receiver_listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        assert!(instance.read_file_resumable(target).await.is_err());
+    };
+
+    let proxy = async move {
+        let (inbound, _) = proxy_listener.accept().await.unwrap();
+        let outbound = TcpStream::connect(receiver_host).await.unwrap();
+        let (inbound_read, mut inbound_write) = inbound.into_split();
+        let (mut outbound_read, mut outbound_write) = outbound.into_split();
+
+        let backward = tokio::spawn(async move {
+            let _ = copy(&mut outbound_read, &mut inbound_write).await;
+        });
+        copy(&mut inbound_read.take(limit), &mut outbound_write)
+            .await
+            .unwrap();
+        outbound_write.shutdown().await.unwrap();
+        backward.abort();
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(proxy_host).await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        assert!(instance.write_file_resumable(source).await.is_err());
+    };
+
+    timeout(Duration::from_secs(10), async {
+        join!(receiver, proxy, sender)
+    })
+    .await
+    .unwrap();
+}
+
+/// Transfer a file directly
+async fn direct_transfer(host: &'static str, source: PathBuf, target: PathBuf) {
+    let listener = TcpListener::bind(host).await.unwrap();
+
+    let receiver = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.read_file_resumable(target).await.unwrap();
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.write_file_resumable(source).await.unwrap();
+    };
+
+    timeout(Duration::from_secs(10), async { join!(receiver, sender) })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_resume_interrupted_transfer() -> Result<(), std::io::Error> {
+    let temp_dir = current_dir()?.join("res").join(".temp").join("resume");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(&temp_dir)?;
+    }
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("model.bin");
+    let content: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&source, &content)?;
+
+    let target = temp_dir.join("received").join("model.bin");
+    let part = temp_dir.join("received").join("model.bin.part");
+
+    // Header is 48 bytes, so about 300 KB of content arrives before the drop
+    interrupted_transfer(
+        "localhost:5030",
+        "localhost:5031",
+        source.clone(),
+        target.clone(),
+        300_048,
+    )
+    .await;
+
+    assert!(!target.exists());
+    let part_len = std::fs::metadata(&part)?.len();
+    assert!(part_len > 0 && part_len < content.len() as u64);
+
+    direct_transfer("localhost:5032", source.clone(), target.clone()).await;
+
+    assert_eq!(std::fs::read(&target)?, content);
+    assert!(!part.exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_discards_mismatched_part() -> Result<(), std::io::Error> {
+    let temp_dir = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("resume_mismatch");
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(&temp_dir)?;
+    }
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("texture.bin");
+    let content = b"new texture content".repeat(10_000);
+    std::fs::write(&source, &content)?;
+
+    // Stale partial file of another version of the file
+    let target = temp_dir.join("texture_received.bin");
+    std::fs::write(temp_dir.join("texture_received.bin.part"), b"old texture")?;
+    std::fs::write(
+        temp_dir.join("texture_received.bin.part.meta"),
+        "11 0000000000000000000000000000000000000000000000000000000000000000",
+    )?;
+
+    direct_transfer("localhost:5033", source.clone(), target.clone()).await;
+
+    assert_eq!(std::fs::read(&target)?, content);
+
+    Ok(())
+}
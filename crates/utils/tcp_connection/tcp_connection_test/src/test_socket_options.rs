@@ -0,0 +1,23 @@
+use tcp_connection::instance::{ConnectionConfig, ConnectionInstance};
+use tokio::net::{TcpListener, TcpStream};
+
+#[tokio::test]
+async fn test_config_applies_nodelay() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5056").await?;
+    let addr = listener.local_addr()?;
+
+    let config = ConnectionConfig {
+        tcp_nodelay: true,
+        send_buffer_size: Some(64 * 1024),
+        recv_buffer_size: Some(64 * 1024),
+        ..Default::default()
+    };
+    let client = ConnectionInstance::with_config(TcpStream::connect(addr).await?, config);
+    let (stream, _) = listener.accept().await?;
+    let server = ConnectionInstance::from(stream);
+
+    assert!(client.nodelay().unwrap());
+    assert!(!server.nodelay().unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::{env::current_dir, path::PathBuf, time::Duration};
+
+use tcp_connection::{
+    instance::{ConnectionConfig, ConnectionInstance},
+    instance_tls::{TlsConfig, TlsSide},
+};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+fn tls_res(name: &str) -> PathBuf {
+    current_dir().unwrap().join("res").join("tls").join(name)
+}
+
+#[tokio::test]
+async fn test_tls_file_transfer() -> Result<(), std::io::Error> {
+    let host = "localhost:5022";
+    let listener = TcpListener::bind(host).await?;
+
+    let image_path = current_dir()?
+        .join("res")
+        .join("image")
+        .join("test_transfer.png");
+    let save_path = current_dir()?
+        .join("res")
+        .join(".temp")
+        .join("tls")
+        .join("test_transfer.png");
+
+    let server_config = ConnectionConfig {
+        tls: Some(TlsConfig {
+            cert_path: Some(tls_res("test_tls_cert.pem")),
+            key_path: Some(tls_res("test_tls_key.pem")),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let client_config = ConnectionConfig {
+        tls: Some(TlsConfig {
+            ca_path: Some(tls_res("test_tls_ca.pem")),
+            server_name: "localhost".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let server_image_path = image_path.clone();
+    let future_server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_tls(stream, server_config, TlsSide::Server)
+            .await
+            .unwrap();
+        instance.write_file(server_image_path).await.unwrap();
+        instance.write_msgpack("done").await.unwrap();
+    };
+
+    let client_save_path = save_path.clone();
+    let future_client = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_tls(stream, client_config, TlsSide::Client)
+            .await
+            .unwrap();
+        instance.read_file(client_save_path).await.unwrap();
+        let done: String = instance.read_msgpack().await.unwrap();
+        assert_eq!(done, "done");
+    };
+
+    let test_timeout = Duration::from_secs(10);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    assert_eq!(std::fs::read(&image_path)?, std::fs::read(&save_path)?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls_rejects_untrusted_server() -> Result<(), std::io::Error> {
+    let host = "localhost:5023";
+    let listener = TcpListener::bind(host).await?;
+
+    let server_config = ConnectionConfig {
+        tls: Some(TlsConfig {
+            cert_path: Some(tls_res("test_tls_cert.pem")),
+            key_path: Some(tls_res("test_tls_key.pem")),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // The certificate is only valid for localhost
+    let client_config = ConnectionConfig {
+        tls: Some(TlsConfig {
+            ca_path: Some(tls_res("test_tls_ca.pem")),
+            server_name: "example.com".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let future_server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let _ = ConnectionInstance::with_tls(stream, server_config, TlsSide::Server).await;
+    };
+
+    let future_client = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        ConnectionInstance::with_tls(stream, client_config, TlsSide::Client)
+            .await
+            .is_err()
+    };
+
+    let (rejected, _) = timeout(Duration::from_secs(10), async {
+        join!(future_client, future_server)
+    })
+    .await?;
+    assert!(
+        rejected,
+        "Handshake should fail for a mismatched server name"
+    );
+
+    Ok(())
+}
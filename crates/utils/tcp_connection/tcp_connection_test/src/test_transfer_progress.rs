@@ -0,0 +1,64 @@
+use std::{env::current_dir, time::Duration};
+
+use tcp_connection::instance::{ConnectionConfig, ConnectionInstance};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_file_transfer_progress() -> Result<(), std::io::Error> {
+    let host = "localhost:5034";
+    let temp_dir = current_dir()?.join("res").join(".temp").join("progress");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("audio.wav");
+    let content = vec![7u8; 100_000];
+    std::fs::write(&source, &content)?;
+    let target = temp_dir.join("audio_received.wav");
+    let file_size = content.len() as u64;
+
+    let config = ConnectionConfig {
+        chunk_size: 8 * 1024,
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let receiver_config = config.clone();
+    let receiver = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, receiver_config);
+        let mut samples = Vec::new();
+        instance
+            .read_file_with_progress(target, &mut |done, total| samples.push((done, total)))
+            .await
+            .unwrap();
+        samples
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        let mut samples = Vec::new();
+        instance
+            .write_file_with_progress(source, &mut |done, total| samples.push((done, total)))
+            .await
+            .unwrap();
+        samples
+    };
+
+    let (read_samples, write_samples) =
+        timeout(Duration::from_secs(10), async { join!(receiver, sender) })
+            .await
+            .unwrap();
+
+    for samples in [read_samples, write_samples] {
+        assert!(samples.len() > 1);
+        assert!(samples.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(samples.iter().all(|(_, total)| *total == file_size));
+        assert_eq!(samples.last(), Some(&(file_size, file_size)));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{
+    env::current_dir,
+    time::{Duration, Instant},
+};
+
+use tcp_connection::instance::{ConnectionConfig, ConnectionInstance};
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_file_transfer_throttle() -> Result<(), std::io::Error> {
+    let host = "localhost:5045";
+    let temp_dir = current_dir()?.join("res").join(".temp").join("throttle");
+    std::fs::create_dir_all(&temp_dir)?;
+
+    let source = temp_dir.join("texture.png");
+    let content = vec![3u8; 32 * 1024];
+    std::fs::write(&source, &content)?;
+    let target = temp_dir.join("texture_received.png");
+
+    let max_bytes_per_sec = 64 * 1024;
+    let config = ConnectionConfig {
+        chunk_size: 4 * 1024,
+        max_bytes_per_sec: Some(max_bytes_per_sec),
+        ..Default::default()
+    };
+    let listener = TcpListener::bind(host).await?;
+
+    let receiver_config = config.clone();
+    let receive_target = target.clone();
+    let receiver = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, receiver_config);
+        instance.read_file(receive_target).await.unwrap();
+    };
+
+    let sender = async move {
+        let stream = TcpStream::connect(host).await.unwrap();
+        let mut instance = ConnectionInstance::with_config(stream, config);
+        let started = Instant::now();
+        instance.write_file(source).await.unwrap();
+        started.elapsed()
+    };
+
+    let (_, elapsed) = timeout(Duration::from_secs(10), async { join!(receiver, sender) })
+        .await
+        .unwrap();
+
+    // The bucket starts empty, so the whole file is paced by the cap
+    let min_elapsed = Duration::from_secs_f64(content.len() as f64 / max_bytes_per_sec as f64);
+    assert!(
+        elapsed >= min_elapsed,
+        "transfer took {:?}, expected at least {:?}",
+        elapsed,
+        min_elapsed
+    );
+    assert_eq!(std::fs::read(&target)?, content);
+
+    Ok(())
+}
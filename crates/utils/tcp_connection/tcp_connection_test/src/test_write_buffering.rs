@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    time::timeout,
+};
+
+#[tokio::test]
+async fn test_buffered_writes_are_sent_before_read() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5054").await?;
+    let addr = listener.local_addr()?;
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.set_nodelay(true).unwrap();
+        instance.set_write_buffering(true).await.unwrap();
+        assert!(instance.is_write_buffering());
+
+        for i in 0..100u32 {
+            instance.write_msgpack(i).await.unwrap();
+        }
+        instance.write_msgpack("x".repeat(10000)).await.unwrap();
+
+        // Reading sends the buffered messages first
+        let reply: String = instance.read_msgpack().await.unwrap();
+        instance.set_write_buffering(false).await.unwrap();
+        reply
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        let mut numbers = Vec::new();
+        for _ in 0..100 {
+            numbers.push(instance.read_msgpack::<u32>().await.unwrap());
+        }
+        let large: String = instance.read_msgpack().await.unwrap();
+        instance.write_msgpack("received").await.unwrap();
+        (numbers, large)
+    };
+
+    let (reply, (numbers, large)) =
+        timeout(Duration::from_secs(10), async { join!(server, client) })
+            .await
+            .unwrap();
+
+    assert_eq!(reply, "received");
+    assert_eq!(numbers, (0..100).collect::<Vec<u32>>());
+    assert_eq!(large.len(), 10000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_explicit_flush_delivers_buffered_writes() -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind("localhost:5055").await?;
+    let addr = listener.local_addr()?;
+    let (written_tx, written_rx) = oneshot::channel();
+    let (checked_tx, checked_rx) = oneshot::channel();
+
+    let server = async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut instance = ConnectionInstance::from(stream);
+        instance.set_write_buffering(true).await.unwrap();
+        instance.write_msgpack("buffered").await.unwrap();
+        written_tx.send(()).unwrap();
+
+        // Keep the connection open until the client saw nothing arrive
+        checked_rx.await.unwrap();
+        instance.flush().await.unwrap();
+        let _: bool = instance.read_msgpack().await.unwrap();
+    };
+
+    let client = async move {
+        let mut instance = ConnectionInstance::from(TcpStream::connect(addr).await.unwrap());
+        written_rx.await.unwrap();
+        let early = timeout(
+            Duration::from_millis(300),
+            instance.read_msgpack::<String>(),
+        )
+        .await;
+        checked_tx.send(()).unwrap();
+
+        let message: String = instance.read_msgpack().await.unwrap();
+        instance.write_msgpack(true).await.unwrap();
+        (early.is_err(), message)
+    };
+
+    let (_, (nothing_before_flush, message)) =
+        timeout(Duration::from_secs(10), async { join!(server, client) })
+            .await
+            .unwrap();
+
+    assert!(nothing_before_flush);
+    assert_eq!(message, "buffered");
+
+    Ok(())
+}
@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use action_system::action::ActionContext;
 use cfg_file::config::ConfigFile;
-use tcp_connection::{error::TcpTargetError, instance::ConnectionInstance};
+use log::{info, warn};
+use tcp_connection::{
+    error::TcpTargetError, instance::ConnectionInstance, instance_challenge::ChallengeOutcome,
+};
 use tokio::sync::{Mutex, mpsc::Sender};
 use vcs_data::{
     constants::{SERVER_PATH_MEMBER_PUB, VAULT_HOST_NAME},
@@ -97,35 +100,57 @@ pub async fn auth_member(
         let using_host_mode = mut_instance.read_msgpack::<bool>().await?;
 
         let result = mut_instance
-            .challenge(vault.vault_path().join(SERVER_PATH_MEMBER_PUB))
+            .challenge_outcome(vault.vault_path().join(SERVER_PATH_MEMBER_PUB))
             .await;
 
         return match result {
-            Ok((pass, member_id)) => {
-                if !pass {
+            Ok(outcome) => {
+                let ChallengeOutcome::Verified { key_id: member_id } = outcome else {
+                    match &outcome {
+                        ChallengeOutcome::NoKeys => {
+                            warn!("Authenticate failed: the vault has no member public keys")
+                        }
+                        ChallengeOutcome::UnknownKey { key_id } => {
+                            warn!("Authenticate failed: no public key for `{}`", key_id)
+                        }
+                        ChallengeOutcome::MalformedSignature { key_id } => {
+                            warn!("Authenticate failed: malformed signature from `{}`", key_id)
+                        }
+                        ChallengeOutcome::AlgorithmMismatch { key_id } => {
+                            warn!(
+                                "Authenticate failed: key algorithm mismatch for `{}`",
+                                key_id
+                            )
+                        }
+                        ChallengeOutcome::Rejected { key_id } => {
+                            info!("Authenticate failed: signature rejected for `{}`", key_id)
+                        }
+                        ChallengeOutcome::Verified { .. } => {}
+                    }
+
                     // Send false to inform the client that authentication failed
                     mut_instance.write(false).await?;
-                    Err(TcpTargetError::Authentication(
+                    return Err(TcpTargetError::Authentication(
                         "Authenticate failed.".to_string(),
-                    ))
-                } else {
-                    if using_host_mode {
-                        if vault.config().vault_host_list().contains(&member_id) {
-                            // Using Host mode authentication, and is indeed an administrator
-                            mut_instance.write(true).await?;
-                            Ok((member_id, true))
-                        } else {
-                            // Using Host mode authentication, but not an administrator
-                            mut_instance.write(false).await?;
-                            Err(TcpTargetError::Authentication(
-                                "Authenticate failed.".to_string(),
-                            ))
-                        }
-                    } else {
-                        // Not using Host mode authentication
+                    ));
+                };
+
+                if using_host_mode {
+                    if vault.config().vault_host_list().contains(&member_id) {
+                        // Using Host mode authentication, and is indeed an administrator
                         mut_instance.write(true).await?;
-                        Ok((member_id, false))
+                        Ok((member_id, true))
+                    } else {
+                        // Using Host mode authentication, but not an administrator
+                        mut_instance.write(false).await?;
+                        Err(TcpTargetError::Authentication(
+                            "Authenticate failed.".to_string(),
+                        ))
                     }
+                } else {
+                    // Not using Host mode authentication
+                    mut_instance.write(true).await?;
+                    Ok((member_id, false))
                 }
             }
             Err(e) => Err(e),
@@ -1,13 +1,14 @@
 use std::{
     collections::{HashMap, HashSet},
-    io::ErrorKind,
     net::SocketAddr,
     path::PathBuf,
+    pin::pin,
     time::SystemTime,
 };
 
 use action_system::{action::ActionContext, macros::action_gen};
 use cfg_file::config::ConfigFile;
+use futures::StreamExt;
 use log::info;
 use serde::{Deserialize, Serialize};
 use tcp_connection::error::TcpTargetError;
@@ -20,11 +21,11 @@ use vcs_data::{
         local::{
             cached_sheet::CachedSheet,
             config::LocalConfig,
-            latest_file_data::LatestFileData,
+            latest_file_data::{LatestFileData, LatestFileInfo},
             latest_info::{LatestInfo, SheetInfo},
+            local_sheet::{LocalSheet, MoveConflict},
             vault_modified::sign_vault_modified,
         },
-        member::MemberId,
         sheet::{SheetData, SheetName, SheetPathBuf},
         vault::{
             config::VaultUuid,
@@ -141,10 +142,46 @@ pub enum SyncCachedSheetFailReason {
     PathAlreadyExist(PathBuf),
 }
 
+/// Phase of `update_to_latest_info_action` reported to an `UpdateProgressReporter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateToLatestInfoPhase {
+    /// Latest info received from the upstream
+    LatestInfo,
+
+    /// Remote sheets synced, with the number of sheets received
+    RemoteSheets { synced_sheets: usize },
+
+    /// Held status fetched, with the number of files whose holders were fetched
+    HeldStatus { fetched_files: usize },
+
+    /// Cached sheets synced to local sheets, with the number of mappings moved
+    CachedToLocal { moved_mappings: usize },
+}
+
+/// Receives the progress of `update_to_latest_info_action` on the local side
+///
+/// Insert it into the local ActionContext with `insert_data`,
+/// it is called once per phase, in the order of `UpdateToLatestInfoPhase`
+pub struct UpdateProgressReporter(Box<dyn Fn(UpdateToLatestInfoPhase) + Send + Sync>);
+
+impl UpdateProgressReporter {
+    pub fn new(report: impl Fn(UpdateToLatestInfoPhase) + Send + Sync + 'static) -> Self {
+        Self(Box::new(report))
+    }
+}
+
+/// Report a phase to the reporter in the context, if any
+fn report_update_progress(ctx: &ActionContext, phase: UpdateToLatestInfoPhase) {
+    if ctx.is_proc_on_local()
+        && let Some(reporter) = ctx.get::<UpdateProgressReporter>()
+    {
+        (reporter.0)(phase);
+    }
+}
+
 #[action_gen]
 pub async fn update_to_latest_info_action(
     ctx: ActionContext,
-    _unused: (),
 ) -> Result<UpdateToLatestInfoResult, TcpTargetError> {
     let instance = check_connection_instance(&ctx)?;
 
@@ -170,32 +207,35 @@ pub async fn update_to_latest_info_action(
             let mut member_visible = Vec::new();
             let mut ref_sheets = HashSet::new();
 
-            for sheet in vault.sheets().await? {
+            let mut sheets = pin!(vault.sheets_stream()?);
+            while let Some(sheet) = sheets.next().await {
+                let sheet = sheet?;
+
                 // Build share parts
-                if let Some(holder) = sheet.holder() {
-                    if holder == &member_id || holder == VAULT_HOST_NAME {
-                        let mut sheet_shares: HashMap<SheetShareId, Share> = HashMap::new();
-                        for share in sheet.get_shares().await? {
-                            // Get SharePath
-                            let Some(share_path) = share.path.clone() else {
-                                continue;
-                            };
-                            // Get ShareId from SharePath
-                            let Some(share_id) = share_path.file_name() else {
-                                continue;
-                            };
-                            let share_id = share_id.display().to_string();
-                            let share_id_trimed =
-                                share_id.trim_end_matches(SERVER_SUFFIX_SHEET_SHARE_FILE);
-                            sheet_shares.insert(share_id_trimed.to_string(), share);
-                        }
-                        shares_in_my_sheets.insert(sheet.name().clone(), sheet_shares);
+                if let Some(holder) = sheet.holder()
+                    && (holder == &member_id || holder == VAULT_HOST_NAME)
+                {
+                    let mut sheet_shares: HashMap<SheetShareId, Share> = HashMap::new();
+                    for share in sheet.get_shares().await? {
+                        // Get SharePath
+                        let Some(share_path) = share.path.clone() else {
+                            continue;
+                        };
+                        // Get ShareId from SharePath
+                        let Some(share_id) = share_path.file_name() else {
+                            continue;
+                        };
+                        let share_id = share_id.display().to_string();
+                        let share_id_trimed =
+                            share_id.trim_end_matches(SERVER_SUFFIX_SHEET_SHARE_FILE);
+                        sheet_shares.insert(share_id_trimed.to_string(), share);
                     }
+                    shares_in_my_sheets.insert(sheet.name().clone(), sheet_shares);
                 }
 
                 // Build sheet parts
                 let holder_is_host =
-                    sheet.holder().unwrap_or(&String::default()) == &VAULT_HOST_NAME;
+                    sheet.holder().unwrap_or(&String::default()) == VAULT_HOST_NAME;
                 if sheet.holder().is_some()
                     && (sheet.holder().unwrap() == &member_id || holder_is_host)
                 {
@@ -221,7 +261,7 @@ pub async fn update_to_latest_info_action(
             latest_info.ref_sheet_content = ref_sheet_data.clone();
             latest_info.ref_sheet_vfs_mapping = ref_sheet_data
                 .mapping()
-                .into_iter()
+                .iter()
                 .map(|(path, file)| (file.id.clone(), path.clone()))
                 .collect::<HashMap<VirtualFileId, SheetPathBuf>>();
             latest_info.reference_sheets = ref_sheets;
@@ -251,6 +291,7 @@ pub async fn update_to_latest_info_action(
                 LatestInfo::latest_info_path(workspace.local_path(), &member_id),
             )
             .await?;
+            report_update_progress(&ctx, UpdateToLatestInfoPhase::LatestInfo);
         }
     }
 
@@ -283,6 +324,7 @@ pub async fn update_to_latest_info_action(
 
             // Send the version list
             let len = local_versions.len();
+            let mut synced_sheets = 0;
             instance.lock().await.write_msgpack(local_versions).await?;
 
             if len < 1 {
@@ -302,19 +344,38 @@ pub async fn update_to_latest_info_action(
                             let (sheet_name, data): (SheetName, SheetData) =
                                 mut_instance.read_large_msgpack(1024u16).await?;
 
-                            let Some(path) = CachedSheet::cached_sheet_path(sheet_name) else {
-                                return Err(TcpTargetError::NotFound(
-                                    "Workspace not found".to_string(),
-                                ));
-                            };
-
-                            SheetData::write_to(&data, path).await?;
+                            if let Ok(cached) = CachedSheet::cached_sheet_data(&sheet_name).await {
+                                let diff = cached.diff(&data);
+                                info!(
+                                    "Sheet `{}` updated: {} added, {} removed, {} changed",
+                                    sheet_name,
+                                    diff.added.len(),
+                                    diff.removed.len(),
+                                    diff.version_changed.len()
+                                );
+
+                                // Only patch what changed
+                                CachedSheet::apply_diff(&sheet_name, cached, &diff, &data).await?;
+                            } else {
+                                let Some(path) = CachedSheet::cached_sheet_path(sheet_name) else {
+                                    return Err(TcpTargetError::NotFound(
+                                        "Workspace not found".to_string(),
+                                    ));
+                                };
+
+                                SheetData::write_to(&data, path).await?;
+                            }
+                            synced_sheets += 1;
                         } else {
                             break;
                         }
                     }
                 }
             }
+            report_update_progress(
+                &ctx,
+                UpdateToLatestInfoPhase::RemoteSheets { synced_sheets },
+            );
         }
         if ctx.is_proc_on_remote() {
             let vault = try_get_vault(&ctx)?;
@@ -370,14 +431,8 @@ pub async fn update_to_latest_info_action(
                 .await?;
 
             // Receive information and write to local
-            let result: HashMap<
-                VirtualFileId,
-                (
-                    Option<MemberId>,
-                    VirtualFileVersion,
-                    Vec<(VirtualFileVersion, VirtualFileVersionDescription)>,
-                ),
-            > = mut_instance.read_large_msgpack(1024u16).await?;
+            let result: HashMap<VirtualFileId, LatestFileInfo> =
+                mut_instance.read_large_msgpack(1024u16).await?;
 
             // Read configuration file
             let path = LatestFileData::data_path(&member_id)?;
@@ -385,10 +440,12 @@ pub async fn update_to_latest_info_action(
                 LatestFileData::read_from(&path).await.unwrap_or_default();
 
             // Write the received information
+            let fetched_files = result.len();
             latest_file_data.update_info(result);
 
             // Write
             LatestFileData::write_to(&latest_file_data, &path).await?;
+            report_update_progress(&ctx, UpdateToLatestInfoPhase::HeldStatus { fetched_files });
         }
 
         if ctx.is_proc_on_remote() {
@@ -400,14 +457,7 @@ pub async fn update_to_latest_info_action(
                 mut_instance.read_large_msgpack(1024u16).await?;
 
             // Organize the information
-            let mut result: HashMap<
-                VirtualFileId,
-                (
-                    Option<MemberId>,
-                    VirtualFileVersion,
-                    Vec<(VirtualFileVersion, VirtualFileVersionDescription)>,
-                ),
-            > = HashMap::new();
+            let mut result: HashMap<VirtualFileId, LatestFileInfo> = HashMap::new();
             for id in holder_wants_know {
                 let Ok(meta) = vault.virtual_file_meta(&id).await else {
                     continue;
@@ -424,9 +474,7 @@ pub async fn update_to_latest_info_action(
                 let histories = all_versions
                     .iter()
                     .filter_map(|v| {
-                        let Some(desc) = all_descriptions.get(v) else {
-                            return None;
-                        };
+                        let desc = all_descriptions.get(v)?;
                         Some((v.clone(), desc.clone()))
                     })
                     .collect::<Vec<(VirtualFileVersion, VirtualFileVersionDescription)>>();
@@ -446,12 +494,18 @@ pub async fn update_to_latest_info_action(
         let local_sheet_path = workspace.local_path().join(CLIENT_PATH_LOCAL_SHEET);
         if !local_sheet_path.exists() || !cached_sheet_path.exists() {
             // No need to sync
+            report_update_progress(
+                &ctx,
+                UpdateToLatestInfoPhase::CachedToLocal { moved_mappings: 0 },
+            );
             if ctx.is_proc_on_local() {
                 sign_vault_modified(false).await;
             }
             return Ok(UpdateToLatestInfoResult::Success);
         }
 
+        let mut moved_mappings = 0;
+
         let cached_sheet_paths =
             extract_sheet_names_from_paths(CachedSheet::cached_sheet_paths().await?)?;
 
@@ -469,6 +523,8 @@ pub async fn update_to_latest_info_action(
                 continue;
             };
 
+            // Collect the moves needed to follow the cached sheet
+            let mut moves = Vec::new();
             for (cached_item_id, cached_item_path) in cached_sheet_id_mapping.iter() {
                 let path_by_id = { local_sheet.path_by_id(cached_item_id).cloned() };
 
@@ -481,23 +537,42 @@ pub async fn update_to_latest_info_action(
                     continue;
                 }
 
-                // If path not match, try to move
-                let move_result = local_sheet.move_mapping(&local_path, cached_item_path);
-                if let Err(e) = move_result {
-                    match e.kind() {
-                        ErrorKind::AlreadyExists => {
-                            return Ok(UpdateToLatestInfoResult::SyncCachedSheetFail(
-                                SyncCachedSheetFailReason::PathAlreadyExist(
-                                    cached_item_path.clone(),
-                                ),
-                            ));
-                        }
-                        _ => return Err(e.into()),
+                moves.push((local_path, cached_item_path.clone()));
+            }
+            if moves.is_empty() {
+                continue;
+            }
+
+            // Vacate each destination first, so chained renames do not depend on the id mapping order
+            let moves = LocalSheet::order_moves(moves);
+
+            // Validate the whole batch first, so a conflict leaves the local sheet untouched
+            if let Some(conflict) = local_sheet.validate_moves(&moves)?.into_iter().next() {
+                match conflict {
+                    MoveConflict::DestinationExists(path) => {
+                        return Ok(UpdateToLatestInfoResult::SyncCachedSheetFail(
+                            SyncCachedSheetFailReason::PathAlreadyExist(path),
+                        ));
+                    }
+                    MoveConflict::SourceNotFound(path) => {
+                        return Err(TcpTargetError::NotFound(format!(
+                            "Local mapping `{}` not found",
+                            path.display()
+                        )));
                     }
                 }
-                local_sheet.write().await?;
             }
+
+            for (from, to) in moves.iter() {
+                local_sheet.move_mapping(from, to)?;
+            }
+            local_sheet.write().await?;
+            moved_mappings += moves.len();
         }
+        report_update_progress(
+            &ctx,
+            UpdateToLatestInfoPhase::CachedToLocal { moved_mappings },
+        );
     }
 
     if ctx.is_proc_on_local() {
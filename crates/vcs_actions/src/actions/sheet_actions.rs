@@ -11,7 +11,7 @@ use vcs_data::{
             workspace_analyzer::{FromRelativePathBuf, ToRelativePathBuf},
         },
         sheet::SheetName,
-        vault::sheet_share::{ShareMergeMode, SheetShareId},
+        vault::sheet_share::{ShareMergeConflict, ShareMergeMode, SheetShareId},
     },
 };
 
@@ -60,7 +60,7 @@ pub async fn make_sheet_action(
         };
 
         // Check if the sheet already exists
-        if let Ok(mut sheet) = vault.sheet(&sheet_name).await {
+        if let Ok(mut sheet) = vault.sheet_for_update(&sheet_name).await {
             // If the sheet has no holder, assign it to the current member (restore operation)
             if sheet.holder().is_none() {
                 sheet.set_holder(holder.clone());
@@ -164,7 +164,7 @@ pub async fn drop_sheet_action(
         let vault = try_get_vault(&ctx)?;
 
         // Check if the sheet exists
-        let mut sheet = match vault.sheet(&sheet_name).await {
+        let mut sheet = match vault.sheet_for_update(&sheet_name).await {
             Ok(sheet) => sheet,
             Err(e) => {
                 if e.kind() == ErrorKind::NotFound {
@@ -282,7 +282,7 @@ pub async fn edit_mapping_action(
 
     if ctx.is_proc_on_remote() {
         let vault = try_get_vault(&ctx)?;
-        let mut sheet = vault.sheet(&sheet_name).await?;
+        let mut sheet = vault.sheet_for_update(&sheet_name).await?;
 
         // Precheck
         for (from_path, (operation, to_path)) in args.operations.iter() {
@@ -360,6 +360,104 @@ pub async fn edit_mapping_action(
     Ok(EditMappingActionResult::Success)
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MoveSubtreeActionArguments {
+    pub from_prefix: FromRelativePathBuf,
+    pub to_prefix: ToRelativePathBuf,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub enum MoveSubtreeActionResult {
+    /// Number of moved mappings
+    Success(usize),
+
+    // Fail
+    AuthorizeFailed(String),
+    EditNotAllowed,
+    MappingNotFound(FromRelativePathBuf),
+    /// A path under the target directory is already mapped
+    TargetAlreadyExists(ToRelativePathBuf),
+    InvalidTarget(String),
+
+    #[default]
+    Unknown,
+}
+
+/// Relocate every mapping under a directory to another directory
+///
+/// Like `edit_mapping_action`, this Action only modifies Sheet Mapping
+#[action_gen]
+pub async fn move_subtree_action(
+    ctx: ActionContext,
+    args: MoveSubtreeActionArguments,
+) -> Result<MoveSubtreeActionResult, TcpTargetError> {
+    let instance = check_connection_instance(&ctx)?;
+
+    // Auth Member
+    let (member_id, is_host_mode) = match auth_member(&ctx, instance).await {
+        Ok(id) => id,
+        Err(e) => {
+            return Ok(MoveSubtreeActionResult::AuthorizeFailed(e.to_string()));
+        }
+    };
+
+    // Check sheet
+    let (sheet_name, is_ref_sheet) =
+        get_current_sheet_name(&ctx, instance, &member_id, true).await?;
+
+    // Can modify Sheet when not in reference sheet or in Host mode
+    if is_ref_sheet && !is_host_mode {
+        return Ok(MoveSubtreeActionResult::EditNotAllowed);
+    }
+
+    if ctx.is_proc_on_remote() {
+        let vault = try_get_vault(&ctx)?;
+        let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+
+        // Process, the sheet is left unchanged on failure
+        let count = match sheet.move_subtree(&args.from_prefix, &args.to_prefix) {
+            Ok(count) => count,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                write_and_return!(
+                    instance,
+                    MoveSubtreeActionResult::MappingNotFound(args.from_prefix.clone())
+                );
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                write_and_return!(
+                    instance,
+                    MoveSubtreeActionResult::TargetAlreadyExists(args.to_prefix.clone())
+                );
+            }
+            Err(e) => {
+                write_and_return!(
+                    instance,
+                    MoveSubtreeActionResult::InvalidTarget(e.to_string())
+                );
+            }
+        };
+
+        // Write
+        sheet.persist().await?;
+
+        write_and_return!(instance, MoveSubtreeActionResult::Success(count));
+    }
+
+    if ctx.is_proc_on_local() {
+        let result = instance
+            .lock()
+            .await
+            .read::<MoveSubtreeActionResult>()
+            .await?;
+        if matches!(result, MoveSubtreeActionResult::Success(_)) {
+            sign_vault_modified(true).await;
+        }
+        return Ok(result);
+    }
+
+    Ok(MoveSubtreeActionResult::Unknown)
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct ShareMappingArguments {
     pub mappings: Vec<FromRelativePathBuf>,
@@ -490,9 +588,10 @@ pub struct MergeShareMappingArguments {
 #[derive(Serialize, Deserialize, Default)]
 pub enum MergeShareMappingActionResult {
     Success,
+    SuccessWithUnresolved(ShareMergeConflict),
 
     // Fail
-    HasConflicts,
+    HasConflicts(ShareMergeConflict),
     AuthorizeFailed(String),
     EditNotAllowed,
     ShareIdNotFound(SheetShareId),
@@ -548,11 +647,26 @@ pub async fn merge_share_mapping_action(
         };
 
         // Perform the merge
-        match sheet.merge_share(share, args.share_merge_mode).await {
-            Ok(_) => write_and_return!(instance, MergeShareMappingActionResult::Success),
+        match sheet
+            .merge_share_allow_ref(share, args.share_merge_mode, is_host_mode)
+            .await
+        {
+            Ok(unresolved) if unresolved.ok() => {
+                write_and_return!(instance, MergeShareMappingActionResult::Success)
+            }
+            Ok(unresolved) => write_and_return!(
+                instance,
+                MergeShareMappingActionResult::SuccessWithUnresolved(unresolved.clone())
+            ),
             Err(e) => match e.kind() {
                 ErrorKind::AlreadyExists => {
-                    write_and_return!(instance, MergeShareMappingActionResult::HasConflicts);
+                    let conflicts = ShareMergeConflict::from_error(&e)
+                        .cloned()
+                        .unwrap_or_default();
+                    write_and_return!(
+                        instance,
+                        MergeShareMappingActionResult::HasConflicts(conflicts.clone())
+                    );
                 }
                 _ => {
                     write_and_return!(
@@ -570,11 +684,10 @@ pub async fn merge_share_mapping_action(
             .await
             .read::<MergeShareMappingActionResult>()
             .await?;
-        match result {
-            MergeShareMappingActionResult::Success => {
-                sign_vault_modified(true).await;
-            }
-            _ => {}
+        if let MergeShareMappingActionResult::Success
+        | MergeShareMappingActionResult::SuccessWithUnresolved(_) = result
+        {
+            sign_vault_modified(true).await;
         }
         return Ok(result);
     }
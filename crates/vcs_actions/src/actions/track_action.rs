@@ -1,14 +1,14 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
-    time::SystemTime,
+    time::{Duration, Instant, SystemTime},
 };
 
 use action_system::{action::ActionContext, macros::action_gen};
 use cfg_file::config::ConfigFile;
 use serde::{Deserialize, Serialize};
-use sha1_hash::calc_sha1;
+use sha1_hash::calc_sha1_default;
 use tcp_connection::{error::TcpTargetError, instance::ConnectionInstance};
 use tokio::{fs, sync::Mutex};
 use vcs_data::{
@@ -16,12 +16,13 @@ use vcs_data::{
     data::{
         local::{
             cached_sheet::CachedSheet, latest_file_data::LatestFileData,
-            local_sheet::LocalMappingMetadata, vault_modified::sign_vault_modified,
-            workspace_analyzer::AnalyzeResult,
+            local_files::get_relative_paths_by_patterns, local_sheet::LocalMappingMetadata,
+            vault_modified::sign_vault_modified, workspace_analyzer::AnalyzeResult,
         },
         member::MemberId,
-        sheet::SheetName,
+        sheet::{Sheet, SheetManifestItem, SheetName},
         vault::{
+            Vault,
             config::VaultUuid,
             virtual_file::{VirtualFileId, VirtualFileVersion, VirtualFileVersionDescription},
         },
@@ -41,6 +42,14 @@ pub type UpdateDescription = String;
 
 const TEMP_NAME: &str = "{temp_name}";
 
+/// (Path, VirtualFileId, Version, VersionDescription) of a file that needs no transfer
+type IdenticalFileInfo = (
+    PathBuf,
+    VirtualFileId,
+    VirtualFileVersion,
+    VirtualFileVersionDescription,
+);
+
 #[derive(Serialize, Deserialize)]
 pub struct TrackFileActionArguments {
     // Path need to track
@@ -54,6 +63,18 @@ pub struct TrackFileActionArguments {
 
     // overwrite modified files
     pub allow_overwrite_modified: bool,
+
+    // Delete the local files erased upstream, instead of only unmapping them
+    #[serde(default)]
+    pub delete_erased_files: bool,
+
+    // Glob patterns relative to the workspace root, expanded locally and tracked with `relative_pathes`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    // Suffix an updated version whose name is already taken (`1.0.0` as `1.0.0_2`), instead of failing
+    #[serde(default)]
+    pub auto_suffix: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -63,6 +84,11 @@ pub enum TrackFileActionResult {
         updated: Vec<PathBuf>,
         synced: Vec<PathBuf>,
         skipped: Vec<PathBuf>,
+        erased: Vec<PathBuf>,
+
+        // Aggregate stats, only filled in on the local side
+        #[serde(default)]
+        summary: TrackSummary,
     },
 
     // Fail
@@ -77,6 +103,46 @@ pub enum TrackFileActionResult {
     SyncTaskFailed(SyncTaskResult),
 }
 
+/// Counts, transferred bytes and phase durations of a finished track
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TrackSummary {
+    pub created_count: usize,
+    pub updated_count: usize,
+    pub synced_count: usize,
+    pub skipped_count: usize,
+    pub erased_count: usize,
+
+    /// File content sent to the upstream by create and update tasks
+    pub bytes_sent: u64,
+
+    /// File content received from the upstream by sync tasks
+    pub bytes_received: u64,
+
+    /// Time spent analyzing the workspace and sorting paths into tasks
+    pub analyze_duration: Duration,
+    pub create_duration: Duration,
+    pub update_duration: Duration,
+    pub sync_duration: Duration,
+}
+
+impl TrackSummary {
+    /// Fill in the counts from the result lists
+    fn count(
+        &mut self,
+        created: &[PathBuf],
+        updated: &[PathBuf],
+        synced: &[PathBuf],
+        skipped: &[PathBuf],
+        erased: &[PathBuf],
+    ) {
+        self.created_count = created.len();
+        self.updated_count = updated.len();
+        self.synced_count = synced.len();
+        self.skipped_count = skipped.len();
+        self.erased_count = erased.len();
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum CreateTaskResult {
     Success(Vec<PathBuf>), // Success(success_relative_pathes)
@@ -119,7 +185,7 @@ pub async fn track_file_action(
     ctx: ActionContext,
     arguments: TrackFileActionArguments,
 ) -> Result<TrackFileActionResult, TcpTargetError> {
-    let relative_pathes = arguments.relative_pathes;
+    let mut relative_pathes = arguments.relative_pathes;
     let instance = check_connection_instance(&ctx)?;
 
     // Auth Member
@@ -137,11 +203,30 @@ pub async fn track_file_action(
 
     if ctx.is_proc_on_local() {
         let workspace = try_get_local_workspace(&ctx)?;
+        let mut summary = TrackSummary::default();
+        let phase_started = Instant::now();
+
+        // Expand patterns, the upstream only ever sees resolved paths
+        if !arguments.patterns.is_empty() {
+            relative_pathes.extend(get_relative_paths_by_patterns(
+                workspace.local_path(),
+                &arguments.patterns,
+            )?);
+        }
+
         let analyzed = AnalyzeResult::analyze_local_status(&workspace).await?;
         let latest_file_data =
             LatestFileData::read_from(LatestFileData::data_path(&member_id)?).await?;
 
-        if !analyzed.lost.is_empty() || !analyzed.moved.is_empty() {
+        // Moved files tracked at their new path are identical renames (target -> source),
+        // the others have to be solved first
+        let renames: HashMap<PathBuf, PathBuf> = analyzed
+            .moved
+            .values()
+            .filter(|(_, to)| relative_pathes.contains(to))
+            .map(|(from, to)| (to.clone(), from.clone()))
+            .collect();
+        if !analyzed.lost.is_empty() || analyzed.moved.len() > renames.len() {
             return Ok(TrackFileActionResult::StructureChangesNotSolved);
         }
 
@@ -150,8 +235,16 @@ pub async fn track_file_action(
         };
 
         // Read local sheet and member held
-        let local_sheet = workspace.local_sheet(&member_id, &sheet_in_use).await?;
+        let mut local_sheet = workspace.local_sheet(&member_id, &sheet_in_use).await?;
         let cached_sheet = CachedSheet::cached_sheet_data(&sheet_in_use).await?;
+
+        // Clean up the files erased upstream, so they are not re-uploaded
+        let erased = local_sheet
+            .remove_erased(&analyzed.erased, arguments.delete_erased_files)
+            .await?;
+        if !erased.is_empty() {
+            local_sheet.write().await?;
+        }
         let member_held = LatestFileData::read_from(LatestFileData::data_path(&member_id)?).await?;
 
         let modified = analyzed
@@ -160,10 +253,11 @@ pub async fn track_file_action(
             .cloned()
             .collect::<Vec<_>>();
 
-        // Filter out created files
+        // Filter out created files, renames are created without transfer
         let created_task = analyzed
             .created
             .intersection(&relative_pathes)
+            .chain(renames.keys())
             .cloned()
             .collect::<Vec<_>>();
 
@@ -173,9 +267,7 @@ pub async fn track_file_action(
                 if let Ok(local_data) = local_sheet.mapping_data(p) {
                     let id = local_data.mapping_vfid();
                     let local_ver = local_data.version_when_updated();
-                    let Some(latest_ver) = latest_file_data.file_version(id) else {
-                        return None;
-                    };
+                    let latest_ver = latest_file_data.file_version(id)?;
                     if let Some(held_member) = member_held.file_holder(id) {
                         // Check if holder and version match
                         if held_member == &member_id && local_ver == latest_ver {
@@ -194,7 +286,9 @@ pub async fn track_file_action(
         let mut sync_task: Vec<PathBuf> = {
             let other: Vec<PathBuf> = relative_pathes
                 .iter()
-                .filter(|p| !created_task.contains(p) && !update_task.contains(p))
+                .filter(|p| {
+                    !created_task.contains(p) && !update_task.contains(p) && !erased.contains(p)
+                })
                 .cloned()
                 .collect();
 
@@ -270,8 +364,10 @@ pub async fn track_file_action(
                 .await?;
             // Drop mutex here
         }
+        summary.analyze_duration = phase_started.elapsed();
 
         // Process create tasks
+        let phase_started = Instant::now();
         let mut success_create = Vec::<PathBuf>::new();
         if can_modify_sheet {
             success_create = match proc_create_tasks_local(
@@ -280,7 +376,9 @@ pub async fn track_file_action(
                 &member_id,
                 &sheet_name,
                 tasks.0,
+                renames,
                 arguments.print_infos,
+                &mut summary.bytes_sent,
             )
             .await
             {
@@ -293,8 +391,10 @@ pub async fn track_file_action(
                 Err(e) => return Err(e),
             };
         }
+        summary.create_duration = phase_started.elapsed();
 
         // Process update tasks
+        let phase_started = Instant::now();
         let mut success_update = Vec::<PathBuf>::new();
         if can_modify_sheet {
             success_update = match proc_update_tasks_local(
@@ -305,6 +405,7 @@ pub async fn track_file_action(
                 tasks.1,
                 arguments.print_infos,
                 arguments.file_update_info,
+                &mut summary.bytes_sent,
             )
             .await
             {
@@ -317,8 +418,10 @@ pub async fn track_file_action(
                 Err(e) => return Err(e),
             };
         }
+        summary.update_duration = phase_started.elapsed();
 
         // Process sync tasks
+        let phase_started = Instant::now();
         let success_sync = match proc_sync_tasks_local(
             &ctx,
             instance.clone(),
@@ -326,6 +429,7 @@ pub async fn track_file_action(
             &sheet_name,
             tasks.2,
             arguments.print_infos,
+            &mut summary.bytes_received,
         )
         .await
         {
@@ -334,16 +438,26 @@ pub async fn track_file_action(
             },
             Err(e) => return Err(e),
         };
+        summary.sync_duration = phase_started.elapsed();
 
         if success_create.len() + success_update.len() > 0 {
             sign_vault_modified(true).await;
         }
 
+        summary.count(
+            &success_create,
+            &success_update,
+            &success_sync,
+            &skipped_task,
+            &erased,
+        );
         return Ok(TrackFileActionResult::Done {
             created: success_create,
             updated: success_update,
             synced: success_sync,
             skipped: skipped_task,
+            erased,
+            summary,
         });
     }
 
@@ -386,6 +500,7 @@ pub async fn track_file_action(
                 &sheet_name,
                 update_task,
                 arguments.file_update_info,
+                arguments.auto_suffix,
             )
             .await
             {
@@ -420,26 +535,31 @@ pub async fn track_file_action(
             updated: success_update,
             synced: success_sync,
             skipped: Vec::new(), // The server doesn't know which files were skipped
+            erased: Vec::new(),
+            summary: TrackSummary::default(),
         });
     }
 
     Err(TcpTargetError::NoResult("No result.".to_string()))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn proc_create_tasks_local(
     ctx: &ActionContext,
     instance: Arc<Mutex<ConnectionInstance>>,
     member_id: &MemberId,
     sheet_name: &SheetName,
     relative_paths: Vec<PathBuf>,
+    renames: HashMap<PathBuf, PathBuf>,
     print_infos: bool,
+    bytes_sent: &mut u64,
 ) -> Result<CreateTaskResult, TcpTargetError> {
     let workspace = try_get_local_workspace(ctx)?;
     let local_output = try_get_local_output(ctx)?;
     let mut mut_instance = instance.lock().await;
     let mut local_sheet = workspace.local_sheet(member_id, sheet_name).await?;
 
-    if print_infos && relative_paths.len() > 0 {
+    if print_infos && !relative_paths.is_empty() {
         local_println!(local_output, "Creating {} files...", relative_paths.len());
     }
 
@@ -449,6 +569,13 @@ async fn proc_create_tasks_local(
         return Ok(CreateTaskResult::SheetNotFound(sheet_name.clone()));
     }
 
+    // Send manifest and renames, read back the files whose content already exists on remote
+    let manifest = calc_manifest(workspace.local_path(), &relative_paths).await;
+    mut_instance
+        .write_large_msgpack((manifest.clone(), renames.clone()), 1024u16)
+        .await?;
+    let identical: Vec<IdenticalFileInfo> = mut_instance.read_large_msgpack(1024u16).await?;
+
     // Wait for remote detection of whether the file exists
     let (hasnt_duplicate, duplicate_path) = mut_instance.read_msgpack::<(bool, PathBuf)>().await?;
     if !hasnt_duplicate {
@@ -457,13 +584,74 @@ async fn proc_create_tasks_local(
 
     let mut success_relative_pathes = Vec::new();
 
-    // Start sending files
+    // Byte-identical files are not transferred, only the mappings are recorded
+    let hashes: HashMap<PathBuf, String> = manifest.into_iter().collect();
+    let mut identical_paths = HashSet::new();
+    for (path, vfid, version, version_desc) in identical {
+        let Some(hash) = hashes.get(&path) else {
+            continue;
+        };
+        let full_path = workspace.local_path().join(&path);
+        let metadata = std::fs::metadata(&full_path)?;
+        let time = metadata.modified()?;
+
+        // An identical rename only moves the mapping, its content is unchanged
+        match renames.get(&path) {
+            Some(from) => local_sheet.move_mapping(from, &path)?,
+            None => local_sheet.add_mapping(
+                &path.clone(),
+                LocalMappingMetadata::new(
+                    hash.clone(),   // hash_when_updated
+                    time,           // time_when_updated
+                    metadata.len(), // size_when_updated
+                    version_desc,   // version_desc_when_updated
+                    version,        // version_when_updated
+                    vfid,           // mapping_vfid
+                    time,           // last_modifiy_check_itme
+                    false,          // last_modifiy_check_result
+                ),
+            )?,
+        }
+
+        // Print success info
+        if print_infos {
+            match renames.get(&path) {
+                Some(from) => {
+                    local_println!(local_output, "= {} -> {}", from.display(), path.display())
+                }
+                None => local_println!(local_output, "= {}", path.display()),
+            }
+        }
+
+        identical_paths.insert(path.clone());
+        success_relative_pathes.push(path);
+    }
+
+    // Start sending files, renames the remote did not find identical are left unsolved
     for path in relative_paths {
+        if identical_paths.contains(&path) || renames.contains_key(&path) {
+            continue;
+        }
+
+        // Stop between files on cancellation, the mappings are only kept
+        // once the remote confirmed it committed the files already created
+        if let Err(e) = mut_instance.check_cancelled() {
+            mut_instance.write_msgpack(false).await?;
+            if mut_instance.read_msgpack::<bool>().await? {
+                local_sheet.write().await?;
+            }
+            return Err(e);
+        }
+        mut_instance.write_msgpack(true).await?; // Continue
+
         let full_path = workspace.local_path().join(&path);
 
         // Send file
-        if mut_instance.write_file(&full_path).await.is_err() {
-            continue;
+        match write_file_counted(&mut mut_instance, &full_path, bytes_sent).await {
+            Ok(_) => {}
+            // Cancelled mid-file, the remote is left mid-transfer and commits nothing
+            Err(e @ TcpTargetError::Cancelled(_)) => return Err(e),
+            Err(_) => continue,
         }
 
         // Read virtual file id and version
@@ -476,7 +664,7 @@ async fn proc_create_tasks_local(
             .await?;
 
         // Add mapping to local sheet
-        let hash = sha1_hash::calc_sha1(&full_path, 2048).await.unwrap().hash;
+        let hash = sha1_hash::calc_sha1_default(&full_path).await.unwrap().hash;
         let time = std::fs::metadata(&full_path)?.modified()?;
         local_sheet.add_mapping(
             &path.clone(),
@@ -517,16 +705,28 @@ async fn proc_create_tasks_remote(
     let mut mut_instance = instance.lock().await;
 
     // Sheet check
-    let Ok(mut sheet) = vault.sheet(sheet_name).await else {
+    let Ok(mut sheet) = vault.sheet_for_update(sheet_name).await else {
         // Sheet not found
         mut_instance.write_msgpack(false).await?;
         return Ok(CreateTaskResult::SheetNotFound(sheet_name.to_string()));
     };
     mut_instance.write_msgpack(true).await?;
 
-    // Duplicate create precheck
+    // Read manifest and renames, tell client which files are already byte-identical
+    let (manifest, renames): (Vec<SheetManifestItem>, HashMap<PathBuf, PathBuf>) =
+        mut_instance.read_large_msgpack(1024u16).await?;
+    let mut identical_paths = sheet.identical_mappings(&manifest, &renames).await;
+    identical_paths.retain(|to, from| to == from || sheet.check_path_limits(to).is_ok());
+    let hashes: HashMap<PathBuf, String> = manifest.into_iter().collect();
+    let identical = collect_identical_infos(&vault, &sheet, &identical_paths).await;
+    mut_instance.write_large_msgpack(identical, 1024u16).await?;
+
+    // Duplicate create precheck, for the files to be transferred
     for path in relative_paths.iter() {
-        if sheet.mapping().contains_key(path) {
+        if sheet.mapping().contains_key(path)
+            && !identical_paths.contains_key(path)
+            && !renames.contains_key(path)
+        {
             // Duplicate file
             mut_instance.write_msgpack((false, path)).await?;
             return Ok(CreateTaskResult::CreateFileOnExistPath(path.clone()));
@@ -534,15 +734,51 @@ async fn proc_create_tasks_remote(
     }
     mut_instance.write_msgpack((true, PathBuf::new())).await?;
 
+    // Identical renames only move the mapping
+    for (to, from) in identical_paths.iter().filter(|(to, from)| to != from) {
+        if let Some(mapping) = sheet.mapping_mut().remove(from) {
+            sheet.mapping_mut().insert(to.clone(), mapping);
+        }
+    }
+
     let mut success_relative_pathes = Vec::new();
 
     // Start receiving files
     for path in relative_paths {
-        // Read file and create virtual file
-        let Ok(vfid) = vault
-            .create_virtual_file_from_connection(&mut mut_instance, member_id)
-            .await
-        else {
+        // Byte-identical files are not transferred, unsolved renames are skipped
+        if identical_paths.contains_key(&path) {
+            success_relative_pathes.push(path);
+            continue;
+        }
+        if renames.contains_key(&path) {
+            continue;
+        }
+
+        // The client cancelled, commit the files created so far and tell it whether that worked
+        if !mut_instance.read_msgpack::<bool>().await? {
+            let persisted = sheet.persist().await;
+            mut_instance.write_msgpack(persisted.is_ok()).await?;
+            persisted?;
+            return Err(TcpTargetError::Cancelled(
+                "Create cancelled by the client".to_string(),
+            ));
+        }
+
+        // Read file and create virtual file, a retried create of the same content reuses it
+        let created = match hashes.get(&path) {
+            Some(hash) => {
+                let token = format!("{}:{}", path.display(), hash);
+                vault
+                    .create_virtual_file_from_connection_once(&mut mut_instance, member_id, &token)
+                    .await
+            }
+            None => {
+                vault
+                    .create_virtual_file_from_connection(&mut mut_instance, member_id)
+                    .await
+            }
+        };
+        let Ok(vfid) = created else {
             continue;
         };
 
@@ -571,6 +807,7 @@ async fn proc_create_tasks_remote(
     Ok(CreateTaskResult::Success(success_relative_pathes))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn proc_update_tasks_local(
     ctx: &ActionContext,
     instance: Arc<Mutex<ConnectionInstance>>,
@@ -579,19 +816,60 @@ async fn proc_update_tasks_local(
     relative_paths: Vec<PathBuf>,
     print_infos: bool,
     file_update_info: HashMap<PathBuf, (NextVersion, UpdateDescription)>,
+    bytes_sent: &mut u64,
 ) -> Result<UpdateTaskResult, TcpTargetError> {
     let workspace = try_get_local_workspace(ctx)?;
     let local_output = try_get_local_output(ctx)?;
     let mut mut_instance = instance.lock().await;
     let mut local_sheet = workspace.local_sheet(member_id, sheet_name).await?;
 
+    // Each file exchanges several small messages, send them in as few packets as possible
+    mut_instance.set_write_buffering(true).await?;
+
     let mut success = Vec::new();
 
-    if print_infos && relative_paths.len() > 0 {
+    if print_infos && !relative_paths.is_empty() {
         local_println!(local_output, "Updating {} files...", relative_paths.len());
     }
 
+    // Send manifest, read back the files whose content already exists on remote
+    let manifest = calc_manifest(workspace.local_path(), &relative_paths).await;
+    mut_instance
+        .write_large_msgpack(manifest.clone(), 1024u16)
+        .await?;
+    let identical: Vec<IdenticalFileInfo> = mut_instance.read_large_msgpack(1024u16).await?;
+
+    // Byte-identical files are not transferred, only the mappings are updated
+    let hashes: HashMap<PathBuf, String> = manifest.into_iter().collect();
+    let mut identical_paths = HashSet::new();
+    for (path, _, version, version_desc) in identical {
+        let (Some(hash), Ok(mapping_data_mut)) =
+            (hashes.get(&path), local_sheet.mapping_data_mut(&path))
+        else {
+            continue;
+        };
+        mapping_data_mut.set_hash_when_updated(hash.clone());
+        mapping_data_mut.set_version_when_updated(version.clone());
+        mapping_data_mut.set_version_desc_when_updated(version_desc);
+        mapping_data_mut.set_last_modifiy_check_result(false); // Mark file not modified
+
+        // Print success info
+        if print_infos {
+            local_println!(local_output, "= {} ({})", path.display(), version);
+        }
+
+        identical_paths.insert(path.clone());
+        success.push(path);
+    }
+
     for path in relative_paths.iter() {
+        if identical_paths.contains(path) {
+            continue;
+        }
+
+        // Stop between files on cancellation, the remote drops the unfinished batch
+        mut_instance.check_cancelled()?;
+
         let Ok(mapping) = local_sheet.mapping_data(path) else {
             // Is mapping not found, write empty
             mut_instance.write_msgpack("".to_string()).await?;
@@ -609,6 +887,7 @@ async fn proc_update_tasks_local(
         let verify_result: bool = mut_instance.read_msgpack().await?;
         if !verify_result {
             let reason = mut_instance.read_msgpack::<VerifyFailReason>().await?;
+            mut_instance.set_write_buffering(false).await?;
             return Ok(UpdateTaskResult::VerifyFailed {
                 path: path.clone(),
                 reason: reason.clone(),
@@ -616,14 +895,14 @@ async fn proc_update_tasks_local(
         }
 
         // Calc hash
-        let hash_result = match sha1_hash::calc_sha1(workspace.local_path().join(path), 2048).await
-        {
-            Ok(r) => r,
-            Err(_) => {
-                mut_instance.write_msgpack(false).await?; // Not Ready
-                continue;
-            }
-        };
+        let hash_result =
+            match sha1_hash::calc_sha1_default(workspace.local_path().join(path)).await {
+                Ok(r) => r,
+                Err(_) => {
+                    mut_instance.write_msgpack(false).await?; // Not Ready
+                    continue;
+                }
+            };
 
         // Get next version
         let Some((next_version, description)) = file_update_info.get(path) else {
@@ -633,25 +912,25 @@ async fn proc_update_tasks_local(
 
         // Write
         mut_instance.write_msgpack(true).await?; // Ready
-        mut_instance.write_file(path).await?;
+        write_file_counted(&mut mut_instance, path, bytes_sent).await?;
 
-        // Read upload result
+        // Read upload result, the remote drops the whole batch on failure
         let upload_result: bool = mut_instance.read_msgpack().await?;
         if upload_result {
-            // Success
+            // Success, the remote may have normalized the version name
+            let stored_version: VirtualFileVersion = mut_instance.read_msgpack().await?;
+            let mut version_desc =
+                VirtualFileVersionDescription::new(member_id.clone(), description.clone());
+            if stored_version != *next_version {
+                version_desc.display_name = Some(next_version.clone());
+            }
             let mapping_data_mut = local_sheet.mapping_data_mut(path).unwrap();
             let version = mapping_data_mut.version_when_updated().clone();
             mapping_data_mut.set_hash_when_updated(hash_result.hash);
-            mapping_data_mut.set_version_when_updated(next_version.clone());
-            mapping_data_mut.set_version_desc_when_updated(VirtualFileVersionDescription {
-                creator: member_id.clone(),
-                description: description.clone(),
-            });
+            mapping_data_mut.set_version_when_updated(stored_version);
+            mapping_data_mut.set_version_desc_when_updated(version_desc);
             mapping_data_mut.set_last_modifiy_check_result(false); // Mark file not modified
 
-            // Write
-            local_sheet.write().await?;
-
             // Push path into success vec
             success.push(path.clone());
 
@@ -665,9 +944,24 @@ async fn proc_update_tasks_local(
                     next_version
                 );
             }
+        } else {
+            return Err(TcpTargetError::File(format!(
+                "Failed to update `{}`, no files were updated",
+                path.display()
+            )));
         }
     }
 
+    // Write once the remote stored the whole batch
+    let committed: bool = mut_instance.read_msgpack().await?;
+    mut_instance.set_write_buffering(false).await?;
+    if !committed {
+        return Err(TcpTargetError::File(
+            "Failed to store the updated files, no files were updated".to_string(),
+        ));
+    }
+    local_sheet.write().await?;
+
     Ok(UpdateTaskResult::Success(success))
 }
 
@@ -678,17 +972,39 @@ async fn proc_update_tasks_remote(
     sheet_name: &SheetName,
     relative_paths: Vec<PathBuf>,
     file_update_info: HashMap<PathBuf, (NextVersion, UpdateDescription)>,
+    auto_suffix: bool,
 ) -> Result<UpdateTaskResult, TcpTargetError> {
     let vault = try_get_vault(ctx)?;
     let mut mut_instance = instance.lock().await;
 
     let mut success = Vec::new();
 
+    // Read manifest, tell client which files are already byte-identical
+    let manifest: Vec<SheetManifestItem> = mut_instance.read_large_msgpack(1024u16).await?;
+
+    // The batch is all or nothing, the sheet stays locked and the received versions are staged
+    // until every file succeeded, a failed batch drops the transaction
+    let mut transaction = vault.transaction(sheet_name).await;
+    let (identical_paths, identical) = match &transaction {
+        Ok(transaction) => {
+            let sheet = transaction.sheet();
+            let identical_paths = sheet.identical_mappings(&manifest, &HashMap::new()).await;
+            let identical = collect_identical_infos(&vault, sheet, &identical_paths).await;
+            (identical_paths, identical)
+        }
+        Err(_) => (HashMap::new(), Vec::new()),
+    };
+    mut_instance.write_large_msgpack(identical, 1024u16).await?;
+
     for path in relative_paths.iter() {
-        // Read version
-        let Ok(version) = mut_instance.read_msgpack::<VirtualFileVersion>().await else {
+        // Byte-identical files are not transferred
+        if identical_paths.contains_key(path) {
+            success.push(path.clone());
             continue;
-        };
+        }
+
+        // Read version, a lost connection drops the batch
+        let version = mut_instance.read_msgpack::<VirtualFileVersion>().await?;
         if version.is_empty() {
             continue;
         }
@@ -703,7 +1019,7 @@ async fn proc_update_tasks_remote(
                 reason,
             }); // Sheet not found
         };
-        let Ok(mut sheet) = vault.sheet(sheet_name).await else {
+        let Ok(transaction) = transaction.as_mut() else {
             mut_instance.write_msgpack(false).await?;
             let reason = VerifyFailReason::SheetNotFound(sheet_name.clone());
             mut_instance.write_msgpack(reason.clone()).await?;
@@ -712,7 +1028,7 @@ async fn proc_update_tasks_remote(
                 reason,
             }); // Sheet not found
         };
-        let Some(mapping_data) = sheet.mapping_mut().get_mut(path) else {
+        let Some(mapping_data) = transaction.sheet().mapping().get(path) else {
             mut_instance.write_msgpack(false).await?;
             let reason = VerifyFailReason::MappingNotFound;
             mut_instance.write_msgpack(reason.clone()).await?;
@@ -739,7 +1055,10 @@ async fn proc_update_tasks_remote(
                 reason,
             }); // Read virtual file metadata failed
         };
-        if vf_metadata.versions().contains(next_version) {
+        // Compare and record the version under the name it is stored as, a taken name is
+        // suffixed on receive with `auto_suffix`
+        let next_version = vault.normalize_version_name(next_version);
+        if !auto_suffix && vf_metadata.versions().contains(&next_version) {
             mut_instance.write_msgpack(false).await?;
             let reason = VerifyFailReason::VersionAlreadyExist(version);
             mut_instance.write_msgpack(reason.clone()).await?;
@@ -775,29 +1094,28 @@ async fn proc_update_tasks_remote(
             continue;
         }
 
-        // Read and update virtual file
-        match vault
-            .update_virtual_file_from_connection(
+        // Read and stage the new version
+        let id = mapping_data.id.clone();
+        match transaction
+            .receive_version(
                 &mut mut_instance,
                 member_id,
-                &mapping_data.id,
-                next_version,
-                VirtualFileVersionDescription {
-                    creator: member_id.clone(),
-                    description: description.clone(),
-                },
+                &id,
+                &next_version,
+                VirtualFileVersionDescription::new(member_id.clone(), description.clone()),
+                auto_suffix,
             )
             .await
         {
-            Ok(_) => {
+            Ok(stored_version) => {
                 // Update version to sheet
-                mapping_data.version = next_version.clone();
-
-                // Persist
-                sheet.persist().await?;
+                if let Some(mapping_data) = transaction.sheet_mut().mapping_mut().get_mut(path) {
+                    mapping_data.version = stored_version.clone();
+                }
 
                 success.push(path.clone());
                 mut_instance.write_msgpack(true).await?; // Success
+                mut_instance.write_msgpack(stored_version).await?; // Stored version
             }
             Err(e) => {
                 mut_instance.write_msgpack(false).await?; // Fail
@@ -806,9 +1124,84 @@ async fn proc_update_tasks_remote(
         }
     }
 
+    // Store the batch, then tell the client whether it was stored
+    let committed = match transaction {
+        Ok(transaction) => transaction.commit().await,
+        Err(_) => Ok(()),
+    };
+    mut_instance.write_msgpack(committed.is_ok()).await?;
+    committed?;
+
     Ok(UpdateTaskResult::Success(success))
 }
 
+/// Send a file, adding its size to `bytes` once the transfer succeeded
+async fn write_file_counted(
+    instance: &mut ConnectionInstance,
+    path: impl AsRef<Path>,
+    bytes: &mut u64,
+) -> Result<(), TcpTargetError> {
+    let mut sent = 0;
+    instance
+        .write_file_with_progress(path, &mut |done, _| sent = done)
+        .await?;
+    *bytes += sent;
+    Ok(())
+}
+
+/// Receive a file, adding its size to `bytes` once the transfer succeeded
+async fn read_file_counted(
+    instance: &mut ConnectionInstance,
+    path: impl AsRef<Path>,
+    bytes: &mut u64,
+) -> Result<(), TcpTargetError> {
+    let mut received = 0;
+    instance
+        .read_file_with_progress(path, &mut |done, _| received = done)
+        .await?;
+    *bytes += received;
+    Ok(())
+}
+
+/// Calculate the manifest of the files to be transferred
+async fn calc_manifest(local_path: &Path, relative_paths: &[PathBuf]) -> Vec<SheetManifestItem> {
+    let mut manifest = Vec::new();
+    for path in relative_paths {
+        if let Ok(result) = calc_sha1_default(local_path.join(path)).await {
+            manifest.push((path.clone(), result.hash));
+        }
+    }
+    manifest
+}
+
+/// Collect the mapping infos of the byte-identical files (path -> identical mapping path)
+async fn collect_identical_infos(
+    vault: &Vault,
+    sheet: &Sheet<'_>,
+    identical_paths: &HashMap<PathBuf, PathBuf>,
+) -> Vec<IdenticalFileInfo> {
+    let mut infos = Vec::new();
+    for (path, mapped_path) in identical_paths {
+        let Some(mapping) = sheet.mapping().get(mapped_path) else {
+            continue;
+        };
+        let description = match vault.virtual_file_meta(&mapping.id).await {
+            Ok(meta) => meta
+                .version_description(mapping.version.clone())
+                .cloned()
+                .unwrap_or_default(),
+            Err(_) => VirtualFileVersionDescription::default(),
+        };
+        infos.push((
+            path.clone(),
+            mapping.id.clone(),
+            mapping.version.clone(),
+            description,
+        ));
+    }
+    infos
+}
+
 type SyncVersionInfo = Option<(
     VirtualFileVersion,
     VirtualFileVersionDescription,
@@ -822,17 +1215,21 @@ async fn proc_sync_tasks_local(
     sheet_name: &SheetName,
     relative_paths: Vec<PathBuf>,
     print_infos: bool,
+    bytes_received: &mut u64,
 ) -> Result<SyncTaskResult, TcpTargetError> {
     let workspace = try_get_local_workspace(ctx)?;
     let local_output = try_get_local_output(ctx)?;
     let mut mut_instance = instance.lock().await;
     let mut success: Vec<PathBuf> = Vec::new();
 
-    if print_infos && relative_paths.len() > 0 {
+    if print_infos && !relative_paths.is_empty() {
         local_println!(local_output, "Syncing {} files...", relative_paths.len());
     }
 
     for path in relative_paths {
+        // Stop between files on cancellation, the files already synced stay recorded
+        mut_instance.check_cancelled()?;
+
         let Some((version, description, vfid)) =
             mut_instance.read_msgpack::<SyncVersionInfo>().await?
         else {
@@ -847,7 +1244,7 @@ async fn proc_sync_tasks_local(
         let copy_to = workspace.local_path().join(&path);
 
         // Read file
-        match mut_instance.read_file(&temp_path).await {
+        match read_file_counted(&mut mut_instance, &temp_path, bytes_received).await {
             Ok(_) => {
                 if !temp_path.exists() {
                     continue;
@@ -859,7 +1256,7 @@ async fn proc_sync_tasks_local(
         }
 
         // Calc hash
-        let new_hash = match calc_sha1(&temp_path, 2048).await {
+        let new_hash = match calc_sha1_default(&temp_path).await {
             Ok(hash) => hash,
             Err(_) => {
                 continue;
@@ -876,7 +1273,7 @@ async fn proc_sync_tasks_local(
 
         // Write file
         if copy_to.exists() {
-            if let Err(_) = fs::remove_file(&copy_to).await {
+            if fs::remove_file(&copy_to).await.is_err() {
                 continue;
             }
         } else {
@@ -885,7 +1282,7 @@ async fn proc_sync_tasks_local(
                 fs::create_dir_all(path).await?;
             }
         }
-        if let Err(_) = fs::rename(&temp_path, &copy_to).await {
+        if fs::rename(&temp_path, &copy_to).await.is_err() {
             continue;
         }
 
@@ -904,7 +1301,7 @@ async fn proc_sync_tasks_local(
                 // First download
                 let mut data = LocalMappingMetadata::default();
                 data.set_mapping_vfid(vfid);
-                if let Err(_) = local_sheet.add_mapping(&path, data) {
+                if local_sheet.add_mapping(&path, data).is_err() {
                     continue;
                 }
                 match local_sheet.mapping_data_mut(&path) {
@@ -924,7 +1321,7 @@ async fn proc_sync_tasks_local(
         mapping.set_size_when_updated(new_size);
         mapping.set_time_when_updated(time);
         mapping.set_last_modifiy_check_time(time);
-        if let Err(_) = local_sheet.write().await {
+        if local_sheet.write().await.is_err() {
             continue;
         }
 
@@ -968,12 +1365,10 @@ async fn proc_sync_tasks_remote(
         mut_instance
             .write_msgpack::<SyncVersionInfo>(Some((
                 version.clone(),
-                vf_meta.version_description(version).cloned().unwrap_or(
-                    VirtualFileVersionDescription {
-                        creator: MemberId::default(),
-                        description: "".to_string(),
-                    },
-                ),
+                vf_meta
+                    .version_description(version)
+                    .cloned()
+                    .unwrap_or_default(),
                 vf.id(),
             )))
             .await?; // (ready)
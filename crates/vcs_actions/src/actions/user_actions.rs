@@ -87,11 +87,16 @@ pub async fn change_virtual_file_edit_right_action(
                     Err(_) => continue,
                 }
             } else
-            // Throw file
-            if (has_edit_right || is_host_mode)
-                && behaviour == EditRightChangeBehaviour::Throw
-            {
-                match vault.revoke_virtual_file_edit_right(&mapping.id).await {
+            // Throw file, admins can throw any file
+            if behaviour == EditRightChangeBehaviour::Throw {
+                let revoke = if is_host_mode {
+                    vault.revoke_virtual_file_edit_right(&mapping.id).await
+                } else {
+                    vault
+                        .revoke_virtual_file_edit_right_as(&member_id, &mapping.id)
+                        .await
+                };
+                match revoke {
                     Ok(_) => {
                         success_throw.push(path.clone());
                     }
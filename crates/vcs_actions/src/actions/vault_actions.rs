@@ -1 +1,111 @@
+use std::path::PathBuf;
 
+use action_system::{action::ActionContext, macros::action_gen};
+use serde::{Deserialize, Serialize};
+use string_proc::format_path::format_path;
+use tcp_connection::error::TcpTargetError;
+use vcs_data::data::vault::virtual_file::VirtualFileVersion;
+
+use crate::{
+    actions::{auth_member, check_connection_instance, get_current_sheet_name, try_get_vault},
+    write_and_return,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchVersionActionArguments {
+    // Path of the mapping in the sheet
+    pub relative_path: PathBuf,
+
+    // Version to fetch
+    pub version: VirtualFileVersion,
+
+    // Local path to save the content
+    pub save_path: PathBuf,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub enum FetchVersionActionResult {
+    // Saved to the caller's `save_path`, the upstream receives it with the arguments but never uses it
+    Success,
+
+    // Fail
+    AuthorizeFailed(String),
+    MappingNotFound(PathBuf),
+    VersionNotFound(VirtualFileVersion),
+    TransferFailed(String),
+
+    #[default]
+    Unknown,
+}
+
+/// Fetch the content of a specific version of a tracked file
+///
+/// The content is saved to `save_path` only,
+/// the local sheet and the workspace files are not touched
+#[action_gen]
+pub async fn fetch_version_action(
+    ctx: ActionContext,
+    arguments: FetchVersionActionArguments,
+) -> Result<FetchVersionActionResult, TcpTargetError> {
+    let instance = check_connection_instance(&ctx)?;
+
+    // Auth Member
+    let (member_id, _is_host_mode) = match auth_member(&ctx, instance).await {
+        Ok(id) => id,
+        Err(e) => return Ok(FetchVersionActionResult::AuthorizeFailed(e.to_string())),
+    };
+
+    // Check sheet
+    let (sheet_name, _is_ref_sheet) =
+        get_current_sheet_name(&ctx, instance, &member_id, true).await?;
+
+    if ctx.is_proc_on_remote() {
+        let vault = try_get_vault(&ctx)?;
+        let sheet = vault.sheet(&sheet_name).await;
+
+        // Resolve mapping
+        let relative_path = format_path(arguments.relative_path.clone()).ok();
+        let Some(mapping) = sheet
+            .as_ref()
+            .ok()
+            .and_then(|sheet| sheet.mapping().get(relative_path.as_ref()?))
+        else {
+            write_and_return!(
+                instance,
+                FetchVersionActionResult::MappingNotFound(arguments.relative_path.clone())
+            );
+        };
+
+        // Check version
+        let Ok(real_path) = vault
+            .virtual_file_version_path(&mapping.id, &arguments.version)
+            .await
+        else {
+            write_and_return!(
+                instance,
+                FetchVersionActionResult::VersionNotFound(arguments.version.clone())
+            );
+        };
+
+        // Send version instance
+        let mut mut_instance = instance.lock().await;
+        mut_instance
+            .write(FetchVersionActionResult::Success)
+            .await?;
+        mut_instance.write_file(real_path).await?;
+        return Ok(FetchVersionActionResult::Success);
+    }
+
+    if ctx.is_proc_on_local() {
+        let mut mut_instance = instance.lock().await;
+        let result = mut_instance.read::<FetchVersionActionResult>().await?;
+        if let FetchVersionActionResult::Success = result
+            && let Err(e) = mut_instance.read_file(&arguments.save_path).await
+        {
+            return Ok(FetchVersionActionResult::TransferFailed(e.to_string()));
+        }
+        return Ok(result);
+    }
+
+    Err(TcpTargetError::NoResult("No result.".to_string()))
+}
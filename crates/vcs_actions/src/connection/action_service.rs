@@ -198,6 +198,7 @@ async fn process_connection(stream: TcpStream, vault: Arc<Vault>, action_pool: A
 
     // Build context
     let ctx: ActionContext = ActionContext::remote().insert_instance(instance);
+    let instance = ctx.instance().clone();
 
     // Insert vault into context
     let ctx = ctx.with_arc_data(vault);
@@ -209,7 +210,12 @@ async fn process_connection(stream: TcpStream, vault: Arc<Vault>, action_pool: A
 
     // Process action
     let result = action_pool
-        .process_json(&msg.action_name, ctx, msg.action_args_json)
+        .process_json_versioned(
+            &msg.action_name,
+            msg.action_version,
+            ctx,
+            msg.action_args_json,
+        )
         .await;
 
     match result {
@@ -218,4 +224,12 @@ async fn process_connection(stream: TcpStream, vault: Arc<Vault>, action_pool: A
             warn!("Failed to process action `{}`: {}", msg.action_name, e);
         }
     }
+
+    // Close the connection cleanly, so the client sees the end of the session
+    if let Some(instance) = instance
+        && let Ok(instance) = Arc::try_unwrap(instance)
+        && let Err(e) = instance.into_inner().close().await
+    {
+        warn!("Failed to close connection: {}", e);
+    }
 }
@@ -1,7 +1,25 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RemoteActionInvoke {
     pub action_name: String,
     pub action_args_json: String,
+
+    /// Protocol version of the action, missing from older clients which only had version 1
+    #[serde(default = "default_action_version")]
+    pub action_version: u32,
+}
+
+impl Default for RemoteActionInvoke {
+    fn default() -> Self {
+        Self {
+            action_name: String::default(),
+            action_args_json: String::default(),
+            action_version: default_action_version(),
+        }
+    }
+}
+
+fn default_action_version() -> u32 {
+    1
 }
@@ -15,10 +15,12 @@ use crate::{
         },
         sheet_actions::{
             register_drop_sheet_action, register_edit_mapping_action, register_make_sheet_action,
-            register_merge_share_mapping_action, register_share_mapping_action,
+            register_merge_share_mapping_action, register_move_subtree_action,
+            register_share_mapping_action,
         },
         track_action::register_track_file_action,
         user_actions::register_change_virtual_file_edit_right_action,
+        vault_actions::register_fetch_version_action,
     },
     connection::protocol::RemoteActionInvoke,
 };
@@ -34,6 +36,7 @@ fn register_actions(pool: &mut ActionPool) {
     register_make_sheet_action(pool);
     register_drop_sheet_action(pool);
     register_edit_mapping_action(pool);
+    register_move_subtree_action(pool);
 
     // Share / Merge Share Actions
     register_share_mapping_action(pool);
@@ -44,6 +47,9 @@ fn register_actions(pool: &mut ActionPool) {
 
     // User Actions
     register_change_virtual_file_edit_right_action(pool);
+
+    // Vault Actions
+    register_fetch_version_action(pool);
 }
 
 pub fn client_action_pool() -> ActionPool {
@@ -67,8 +73,9 @@ async fn on_proc_begin(
     // Is ctx remote
     let is_remote = ctx.is_remote_action();
 
-    // Action name and arguments
+    // Action name, version and arguments
     let action_name = ctx.action_name().to_string();
+    let action_version = ctx.action_version();
     let action_args_json = ctx.action_args_json().clone();
 
     // Insert LocalWorkspace Arc
@@ -111,6 +118,7 @@ async fn on_proc_begin(
         let msg = RemoteActionInvoke {
             action_name,
             action_args_json,
+            action_version,
         };
 
         // Send
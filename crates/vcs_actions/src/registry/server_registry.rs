@@ -4,10 +4,12 @@ use crate::actions::{
     local_actions::{register_set_upstream_vault_action, register_update_to_latest_info_action},
     sheet_actions::{
         register_drop_sheet_action, register_edit_mapping_action, register_make_sheet_action,
-        register_merge_share_mapping_action, register_share_mapping_action,
+        register_merge_share_mapping_action, register_move_subtree_action,
+        register_share_mapping_action,
     },
     track_action::register_track_file_action,
     user_actions::register_change_virtual_file_edit_right_action,
+    vault_actions::register_fetch_version_action,
 };
 
 pub fn server_action_pool() -> ActionPool {
@@ -21,6 +23,7 @@ pub fn server_action_pool() -> ActionPool {
     register_make_sheet_action(&mut pool);
     register_drop_sheet_action(&mut pool);
     register_edit_mapping_action(&mut pool);
+    register_move_subtree_action(&mut pool);
 
     // Share / Merge Share Actions
     register_share_mapping_action(&mut pool);
@@ -32,5 +35,8 @@ pub fn server_action_pool() -> ActionPool {
     // User Actions
     register_change_virtual_file_edit_right_action(&mut pool);
 
+    // Vault Actions
+    register_fetch_version_action(&mut pool);
+
     pool
 }
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use tokio::fs;
+
+pub mod test_utils;
+
+#[cfg(test)]
+pub mod test_track_cancel;
+
+#[cfg(test)]
+pub mod test_track_summary;
+
+#[cfg(test)]
+pub mod test_track_identical;
+
+#[cfg(test)]
+pub mod test_track_update_batch;
+
+#[cfg(test)]
+pub mod test_track_auto_suffix;
+
+#[cfg(test)]
+pub mod test_analyze_fuzzy_move;
+
+#[cfg(test)]
+pub mod test_fetch_version;
+
+pub async fn get_test_dir(area: &str) -> Result<PathBuf, std::io::Error> {
+    // Not relative to the current directory, the local side of an action moves into the workspace
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join(".temp")
+        .join("test")
+        .join(area);
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)?;
+    } else {
+        // Regenerate existing directory
+        fs::remove_dir_all(&dir).await?;
+        fs::create_dir_all(&dir).await?;
+    }
+    Ok(dir)
+}
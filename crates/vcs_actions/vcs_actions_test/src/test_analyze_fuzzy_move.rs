@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use tcp_connection::error::TcpTargetError;
+use vcs_actions::actions::track_action::{TrackFileAction, TrackFileActionResult};
+use vcs_data::data::local::workspace_analyzer::AnalyzeResult;
+
+use crate::test_utils::{ActionTestEnv, track_arguments};
+
+#[tokio::test]
+async fn test_analyze_fuzzy_move_with_unmatched_new_file() -> Result<(), TcpTargetError> {
+    let host = "localhost:5076";
+    let env = ActionTestEnv::setup("analyze_fuzzy_move", host).await?;
+    env.write_local_file("Assets/hero_sword.png", vec![1u8; 10_000])
+        .await?;
+    let (local, remote) = env
+        .run_action::<TrackFileAction, _, _>(host, track_arguments(&["Assets/hero_sword.png"]))
+        .await;
+    remote?;
+    assert!(matches!(local?, TrackFileActionResult::Done { .. }));
+    env.update_to_latest_info(host).await?;
+
+    // Moved and edited, next to an unrelated new file: one lost, two new
+    let local_path = env.workspace.local_path();
+    tokio::fs::remove_file(local_path.join("Assets/hero_sword.png")).await?;
+    env.write_local_file("Assets/Weapons/hero_sword_v2.png", vec![2u8; 10_240])
+        .await?;
+    env.write_local_file("Docs/readme.txt", "Unrelated").await?;
+
+    let result = AnalyzeResult::analyze_local_status(&env.workspace).await?;
+    let moved: Vec<_> = result.moved.values().cloned().collect();
+    assert_eq!(
+        moved,
+        vec![(
+            PathBuf::from("Assets/hero_sword.png"),
+            PathBuf::from("Assets/Weapons/hero_sword_v2.png"),
+        )]
+    );
+    assert!(result.lost.is_empty());
+    assert_eq!(
+        result.created.into_iter().collect::<Vec<_>>(),
+        vec![PathBuf::from("Docs/readme.txt")]
+    );
+
+    Ok(())
+}
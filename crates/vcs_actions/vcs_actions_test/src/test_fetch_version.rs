@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use tcp_connection::error::TcpTargetError;
+use vcs_actions::actions::{
+    track_action::{TrackFileAction, TrackFileActionResult},
+    vault_actions::{FetchVersionAction, FetchVersionActionArguments, FetchVersionActionResult},
+};
+
+use crate::{
+    get_test_dir,
+    test_utils::{ActionTestEnv, track_arguments},
+};
+
+const OLD_CONTENT: &[u8] = b"Old content of the tracked file";
+const LATEST_CONTENT: &[u8] = b"Latest content of the tracked file";
+
+/// Run a fetch, returns the result of the local side
+async fn fetch(
+    env: &ActionTestEnv,
+    host: &str,
+    relative_path: &str,
+    version: &str,
+    save_path: &Path,
+) -> Result<FetchVersionActionResult, TcpTargetError> {
+    let args = FetchVersionActionArguments {
+        relative_path: PathBuf::from(relative_path),
+        version: version.to_string(),
+        save_path: save_path.to_path_buf(),
+    };
+    let (local, remote) = env.run_action::<FetchVersionAction, _, _>(host, args).await;
+    remote?;
+    local
+}
+
+#[tokio::test]
+async fn test_fetch_version() -> Result<(), TcpTargetError> {
+    let host = "localhost:5078";
+    let env = ActionTestEnv::setup("fetch_version", host).await?;
+    let save_dir = get_test_dir("fetch_version_save").await?;
+    let save_path = save_dir.join("fetched.txt");
+
+    // Track the old content, then update the file to the latest content
+    env.write_local_file("Docs/a.txt", OLD_CONTENT).await?;
+    let (local, remote) = env
+        .run_action::<TrackFileAction, _, _>(host, track_arguments(&["Docs/a.txt"]))
+        .await;
+    remote?;
+    assert!(matches!(local?, TrackFileActionResult::Done { .. }));
+    env.update_to_latest_info(host).await?;
+
+    env.write_local_file("Docs/a.txt", LATEST_CONTENT).await?;
+    let mut args = track_arguments(&["Docs/a.txt"]);
+    args.file_update_info.insert(
+        PathBuf::from("Docs/a.txt"),
+        ("0.2.0".to_string(), "Update".to_string()),
+    );
+    let (local, remote) = env.run_action::<TrackFileAction, _, _>(host, args).await;
+    remote?;
+    assert!(matches!(local?, TrackFileActionResult::Done { updated, .. } if updated.len() == 1));
+
+    // The old version is fetched, given an unformatted path of the mapping
+    let result = fetch(&env, host, "./Docs//a.txt", "0.1.0", &save_path).await?;
+    assert!(matches!(result, FetchVersionActionResult::Success));
+    assert_eq!(tokio::fs::read(&save_path).await?, OLD_CONTENT);
+
+    // The workspace file is not touched
+    let local_file = env.workspace.local_path().join("Docs/a.txt");
+    assert_eq!(tokio::fs::read(local_file).await?, LATEST_CONTENT);
+
+    // Missing versions and mappings are reported
+    let result = fetch(&env, host, "Docs/a.txt", "9.9.9", &save_path).await?;
+    assert!(matches!(result, FetchVersionActionResult::VersionNotFound(v) if v == "9.9.9"));
+    let result = fetch(&env, host, "Docs/b.txt", "0.1.0", &save_path).await?;
+    assert!(
+        matches!(result, FetchVersionActionResult::MappingNotFound(p) if p == Path::new("Docs/b.txt"))
+    );
+
+    Ok(())
+}
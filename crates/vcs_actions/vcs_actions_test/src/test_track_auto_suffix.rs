@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use tcp_connection::error::TcpTargetError;
+use vcs_actions::actions::track_action::{
+    TrackFileAction, TrackFileActionArguments, TrackFileActionResult,
+};
+
+use crate::test_utils::{ActionTestEnv, track_arguments};
+
+/// Arguments updating `a.txt` to version `1.1`
+fn update_arguments(auto_suffix: bool) -> TrackFileActionArguments {
+    let mut args = track_arguments(&["a.txt"]);
+    args.file_update_info.insert(
+        PathBuf::from("a.txt"),
+        ("1.1".to_string(), "Update".to_string()),
+    );
+    args.auto_suffix = auto_suffix;
+    args
+}
+
+/// Run a track, returns the result of the local side
+async fn track(
+    env: &ActionTestEnv,
+    host: &str,
+    args: TrackFileActionArguments,
+) -> Result<TrackFileActionResult, TcpTargetError> {
+    let (local, remote) = env.run_action::<TrackFileAction, _, _>(host, args).await;
+    remote?;
+    local
+}
+
+#[tokio::test]
+async fn test_track_update_auto_suffix() -> Result<(), TcpTargetError> {
+    let host = "localhost:5073";
+    let env = ActionTestEnv::setup("track_auto_suffix", host).await?;
+    let path = PathBuf::from("a.txt");
+    env.write_local_file("a.txt", vec![1u8; 4096]).await?;
+    track(&env, host, track_arguments(&["a.txt"])).await?;
+
+    // Store version `1.1`
+    env.update_to_latest_info(host).await?;
+    env.write_local_file("a.txt", vec![2u8; 4096]).await?;
+    let result = track(&env, host, update_arguments(false)).await?;
+    assert!(
+        matches!(result, TrackFileActionResult::Done { updated, .. } if updated == [path.clone()])
+    );
+
+    // `1.1` is taken now
+    env.update_to_latest_info(host).await?;
+    env.write_local_file("a.txt", vec![3u8; 4096]).await?;
+    let result = track(&env, host, update_arguments(false)).await?;
+    assert!(matches!(result, TrackFileActionResult::UpdateTaskFailed(_)));
+
+    // Opting in stores it as `1.1_2`, on both sides
+    let result = track(&env, host, update_arguments(true)).await?;
+    let TrackFileActionResult::Done { updated, .. } = result else {
+        return Err(TcpTargetError::NoResult("Track failed".to_string()));
+    };
+    assert_eq!(updated, vec![path.clone()]);
+
+    let suffixed = "1.1_2".to_string();
+    let mapping = env.vault.sheet(&env.sheet_name()).await?.mapping()[&path].clone();
+    assert_eq!(mapping.version, suffixed);
+    let meta = env.vault.virtual_file_meta(&mapping.id).await?;
+    assert_eq!(
+        meta.versions(),
+        &vec!["0.1.0".to_string(), "1.1".to_string(), suffixed.clone()]
+    );
+    let local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    assert_eq!(
+        local_sheet.mapping_data(&path)?.version_when_updated(),
+        &suffixed
+    );
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::{collections::HashSet, path::PathBuf, time::Duration};
+
+use action_system::action::Action;
+use tcp_connection::error::TcpTargetError;
+use tokio::{join, sync::mpsc, time::sleep};
+use tokio_util::sync::CancellationToken;
+use vcs_actions::actions::track_action::TrackFileAction;
+
+use crate::test_utils::{ActionTestEnv, connect, track_arguments};
+
+const FILES: [&str; 4] = ["a.txt", "b.txt", "c.txt", "d.txt"];
+
+#[tokio::test]
+async fn test_track_cancel_keeps_sheets_consistent() -> Result<(), std::io::Error> {
+    let host = "localhost:5067";
+    let env = ActionTestEnv::setup("track_cancel", host).await?;
+    for (i, file) in FILES.iter().enumerate() {
+        env.write_local_file(file, format!("content {}", i)).await?;
+    }
+
+    let mut args = track_arguments(&FILES);
+    args.print_infos = true;
+    let remote_args = serde_json::from_str(&serde_json::to_string(&args)?)?;
+
+    let (mut local_instance, remote_instance) =
+        connect(host).await.map_err(std::io::Error::other)?;
+    let token = CancellationToken::new();
+    local_instance.set_cancellation_token(Some(token.clone()));
+
+    // The output is not read until the cancel, so the local side waits
+    // on the line of the first created file before moving on to the next one
+    let (output, mut output_rx) = mpsc::channel::<String>(1);
+    let local = TrackFileAction::process(env.local_context(local_instance, output), args);
+    let remote = TrackFileAction::process(env.remote_context(remote_instance), remote_args);
+    let cancel = async {
+        // The first file is received by the remote once its meta is written
+        loop {
+            if let Some(id) = env.vault.virtual_file_ids()?.first()
+                && env.vault.virtual_file_meta(id).await.is_ok()
+            {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        token.cancel();
+        while output_rx.recv().await.is_some() {}
+        Ok::<(), std::io::Error>(())
+    };
+
+    let (local, remote, cancelled) = join!(local, remote, cancel);
+    cancelled?;
+    assert!(matches!(local, Err(TcpTargetError::Cancelled(_))));
+    assert!(matches!(remote, Err(TcpTargetError::Cancelled(_))));
+
+    // Both sides hold exactly the mapping of the file created before the cancel
+    let sheet = env.vault.sheet(&env.sheet_name()).await?;
+    let local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    let mut remote_mapped = HashSet::new();
+    for file in FILES {
+        let path = PathBuf::from(file);
+        let remote_id = sheet.mapping().get(&path).map(|mapping| mapping.id.clone());
+        let local_id = local_sheet
+            .mapping_data(&path)
+            .ok()
+            .map(|mapping| mapping.mapping_vfid().clone());
+        assert_eq!(remote_id, local_id, "`{}` differs", file);
+        if remote_id.is_some() {
+            remote_mapped.insert(path);
+        }
+    }
+    assert_eq!(remote_mapped.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use tcp_connection::error::TcpTargetError;
+use vcs_actions::actions::track_action::{
+    TrackFileAction, TrackFileActionArguments, TrackFileActionResult, TrackSummary,
+};
+
+use crate::test_utils::{ActionTestEnv, track_arguments};
+
+/// Run a track and return the created paths and the summary of the local side
+async fn track(
+    env: &ActionTestEnv,
+    host: &str,
+    args: TrackFileActionArguments,
+) -> Result<(Vec<PathBuf>, TrackSummary), TcpTargetError> {
+    let (local, remote) = env.run_action::<TrackFileAction, _, _>(host, args).await;
+    remote?;
+    match local? {
+        TrackFileActionResult::Done {
+            created, summary, ..
+        } => Ok((created, summary)),
+        _ => Err(TcpTargetError::NoResult("Track failed".to_string())),
+    }
+}
+
+#[tokio::test]
+async fn test_track_unchanged_file_transfers_nothing() -> Result<(), TcpTargetError> {
+    let host = "localhost:5069";
+    let env = ActionTestEnv::setup("track_identical_retrack", host).await?;
+    env.write_local_file("a.txt", vec![1u8; 4096]).await?;
+
+    let (created, summary) = track(&env, host, track_arguments(&["a.txt"])).await?;
+    assert_eq!(created, vec![PathBuf::from("a.txt")]);
+    assert_eq!(summary.bytes_sent, 4096);
+
+    // Forget the local mapping, the unchanged file is tracked again
+    let mut local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    local_sheet.remove_mapping(&PathBuf::from("a.txt"))?;
+    local_sheet.write().await?;
+
+    let (created, summary) = track(&env, host, track_arguments(&["a.txt"])).await?;
+    assert_eq!(created, vec![PathBuf::from("a.txt")]);
+    assert_eq!(summary.bytes_sent, 0);
+    assert_eq!(env.vault.virtual_file_ids()?.len(), 1);
+
+    // The mapping is recorded again, with the existing virtual file
+    let sheet = env.vault.sheet(&env.sheet_name()).await?;
+    let local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    assert_eq!(
+        local_sheet
+            .mapping_data(&PathBuf::from("a.txt"))?
+            .mapping_vfid(),
+        &sheet.mapping()[&PathBuf::from("a.txt")].id
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_track_identical_rename_transfers_nothing() -> Result<(), TcpTargetError> {
+    let host = "localhost:5070";
+    let env = ActionTestEnv::setup("track_identical_rename", host).await?;
+    env.write_local_file("old/b.txt", vec![2u8; 4096]).await?;
+
+    let (_, summary) = track(&env, host, track_arguments(&["old/b.txt"])).await?;
+    assert_eq!(summary.bytes_sent, 4096);
+    let id = env.vault.sheet(&env.sheet_name()).await?.mapping()[&PathBuf::from("old/b.txt")]
+        .id
+        .clone();
+
+    // Rename the file once the workspace knows it upstream, then track it at the new path
+    env.update_to_latest_info(host).await?;
+    let local_path = env.workspace.local_path();
+    tokio::fs::create_dir_all(local_path.join("new")).await?;
+    tokio::fs::rename(local_path.join("old/b.txt"), local_path.join("new/b.txt")).await?;
+
+    let (created, summary) = track(&env, host, track_arguments(&["new/b.txt"])).await?;
+    assert_eq!(created, vec![PathBuf::from("new/b.txt")]);
+    assert_eq!(summary.bytes_sent, 0);
+    assert_eq!(env.vault.virtual_file_ids()?.len(), 1);
+
+    // Both sheets moved the mapping, keeping the virtual file
+    let sheet = env.vault.sheet(&env.sheet_name()).await?;
+    assert!(!sheet.mapping().contains_key(&PathBuf::from("old/b.txt")));
+    assert_eq!(sheet.mapping()[&PathBuf::from("new/b.txt")].id, id);
+    let local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    assert!(
+        local_sheet
+            .mapping_data(&PathBuf::from("old/b.txt"))
+            .is_err()
+    );
+    assert_eq!(
+        local_sheet
+            .mapping_data(&PathBuf::from("new/b.txt"))?
+            .mapping_vfid(),
+        &id
+    );
+
+    Ok(())
+}
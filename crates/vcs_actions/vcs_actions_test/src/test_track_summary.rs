@@ -0,0 +1,71 @@
+use std::{path::PathBuf, time::Duration};
+
+use tcp_connection::error::TcpTargetError;
+use vcs_actions::actions::track_action::{
+    TrackFileAction, TrackFileActionArguments, TrackFileActionResult, TrackSummary,
+};
+
+use crate::test_utils::{ActionTestEnv, track_arguments};
+
+/// Run a track and return the summary of the local side
+async fn track(
+    env: &ActionTestEnv,
+    host: &str,
+    args: TrackFileActionArguments,
+) -> Result<TrackSummary, TcpTargetError> {
+    let (local, remote) = env.run_action::<TrackFileAction, _, _>(host, args).await;
+    remote?;
+    match local? {
+        TrackFileActionResult::Done { summary, .. } => Ok(summary),
+        _ => Err(TcpTargetError::NoResult("Track failed".to_string())),
+    }
+}
+
+#[tokio::test]
+async fn test_track_summary_counts_and_bytes() -> Result<(), TcpTargetError> {
+    let host = "localhost:5068";
+    let env = ActionTestEnv::setup("track_summary", host).await?;
+    env.write_local_file("a.txt", vec![1u8; 1000]).await?;
+    env.write_local_file("b.txt", vec![2u8; 3000]).await?;
+
+    // Create
+    let summary = track(&env, host, track_arguments(&["a.txt", "b.txt"])).await?;
+    assert_eq!(summary.created_count, 2);
+    assert_eq!(summary.updated_count, 0);
+    assert_eq!(summary.synced_count, 0);
+    assert_eq!(summary.bytes_sent, 4000);
+    assert_eq!(summary.bytes_received, 0);
+    assert!(summary.analyze_duration > Duration::ZERO);
+    assert!(summary.create_duration > Duration::ZERO);
+
+    // Update, once the workspace knows the files are held by the member
+    env.update_to_latest_info(host).await?;
+    env.write_local_file("a.txt", vec![3u8; 2000]).await?;
+    let mut args = track_arguments(&["a.txt"]);
+    args.file_update_info.insert(
+        PathBuf::from("a.txt"),
+        ("1.1".to_string(), "Update a".to_string()),
+    );
+    let summary = track(&env, host, args).await?;
+    assert_eq!(summary.created_count, 0);
+    assert_eq!(summary.updated_count, 1);
+    assert_eq!(summary.bytes_sent, 2000);
+    assert_eq!(summary.bytes_received, 0);
+    assert!(summary.update_duration > Duration::ZERO);
+
+    // Sync, a file mapped upstream but never downloaded is received
+    let mut local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    local_sheet.remove_mapping(&PathBuf::from("b.txt"))?;
+    local_sheet.write().await?;
+    tokio::fs::remove_file(env.workspace.local_path().join("b.txt")).await?;
+    let summary = track(&env, host, track_arguments(&["b.txt"])).await?;
+    assert_eq!(summary.synced_count, 1);
+    assert_eq!(summary.bytes_sent, 0);
+    assert_eq!(summary.bytes_received, 3000);
+    assert!(summary.sync_duration > Duration::ZERO);
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use std::{path::PathBuf, time::Duration};
+
+use action_system::action::Action;
+use tcp_connection::error::TcpTargetError;
+use tokio::{join, sync::mpsc, time::sleep};
+use tokio_util::sync::CancellationToken;
+use vcs_actions::actions::track_action::{
+    TrackFileAction, TrackFileActionArguments, TrackFileActionResult,
+};
+use vcs_data::data::vault::virtual_file::VirtualFileVersion;
+
+use crate::test_utils::{ActionTestEnv, connect, track_arguments};
+
+const FILES: [&str; 4] = ["a.txt", "b.txt", "c.txt", "d.txt"];
+const FILE_SIZE: usize = 64 * 1024;
+const NEXT_VERSION: &str = "1.1";
+
+/// Arguments updating every file to `NEXT_VERSION`
+fn update_arguments() -> TrackFileActionArguments {
+    let mut args = track_arguments(&FILES);
+    for file in FILES {
+        args.file_update_info.insert(
+            PathBuf::from(file),
+            (NEXT_VERSION.to_string(), format!("Update {}", file)),
+        );
+    }
+    args
+}
+
+/// (Sheet, Histories, Local) versions of a file
+type FileVersions = (
+    VirtualFileVersion,
+    Vec<VirtualFileVersion>,
+    VirtualFileVersion,
+);
+
+/// Versions of every file in the sheet, in the virtual file metadata and in the local sheet
+async fn versions(env: &ActionTestEnv) -> Result<Vec<FileVersions>, std::io::Error> {
+    let sheet = env.vault.sheet(&env.sheet_name()).await?;
+    let local_sheet = env
+        .workspace
+        .local_sheet(&env.member_id(), &env.sheet_name())
+        .await?;
+    let mut versions = Vec::new();
+    for file in FILES {
+        let path = PathBuf::from(file);
+        let mapping = &sheet.mapping()[&path];
+        let meta = env.vault.virtual_file_meta(&mapping.id).await?;
+        versions.push((
+            mapping.version.clone(),
+            meta.versions().clone(),
+            local_sheet
+                .mapping_data(&path)?
+                .version_when_updated()
+                .clone(),
+        ));
+    }
+    Ok(versions)
+}
+
+#[tokio::test]
+async fn test_track_update_batch_is_all_or_nothing() -> Result<(), std::io::Error> {
+    let host = "localhost:5071";
+    let env = ActionTestEnv::setup("track_update_batch", host).await?;
+    for (i, file) in FILES.iter().enumerate() {
+        env.write_local_file(file, vec![i as u8; FILE_SIZE]).await?;
+    }
+    let (local, remote) = env
+        .run_action::<TrackFileAction, _, _>(host, track_arguments(&FILES))
+        .await;
+    local.map_err(std::io::Error::other)?;
+    remote.map_err(std::io::Error::other)?;
+
+    // Modify every file once the workspace knows they are held by the member
+    env.update_to_latest_info(host).await?;
+    for (i, file) in FILES.iter().enumerate() {
+        env.write_local_file(file, vec![i as u8 + 16; FILE_SIZE])
+            .await?;
+    }
+    let before = versions(&env).await?;
+
+    let mut args = update_arguments();
+    args.print_infos = true;
+    let remote_args = serde_json::from_str(&serde_json::to_string(&args)?)?;
+
+    let (mut local_instance, remote_instance) =
+        connect(host).await.map_err(std::io::Error::other)?;
+    let token = CancellationToken::new();
+    local_instance.set_cancellation_token(Some(token.clone()));
+
+    // The output is not read until the cancel, so the local side waits
+    // on the line of the first updated file before moving on to the next one
+    let (output, mut output_rx) = mpsc::channel::<String>(1);
+    let local = TrackFileAction::process(env.local_context(local_instance, output), args);
+    let remote = TrackFileAction::process(env.remote_context(remote_instance), remote_args);
+    let temp_dir = env
+        .vault
+        .virtual_file_temp_path()
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let cancel = async {
+        // The first file is staged by the remote once it is fully received
+        'staged: loop {
+            if temp_dir.exists() {
+                let mut dir = tokio::fs::read_dir(&temp_dir).await?;
+                while let Some(entry) = dir.next_entry().await? {
+                    if entry.metadata().await?.len() == FILE_SIZE as u64 {
+                        break 'staged;
+                    }
+                }
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        token.cancel();
+        while output_rx.recv().await.is_some() {}
+        Ok::<(), std::io::Error>(())
+    };
+
+    let (local, remote, cancelled) = join!(local, remote, cancel);
+    cancelled?;
+    assert!(matches!(local, Err(TcpTargetError::Cancelled(_))));
+    assert!(remote.is_err());
+
+    // Nothing of the batch was stored, on either side
+    assert_eq!(versions(&env).await?, before);
+    let sheet = env.vault.sheet(&env.sheet_name()).await?;
+    for file in FILES {
+        let id = &sheet.mapping()[&PathBuf::from(file)].id;
+        let instance = env
+            .vault
+            .virtual_file_real_path(id, &NEXT_VERSION.to_string());
+        assert!(!instance.exists(), "`{}` was stored", file);
+    }
+    let mut dir = tokio::fs::read_dir(&temp_dir).await?;
+    assert!(dir.next_entry().await?.is_none());
+
+    // The sheet is unlocked again, the same batch succeeds as a whole
+    let (local, remote) = env
+        .run_action::<TrackFileAction, _, _>(host, update_arguments())
+        .await;
+    remote.map_err(std::io::Error::other)?;
+    let Ok(TrackFileActionResult::Done { summary, .. }) = local else {
+        return Err(std::io::Error::other("Track failed"));
+    };
+    assert_eq!(summary.updated_count, FILES.len());
+    let next_version = NEXT_VERSION.to_string();
+    for (version, histories, local_version) in versions(&env).await? {
+        assert_eq!(version, next_version);
+        assert!(histories.contains(&next_version));
+        assert_eq!(local_version, next_version);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use action_system::action::{Action, ActionContext};
+use cfg_file::config::ConfigFile;
+use serde::{Serialize, de::DeserializeOwned};
+use tcp_connection::{error::TcpTargetError, instance::ConnectionInstance};
+use tokio::{
+    fs, join,
+    net::{TcpListener, TcpStream},
+    sync::{
+        Mutex, MutexGuard,
+        mpsc::{self, Sender},
+    },
+};
+use vcs_actions::actions::{
+    local_actions::{UpdateToLatestInfoAction, UpdateToLatestInfoResult},
+    track_action::TrackFileActionArguments,
+};
+use vcs_data::{
+    constants::{CLIENT_FILE_TODOLIST, SERVER_FILE_MEMBER_PUB, SERVER_FILE_VAULT},
+    data::{
+        local::{LocalWorkspace, config::LocalConfig},
+        member::{Member, MemberId},
+        sheet::SheetName,
+        user::UserDirectory,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+pub const MEMBER: &str = "member";
+pub const SHEET: &str = "work";
+
+/// The local side of an action reads the workspace from the current directory,
+/// tests that change it wait for each other
+static CURRENT_DIR_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// A vault and a local workspace using one of its sheets, connected by a member account
+pub struct ActionTestEnv {
+    pub vault: Arc<Vault>,
+    pub workspace: Arc<LocalWorkspace>,
+    pub user_directory: Arc<UserDirectory>,
+    _current_dir: MutexGuard<'static, ()>,
+}
+
+impl ActionTestEnv {
+    /// Setup the vault and the workspace, the current directory is moved into the workspace
+    pub async fn setup(area: &str, host: &str) -> Result<Self, std::io::Error> {
+        let current_dir = CURRENT_DIR_LOCK.lock().await;
+        let dir = get_test_dir(area).await?;
+        let key_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("res")
+            .join("key");
+
+        // Setup vault, with a sheet held by the member
+        let vault_dir = dir.join("vault");
+        fs::create_dir_all(&vault_dir).await?;
+        Vault::setup_vault(vault_dir.clone(), "TestVault").await?;
+        let config = VaultConfig::read_from(vault_dir.join(SERVER_FILE_VAULT)).await?;
+        let vault = Vault::init(config, &vault_dir)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Vault not found!"))?;
+        vault.register_member_to_vault(Member::new(MEMBER)).await?;
+        fs::copy(
+            key_dir.join("member_key.pem"),
+            vault_dir.join(SERVER_FILE_MEMBER_PUB.replace("{member_id}", MEMBER)),
+        )
+        .await?;
+        vault
+            .create_sheet(&SHEET.to_string(), &MEMBER.to_string())
+            .await?;
+
+        // Setup the user directory outside the workspace, holding the member's private key
+        let user_dir = dir.join("user");
+        fs::create_dir_all(&user_dir).await?;
+        let user_directory = UserDirectory::from_path(&user_dir).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "User directory not found!")
+        })?;
+        user_directory.register_account(Member::new(MEMBER)).await?;
+        let private_key = user_directory.account_private_key_path(&MEMBER.to_string());
+        fs::create_dir_all(private_key.parent().unwrap()).await?;
+        fs::copy(key_dir.join("member_key_private.pem"), &private_key).await?;
+
+        // Setup workspace, sheets are only used in an empty workspace
+        let local_dir = dir.join("workspace");
+        fs::create_dir_all(&local_dir).await?;
+        LocalWorkspace::setup_local_workspace(local_dir.clone()).await?;
+        fs::remove_file(local_dir.join(CLIENT_FILE_TODOLIST)).await?;
+        std::env::set_current_dir(&local_dir)?;
+        let mut config = LocalConfig::read().await?;
+        config.set_current_account(MEMBER.to_string())?;
+        LocalConfig::write(&config).await?;
+        let workspace = LocalWorkspace::init(config, &local_dir).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Local workspace not found!")
+        })?;
+
+        let env = Self {
+            vault: Arc::new(vault),
+            workspace: Arc::new(workspace),
+            user_directory: Arc::new(user_directory),
+            _current_dir: current_dir,
+        };
+
+        // Fetch the latest info, then use the sheet
+        env.update_to_latest_info(host).await?;
+        env.workspace
+            .config()
+            .lock()
+            .await
+            .use_sheet(SHEET.to_string())
+            .await?;
+
+        Ok(env)
+    }
+
+    /// Fetch the latest info of the vault into the workspace
+    pub async fn update_to_latest_info(&self, host: &str) -> Result<(), std::io::Error> {
+        let (local, remote) = self
+            .run_action::<UpdateToLatestInfoAction, _, _>(host, ())
+            .await;
+        let (Ok(UpdateToLatestInfoResult::Success), Ok(UpdateToLatestInfoResult::Success)) =
+            (local, remote)
+        else {
+            return Err(std::io::Error::other("Update to latest info failed!"));
+        };
+        Ok(())
+    }
+
+    pub fn member_id(&self) -> MemberId {
+        MEMBER.to_string()
+    }
+
+    pub fn sheet_name(&self) -> SheetName {
+        SHEET.to_string()
+    }
+
+    /// Write a file into the workspace
+    pub async fn write_local_file(
+        &self,
+        relative_path: &str,
+        content: impl AsRef<[u8]>,
+    ) -> Result<(), std::io::Error> {
+        let path = self.workspace.local_path().join(relative_path);
+        fs::create_dir_all(path.parent().unwrap()).await?;
+        fs::write(path, content).await
+    }
+
+    /// Context of the local side of an action
+    pub fn local_context(
+        &self,
+        instance: ConnectionInstance,
+        output: Sender<String>,
+    ) -> ActionContext {
+        let mut ctx = ActionContext::local()
+            .insert_instance(instance)
+            .with_arc_data(self.workspace.clone())
+            .with_arc_data(self.user_directory.clone())
+            .with_arc_data(Arc::new(output));
+        ctx.set_is_remote_action(true);
+        ctx
+    }
+
+    /// Context of the remote side of an action
+    pub fn remote_context(&self, instance: ConnectionInstance) -> ActionContext {
+        let mut ctx = ActionContext::remote()
+            .insert_instance(instance)
+            .with_arc_data(self.vault.clone());
+        ctx.set_is_remote_action(true);
+        ctx
+    }
+
+    /// Run both sides of an action, returning the (local, remote) results
+    pub async fn run_action<A, Args, Return>(
+        &self,
+        host: &str,
+        args: Args,
+    ) -> (
+        Result<Return, TcpTargetError>,
+        Result<Return, TcpTargetError>,
+    )
+    where
+        A: Action<Args, Return>,
+        Args: Serialize + DeserializeOwned + Send,
+        Return: Serialize + DeserializeOwned + Send,
+    {
+        let (local_instance, remote_instance) = match connect(host).await {
+            Ok(instances) => instances,
+            Err(e) => return (Err(e.clone()), Err(e)),
+        };
+
+        // The remote receives the arguments as json, like from the action pool
+        let remote_args = match serde_json::to_string(&args)
+            .and_then(|json| serde_json::from_str::<Args>(&json))
+        {
+            Ok(args) => args,
+            Err(e) => {
+                let e = TcpTargetError::Serialization(e.to_string());
+                return (Err(e.clone()), Err(e));
+            }
+        };
+
+        let (output, mut output_rx) = mpsc::channel::<String>(16);
+        let local = A::process(self.local_context(local_instance, output), args);
+        let remote = A::process(self.remote_context(remote_instance), remote_args);
+        let drain = async { while output_rx.recv().await.is_some() {} };
+
+        let (local, remote, _) = join!(local, remote, drain);
+        (local, remote)
+    }
+}
+
+/// Arguments tracking the given paths
+pub fn track_arguments(paths: &[&str]) -> TrackFileActionArguments {
+    TrackFileActionArguments {
+        relative_pathes: paths.iter().map(PathBuf::from).collect(),
+        file_update_info: HashMap::new(),
+        print_infos: false,
+        allow_overwrite_modified: false,
+        delete_erased_files: false,
+        patterns: Vec::new(),
+        auto_suffix: false,
+    }
+}
+
+/// Connect a (local, remote) pair of instances
+pub async fn connect(
+    host: &str,
+) -> Result<(ConnectionInstance, ConnectionInstance), TcpTargetError> {
+    let listener = TcpListener::bind(host).await?;
+    let (local, remote) = join!(TcpStream::connect(host), listener.accept());
+    Ok((
+        ConnectionInstance::from(local?),
+        ConnectionInstance::from(remote?.0),
+    ))
+}
@@ -48,6 +48,12 @@ pub const SERVER_PATH_SHEETS: &str = "./sheets/";
 pub const SERVER_PATH_SHARES: &str = "./sheets/shares/{sheet_name}/";
 pub const SERVER_FILE_SHEET: &str = "./sheets/{sheet_name}.st";
 pub const SERVER_FILE_SHEET_SHARE: &str = "./sheets/shares/{sheet_name}/{share_id}.sre";
+pub const SERVER_PATH_SHEET_HISTORY: &str = "./sheets/history/{sheet_name}/";
+pub const SERVER_FILE_SHEET_HISTORY: &str = "./sheets/history/{sheet_name}/{write_count}.st";
+
+// Server - Sheets - Default path limits
+pub const SHEET_PATH_MAX_LENGTH: usize = 200;
+pub const SHEET_PATH_MAX_DEPTH: usize = 32;
 
 // Server - Members
 pub const SERVER_PATH_MEMBERS: &str = "./members/";
@@ -66,6 +72,9 @@ pub const SERVER_NAME_VF_META: &str = "meta.vf";
 // Server - Updates
 pub const SERVER_FILE_UPDATES: &str = "./.updates.txt";
 
+// Server - Audit
+pub const SERVER_FILE_AUDIT_LOG: &str = "./audit.jsonl";
+
 // Server - Service
 pub const SERVER_FILE_LOCKFILE: &str = "./.lock";
 
@@ -99,6 +108,7 @@ pub const CLIENT_FILE_LATEST_INFO_NOSET: &str = "./.jv/.temp/wrong.json";
 // Client - Other
 pub const CLIENT_FILE_IGNOREFILES: &str = "IGNORE_RULES.toml";
 pub const CLIENT_FILE_TODOLIST: &str = "./SETUP.md";
+pub const CLIENT_FILE_JVIGNORE: &str = "./.jvignore";
 pub const CLIENT_FILE_GITIGNORE: &str = "./.jv/.gitignore";
 pub const CLIENT_CONTENT_GITIGNORE: &str = "# Git support for JVCS Workspace
 
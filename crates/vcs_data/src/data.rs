@@ -1,3 +1,4 @@
+pub(crate) mod keyed_lock;
 pub mod local;
 pub mod member;
 pub mod sheet;
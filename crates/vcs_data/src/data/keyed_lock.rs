@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, LazyLock},
+};
+
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// In-process locks keyed by a value, such as a file path or a virtual file id
+///
+/// An entry only lives while its lock is held or awaited,
+/// it is removed when the last guard of the key is dropped.
+pub(crate) struct KeyedLocks<K> {
+    locks: LazyLock<std::sync::Mutex<HashMap<K, Arc<Mutex<()>>>>>,
+}
+
+/// Guard of a lock acquired from `KeyedLocks::lock`
+pub(crate) struct KeyedLockGuard<K: Eq + Hash + 'static> {
+    locks: &'static KeyedLocks<K>,
+    key: K,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl<K: Eq + Hash + Clone> KeyedLocks<K> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            locks: LazyLock::new(Default::default),
+        }
+    }
+
+    /// Acquire the lock of the given key
+    pub(crate) async fn lock(&'static self, key: K) -> KeyedLockGuard<K> {
+        let lock = self.entries().entry(key.clone()).or_default().clone();
+        KeyedLockGuard {
+            locks: self,
+            key,
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<K, Arc<Mutex<()>>>> {
+        self.locks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries().len()
+    }
+}
+
+impl<K: Eq + Hash + 'static> Drop for KeyedLockGuard<K> {
+    fn drop(&mut self) {
+        let Some(guard) = self.guard.take() else {
+            return;
+        };
+        let lock = Arc::clone(OwnedMutexGuard::mutex(&guard));
+        drop(guard);
+
+        // Every waiter holds a clone of the lock, so only the entry and `lock` are left if none
+        let mut entries = self.locks.locks.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .get(&self.key)
+            .is_some_and(|entry| Arc::ptr_eq(entry, &lock) && Arc::strong_count(entry) == 2)
+        {
+            entries.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keyed_lock_entry_is_removed_after_last_guard() {
+        static LOCKS: KeyedLocks<u32> = KeyedLocks::new();
+
+        let guard = LOCKS.lock(1).await;
+        let waiter = tokio::spawn(async {
+            let _guard = LOCKS.lock(1).await;
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // The entry is kept for the waiter, then removed once it is done too
+        drop(guard);
+        waiter.await.unwrap();
+        assert_eq!(LOCKS.len(), 0);
+
+        let _first = LOCKS.lock(1).await;
+        let _second = LOCKS.lock(2).await;
+        assert_eq!(LOCKS.len(), 2);
+    }
+}
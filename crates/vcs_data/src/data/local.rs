@@ -30,6 +30,7 @@ use crate::{
 pub mod align;
 pub mod cached_sheet;
 pub mod config;
+pub mod ignore;
 pub mod latest_file_data;
 pub mod latest_info;
 pub mod local_files;
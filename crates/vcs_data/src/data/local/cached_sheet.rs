@@ -1,4 +1,7 @@
-use std::{io::Error, path::PathBuf};
+use std::{
+    io::Error,
+    path::{Path, PathBuf},
+};
 
 use cfg_file::config::ConfigFile;
 use string_proc::{format_path::format_path, snake_case};
@@ -9,7 +12,7 @@ use crate::{
         CLIENT_FILE_CACHED_SHEET, CLIENT_PATH_CACHED_SHEET, CLIENT_SUFFIX_CACHED_SHEET_FILE,
     },
     current::current_local_path,
-    data::sheet::{SheetData, SheetName},
+    data::sheet::{SheetData, SheetDiff, SheetName},
 };
 
 pub type CachedSheetPathBuf = PathBuf;
@@ -38,6 +41,43 @@ impl CachedSheet {
         Ok(data)
     }
 
+    /// Patch the already read cached sheet data with the changes of a diff against the latest sheet data.
+    ///
+    /// Unchanged mappings are kept from the cached sheet instead of being replaced by `latest`,
+    /// the write count and id mapping follow the patched result.
+    /// Nothing is written if the diff is empty and the write count is unchanged.
+    pub async fn apply_diff(
+        sheet_name: &SheetName,
+        cached: SheetData,
+        diff: &SheetDiff,
+        latest: &SheetData,
+    ) -> Result<SheetData, std::io::Error> {
+        let sheet_name = snake_case!(sheet_name.clone());
+
+        let Some(path) = Self::cached_sheet_path(sheet_name) else {
+            return Err(Error::new(
+                std::io::ErrorKind::NotFound,
+                "Local workspace not found!",
+            ));
+        };
+        Self::apply_diff_at(path, cached, diff, latest).await
+    }
+
+    /// Patch the cached sheet data and write it to the given path, see `apply_diff`.
+    pub async fn apply_diff_at(
+        path: impl AsRef<Path>,
+        mut cached: SheetData,
+        diff: &SheetDiff,
+        latest: &SheetData,
+    ) -> Result<SheetData, std::io::Error> {
+        if diff.is_empty() && cached.write_count() == latest.write_count() {
+            return Ok(cached);
+        }
+        cached.apply_diff(diff, latest);
+        SheetData::write_to(&cached, path.as_ref()).await?;
+        Ok(cached)
+    }
+
     /// Get the path to the cached sheet file.
     pub fn cached_sheet_path(sheet_name: SheetName) -> Option<PathBuf> {
         let current_workspace = current_local_path()?;
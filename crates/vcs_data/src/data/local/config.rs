@@ -51,6 +51,18 @@ pub struct LocalConfig {
     /// The name of the sheet currently in use.
     #[serde(rename = "use")]
     sheet_in_use: Option<SheetName>,
+
+    /// Minimum similarity (0.0 - 1.0) for pairing a lost file with a created file as a move.
+    /// Used by fuzzy move detection when the file content has also changed.
+    #[serde(rename = "fuzzy_move", default = "default_fuzzy_move_threshold")]
+    fuzzy_move_threshold: f32,
+}
+
+/// Conservative by default, a wrong pairing is worse than a missed one
+const DEFAULT_FUZZY_MOVE_THRESHOLD: f32 = 0.8;
+
+fn default_fuzzy_move_threshold() -> f32 {
+    DEFAULT_FUZZY_MOVE_THRESHOLD
 }
 
 impl Default for LocalConfig {
@@ -64,6 +76,7 @@ impl Default for LocalConfig {
             using_host_mode: false,
             stained_uuid: None,
             sheet_in_use: None,
+            fuzzy_move_threshold: DEFAULT_FUZZY_MOVE_THRESHOLD,
         }
     }
 }
@@ -341,6 +354,16 @@ impl LocalConfig {
         &self.sheet_in_use
     }
 
+    /// Get the similarity threshold of fuzzy move detection
+    pub fn fuzzy_move_threshold(&self) -> f32 {
+        self.fuzzy_move_threshold
+    }
+
+    /// Set the similarity threshold of fuzzy move detection
+    pub fn set_fuzzy_move_threshold(&mut self, threshold: f32) {
+        self.fuzzy_move_threshold = threshold.clamp(0.0, 1.0);
+    }
+
     /// Get draft folder
     pub fn draft_folder(
         &self,
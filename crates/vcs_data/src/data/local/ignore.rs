@@ -0,0 +1,112 @@
+use std::{
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use glob::{MatchOptions, Pattern};
+use tokio::fs;
+
+use crate::constants::CLIENT_FILE_JVIGNORE;
+
+/// # Struct - IgnoreRules
+///
+/// Gitignore-style rules read from the `.jvignore` file at the workspace root
+///
+/// - Blank lines and lines starting with `#` are skipped
+/// - A pattern ending with `/` only matches directories, excluding everything inside them
+/// - A pattern containing `/` elsewhere is matched against the path relative to the workspace root,
+///   otherwise it is matched against the file or directory name at any depth
+/// - `*` never crosses a `/`, `**` matches any number of directories
+/// - A pattern starting with `!` re-includes what an earlier pattern excluded,
+///   the last matching pattern wins
+#[derive(Debug, Default, Clone)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    match_path: bool,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+impl IgnoreRules {
+    /// Parse rules from the content of an ignore file
+    pub fn parse(content: &str) -> Result<Self, std::io::Error> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let match_path = line.contains('/');
+            let line = line.trim_start_matches('/');
+
+            let pattern = Pattern::new(line).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid ignore pattern `{}`: {}", line, e),
+                )
+            })?;
+            rules.push(IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+                match_path,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Read the rules of the workspace, no rules if the workspace has no ignore file
+    pub async fn read_from_workspace(local_path: &Path) -> Result<Self, std::io::Error> {
+        match fs::read_to_string(local_path.join(CLIENT_FILE_JVIGNORE)).await {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check whether there are no rules at all
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check whether a path relative to the workspace root is ignored
+    ///
+    /// Only the path itself is checked, a file inside an ignored directory
+    /// is excluded by skipping that directory
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let name = path.rsplit('/').next().unwrap_or_default();
+
+        let mut ignored = false;
+        for rule in self.rules.iter() {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let target = if rule.match_path { path.as_str() } else { name };
+            if rule.pattern.matches_with(target, MATCH_OPTIONS) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
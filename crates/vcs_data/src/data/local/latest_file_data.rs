@@ -14,6 +14,13 @@ use crate::{
 
 const ACCOUNT: &str = "{account}";
 
+/// (Holder, LatestVersion, Histories) of a virtual file
+pub type LatestFileInfo = (
+    Option<MemberId>,
+    VirtualFileVersion,
+    Vec<(VirtualFileVersion, VirtualFileVersionDescription)>,
+);
+
 /// # Latest file data
 /// Records the file holder and the latest version for permission and update checks
 #[derive(Debug, Default, Clone, Serialize, Deserialize, ConfigFile)]
@@ -79,17 +86,7 @@ impl LatestFileData {
     }
 
     /// Update the held status of the files.
-    pub fn update_info(
-        &mut self,
-        map: HashMap<
-            VirtualFileId,
-            (
-                Option<MemberId>,
-                VirtualFileVersion,
-                Vec<(VirtualFileVersion, VirtualFileVersionDescription)>,
-            ),
-        >,
-    ) {
+    pub fn update_info(&mut self, map: HashMap<VirtualFileId, LatestFileInfo>) {
         for (vfid, (member_id, version, desc)) in map {
             self.held_status.insert(
                 vfid.clone(),
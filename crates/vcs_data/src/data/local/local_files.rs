@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+use glob::{MatchOptions, Pattern};
 use string_proc::format_path::format_path;
 use tokio::fs;
 
@@ -37,6 +38,62 @@ pub async fn get_relative_paths(local_path: &PathBuf, paths: &[PathBuf]) -> Opti
     Some(RelativeFiles { files })
 }
 
+/// Read the relative paths of the files within the project matching any of the glob patterns
+///
+/// Patterns are matched against `/` separated paths relative to the workspace root,
+/// `*` never crosses a separator, use `**` to match any number of directories.
+/// Paths containing `.jv` are skipped, the same as in the workspace analyzer
+pub fn get_relative_paths_by_patterns(
+    local_path: &Path,
+    patterns: &[String],
+) -> Result<RelativeFiles, std::io::Error> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid pattern `{}`: {}", pattern, e),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(local_path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(local_path) else {
+            continue;
+        };
+        let Ok(relative) = format_path(relative.to_path_buf()) else {
+            continue;
+        };
+
+        let relative_str = relative.to_string_lossy().replace('\\', "/");
+        if relative_str.contains(".jv") {
+            continue;
+        }
+        if patterns
+            .iter()
+            .any(|pattern| pattern.matches_with(&relative_str, options))
+        {
+            files.push(relative);
+        }
+    }
+    files.sort();
+    Ok(RelativeFiles { files })
+}
+
 /// Normalize the input paths
 async fn format_input_paths(
     local_path: &Path,
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, io::Error, path::PathBuf, time::SystemTime};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Error,
+    path::PathBuf,
+    time::SystemTime,
+};
 
 use ::serde::{Deserialize, Serialize};
 use cfg_file::{ConfigFile, config::ConfigFile};
@@ -9,7 +14,7 @@ use crate::{
     data::{
         local::LocalWorkspace,
         member::MemberId,
-        sheet::SheetName,
+        sheet::{SheetData, SheetName},
         vault::virtual_file::{VirtualFileId, VirtualFileVersion, VirtualFileVersionDescription},
     },
 };
@@ -17,6 +22,9 @@ use crate::{
 pub type LocalFilePathBuf = PathBuf;
 pub type LocalSheetPathBuf = PathBuf;
 
+/// Files hashed at the same time by `LocalSheet::modified_paths`
+const MODIFY_CHECK_CONCURRENCY: usize = 16;
+
 /// # Local Sheet
 /// Local sheet information, used to record metadata of actual local files,
 /// to compare with upstream information for more optimized file submission,
@@ -78,6 +86,16 @@ pub struct LocalMappingMetadata {
     pub(crate) last_modify_check_hash: Option<String>,
 }
 
+/// A move rejected by `LocalSheet::validate_moves`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveConflict {
+    /// The source path is not mapped
+    SourceNotFound(LocalFilePathBuf),
+
+    /// The destination path is already mapped
+    DestinationExists(LocalFilePathBuf),
+}
+
 impl LocalSheetData {
     /// Wrap LocalSheetData into LocalSheet with workspace, member, and sheet name
     pub fn wrap_to_local_sheet<'a>(
@@ -210,6 +228,26 @@ impl LocalMappingMetadata {
     pub fn set_last_modifiy_check_hash(&mut self, hash: Option<String>) {
         self.last_modify_check_hash = hash;
     }
+
+    /// Get the cached modification check result,
+    /// `None` if the file must be hashed because its modified time or size changed
+    fn cached_modify_check(&self, modified_time: SystemTime, size: u64) -> Option<bool> {
+        let cached_result = self.last_modify_check_result;
+        let size_changed = size != self.size_when_updated;
+        if modified_time == self.last_modify_check_time && (cached_result || !size_changed) {
+            return Some(cached_result);
+        }
+        None
+    }
+
+    /// Record the result of hashing the file, returns whether it is modified
+    fn record_modify_check(&mut self, modified_time: SystemTime, hash: String) -> bool {
+        let modified = hash != self.hash_when_updated;
+        self.last_modify_check_time = modified_time;
+        self.last_modify_check_result = modified;
+        self.last_modify_check_hash = Some(hash);
+        modified
+    }
 }
 
 impl Default for LocalMappingMetadata {
@@ -302,6 +340,55 @@ impl<'a> LocalSheet<'a> {
         Ok(())
     }
 
+    /// Check a batch of moves without applying any of them
+    ///
+    /// The moves are simulated in order, exactly as repeated `move_mapping` calls would apply them.
+    /// Returns the conflicting moves, an empty result means the whole batch can be applied
+    pub fn validate_moves(
+        &self,
+        moves: &[(LocalFilePathBuf, LocalFilePathBuf)],
+    ) -> Result<Vec<MoveConflict>, std::io::Error> {
+        let mut paths: HashSet<LocalFilePathBuf> = self.data.mapping.keys().cloned().collect();
+        let mut conflicts = Vec::new();
+        for (from, to) in moves {
+            let from = format_path(from)?;
+            let to = format_path(to)?;
+            if paths.contains(&to) {
+                conflicts.push(MoveConflict::DestinationExists(to));
+            } else if paths.remove(&from) {
+                paths.insert(to);
+            } else {
+                conflicts.push(MoveConflict::SourceNotFound(from));
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Order a batch of moves so each destination is vacated before it is used
+    ///
+    /// A move whose destination is the source of another move in the batch runs after it,
+    /// such as `a -> b` after `b -> c`. Moves forming a cycle cannot be ordered,
+    /// they are kept at the end and reported by `validate_moves`
+    pub fn order_moves(
+        mut moves: Vec<(LocalFilePathBuf, LocalFilePathBuf)>,
+    ) -> Vec<(LocalFilePathBuf, LocalFilePathBuf)> {
+        moves.sort();
+        let mut ordered = Vec::with_capacity(moves.len());
+        while !moves.is_empty() {
+            let pending: HashSet<LocalFilePathBuf> =
+                moves.iter().map(|(from, _)| from.clone()).collect();
+            let (ready, blocked): (Vec<_>, Vec<_>) =
+                moves.into_iter().partition(|(_, to)| !pending.contains(to));
+            if ready.is_empty() {
+                ordered.extend(blocked);
+                break;
+            }
+            ordered.extend(ready);
+            moves = blocked;
+        }
+        ordered
+    }
+
     /// Remove mapping from local sheet
     pub fn remove_mapping(
         &mut self,
@@ -347,6 +434,87 @@ impl<'a> LocalSheet<'a> {
         Ok(data)
     }
 
+    /// Check if the local file at `path` differs from the version downloaded to the workspace
+    ///
+    /// The cached check is reused while the modification time is unchanged
+    /// and the size agrees with the cached result, only otherwise the file is hashed.
+    /// The cached check fields are updated in memory, call `write` to persist them
+    pub async fn is_modified(&mut self, path: &LocalFilePathBuf) -> Result<bool, std::io::Error> {
+        let file_path = self.local_workspace.local_path().join(path);
+        let mapping_data = self.mapping_data_mut(path)?;
+
+        // Modified time and size not changed, use the cached result
+        let metadata = tokio::fs::metadata(&file_path).await?;
+        let modified_time = metadata.modified()?;
+        if let Some(cached_result) = mapping_data.cached_modify_check(modified_time, metadata.len())
+        {
+            return Ok(cached_result);
+        }
+
+        // Calculate hash
+        let hash_calc = sha1_hash::calc_sha1_default(&file_path)
+            .await
+            .map_err(Error::other)?;
+        Ok(mapping_data.record_modify_check(modified_time, hash_calc.hash))
+    }
+
+    /// Check many mapped files for modifications, see `is_modified`
+    ///
+    /// The files whose cached result cannot be reused are collected first,
+    /// then hashed concurrently, at most `MODIFY_CHECK_CONCURRENCY` at a time.
+    /// Unmapped paths are skipped, returns the modified paths
+    pub async fn modified_paths<'b>(
+        &mut self,
+        paths: impl IntoIterator<Item = &'b LocalFilePathBuf>,
+    ) -> Result<HashSet<LocalFilePathBuf>, std::io::Error> {
+        let mut modified = HashSet::new();
+
+        // Collect the files that need hashing
+        let mut candidates = Vec::new();
+        for path in paths {
+            let Ok(mapping_data) = self.mapping_data(path) else {
+                continue;
+            };
+            let file_path = self.local_workspace.local_path().join(path);
+            let metadata = tokio::fs::metadata(&file_path).await?;
+            let modified_time = metadata.modified()?;
+            match mapping_data.cached_modify_check(modified_time, metadata.len()) {
+                Some(true) => {
+                    modified.insert(path.clone());
+                }
+                Some(false) => {}
+                None => candidates.push((path.clone(), file_path, modified_time)),
+            }
+        }
+
+        let results = sha1_hash::calc_sha1_multi_bounded(
+            candidates
+                .iter()
+                .map(|(_, file_path, _)| file_path.clone())
+                .collect::<Vec<_>>(),
+            sha1_hash::DEFAULT_BUFFER_SIZE,
+            MODIFY_CHECK_CONCURRENCY,
+        )
+        .await;
+
+        // Record the results
+        for ((path, _, modified_time), result) in candidates.into_iter().zip(results) {
+            let hash = result
+                .map_err(|(file_path, e)| {
+                    Error::other(format!("Failed to hash `{}`: {}", file_path.display(), e))
+                })?
+                .hash;
+            if self
+                .mapping_data_mut(&path)?
+                .record_modify_check(modified_time, hash)
+            {
+                modified.insert(path);
+            }
+        }
+
+        Ok(modified)
+    }
+
     /// Write the sheet to disk
     pub async fn write(&mut self) -> Result<(), std::io::Error> {
         let path = self
@@ -374,4 +542,106 @@ impl<'a> LocalSheet<'a> {
     pub fn path_by_id(&self, vfid: &VirtualFileId) -> Option<&PathBuf> {
         self.data.vfs.get(vfid)
     }
+
+    /// Collect the paths mapped in the local sheet but no longer in the cached sheet,
+    /// these files were erased upstream
+    pub fn erased_paths(&self, cached_sheet: &SheetData) -> HashSet<LocalFilePathBuf> {
+        self.data
+            .mapping
+            .keys()
+            .filter(|path| !cached_sheet.mapping().contains_key(*path))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove the mappings of files erased upstream
+    ///
+    /// If `delete_files` is true the local files are deleted as well,
+    /// otherwise they are only unmapped and stay on disk.
+    /// Returns the removed paths, sorted
+    pub async fn remove_erased<'b>(
+        &mut self,
+        erased: impl IntoIterator<Item = &'b LocalFilePathBuf>,
+        delete_files: bool,
+    ) -> Result<Vec<LocalFilePathBuf>, std::io::Error> {
+        let mut removed = Vec::new();
+        for path in erased {
+            let path = format_path(path)?;
+            if self.data.mapping.remove(&path).is_none() {
+                continue;
+            }
+            if delete_files {
+                let file = self.local_workspace.local_path().join(&path);
+                if file.is_file() {
+                    tokio::fs::remove_file(&file).await?;
+                }
+            }
+            removed.push(path);
+        }
+        removed.sort();
+        Ok(removed)
+    }
+
+    /// Compute the tree hash of the mapped files as they currently are on disk
+    ///
+    /// Two workspaces with the same tree hash hold the same content at the same paths,
+    /// see `tree_hash` for the construction
+    pub async fn workspace_tree_hash(&self) -> Result<String, std::io::Error> {
+        let mut entries = Vec::with_capacity(self.data.mapping.len());
+        for path in self.data.mapping.keys() {
+            let file_path = self.local_workspace.local_path().join(path);
+            let hash = sha1_hash::calc_sha1_default(&file_path)
+                .await
+                .map_err(Error::other)?
+                .hash;
+            entries.push((path.clone(), hash));
+        }
+        Ok(tree_hash(entries))
+    }
+
+    /// Compute the tree hash of the hashes recorded when the files were last updated
+    ///
+    /// Cheap, but blind to local modifications, use `workspace_tree_hash` to include them
+    pub fn recorded_tree_hash(&self) -> String {
+        tree_hash(
+            self.data
+                .mapping
+                .iter()
+                .map(|(path, mapping)| (path.clone(), mapping.hash_when_updated.clone())),
+        )
+    }
+}
+
+/// Compute a Merkle root over `(path, file hash)` pairs
+///
+/// Entries are sorted by their `/` separated path first, so the root does not depend on
+/// the input order or the platform. Each leaf hashes `path\0hash`, each parent hashes
+/// the concatenation of its two children, an odd node is carried up unchanged.
+/// An empty tree hashes the empty string
+pub fn tree_hash(entries: impl IntoIterator<Item = (LocalFilePathBuf, String)>) -> String {
+    let mut leaves = entries
+        .into_iter()
+        .map(|(path, hash)| (path.to_string_lossy().replace('\\', "/"), hash))
+        .collect::<Vec<_>>();
+    leaves.sort();
+
+    let mut level = leaves
+        .into_iter()
+        .map(|(path, hash)| sha1_hash::calc_sha1_string(format!("{}\0{}", path, hash)))
+        .collect::<Vec<_>>();
+    if level.is_empty() {
+        return sha1_hash::calc_sha1_string("");
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => sha1_hash::calc_sha1_string(format!("{}{}", left, right)),
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level.remove(0)
 }
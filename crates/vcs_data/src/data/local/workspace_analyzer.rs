@@ -1,15 +1,17 @@
 use std::{
     collections::{HashMap, HashSet},
     io::Error,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use sha1_hash::calc_sha1_multi;
+use sha1_hash::{Sha1, calc_hash_multi_partial};
 use string_proc::format_path::format_path;
 use walkdir::WalkDir;
 
 use crate::data::{
-    local::{LocalWorkspace, cached_sheet::CachedSheet, local_sheet::LocalSheet},
+    local::{
+        LocalWorkspace, cached_sheet::CachedSheet, ignore::IgnoreRules, local_sheet::LocalSheet,
+    },
     member::MemberId,
     sheet::{SheetData, SheetName},
     vault::virtual_file::VirtualFileId,
@@ -41,6 +43,162 @@ pub struct AnalyzeResult<'a> {
     pub modified: HashSet<ModifiedRelativePathBuf>,
 }
 
+/// Collect the relative paths of all files in the workspace
+///
+/// Paths containing `.jv` and paths excluded by the ignore rules are skipped,
+/// except for the `mapped_paths`, which are always collected if they exist
+pub fn scan_local_files(
+    local_path: &Path,
+    ignore_rules: &IgnoreRules,
+    mapped_paths: &[&PathBuf],
+) -> HashSet<PathBuf> {
+    let mut paths = HashSet::new();
+    let walker = WalkDir::new(local_path).into_iter().filter_entry(|entry| {
+        if ignore_rules.is_empty() {
+            return true;
+        }
+        let Ok(relative_path) = entry.path().strip_prefix(local_path) else {
+            return true;
+        };
+        if relative_path.as_os_str().is_empty()
+            || !ignore_rules.is_ignored(relative_path, entry.file_type().is_dir())
+        {
+            return true;
+        }
+
+        // Keep walking ignored directories that still contain mapped files
+        let relative_path = format_path(relative_path.to_path_buf()).unwrap_or_default();
+        mapped_paths
+            .iter()
+            .any(|mapped| mapped.starts_with(&relative_path))
+    });
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        // Skip entries that contain ".jv" in their path
+        if entry.path().to_string_lossy().contains(".jv") {
+            continue;
+        }
+
+        if entry.file_type().is_file()
+            && let Ok(relative_path) = entry.path().strip_prefix(local_path)
+        {
+            let format = format_path(relative_path.to_path_buf());
+            let Ok(format) = format else {
+                continue;
+            };
+
+            // Unmapped files inside a directory walked only for its mapped files
+            if !ignore_rules.is_empty()
+                && !mapped_paths.contains(&&format)
+                && format.ancestors().skip(1).any(|ancestor| {
+                    !ancestor.as_os_str().is_empty() && ignore_rules.is_ignored(ancestor, true)
+                })
+            {
+                continue;
+            }
+            paths.insert(format);
+        }
+    }
+
+    paths
+}
+
+/// A lost or created file taking part in fuzzy move detection
+pub struct FuzzyMoveCandidate {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Pair lost files with created files that look like the same file moved and edited
+///
+/// Files must share the extension, the similarity is the average of
+/// file stem similarity and size proximity, only pairs reaching `threshold` are returned.
+/// Each file is paired at most once, best matches first
+pub fn fuzzy_match_moved(
+    lost: &[FuzzyMoveCandidate],
+    created: &[FuzzyMoveCandidate],
+    threshold: f32,
+) -> Vec<(FromRelativePathBuf, ToRelativePathBuf)> {
+    let mut scored: Vec<(f32, usize, usize)> = Vec::new();
+    for (lost_index, lost_file) in lost.iter().enumerate() {
+        for (created_index, created_file) in created.iter().enumerate() {
+            let score = move_similarity(lost_file, created_file);
+            if score >= threshold {
+                scored.push((score, lost_index, created_index));
+            }
+        }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut lost_used = HashSet::new();
+    let mut created_used = HashSet::new();
+    let mut pairs = Vec::new();
+    for (_, lost_index, created_index) in scored {
+        if lost_used.contains(&lost_index) || created_used.contains(&created_index) {
+            continue;
+        }
+        lost_used.insert(lost_index);
+        created_used.insert(created_index);
+        pairs.push((
+            lost[lost_index].path.clone(),
+            created[created_index].path.clone(),
+        ));
+    }
+    pairs
+}
+
+/// Similarity (0.0 - 1.0) between a lost file and a created file
+fn move_similarity(lost: &FuzzyMoveCandidate, created: &FuzzyMoveCandidate) -> f32 {
+    let extension = |p: &PathBuf| p.extension().map(|e| e.to_string_lossy().to_lowercase());
+    if extension(&lost.path) != extension(&created.path) {
+        return 0.0;
+    }
+
+    let size_score = match lost.size.max(created.size) {
+        0 => 1.0,
+        max => lost.size.min(created.size) as f32 / max as f32,
+    };
+
+    let stem = |p: &PathBuf| {
+        p.file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+    let name_score = bigram_similarity(&stem(&lost.path), &stem(&created.path));
+
+    (size_score + name_score) / 2.0
+}
+
+/// Dice coefficient of the character bigrams of two strings
+fn bigram_similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let a_bigrams = bigrams(a);
+    let mut b_bigrams = bigrams(b);
+    let total = a_bigrams.len() + b_bigrams.len();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let mut shared = 0;
+    for bigram in a_bigrams {
+        if let Some(pos) = b_bigrams.iter().position(|b| *b == bigram) {
+            b_bigrams.swap_remove(pos);
+            shared += 1;
+        }
+    }
+    (2 * shared) as f32 / total as f32
+}
+
 struct AnalyzeContext<'a> {
     member: MemberId,
     sheet_name: SheetName,
@@ -66,37 +224,19 @@ impl<'a> AnalyzeResult<'a> {
             (member, sheet)
         };
 
-        // Local files (RelativePaths)
-        let local_path = workspace.local_path();
-        let file_relative_paths = {
-            let mut paths = HashSet::new();
-            for entry in WalkDir::new(local_path) {
-                let entry = match entry {
-                    Ok(entry) => entry,
-                    Err(_) => continue,
-                };
-
-                // Skip entries that contain ".jv" in their path
-                if entry.path().to_string_lossy().contains(".jv") {
-                    continue;
-                }
-
-                if entry.file_type().is_file()
-                    && let Ok(relative_path) = entry.path().strip_prefix(local_path)
-                {
-                    let format = format_path(relative_path.to_path_buf());
-                    let Ok(format) = format else {
-                        continue;
-                    };
-                    paths.insert(format);
-                }
-            }
+        // Read local sheet
+        let local_sheet = (workspace.local_sheet(&member, &sheet_name).await).ok();
 
-            paths
+        // Ignore rules, files already mapped in the local sheet are never ignored
+        let ignore_rules = IgnoreRules::read_from_workspace(workspace.local_path()).await?;
+        let mapped_paths: Vec<&PathBuf> = match &local_sheet {
+            Some(local_sheet) => local_sheet.data.mapping.keys().collect(),
+            None => Vec::new(),
         };
 
-        // Read local sheet
-        let local_sheet = (workspace.local_sheet(&member, &sheet_name).await).ok();
+        // Local files (RelativePaths)
+        let file_relative_paths =
+            scan_local_files(workspace.local_path(), &ignore_rules, &mapped_paths);
 
         // Read cached sheet
         let cached_sheet_data = match CachedSheet::cached_sheet_data(&sheet_name).await {
@@ -120,13 +260,7 @@ impl<'a> AnalyzeResult<'a> {
             cached_sheet_data,
         };
         Self::analyze_moved(&mut result, &file_relative_paths, &analyze_ctx, workspace).await?;
-        Self::analyze_modified(
-            &mut result,
-            &file_relative_paths,
-            &mut analyze_ctx,
-            workspace,
-        )
-        .await?;
+        Self::analyze_modified(&mut result, &file_relative_paths, &mut analyze_ctx).await?;
 
         Ok(result)
     }
@@ -146,21 +280,11 @@ impl<'a> AnalyzeResult<'a> {
         let file_relative_paths_ref: HashSet<&PathBuf> = file_relative_paths.iter().collect();
 
         // Files that exist locally but not in remote
-        let mut erased_files: HashSet<PathBuf> = HashSet::new();
-
-        if let Some(cached_data) = &analyze_ctx.cached_sheet_data {
-            if let Some(local_sheet) = &analyze_ctx.local_sheet {
-                let cached_sheet_mapping = cached_data.mapping();
-                let local_sheet_mapping = &local_sheet.data.mapping;
-
-                // Find paths that exist in local sheet but not in cached sheet
-                for local_path in local_sheet_mapping.keys() {
-                    if !cached_sheet_mapping.contains_key(local_path) {
-                        erased_files.insert(local_path.clone());
-                    }
-                }
-            }
-        }
+        let erased_files: HashSet<PathBuf> =
+            match (&analyze_ctx.cached_sheet_data, &analyze_ctx.local_sheet) {
+                (Some(cached_data), Some(local_sheet)) => local_sheet.erased_paths(cached_data),
+                _ => HashSet::new(),
+            };
 
         // Files that exist in the local sheet but not in reality are considered lost
         let mut lost_files: HashSet<&PathBuf> = local_sheet_paths
@@ -180,14 +304,14 @@ impl<'a> AnalyzeResult<'a> {
             .iter()
             .map(|p| workspace.local_path.join(p))
             .collect();
+        // Files that cannot be hashed (e.g. unreadable or removed mid-walk) stay as created items
         let file_hashes: HashSet<(PathBuf, String)> =
-            match calc_sha1_multi::<PathBuf, Vec<PathBuf>>(new_files_for_hash, 8192).await {
-                Ok(hash) => hash,
-                Err(e) => return Err(Error::other(e)),
-            }
-            .iter()
-            .map(|r| (r.file_path.clone(), r.hash.to_string()))
-            .collect();
+            calc_hash_multi_partial::<Sha1, PathBuf, Vec<PathBuf>>(new_files_for_hash, 8192)
+                .await
+                .into_iter()
+                .filter_map(|r| r.ok())
+                .map(|r| (r.file_path, r.hash))
+                .collect();
 
         // Build hash mapping table for lost files
         let mut lost_files_hash_mapping: HashMap<String, FromRelativePathBuf> =
@@ -232,10 +356,44 @@ impl<'a> AnalyzeResult<'a> {
         }
 
         // Enter fuzzy matching to match other potentially moved items that haven't been matched
-        // If the total number of new and lost files is divisible by 2, it indicates there might still be files that have been moved, consider trying fuzzy matching
-        if new_files.len() + lost_files.len() % 2 == 0 {
-            // Try fuzzy matching
-            // ...
+        if !new_files.is_empty()
+            && !lost_files.is_empty()
+            && let Some(local_sheet) = &analyze_ctx.local_sheet
+        {
+            let threshold = workspace.config.lock().await.fuzzy_move_threshold();
+
+            // Lost files no longer exist, use the size recorded when they were updated
+            let lost_candidates: Vec<FuzzyMoveCandidate> = lost_files
+                .iter()
+                .filter_map(|path| {
+                    local_sheet
+                        .mapping_data(path)
+                        .ok()
+                        .map(|mapping_data| FuzzyMoveCandidate {
+                            path: (*path).clone(),
+                            size: mapping_data.size_when_updated,
+                        })
+                })
+                .collect();
+            let new_candidates: Vec<FuzzyMoveCandidate> = new_files
+                .iter()
+                .filter_map(|path| {
+                    std::fs::metadata(workspace.local_path.join(path))
+                        .ok()
+                        .map(|metadata| FuzzyMoveCandidate {
+                            path: (*path).clone(),
+                            size: metadata.len(),
+                        })
+                })
+                .collect();
+
+            for (lost_path, new_path) in
+                fuzzy_match_moved(&lost_candidates, &new_candidates, threshold)
+            {
+                lost_files.remove(&lost_path);
+                new_files.remove(&new_path);
+                moved_files.insert((lost_path, new_path));
+            }
         }
 
         // Collect results and set the result
@@ -263,49 +421,13 @@ impl<'a> AnalyzeResult<'a> {
         result: &mut AnalyzeResult<'_>,
         file_relative_paths: &HashSet<PathBuf>,
         analyze_ctx: &mut AnalyzeContext<'a>,
-        workspace: &LocalWorkspace,
     ) -> Result<(), std::io::Error> {
         let local_sheet = &mut analyze_ctx.local_sheet.as_mut().unwrap();
-        let local_path = local_sheet.local_workspace.local_path().clone();
-
-        for path in file_relative_paths {
-            // Get mapping data
-            let Ok(mapping_data) = local_sheet.mapping_data_mut(path) else {
-                continue;
-            };
 
-            // If modified time not changed, skip
-            let modified_time = std::fs::metadata(local_path.join(path))?.modified()?;
-            if &modified_time == mapping_data.last_modifiy_check_time() {
-                if mapping_data.last_modifiy_check_result() {
-                    result.modified.insert(path.clone());
-                }
-                continue;
-            }
-
-            // Calculate hash
-            let hash_calc = match sha1_hash::calc_sha1(workspace.local_path.join(path), 2048).await
-            {
-                Ok(hash) => hash,
-                Err(e) => return Err(Error::other(e)),
-            };
-
-            // If hash not match, mark as modified
-            if &hash_calc.hash != mapping_data.hash_when_updated() {
-                result.modified.insert(path.clone());
-
-                // Update last modified check time to modified time
-                mapping_data.last_modify_check_time = modified_time;
-                mapping_data.last_modify_check_result = true;
-            } else {
-                // Update last modified check time to modified time
-                mapping_data.last_modify_check_time = modified_time;
-                mapping_data.last_modify_check_result = false;
-            }
-
-            // Record latest hash
-            mapping_data.last_modify_check_hash = Some(hash_calc.hash)
-        }
+        // Only mapped files can be modified, changed files are hashed concurrently
+        result
+            .modified
+            .extend(local_sheet.modified_paths(file_relative_paths).await?);
 
         // Persist the local sheet data
         LocalSheet::write(local_sheet).await?;
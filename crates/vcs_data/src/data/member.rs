@@ -15,6 +15,33 @@ pub struct Member {
     /// Member metadata
     #[serde(rename = "meta")]
     metadata: HashMap<String, String>,
+
+    /// Member role, decides what the member may do in the vault
+    #[serde(rename = "role", default)]
+    role: MemberRole,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemberRole {
+    /// Can edit files and revoke the edit right held by any member
+    #[serde(rename = "admin")]
+    Admin,
+
+    /// Can hold and edit files
+    #[default]
+    #[serde(rename = "writer")]
+    Writer,
+
+    /// Can sync files, but never holds or edits them
+    #[serde(rename = "reader")]
+    Reader,
+}
+
+impl MemberRole {
+    /// Check if the role allows holding and editing files
+    pub fn can_edit(&self) -> bool {
+        !matches!(self, MemberRole::Reader)
+    }
 }
 
 impl Default for Member {
@@ -47,14 +74,32 @@ impl Member {
         Self {
             id: snake_case!(new_id.into()),
             metadata: HashMap::new(),
+            role: MemberRole::default(),
         }
     }
 
+    /// Create member struct by id with the given role
+    pub fn with_role(new_id: impl Into<String>, role: MemberRole) -> Self {
+        let mut member = Self::new(new_id);
+        member.role = role;
+        member
+    }
+
     /// Get member id
     pub fn id(&self) -> String {
         self.id.clone()
     }
 
+    /// Get member role
+    pub fn role(&self) -> MemberRole {
+        self.role
+    }
+
+    /// Set member role
+    pub fn set_role(&mut self, role: MemberRole) {
+        self.role = role;
+    }
+
     /// Get metadata
     pub fn metadata(&self, key: impl Into<String>) -> Option<&String> {
         self.metadata.get(&key.into())
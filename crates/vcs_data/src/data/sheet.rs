@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use cfg_file::{ConfigFile, config::ConfigFile};
 use serde::{Deserialize, Serialize};
@@ -6,9 +9,11 @@ use serde::{Deserialize, Serialize};
 use crate::{
     constants::SERVER_FILE_SHEET,
     data::{
+        keyed_lock::{KeyedLockGuard, KeyedLocks},
         member::MemberId,
         vault::{
             Vault,
+            audit::AuditOperation,
             virtual_file::{VirtualFileId, VirtualFileVersion},
         },
     },
@@ -17,8 +22,19 @@ use crate::{
 pub type SheetName = String;
 pub type SheetPathBuf = PathBuf;
 
+/// Manifest item exchanged before transfers: (path, SHA1 hash)
+pub type SheetManifestItem = (SheetPathBuf, String);
+
 const SHEET_NAME: &str = "{sheet_name}";
 
+/// In-process locks of sheet files, keyed by sheet path
+static SHEET_LOCKS: KeyedLocks<PathBuf> = KeyedLocks::new();
+
+/// Acquire the lock of the sheet file at the given path
+pub(crate) async fn lock_sheet_file(sheet_path: &Path) -> KeyedLockGuard<PathBuf> {
+    SHEET_LOCKS.lock(sheet_path.to_path_buf()).await
+}
+
 pub struct Sheet<'a> {
     /// The name of the current sheet
     pub(crate) name: SheetName,
@@ -28,6 +44,9 @@ pub struct Sheet<'a> {
 
     /// Sheet path
     pub(crate) vault_reference: &'a Vault,
+
+    /// Lock of the sheet file, held from `Vault::sheet_for_update` until `persist`
+    pub(crate) guard: Option<KeyedLockGuard<PathBuf>>,
 }
 
 #[derive(Default, Serialize, Deserialize, ConfigFile, Clone)]
@@ -57,6 +76,52 @@ pub struct SheetMappingMetadata {
     pub version: VirtualFileVersion,
 }
 
+/// Changes between two `SheetData` snapshots, see `SheetData::diff`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SheetDiff {
+    /// Paths only mapped in the newer snapshot
+    pub added: Vec<SheetPathBuf>,
+
+    /// Paths only mapped in the older snapshot
+    pub removed: Vec<SheetPathBuf>,
+
+    /// Paths mapped in both snapshots, but to another version or virtual file
+    pub version_changed: Vec<SheetPathBuf>,
+}
+
+impl SheetDiff {
+    /// Whether both snapshots map the same paths to the same versions
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.version_changed.is_empty()
+    }
+}
+
+/// Outcome of `Sheet::remove_mapping`
+#[derive(Debug)]
+pub enum RemoveMappingResult {
+    /// The mapping was removed and its virtual file released
+    RemovedReleased(SheetMappingMetadata),
+
+    /// The mapping was removed, its virtual file doesn't exist
+    RemovedOrphan,
+
+    /// The holder still holds the virtual file, the mapping was kept
+    NotRemovedHeld,
+
+    /// The mapping was kept because the removal could not be checked
+    Error(std::io::Error),
+}
+
+impl RemoveMappingResult {
+    /// Check if the mapping was removed from the sheet
+    pub fn removed(&self) -> bool {
+        matches!(
+            self,
+            RemoveMappingResult::RemovedReleased(_) | RemoveMappingResult::RemovedOrphan
+        )
+    }
+}
+
 impl<'a> Sheet<'a> {
     pub fn name(&self) -> &SheetName {
         &self.name
@@ -100,9 +165,10 @@ impl<'a> Sheet<'a> {
     /// Add (or Edit) a mapping entry to the sheet
     ///
     /// This operation performs safety checks to ensure the member has the right to add the mapping:
-    /// 1. The sheet must have a holder (member) to perform this operation
-    /// 2. If the virtual file ID doesn't exist in the vault, the mapping is added directly
-    /// 3. If the virtual file exists, the mapping is added regardless of member edit rights
+    /// 1. The path must not exceed the length and depth limits of the vault
+    /// 2. The sheet must have a holder (member) to perform this operation
+    /// 3. If the virtual file ID doesn't exist in the vault, the mapping is added directly
+    /// 4. If the virtual file exists, the mapping is added regardless of member edit rights
     ///
     /// Note: Full validation adds overhead - avoid frequent calls
     pub async fn add_mapping(
@@ -111,6 +177,9 @@ impl<'a> Sheet<'a> {
         virtual_file_id: VirtualFileId,
         version: VirtualFileVersion,
     ) -> Result<(), std::io::Error> {
+        // Check if the path is within the limits
+        self.check_path_limits(&sheet_path)?;
+
         // Check if the virtual file exists in the vault
         if self.vault_reference.virtual_file(&virtual_file_id).is_err() {
             // Virtual file doesn't exist, add the mapping directly
@@ -143,24 +212,59 @@ impl<'a> Sheet<'a> {
         Ok(())
     }
 
+    /// Check if the path is within the length and depth limits of the vault
+    pub fn check_path_limits(&self, sheet_path: &SheetPathBuf) -> Result<(), std::io::Error> {
+        let config = self.vault_reference.config();
+
+        let length = sheet_path.as_os_str().len();
+        let max_length = config.sheet_path_max_length();
+        if length > max_length {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Path `{}` is too long: {} bytes (max {})",
+                    sheet_path.display(),
+                    length,
+                    max_length
+                ),
+            ));
+        }
+
+        let depth = sheet_path.components().count();
+        let max_depth = config.sheet_path_max_depth();
+        if depth > max_depth {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Path `{}` is too deep: {} components (max {})",
+                    sheet_path.display(),
+                    depth,
+                    max_depth
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Remove a mapping entry from the sheet
     ///
     /// This operation performs safety checks to ensure the member has the right to remove the mapping:
     /// 1. The sheet must have a holder (member) to perform this operation
     /// 2. Member must NOT have edit rights to the virtual file to release it (ensuring clear ownership)
-    /// 3. If the virtual file doesn't exist, the mapping is removed but no ID is returned
-    /// 4. If member has no edit rights and the file exists, returns the removed virtual file ID
+    /// 3. If the virtual file doesn't exist, the mapping is removed as an orphan
+    /// 4. If member has no edit rights and the file exists, the mapping is removed and returned
     ///
     /// Note: Full validation adds overhead - avoid frequent calls
-    pub async fn remove_mapping(
-        &mut self,
-        sheet_path: &SheetPathBuf,
-    ) -> Option<SheetMappingMetadata> {
+    pub async fn remove_mapping(&mut self, sheet_path: &SheetPathBuf) -> RemoveMappingResult {
         let virtual_file_meta = match self.data.mapping.get(sheet_path) {
             Some(id) => id,
             None => {
                 // The mapping entry doesn't exist, nothing to remove
-                return None;
+                return RemoveMappingResult::Error(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Mapping `{}` not found!", sheet_path.display()),
+                ));
             }
         };
 
@@ -170,13 +274,18 @@ impl<'a> Sheet<'a> {
             .virtual_file(&virtual_file_meta.id)
             .is_err()
         {
-            // Virtual file doesn't exist, remove the mapping and return None
+            // Virtual file doesn't exist, remove the mapping
             self.data.mapping.remove(sheet_path);
-            return None;
+            return RemoveMappingResult::RemovedOrphan;
         }
 
         // Check if the sheet has a holder
-        let holder = self.holder()?;
+        let Some(holder) = self.holder() else {
+            return RemoveMappingResult::Error(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "This sheet has no holder",
+            ));
+        };
 
         // Check if the holder has edit rights to the virtual file
         match self
@@ -185,18 +294,138 @@ impl<'a> Sheet<'a> {
             .await
         {
             Ok(false) => {
-                // Holder doesn't have rights, remove and return the virtual file ID
-                self.data.mapping.remove(sheet_path)
+                // Holder doesn't have rights, remove and return the mapping
+                match self.data.mapping.remove(sheet_path) {
+                    Some(metadata) => RemoveMappingResult::RemovedReleased(metadata),
+                    None => RemoveMappingResult::RemovedOrphan,
+                }
             }
             Ok(true) => {
                 // Holder has edit rights, don't remove the mapping
-                None
+                RemoveMappingResult::NotRemovedHeld
             }
-            Err(_) => {
+            Err(err) => {
                 // Error checking rights, don't remove the mapping
-                None
+                RemoveMappingResult::Error(err)
+            }
+        }
+    }
+
+    /// Plan the relocation of every mapping under `from_prefix` to `to_prefix`
+    ///
+    /// Returns the `(from, to)` pairs, sorted by the source path.
+    /// Prefixes are matched by path components, `src/old` does not match `src/older`.
+    pub fn subtree_move_plan(
+        &self,
+        from_prefix: &SheetPathBuf,
+        to_prefix: &SheetPathBuf,
+    ) -> Vec<(SheetPathBuf, SheetPathBuf)> {
+        let mut plan: Vec<(SheetPathBuf, SheetPathBuf)> = self
+            .data
+            .mapping
+            .keys()
+            .filter_map(|path| {
+                path.strip_prefix(from_prefix)
+                    .ok()
+                    .map(|rest| (path.clone(), to_prefix.join(rest)))
+            })
+            .collect();
+        plan.sort();
+        plan
+    }
+
+    /// Relocate every mapping under `from_prefix` to `to_prefix`
+    ///
+    /// Virtual file IDs and versions are preserved, only the sheet paths change.
+    /// Fails without modifying the sheet if:
+    /// 1. No mapping exists under `from_prefix`
+    /// 2. Any target path is already mapped (and is not being moved away)
+    /// 3. Any target path exceeds the length and depth limits of the vault
+    ///
+    /// Returns the number of moved mappings
+    pub fn move_subtree(
+        &mut self,
+        from_prefix: &SheetPathBuf,
+        to_prefix: &SheetPathBuf,
+    ) -> Result<usize, std::io::Error> {
+        let plan = self.subtree_move_plan(from_prefix, to_prefix);
+        if plan.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("No mapping found under `{}`", from_prefix.display()),
+            ));
+        }
+
+        // Precheck
+        let sources: HashSet<&SheetPathBuf> = plan.iter().map(|(from, _)| from).collect();
+        for (_, to) in plan.iter() {
+            if self.data.mapping.contains_key(to) && !sources.contains(to) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("Target path `{}` already exists", to.display()),
+                ));
             }
+            self.check_path_limits(to)?;
         }
+
+        // Remove all sources first, targets may overlap with them
+        let moved: Vec<(SheetPathBuf, SheetMappingMetadata)> = plan
+            .into_iter()
+            .filter_map(|(from, to)| self.data.mapping.remove(&from).map(|meta| (to, meta)))
+            .collect();
+        let count = moved.len();
+        self.data.mapping.extend(moved);
+
+        Ok(count)
+    }
+
+    /// Find the manifest items whose content already matches a mapped version
+    ///
+    /// For each `(path, hash)` in the manifest, the mapping at that path is compared with the given hash.
+    /// A path in `renames` (target -> source) is compared with the mapping at its source instead,
+    /// as long as the target itself is not mapped.
+    /// Returns the identical manifest paths with the path of the mapping they match,
+    /// these are byte-identical and do not need to be transferred.
+    ///
+    /// The hashes recorded in the virtual file metas are compared, only versions without one are hashed
+    pub async fn identical_mappings(
+        &self,
+        manifest: &[SheetManifestItem],
+        renames: &HashMap<SheetPathBuf, SheetPathBuf>,
+    ) -> HashMap<SheetPathBuf, SheetPathBuf> {
+        let mut identical = HashMap::new();
+
+        for (path, hash) in manifest {
+            let mapped_path = match renames.get(path) {
+                Some(source) if !self.data.mapping.contains_key(path) => source,
+                _ => path,
+            };
+            let Some(mapping) = self.data.mapping.get(mapped_path) else {
+                continue;
+            };
+
+            // Compare with the hash of the mapped version
+            let recorded = match self.vault_reference.virtual_file_meta(&mapping.id).await {
+                Ok(meta) => meta.version_hash(&mapping.version).cloned(),
+                Err(_) => continue,
+            };
+            let mapped_hash = match recorded {
+                Some(recorded) => recorded,
+                None => match self
+                    .vault_reference
+                    .virtual_file_hash(&mapping.id, &mapping.version)
+                    .await
+                {
+                    Ok(mapped_hash) => mapped_hash,
+                    Err(_) => continue,
+                },
+            };
+            if &mapped_hash == hash {
+                identical.insert(path.clone(), mapped_path.clone());
+            }
+        }
+
+        identical
     }
 
     /// Persist the sheet to disk
@@ -204,7 +433,33 @@ impl<'a> Sheet<'a> {
     /// Why not use a reference?
     /// Because I don't want a second instance of the sheet to be kept in memory.
     /// If needed, please deserialize and reload it.
+    ///
+    /// The write is serialized with other writers of the same sheet in this process.
+    /// A sheet from `Vault::sheet_for_update` holds the lock since it was read,
+    /// any other sheet fails to persist if the sheet was written since it was read,
+    /// instead of overwriting that change.
     pub async fn persist(mut self) -> Result<(), std::io::Error> {
+        self.vault_reference.check_writable()?;
+
+        // Hold the sheet lock while writing, a sheet from `sheet_for_update` already holds it
+        let _guard = match self.guard.take() {
+            Some(guard) => guard,
+            None => {
+                let guard = lock_sheet_file(&self.sheet_path()).await;
+
+                // Another writer persisted since this sheet was read
+                if let Ok(on_disk) = SheetData::read_from(self.sheet_path()).await
+                    && on_disk.write_count != self.data.write_count
+                {
+                    return Err(std::io::Error::other(format!(
+                        "Sheet `{}` was changed since it was read!",
+                        self.name
+                    )));
+                }
+                guard
+            }
+        };
+
         self.data.write_count += 1;
 
         // Update id mapping
@@ -221,7 +476,13 @@ impl<'a> Sheet<'a> {
         if self.data.write_count >= i32::MAX - 1 {
             self.data.write_count = 0;
         }
-        SheetData::write_to(&self.data, self.sheet_path()).await
+        SheetData::write_to(&self.data, self.sheet_path()).await?;
+
+        let member = self.data.holder.clone().unwrap_or_default();
+        self.vault_reference
+            .audit(&member, AuditOperation::PersistSheet, &self.name)
+            .await;
+        Ok(())
     }
 
     /// Get the path to the sheet file
@@ -277,4 +538,71 @@ impl SheetData {
     pub fn id_mapping_mut(&mut self) -> &mut Option<HashMap<VirtualFileId, SheetPathBuf>> {
         &mut self.id_mapping
     }
+
+    /// Compare the mappings of this sheet data with a newer snapshot
+    ///
+    /// All paths in the result are sorted
+    pub fn diff(&self, other: &SheetData) -> SheetDiff {
+        let mut diff = SheetDiff::default();
+
+        for (path, metadata) in self.mapping.iter() {
+            match other.mapping.get(path) {
+                None => diff.removed.push(path.clone()),
+                Some(other_metadata) if other_metadata != metadata => {
+                    diff.version_changed.push(path.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        for path in other.mapping.keys() {
+            if !self.mapping.contains_key(path) {
+                diff.added.push(path.clone());
+            }
+        }
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.version_changed.sort();
+        diff
+    }
+
+    /// Apply the changes of a diff against a newer snapshot, see `SheetData::diff`
+    ///
+    /// Only the paths named in the diff are taken from `latest`,
+    /// the write count and holder follow `latest`
+    pub fn apply_diff(&mut self, diff: &SheetDiff, latest: &SheetData) {
+        for path in diff
+            .removed
+            .iter()
+            .chain(diff.added.iter())
+            .chain(diff.version_changed.iter())
+        {
+            if let Some(old) = self.mapping.remove(path)
+                && let Some(id_mapping) = self.id_mapping.as_mut()
+                && id_mapping.get(&old.id) == Some(path)
+            {
+                id_mapping.remove(&old.id);
+            }
+
+            if let Some(metadata) = latest.mapping.get(path) {
+                if let Some(id_mapping) = self.id_mapping.as_mut() {
+                    id_mapping.insert(metadata.id.clone(), path.clone());
+                }
+                self.mapping.insert(path.clone(), metadata.clone());
+            }
+        }
+
+        // Without an id mapping to patch, build it from the result
+        if self.id_mapping.is_none() {
+            self.id_mapping = Some(
+                self.mapping
+                    .iter()
+                    .map(|(path, metadata)| (metadata.id.clone(), path.clone()))
+                    .collect(),
+            );
+        }
+
+        self.write_count = latest.write_count;
+        self.holder = latest.holder.clone();
+    }
 }
@@ -1,6 +1,6 @@
-use std::{env::current_dir, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, env::current_dir, path::PathBuf, sync::Arc};
 
-use tokio::fs::create_dir_all;
+use tokio::{fs::create_dir_all, sync::Mutex};
 use vcs_docs::docs::READMES_VAULT_README;
 
 use crate::{
@@ -9,19 +9,32 @@ use crate::{
         SERVER_PATH_MEMBERS, SERVER_PATH_SHEETS, SERVER_PATH_VF_ROOT, VAULT_HOST_NAME,
     },
     current::{current_vault_path, find_vault_path},
-    data::{member::Member, vault::config::VaultConfig},
+    data::{
+        member::{Member, MemberId, MemberRole},
+        vault::{config::VaultConfig, virtual_file::VirtualFileId},
+    },
 };
 
+pub mod audit;
+pub mod backup;
 pub mod config;
 pub mod member;
 pub mod service;
 pub mod sheet_share;
 pub mod sheets;
+pub mod transaction;
+pub mod verify;
 pub mod virtual_file;
 
 pub struct Vault {
     config: Arc<VaultConfig>,
     vault_path: PathBuf,
+
+    /// Virtual files created with an idempotency token during this session
+    create_tokens: Arc<Mutex<HashMap<(MemberId, String), VirtualFileId>>>,
+
+    /// Whether write operations are rejected
+    read_only: bool,
 }
 
 impl Vault {
@@ -36,15 +49,29 @@ impl Vault {
         Some(Self {
             config: Arc::new(config),
             vault_path,
+            create_tokens: Arc::default(),
+            read_only: false,
         })
     }
 
+    /// Initialize vault without write access
+    ///
+    /// Every operation that modifies the vault fails with `ErrorKind::PermissionDenied`,
+    /// for tools such as backups or audits that must never change it
+    pub fn init_readonly(config: VaultConfig, vault_path: impl Into<PathBuf>) -> Option<Self> {
+        let mut vault = Self::init(config, vault_path)?;
+        vault.read_only = true;
+        Some(vault)
+    }
+
     /// Initialize vault
     pub fn init_current_dir(config: VaultConfig) -> Option<Self> {
         let vault_path = current_vault_path()?;
         Some(Self {
             config: Arc::new(config),
             vault_path,
+            create_tokens: Arc::default(),
+            read_only: false,
         })
     }
 
@@ -102,7 +129,7 @@ impl Vault {
 
         // 6. Create host member
         vault
-            .register_member_to_vault(Member::new(VAULT_HOST_NAME))
+            .register_member_to_vault(Member::with_role(VAULT_HOST_NAME, MemberRole::Admin))
             .await?;
 
         // 7. Setup reference sheet
@@ -129,4 +156,20 @@ impl Vault {
     pub fn config(&self) -> &Arc<VaultConfig> {
         &self.config
     }
+
+    /// Whether the vault was opened with `init_readonly`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with `ErrorKind::PermissionDenied` if the vault was opened read-only
+    pub(crate) fn check_writable(&self) -> Result<(), std::io::Error> {
+        if self.read_only {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "Vault is opened read-only",
+            ));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+
+use crate::{
+    constants::SERVER_FILE_AUDIT_LOG,
+    data::{member::MemberId, vault::Vault},
+};
+
+/// Serializes appends to the audit log, so lines never interleave
+static AUDIT_LOG_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Operation recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditOperation {
+    #[serde(rename = "create_vf")]
+    CreateVirtualFile,
+
+    #[serde(rename = "update_vf")]
+    UpdateVirtualFile,
+
+    #[serde(rename = "grant_edit_right")]
+    GrantEditRight,
+
+    #[serde(rename = "revoke_edit_right")]
+    RevokeEditRight,
+
+    #[serde(rename = "transfer_edit_right")]
+    TransferEditRight,
+
+    #[serde(rename = "amend_version_desc")]
+    AmendVersionDescription,
+
+    #[serde(rename = "persist_sheet")]
+    PersistSheet,
+
+    #[serde(rename = "merge_share")]
+    MergeShare,
+}
+
+/// A line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix timestamp in milliseconds
+    #[serde(rename = "time")]
+    pub timestamp: i64,
+
+    /// The member who performed the operation
+    #[serde(rename = "member")]
+    pub member: MemberId,
+
+    /// The operation performed
+    #[serde(rename = "op")]
+    pub operation: AuditOperation,
+
+    /// The virtual file ID, sheet name or share the operation targeted
+    #[serde(rename = "target")]
+    pub target: String,
+}
+
+/// Filter for `Vault::audit_entries`, unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub member: Option<MemberId>,
+    pub operation: Option<AuditOperation>,
+    pub target: Option<String>,
+}
+
+impl AuditFilter {
+    /// Check if an entry matches the filter
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.member.as_ref().is_none_or(|m| m == &entry.member)
+            && self.operation.is_none_or(|o| o == entry.operation)
+            && self.target.as_ref().is_none_or(|t| t == &entry.target)
+    }
+}
+
+impl Vault {
+    /// Get the path of the audit log
+    pub fn audit_log_path(&self) -> PathBuf {
+        self.vault_path().join(SERVER_FILE_AUDIT_LOG)
+    }
+
+    /// Append an entry to the audit log
+    ///
+    /// Logging is best effort, a failed write never fails the audited operation
+    pub(crate) async fn audit(
+        &self,
+        member: &MemberId,
+        operation: AuditOperation,
+        target: impl Into<String>,
+    ) {
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            member: member.clone(),
+            operation,
+            target: target.into(),
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+
+        let _guard = AUDIT_LOG_LOCK.lock().await;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.audit_log_path())
+            .await;
+        if let Ok(mut file) = file {
+            let _ = file.write_all(line.as_bytes()).await;
+        }
+    }
+
+    /// Read the audit log entries matching the filter, oldest first
+    ///
+    /// Lines that cannot be parsed are skipped
+    pub async fn audit_entries(
+        &self,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEntry>, std::io::Error> {
+        let path = self.audit_log_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = tokio::fs::read_to_string(path).await?;
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| filter.matches(entry))
+            .collect())
+    }
+}
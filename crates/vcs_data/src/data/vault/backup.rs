@@ -0,0 +1,258 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Error, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
+use walkdir::WalkDir;
+
+use crate::{
+    constants::{
+        SERVER_FILE_README, SERVER_FILE_VAULT, SERVER_PATH_MEMBER_PUB, SERVER_PATH_MEMBERS,
+        SERVER_PATH_SHEETS, SERVER_PATH_VF_ROOT,
+    },
+    data::{
+        sheet::{Sheet, lock_sheet_file},
+        vault::{
+            Vault,
+            virtual_file::{VirtualFileId, VirtualFileVersion},
+        },
+    },
+};
+
+/// Magic bytes at the start of a vault backup
+const BACKUP_MAGIC: &[u8; 8] = b"JVBACKUP";
+
+/// Format version of vault backups
+const BACKUP_VERSION: u32 = 1;
+
+impl Vault {
+    /// Write a snapshot of the vault to a single archive
+    ///
+    /// The archive contains the vault config, members, keys, sheets with their shares and history,
+    /// the metadata of every virtual file and the instances of its latest version
+    /// and of every version a sheet or share maps to.
+    /// Audit log, temp files and the service lock are left out.
+    ///
+    /// All sheet locks are held while writing, so no sheet changes during the snapshot
+    pub async fn export_backup(
+        &self,
+        mut writer: impl AsyncWrite + Unpin,
+    ) -> Result<(), std::io::Error> {
+        writer.write_all(BACKUP_MAGIC).await?;
+        writer.write_all(&BACKUP_VERSION.to_be_bytes()).await?;
+
+        // Config and members
+        for file in [SERVER_FILE_VAULT, SERVER_FILE_README] {
+            if self.vault_path.join(file).is_file() {
+                self.write_backup_entry(&mut writer, &self.vault_path.join(file))
+                    .await?;
+            }
+        }
+        for dir in [SERVER_PATH_MEMBERS, SERVER_PATH_MEMBER_PUB] {
+            self.write_backup_dir(&mut writer, &self.vault_path.join(dir))
+                .await?;
+        }
+
+        // Lock every sheet, in name order so concurrent exports cannot deadlock
+        let mut sheet_names = self.sheet_names()?;
+        sheet_names.sort();
+        let mut guards = Vec::new();
+        for sheet_name in sheet_names.iter() {
+            guards.push(lock_sheet_file(&Sheet::sheet_path_with_name(self, sheet_name)).await);
+        }
+
+        // Versions mapped by sheets and shares must survive the restore
+        let mut pinned: HashMap<VirtualFileId, HashSet<VirtualFileVersion>> = HashMap::new();
+        for sheet_name in sheet_names.iter() {
+            let sheet = self.sheet(sheet_name).await?;
+            let shares = sheet.get_shares().await?;
+            let mappings = sheet
+                .mapping()
+                .values()
+                .chain(shares.iter().flat_map(|share| share.mappings.values()));
+            for mapping in mappings {
+                pinned
+                    .entry(mapping.id.clone())
+                    .or_default()
+                    .insert(mapping.version.clone());
+            }
+        }
+
+        self.write_backup_dir(&mut writer, &self.vault_path.join(SERVER_PATH_SHEETS))
+            .await?;
+
+        // Virtual files
+        let mut ids = self.virtual_file_ids()?;
+        ids.sort();
+        for id in ids {
+            let meta = self.virtual_file_meta(&id).await?;
+            self.write_backup_entry(&mut writer, &self.virtual_file_meta_path(&id))
+                .await?;
+
+            let mut versions: Vec<VirtualFileVersion> = pinned
+                .remove(&id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|version| meta.version_exists(version))
+                .collect();
+            versions.push(meta.version_latest());
+            versions.sort();
+            versions.dedup();
+            for version in versions {
+                let instance = self.virtual_file_real_path(&id, &version);
+                if instance.is_file() {
+                    self.write_backup_entry(&mut writer, &instance).await?;
+                }
+            }
+        }
+        drop(guards);
+
+        // End of archive
+        writer.write_all(&0u32.to_be_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Restore a backup written by `export_backup` into an empty directory
+    ///
+    /// The vault can be opened with `Vault::init` afterwards
+    pub async fn import_backup(
+        mut reader: impl AsyncRead + Unpin,
+        vault_path: impl Into<PathBuf>,
+    ) -> Result<(), std::io::Error> {
+        let vault_path: PathBuf = vault_path.into();
+
+        // Ensure directory is empty
+        if vault_path.exists() && vault_path.read_dir()?.next().is_some() {
+            return Err(Error::new(
+                ErrorKind::DirectoryNotEmpty,
+                "DirectoryNotEmpty",
+            ));
+        }
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic).await?;
+        let version = reader.read_u32().await?;
+        if &magic != BACKUP_MAGIC || version != BACKUP_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Not a supported vault backup",
+            ));
+        }
+
+        loop {
+            let path_len = reader.read_u32().await? as usize;
+            if path_len == 0 {
+                break;
+            }
+            let mut path_bytes = vec![0u8; path_len];
+            reader.read_exact(&mut path_bytes).await?;
+            let relative = String::from_utf8(path_bytes)
+                .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid path in backup"))?;
+
+            // Never write outside the vault directory
+            let relative = PathBuf::from(relative);
+            if !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid path `{}` in backup", relative.display()),
+                ));
+            }
+
+            let target = vault_path.join(&relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            let size = reader.read_u64().await?;
+            let mut file = fs::File::create(&target).await?;
+            let copied = tokio::io::copy(&mut (&mut reader).take(size), &mut file).await?;
+            if copied != size {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("Backup entry `{}` is truncated", relative.display()),
+                ));
+            }
+            file.sync_all().await?;
+        }
+
+        // Directories a vault expects, even when empty
+        for dir in [
+            SERVER_PATH_SHEETS,
+            SERVER_PATH_MEMBER_PUB,
+            SERVER_PATH_MEMBERS,
+            SERVER_PATH_VF_ROOT,
+        ] {
+            fs::create_dir_all(vault_path.join(dir)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write every file below the directory to the backup
+    async fn write_backup_dir(
+        &self,
+        writer: &mut (impl AsyncWrite + Unpin),
+        dir: &Path,
+    ) -> Result<(), std::io::Error> {
+        if !dir.exists() {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        for entry in WalkDir::new(dir) {
+            let entry = entry.map_err(Error::other)?;
+            if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+        files.sort();
+        for file in files {
+            self.write_backup_entry(writer, &file).await?;
+        }
+        Ok(())
+    }
+
+    /// Write a single file to the backup, keyed by its path relative to the vault
+    async fn write_backup_entry(
+        &self,
+        writer: &mut (impl AsyncWrite + Unpin),
+        path: &Path,
+    ) -> Result<(), std::io::Error> {
+        let relative = path
+            .strip_prefix(&self.vault_path)
+            .map_err(|_| Error::other(format!("`{}` is outside the vault", path.display())))?
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let mut file = fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+
+        writer
+            .write_all(&(relative.len() as u32).to_be_bytes())
+            .await?;
+        writer.write_all(relative.as_bytes()).await?;
+        writer.write_all(&size.to_be_bytes()).await?;
+
+        // The size is already written, a file that shrinks meanwhile cannot be recovered
+        let copied = tokio::io::copy(&mut (&mut file).take(size), writer).await?;
+        if copied != size {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("`{}` changed during the backup", path.display()),
+            ));
+        }
+        Ok(())
+    }
+}
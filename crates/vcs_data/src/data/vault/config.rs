@@ -4,7 +4,7 @@ use cfg_file::ConfigFile;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::constants::{PORT, SERVER_FILE_VAULT};
+use crate::constants::{PORT, SERVER_FILE_VAULT, SHEET_PATH_MAX_DEPTH, SHEET_PATH_MAX_LENGTH};
 use crate::data::member::{Member, MemberId};
 
 pub type VaultName = String;
@@ -52,18 +52,18 @@ pub enum BehaviourEnabled {
     No,
 }
 
-impl Into<bool> for ServiceEnabled {
-    fn into(self) -> bool {
-        match self {
+impl From<ServiceEnabled> for bool {
+    fn from(value: ServiceEnabled) -> Self {
+        match value {
             ServiceEnabled::Enable => true,
             ServiceEnabled::Disable => false,
         }
     }
 }
 
-impl Into<bool> for BehaviourEnabled {
-    fn into(self) -> bool {
-        match self {
+impl From<BehaviourEnabled> for bool {
+    fn from(value: BehaviourEnabled) -> Self {
+        match value {
             BehaviourEnabled::Yes => true,
             BehaviourEnabled::No => false,
         }
@@ -88,6 +88,18 @@ pub struct VaultConfig {
     /// Vault server configuration, which will be loaded when connecting to the server
     #[serde(rename = "profile")]
     server_config: VaultServerConfig,
+
+    /// Maximum length (in bytes) of a path in the sheets
+    #[serde(rename = "max_path_len")]
+    sheet_path_max_length: Option<usize>,
+
+    /// Maximum number of components of a path in the sheets
+    #[serde(rename = "max_path_depth")]
+    sheet_path_max_depth: Option<usize>,
+
+    /// Whether version names are normalized to dot case, e.g. `Release 2.0` -> `release.2.0`
+    #[serde(rename = "normalize_versions")]
+    normalize_version_names: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -134,6 +146,9 @@ impl Default for VaultConfig {
                 lan_discovery: Some(ServiceEnabled::default()),
                 auth_mode: Some(AuthMode::Key),
             },
+            sheet_path_max_length: None,
+            sheet_path_max_depth: None,
+            normalize_version_names: None,
         }
     }
 }
@@ -198,6 +213,36 @@ impl VaultConfig {
     pub fn set_server_config(&mut self, server_config: VaultServerConfig) {
         self.server_config = server_config;
     }
+
+    /// Get maximum length of a sheet path
+    pub fn sheet_path_max_length(&self) -> usize {
+        self.sheet_path_max_length.unwrap_or(SHEET_PATH_MAX_LENGTH)
+    }
+
+    /// Set maximum length of a sheet path, `None` to use the default value
+    pub fn set_sheet_path_max_length(&mut self, max_length: Option<usize>) {
+        self.sheet_path_max_length = max_length;
+    }
+
+    /// Get maximum depth of a sheet path
+    pub fn sheet_path_max_depth(&self) -> usize {
+        self.sheet_path_max_depth.unwrap_or(SHEET_PATH_MAX_DEPTH)
+    }
+
+    /// Set maximum depth of a sheet path, `None` to use the default value
+    pub fn set_sheet_path_max_depth(&mut self, max_depth: Option<usize>) {
+        self.sheet_path_max_depth = max_depth;
+    }
+
+    /// Check whether version names are normalized, enabled by default
+    pub fn normalize_version_names(&self) -> bool {
+        self.normalize_version_names.unwrap_or(true)
+    }
+
+    /// Enable or disable normalizing version names, `None` to use the default value
+    pub fn set_normalize_version_names(&mut self, normalize: Option<bool>) {
+        self.normalize_version_names = normalize;
+    }
 }
 
 impl VaultServerConfig {
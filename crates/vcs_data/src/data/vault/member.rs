@@ -5,14 +5,15 @@ use std::{
 };
 
 use cfg_file::config::ConfigFile;
+use futures::{Stream, StreamExt, stream};
 
 use crate::{
     constants::{
         SERVER_FILE_MEMBER_INFO, SERVER_FILE_MEMBER_PUB, SERVER_PATH_MEMBERS,
-        SERVER_SUFFIX_MEMBER_INFO_NO_DOT,
+        SERVER_SUFFIX_MEMBER_INFO_NO_DOT, VAULT_HOST_NAME,
     },
     data::{
-        member::{Member, MemberId},
+        member::{Member, MemberId, MemberRole},
         vault::Vault,
     },
 };
@@ -57,6 +58,32 @@ impl Vault {
         Ok(member_ids)
     }
 
+    /// Find the IDs of members whose ID starts with the prefix, sorted
+    ///
+    /// Returns an empty list if nothing matches or the members cannot be listed
+    pub fn find_members(&self, prefix: &str, case_insensitive: bool) -> Vec<MemberId> {
+        let prefix = if case_insensitive {
+            prefix.to_lowercase()
+        } else {
+            prefix.to_string()
+        };
+
+        let mut found: Vec<MemberId> = self
+            .member_ids()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| {
+                if case_insensitive {
+                    id.to_lowercase().starts_with(&prefix)
+                } else {
+                    id.starts_with(&prefix)
+                }
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
     /// Get all members
     /// This method will read and deserialize member information, please pay attention to performance issues
     pub async fn members(&self) -> Result<Vec<Member>, std::io::Error> {
@@ -71,8 +98,17 @@ impl Vault {
         Ok(members)
     }
 
+    /// Load the members one at a time, skipping members whose information cannot be read
+    pub fn members_stream(&self) -> Result<impl Stream<Item = Member> + '_, std::io::Error> {
+        let member_ids = self.member_ids()?;
+        Ok(stream::iter(member_ids)
+            .filter_map(move |member_id| async move { self.member(&member_id).await.ok() }))
+    }
+
     /// Update member info
     pub async fn update_member(&self, member: Member) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         // Ensure member exist
         if self.member_cfg(&member.id()).is_some() {
             let member_cfg_path = self.member_cfg_path(&member.id());
@@ -85,6 +121,8 @@ impl Vault {
 
     /// Register a member to vault
     pub async fn register_member_to_vault(&self, member: Member) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         // Ensure member not exist
         if self.member_cfg(&member.id()).is_some() {
             return Err(Error::new(
@@ -93,6 +131,14 @@ impl Vault {
             ));
         }
 
+        // The host is always an admin
+        if member.id() == VAULT_HOST_NAME && member.role() != MemberRole::Admin {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Member `{}` must be registered as an admin!", member.id()),
+            ));
+        }
+
         // Wrtie config file to member dir
         let member_cfg_path = self.member_cfg_path(&member.id());
         Member::write_to(&member, member_cfg_path).await?;
@@ -100,8 +146,36 @@ impl Vault {
         Ok(())
     }
 
+    /// Get the role of a member
+    ///
+    /// The vault host is always an admin, unregistered members default to writers
+    pub async fn member_role(&self, id: &MemberId) -> Result<MemberRole, std::io::Error> {
+        if id == VAULT_HOST_NAME {
+            return Ok(MemberRole::Admin);
+        }
+        if self.member_cfg(id).is_none() {
+            return Ok(MemberRole::default());
+        }
+        Ok(self.member(id).await?.role())
+    }
+
+    /// Change the role of a registered member
+    pub async fn set_member_role(
+        &self,
+        id: &MemberId,
+        role: MemberRole,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        let mut member = self.member(id).await?;
+        member.set_role(role);
+        self.update_member(member).await
+    }
+
     /// Remove member from vault
     pub fn remove_member_from_vault(&self, id: &MemberId) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         // Ensure member exist
         if let Some(member_cfg_path) = self.member_cfg(id) {
             fs::remove_file(member_cfg_path)?;
@@ -15,6 +15,8 @@ impl Vault {
 
     /// Lock the current Vault
     pub fn lock(&self) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         if self.is_locked() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::AlreadyExists,
@@ -30,6 +32,8 @@ impl Vault {
 
     /// Unlock the current Vault
     pub fn unlock(&self) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         if let Err(e) = std::fs::remove_file(self.lock_file_path())
             && e.kind() != std::io::ErrorKind::NotFound
         {
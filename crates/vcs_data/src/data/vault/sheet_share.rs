@@ -3,17 +3,19 @@ use std::{collections::HashMap, io::Error, path::PathBuf};
 use cfg_file::{ConfigFile, config::ConfigFile};
 use rand::{Rng, rng};
 use serde::{Deserialize, Serialize};
+use sha1_hash::calc_sha1_string;
 use string_proc::{format_path, snake_case};
 use tokio::fs;
 
 use crate::{
     constants::{
-        SERVER_FILE_SHEET_SHARE, SERVER_PATH_SHARES, SERVER_SUFFIX_SHEET_SHARE_FILE_NO_DOT,
+        REF_SHEET_NAME, SERVER_FILE_SHEET_SHARE, SERVER_PATH_SHARES,
+        SERVER_SUFFIX_SHEET_SHARE_FILE_NO_DOT,
     },
     data::{
         member::MemberId,
         sheet::{Sheet, SheetMappingMetadata, SheetName, SheetPathBuf},
-        vault::Vault,
+        vault::{Vault, audit::AuditOperation},
     },
 };
 
@@ -59,11 +61,18 @@ pub enum ShareMergeMode {
     #[default]
     Safe,
 
+    /// If a path already exists during merge, keep the mapping with the newer file version
+    /// Path conflict: if both map the same virtual file, compare the version numbers,
+    /// ties keep the local mapping and are reported. Mappings of different files,
+    /// or with a version unknown to the file, keep the local mapping and are reported
+    /// File conflict: keep the local mapping and report it
+    PreferNewerVersion,
+
     /// Reject all shares
     RejectAll,
 }
 
-#[derive(Default, Serialize, Deserialize, ConfigFile, Clone)]
+#[derive(Default, Serialize, Deserialize, ConfigFile, Clone, Debug, PartialEq, Eq)]
 pub struct ShareMergeConflict {
     /// Duplicate mappings exist
     pub duplicate_mapping: Vec<PathBuf>,
@@ -77,8 +86,35 @@ impl ShareMergeConflict {
     pub fn ok(&self) -> bool {
         self.duplicate_mapping.is_empty() && self.duplicate_file.is_empty()
     }
+
+    /// Get the conflicts carried by an error returned from a Safe mode `merge_share`
+    pub fn from_error(error: &Error) -> Option<&ShareMergeConflict> {
+        error.get_ref()?.downcast_ref::<ShareMergeConflict>()
+    }
+}
+
+impl std::fmt::Display for ShareMergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |paths: &Vec<PathBuf>| {
+            paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(f, "Mappings or files already exist!")?;
+        if !self.duplicate_mapping.is_empty() {
+            write!(f, " Duplicate mappings: {}.", join(&self.duplicate_mapping))?;
+        }
+        if !self.duplicate_file.is_empty() {
+            write!(f, " Duplicate files: {}.", join(&self.duplicate_file))?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for ShareMergeConflict {}
+
 impl Vault {
     /// Get the path of a share item in a sheet
     pub fn share_file_path(&self, sheet_name: &SheetName, share_id: &SheetShareId) -> PathBuf {
@@ -155,23 +191,59 @@ impl<'a> Sheet<'a> {
         self,
         share_id: &SheetShareId,
         share_merge_mode: ShareMergeMode,
-    ) -> Result<(), std::io::Error> {
+    ) -> Result<ShareMergeConflict, std::io::Error> {
         let share = self.get_share(share_id).await?;
         self.merge_share(share, share_merge_mode).await
     }
 
     /// Import a share of a sheet
+    ///
+    /// In Safe mode, conflicts fail the merge with an `AlreadyExists` error
+    /// carrying the `ShareMergeConflict`
+    ///
+    /// Returns the conflicts the merge could not decide and kept local,
+    /// only PreferNewerVersion mode reports any
+    ///
+    /// Shares are never merged into the reference sheet, see `merge_share_allow_ref`
     pub async fn merge_share(
+        self,
+        share: Share,
+        share_merge_mode: ShareMergeMode,
+    ) -> Result<ShareMergeConflict, std::io::Error> {
+        self.merge_share_allow_ref(share, share_merge_mode, false)
+            .await
+    }
+
+    /// Import a share of a sheet, see `merge_share`
+    ///
+    /// Merging into the reference sheet changes what every member sees,
+    /// so it fails with `ErrorKind::PermissionDenied` unless `allow_ref` is set
+    pub async fn merge_share_allow_ref(
         mut self,
         share: Share,
         share_merge_mode: ShareMergeMode,
-    ) -> Result<(), std::io::Error> {
+        allow_ref: bool,
+    ) -> Result<ShareMergeConflict, std::io::Error> {
+        self.vault_reference.check_writable()?;
+
+        // Rejecting shares leaves the mappings untouched
+        if self.name == REF_SHEET_NAME
+            && !allow_ref
+            && share_merge_mode != ShareMergeMode::RejectAll
+        {
+            return Err(Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Merging into sheet `{}` is not allowed!", REF_SHEET_NAME),
+            ));
+        }
+
         // Backup original data and edit based on this backup
         let mut copy_share = share.clone();
         let mut copy_sheet = self.clone_data();
 
         // Pre-check
         let conflicts = self.precheck(&copy_share);
+        let mut unresolved = ShareMergeConflict::default();
         let mut reject_mode = false;
 
         match share_merge_mode {
@@ -179,11 +251,8 @@ impl<'a> Sheet<'a> {
             ShareMergeMode::Safe => {
                 // Conflicts found
                 if !conflicts.ok() {
-                    // Do nothing, return Error
-                    return Err(Error::new(
-                        std::io::ErrorKind::AlreadyExists,
-                        "Mappings or files already exist!",
-                    ));
+                    // Do nothing, return the conflicts, see `ShareMergeConflict::from_error`
+                    return Err(Error::new(std::io::ErrorKind::AlreadyExists, conflicts));
                 }
             }
             // Overwrite mode: when conflicts occur, prioritize the share item
@@ -251,17 +320,65 @@ impl<'a> Sheet<'a> {
                     copy_share.mappings.remove(&path);
                 }
             }
+            // Prefer newer version mode: when paths conflict, prioritize the newer file version
+            ShareMergeMode::PreferNewerVersion => {
+                for path in conflicts.duplicate_mapping {
+                    let Some(share_value) = copy_share.mappings.remove(&path) else {
+                        return Err(Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("Share value `{}` not found!", &path.display()),
+                        ));
+                    };
+                    let Some(local_value) = copy_sheet.mapping().get(&path) else {
+                        return Err(Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("Mapping `{}` not found!", &path.display()),
+                        ));
+                    };
+
+                    // Version numbers only order the versions of the same file
+                    if local_value.id != share_value.id {
+                        unresolved.duplicate_mapping.push(path);
+                        continue;
+                    }
+                    let local_num = self.mapping_version_num(local_value).await?;
+                    let share_num = self.mapping_version_num(&share_value).await?;
+                    let (Some(local_num), Some(share_num)) = (local_num, share_num) else {
+                        unresolved.duplicate_mapping.push(path);
+                        continue;
+                    };
+
+                    if share_num > local_num {
+                        copy_sheet.mapping_mut().insert(path, share_value);
+                    } else if share_num == local_num {
+                        // Tie, keep the local mapping
+                        unresolved.duplicate_mapping.push(path);
+                    }
+                }
+
+                // The same file at another path, keep the local mapping
+                for path in conflicts.duplicate_file {
+                    copy_share.mappings.remove(&path);
+                    unresolved.duplicate_file.push(path);
+                }
+            }
             // Reject all mode: reject all shares
             ShareMergeMode::RejectAll => {
                 reject_mode = true; // Only mark as rejected
             }
         }
 
+        // Keep what the audit log needs, persisting consumes the sheet
+        let vault = self.vault_reference;
+        let member = self.holder().cloned().unwrap_or_default();
+        let target = match share.path.as_ref().and_then(|p| p.file_stem()) {
+            Some(share_id) => format!("{}/{}", self.name, share_id.to_string_lossy()),
+            None => self.name.clone(),
+        };
+
         if !reject_mode {
             // Subsequent merging
-            copy_sheet
-                .mapping_mut()
-                .extend(copy_share.mappings.into_iter());
+            copy_sheet.mapping_mut().extend(copy_share.mappings);
 
             // Merge completed
             self.data = copy_sheet; // Write the result
@@ -281,7 +398,22 @@ impl<'a> Sheet<'a> {
                 std::io::ErrorKind::NotFound,
                 format!("Remove share failed: {}", err.1),
             )
-        })
+        })?;
+
+        vault
+            .audit(&member, AuditOperation::MergeShare, target)
+            .await;
+
+        Ok(unresolved)
+    }
+
+    // Get the version number of a mapping in its virtual file, None if the version is unknown
+    async fn mapping_version_num(
+        &self,
+        metadata: &SheetMappingMetadata,
+    ) -> Result<Option<i32>, std::io::Error> {
+        let meta = self.vault_reference.virtual_file_meta(&metadata.id).await?;
+        Ok(meta.version_num(&metadata.version))
     }
 
     // Pre-check whether the share can be imported into the current sheet without conflicts
@@ -296,14 +428,18 @@ impl<'a> Sheet<'a> {
             }
 
             // Check for duplicate IDs
-            if let Some(id_mapping) = self.id_mapping() {
-                if id_mapping.contains_key(&metadata.id) {
-                    conflicts.duplicate_file.push(mapping.clone());
-                    continue;
-                }
+            if let Some(id_mapping) = self.id_mapping()
+                && id_mapping.contains_key(&metadata.id)
+            {
+                conflicts.duplicate_file.push(mapping.clone());
+                continue;
             }
         }
 
+        // Keep the result stable
+        conflicts.duplicate_mapping.sort();
+        conflicts.duplicate_file.sort();
+
         conflicts
     }
 
@@ -315,9 +451,44 @@ impl<'a> Sheet<'a> {
         sharer: &MemberId,
         description: String,
     ) -> Result<Share, std::io::Error> {
+        self.share_mappings_with_seed(other_sheet, mappings, sharer, description, None)
+            .await
+    }
+
+    /// Share mappings with another sheet, optionally deriving the share ID from a seed
+    ///
+    /// When `seed` is provided, the share ID is derived from the seed and the share content,
+    /// so the same seed and content always produce the same ID (useful for tests and CI).
+    /// When `seed` is `None`, a random share ID is generated.
+    pub async fn share_mappings_with_seed(
+        &self,
+        other_sheet: &SheetName,
+        mappings: Vec<PathBuf>,
+        sharer: &MemberId,
+        description: String,
+        seed: Option<&str>,
+    ) -> Result<Share, std::io::Error> {
+        self.vault_reference.check_writable()?;
+
         let other_sheet = snake_case!(other_sheet.clone());
         let sharer = snake_case!(sharer.clone());
 
+        // Sharing to the sheet itself would conflict with every mapping on merge
+        if other_sheet == snake_case!(self.name.clone()) {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot share sheet `{}` to itself!", &other_sheet),
+            ));
+        }
+
+        // An empty share has nothing to merge
+        if mappings.is_empty() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "No mappings to share!",
+            ));
+        }
+
         // Check if the sheet exists
         let sheet_names = self.vault_reference.sheet_names()?;
         if !sheet_names.contains(&other_sheet) {
@@ -327,6 +498,19 @@ impl<'a> Sheet<'a> {
             ));
         }
 
+        // Validate that the share is valid
+        let mut share_mappings = HashMap::new();
+        for mapping_path in &mappings {
+            if let Some(metadata) = self.mapping().get(mapping_path) {
+                share_mappings.insert(mapping_path.clone(), metadata.clone());
+            } else {
+                return Err(Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Mapping `{}` not found in sheet!", mapping_path.display()),
+                ));
+            }
+        }
+
         // Check if the target file exists, regenerate ID if path already exists, up to 20 attempts
         let target_path = {
             let mut id;
@@ -334,7 +518,18 @@ impl<'a> Sheet<'a> {
             let mut attempts = 0;
 
             loop {
-                id = Share::gen_share_id(&sharer);
+                id = match seed {
+                    Some(seed) if attempts == 0 => {
+                        Share::gen_share_id_with_seed(&sharer, seed, &description, &share_mappings)
+                    }
+                    Some(seed) => Share::gen_share_id_with_seed(
+                        &sharer,
+                        &format!("{}#{}", seed, attempts),
+                        &description,
+                        &share_mappings,
+                    ),
+                    None => Share::gen_share_id(&sharer),
+                };
                 share_path = self.vault_reference.share_file_path(&other_sheet, &id);
 
                 if !share_path.exists() {
@@ -351,19 +546,6 @@ impl<'a> Sheet<'a> {
             }
         };
 
-        // Validate that the share is valid
-        let mut share_mappings = HashMap::new();
-        for mapping_path in &mappings {
-            if let Some(metadata) = self.mapping().get(mapping_path) {
-                share_mappings.insert(mapping_path.clone(), metadata.clone());
-            } else {
-                return Err(Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("Mapping `{}` not found in sheet!", mapping_path.display()),
-                ));
-            }
-        }
-
         // Build share data
         let share_data = Share {
             sharer,
@@ -392,6 +574,36 @@ impl Share {
         format!("{}@{}", sharer_snake, random_part)
     }
 
+    /// Generate a deterministic share ID from a seed and the share content
+    ///
+    /// The random part is taken from the hash of the seed, the description and the mappings,
+    /// so the same seed and content always yield the same ID
+    pub fn gen_share_id_with_seed(
+        sharer: &MemberId,
+        seed: &str,
+        description: &str,
+        mappings: &HashMap<SheetPathBuf, SheetMappingMetadata>,
+    ) -> String {
+        let sharer_snake = snake_case!(sharer.clone());
+
+        // Sort mappings to keep the content stable
+        let mut sorted_mappings: Vec<_> = mappings.iter().collect();
+        sorted_mappings.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut content = format!("{}\n{}\n", seed, description);
+        for (path, metadata) in sorted_mappings {
+            content.push_str(&format!(
+                "{}:{}:{}\n",
+                path.display(),
+                metadata.id,
+                metadata.version
+            ));
+        }
+
+        let hash = calc_sha1_string(content);
+        format!("{}@{}", sharer_snake, &hash[..8])
+    }
+
     /// Delete a share (reject or remove the share item)
     /// If deletion succeeds, returns `Ok(())`;
     /// If deletion fails, returns `Err((self, std::io::Error))`, containing the original share object and the error information.
@@ -413,10 +625,7 @@ impl Share {
         match fs::remove_file(path).await {
             Err(err) => Err((
                 self,
-                Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to delete share file: {}", err),
-                ),
+                Error::other(format!("Failed to delete share file: {}", err)),
             )),
             Ok(_) => Ok(()),
         }
@@ -1,18 +1,29 @@
-use std::{collections::HashMap, io::Error};
+use std::{
+    collections::HashMap,
+    io::Error,
+    path::{Path, PathBuf},
+};
 
 use cfg_file::config::ConfigFile;
+use futures::{Stream, StreamExt, stream};
 use string_proc::snake_case;
 use tokio::fs;
 
 use crate::{
-    constants::{SERVER_PATH_SHEETS, SERVER_SUFFIX_SHEET_FILE_NO_DOT},
+    constants::{
+        REF_SHEET_NAME, SERVER_FILE_SHEET_HISTORY, SERVER_PATH_SHARES, SERVER_PATH_SHEET_HISTORY,
+        SERVER_PATH_SHEETS, SERVER_SUFFIX_SHEET_FILE_NO_DOT, VAULT_HOST_NAME,
+    },
     data::{
         member::MemberId,
-        sheet::{Sheet, SheetData, SheetName},
-        vault::Vault,
+        sheet::{Sheet, SheetData, SheetName, lock_sheet_file},
+        vault::{Vault, sheet_share::Share},
     },
 };
 
+const SHEET_NAME: &str = "{sheet_name}";
+const WRITE_COUNT: &str = "{write_count}";
+
 /// Vault Sheets Management
 impl Vault {
     /// Load all sheets in the vault
@@ -33,6 +44,18 @@ impl Vault {
         Ok(sheets)
     }
 
+    /// Load the sheets in the vault one at a time
+    ///
+    /// Only the sheet names are collected up front, each sheet is read when the stream reaches it,
+    /// so large vaults can be processed without holding every sheet in memory
+    pub fn sheets_stream<'a>(
+        &'a self,
+    ) -> Result<impl Stream<Item = Result<Sheet<'a>, std::io::Error>> + 'a, std::io::Error> {
+        let sheet_names = self.sheet_names()?;
+        Ok(stream::iter(sheet_names)
+            .then(move |sheet_name| async move { self.sheet(&sheet_name).await }))
+    }
+
     /// Search for all sheet names in the vault
     ///
     /// The complexity of this operation is proportional to the number of sheets,
@@ -98,9 +121,29 @@ impl Vault {
             name: sheet_name.clone(),
             data,
             vault_reference: self,
+            guard: None,
         })
     }
 
+    /// Read a sheet for a read-modify-write
+    ///
+    /// Works like `sheet`, but the returned sheet holds the lock of the sheet file
+    /// until it is persisted or dropped, so concurrent updates in this process
+    /// wait for each other instead of overwriting each other's changes.
+    pub async fn sheet_for_update<'a>(
+        &'a self,
+        sheet_name: &SheetName,
+    ) -> Result<Sheet<'a>, std::io::Error> {
+        self.check_writable()?;
+
+        let sheet_name = snake_case!(sheet_name.clone());
+        let guard = lock_sheet_file(&Sheet::sheet_path_with_name(self, &sheet_name)).await;
+
+        let mut sheet = self.sheet(&sheet_name).await?;
+        sheet.guard = Some(guard);
+        Ok(sheet)
+    }
+
     /// Create a sheet locally and return the sheet instance
     ///
     /// This method creates a new sheet in the vault with the given name and holder.
@@ -111,8 +154,18 @@ impl Vault {
         sheet_name: &SheetName,
         holder: &MemberId,
     ) -> Result<Sheet<'a>, std::io::Error> {
+        self.check_writable()?;
+
         let sheet_name = snake_case!(sheet_name.clone());
 
+        // Only the vault itself creates the reference sheet
+        if sheet_name == REF_SHEET_NAME && holder != VAULT_HOST_NAME {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Sheet `{}` is reserved!", REF_SHEET_NAME),
+            ));
+        }
+
         // Ensure member exists
         if !self.member_cfg_path(holder).exists() {
             return Err(Error::new(
@@ -143,6 +196,7 @@ impl Vault {
             name: sheet_name,
             data: sheet_data,
             vault_reference: self,
+            guard: None,
         })
     }
 
@@ -161,10 +215,13 @@ impl Vault {
     /// Note: This function is intended for server-side use only and should not be
     /// arbitrarily called by other members to prevent unauthorized data deletion.
     pub async fn delete_sheet(&self, sheet_name: &SheetName) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         let sheet_name = snake_case!(sheet_name.clone());
 
-        // Ensure sheet exists
+        // Ensure sheet exists, waiting for other writers of the sheet
         let sheet_file_path = Sheet::sheet_path_with_name(self, &sheet_name);
+        let _guard = lock_sheet_file(&sheet_file_path).await;
         if !sheet_file_path.exists() {
             return Err(Error::new(
                 std::io::ErrorKind::NotFound,
@@ -175,6 +232,132 @@ impl Vault {
         // Delete the sheet file
         fs::remove_file(sheet_file_path).await?;
 
+        // Delete the shares and snapshots of the sheet
+        for dir_template in [SERVER_PATH_SHARES, SERVER_PATH_SHEET_HISTORY] {
+            let dir = self
+                .vault_path
+                .join(dir_template.replace(SHEET_NAME, &sheet_name));
+            if dir.exists() {
+                fs::remove_dir_all(dir).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rename a sheet
+    ///
+    /// The sheet file, its shares and its snapshots are moved to the new name,
+    /// and shares exported from the sheet are updated to refer to the new name.
+    /// The reference sheet cannot be renamed, and no sheet can be renamed to it.
+    ///
+    /// Note: Local workspaces using the old name must switch to the new sheet.
+    /// This function is intended for server-side use only.
+    pub async fn rename_sheet(
+        &self,
+        old_name: &SheetName,
+        new_name: &SheetName,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        let old_name = snake_case!(old_name.clone());
+        let new_name = snake_case!(new_name.clone());
+
+        // Protect the reference sheet
+        if old_name == REF_SHEET_NAME || new_name == REF_SHEET_NAME {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Sheet `{}` is reserved!", REF_SHEET_NAME),
+            ));
+        }
+
+        // Hold the locks of both sheets, always in the same order
+        let old_path = Sheet::sheet_path_with_name(self, &old_name);
+        let new_path = Sheet::sheet_path_with_name(self, &new_name);
+        let (first, second) = if old_path <= new_path {
+            (&old_path, &new_path)
+        } else {
+            (&new_path, &old_path)
+        };
+        let _first_guard = lock_sheet_file(first).await;
+        let _second_guard = if first != second {
+            Some(lock_sheet_file(second).await)
+        } else {
+            None
+        };
+
+        // Ensure the old sheet exists and the new name is free
+        if !old_path.exists() {
+            return Err(Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Sheet `{}` not found!", &old_name),
+            ));
+        }
+        if new_path.exists() {
+            return Err(Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Sheet `{}` already exists!", &new_name),
+            ));
+        }
+
+        // Move the sheet file, its shares and its snapshots, undoing the moves on failure
+        let mut moves = vec![(old_path, new_path)];
+        for dir_template in [SERVER_PATH_SHARES, SERVER_PATH_SHEET_HISTORY] {
+            let old_dir = self
+                .vault_path
+                .join(dir_template.replace(SHEET_NAME, &old_name));
+            if old_dir.exists() {
+                let new_dir = self
+                    .vault_path
+                    .join(dir_template.replace(SHEET_NAME, &new_name));
+                moves.push((old_dir, new_dir));
+            }
+        }
+        let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for (from, to) in moves {
+            if let Err(e) = move_sheet_entry(&from, &to).await {
+                undo_moves(moved).await;
+                return Err(e);
+            }
+            moved.push((from, to));
+        }
+
+        // Update shares exported from the sheet, restoring them on failure
+        let mut updated = Vec::new();
+        if let Err(e) = self
+            .update_share_sources(&old_name, &new_name, &mut updated)
+            .await
+        {
+            for share_path in updated {
+                if let Ok(mut share) = Share::read_from(&share_path).await {
+                    share.from_sheet = old_name.clone();
+                    let _ = Share::write_to(&share, &share_path).await;
+                }
+            }
+            undo_moves(moved).await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Point the shares exported from `old_name` to `new_name`, collecting the updated share paths
+    async fn update_share_sources(
+        &self,
+        old_name: &SheetName,
+        new_name: &SheetName,
+        updated: &mut Vec<PathBuf>,
+    ) -> Result<(), std::io::Error> {
+        for sheet_name in self.sheet_names()? {
+            for share_path in self.share_file_paths(&sheet_name).await {
+                let mut share = Share::read_from(&share_path).await?;
+                if &share.from_sheet == old_name {
+                    share.from_sheet = new_name.clone();
+                    Share::write_to(&share, &share_path).await?;
+                    updated.push(share_path);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -193,6 +376,8 @@ impl Vault {
     /// Note: This function is intended for server-side use only and should not be
     /// arbitrarily called by other members to prevent unauthorized data deletion.
     pub async fn delete_sheet_safely(&self, sheet_name: &SheetName) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         let sheet_name = snake_case!(sheet_name.clone());
 
         // Ensure the sheet exists
@@ -231,6 +416,8 @@ impl Vault {
     ///
     /// Restore the specified sheet from the trash to its original location, making it accessible normally.
     pub async fn restore_sheet(&self, sheet_name: &SheetName) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         let sheet_name = snake_case!(sheet_name.clone());
 
         // Search for matching files in the trash
@@ -271,4 +458,113 @@ impl Vault {
 
         Ok(())
     }
+
+    /// Get the path of a sheet snapshot taken at the given write count
+    pub fn sheet_snapshot_path(&self, sheet_name: &SheetName, write_count: i32) -> PathBuf {
+        self.vault_path.join(
+            SERVER_FILE_SHEET_HISTORY
+                .replace(SHEET_NAME, sheet_name)
+                .replace(WRITE_COUNT, &write_count.to_string()),
+        )
+    }
+
+    /// List the write counts of all snapshots of a sheet, in ascending order
+    pub fn sheet_snapshots(&self, sheet_name: &SheetName) -> Result<Vec<i32>, std::io::Error> {
+        let sheet_name = snake_case!(sheet_name.clone());
+        let history_dir = self
+            .vault_path
+            .join(SERVER_PATH_SHEET_HISTORY.replace(SHEET_NAME, &sheet_name));
+        if !history_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut write_counts = Vec::new();
+        for entry in std::fs::read_dir(history_dir)? {
+            let path = entry?.path();
+            if path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == SERVER_SUFFIX_SHEET_FILE_NO_DOT)
+                && let Some(write_count) = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<i32>().ok())
+            {
+                write_counts.push(write_count);
+            }
+        }
+        write_counts.sort();
+
+        Ok(write_counts)
+    }
+
+    /// Snapshot the current state of a sheet
+    ///
+    /// The sheet file is copied to the history folder of the sheet,
+    /// keyed by its current write count, which is returned.
+    pub async fn snapshot_sheet(&self, sheet_name: &SheetName) -> Result<i32, std::io::Error> {
+        self.check_writable()?;
+
+        let sheet = self.sheet(sheet_name).await?;
+        let write_count = sheet.write_count();
+
+        let snapshot_path = self.sheet_snapshot_path(sheet.name(), write_count);
+        if let Some(parent) = snapshot_path.parent()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::copy(sheet.sheet_path(), snapshot_path).await?;
+
+        Ok(write_count)
+    }
+
+    /// Roll a sheet back to the snapshot taken at `target_write_count`
+    ///
+    /// The holder and mappings are restored from the snapshot,
+    /// while the write count keeps increasing so members notice the change.
+    /// Virtual file storage is not touched.
+    pub async fn rollback_sheet(
+        &self,
+        sheet_name: &SheetName,
+        target_write_count: i32,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        let mut sheet = self.sheet_for_update(sheet_name).await?;
+
+        let snapshot_path = self.sheet_snapshot_path(sheet.name(), target_write_count);
+        if !snapshot_path.exists() {
+            return Err(Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "Snapshot `{}` of sheet `{}` not found!",
+                    target_write_count,
+                    sheet.name()
+                ),
+            ));
+        }
+
+        let write_count = sheet.write_count();
+        sheet.data = SheetData::read_from(snapshot_path).await?;
+        sheet.data.write_count = write_count;
+        sheet.persist().await
+    }
+}
+
+/// Move a sheet file or directory, creating the parent of the destination
+async fn move_sheet_entry(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if let Some(parent) = to.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::rename(from, to).await
+}
+
+/// Move entries moved by `move_sheet_entry` back, the last move first
+async fn undo_moves(moved: Vec<(PathBuf, PathBuf)>) {
+    for (from, to) in moved.into_iter().rev() {
+        let _ = fs::rename(to, from).await;
+    }
 }
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::fs;
+
+use crate::data::{
+    member::MemberId,
+    sheet::{Sheet, SheetName},
+    vault::{
+        Vault,
+        audit::AuditOperation,
+        virtual_file::{
+            ReceivedVersion, VirtualFileId, VirtualFileMeta, VirtualFileVersion,
+            VirtualFileVersionDescription, move_new_instance,
+        },
+    },
+};
+
+/// # Struct - VaultTransaction
+///
+/// Stages a batch of new versions for one sheet, nothing is visible in the vault until `commit`
+///
+/// The sheet is locked from `Vault::transaction` until the transaction is committed or dropped,
+/// a dropped transaction discards the staged versions
+pub struct VaultTransaction<'a> {
+    vault: &'a Vault,
+
+    /// The sheet changed by the batch, holding the lock of the sheet file
+    sheet: Sheet<'a>,
+
+    /// Versions received but not yet stored
+    staged: Vec<StagedVersion>,
+}
+
+/// A received version waiting in the temp directory
+struct StagedVersion {
+    member: MemberId,
+    id: VirtualFileId,
+    received: ReceivedVersion,
+}
+
+impl Vault {
+    /// Begin a batch of changes to the given sheet, the sheet stays locked until the batch ends
+    pub async fn transaction(
+        &self,
+        sheet_name: &SheetName,
+    ) -> Result<VaultTransaction<'_>, std::io::Error> {
+        let sheet = self.sheet_for_update(sheet_name).await?;
+        Ok(VaultTransaction {
+            vault: self,
+            sheet,
+            staged: Vec::new(),
+        })
+    }
+}
+
+impl<'a> VaultTransaction<'a> {
+    /// The sheet changed by the batch
+    pub fn sheet(&self) -> &Sheet<'a> {
+        &self.sheet
+    }
+
+    /// The sheet changed by the batch, persisted on `commit`
+    pub fn sheet_mut(&mut self) -> &mut Sheet<'a> {
+        &mut self.sheet
+    }
+
+    /// Receive a new version of a virtual file from the connection, stored on `commit`
+    ///
+    /// Checked like `Vault::update_virtual_file_from_connection`, a version already staged
+    ///    for the same file counts as taken. With `auto_suffix` a taken name is suffixed
+    ///    like `Vault::update_virtual_file_from_connection_auto_suffix` does.
+    ///
+    /// Returns the version name it will be stored as.
+    pub async fn receive_version(
+        &mut self,
+        instance: &mut ConnectionInstance,
+        member: &MemberId,
+        virtual_file_id: &VirtualFileId,
+        new_version: &VirtualFileVersion,
+        description: VirtualFileVersionDescription,
+        auto_suffix: bool,
+    ) -> Result<VirtualFileVersion, std::io::Error> {
+        let staged = &self.staged;
+        let received = self
+            .vault
+            .receive_version_to_temp(
+                instance,
+                member,
+                virtual_file_id,
+                new_version,
+                description,
+                auto_suffix,
+                |version| {
+                    staged.iter().any(|staged| {
+                        &staged.id == virtual_file_id && &staged.received.version == version
+                    })
+                },
+            )
+            .await?;
+
+        let version = received.version.clone();
+        self.staged.push(StagedVersion {
+            member: member.clone(),
+            id: virtual_file_id.clone(),
+            received,
+        });
+        Ok(version)
+    }
+
+    /// Store the staged versions and persist the sheet
+    ///
+    /// Either everything is stored or nothing: if anything fails, the metadata written
+    ///    so far is restored and the instances moved in place are removed
+    pub async fn commit(mut self) -> Result<(), std::io::Error> {
+        self.vault.check_writable()?;
+
+        let staged = std::mem::take(&mut self.staged);
+
+        // Hold the metadata of every file of the batch until it is stored or restored,
+        // locked in a fixed order
+        let mut ids: Vec<&VirtualFileId> = staged.iter().map(|version| &version.id).collect();
+        ids.sort();
+        ids.dedup();
+        let mut guards = Vec::new();
+        for id in ids {
+            guards.push(self.vault.lock_virtual_file_meta(id).await);
+        }
+
+        let mut stored = Vec::new();
+        let mut backups = Vec::new();
+        let mut result = self.store(&staged, &mut stored, &mut backups).await;
+        if result.is_ok() {
+            result = self.sheet.persist().await;
+        }
+
+        if let Err(e) = result {
+            for (id, meta) in backups {
+                let _ =
+                    VirtualFileMeta::write_to(&meta, self.vault.virtual_file_meta_path(id)).await;
+            }
+            for real_path in stored {
+                let _ = fs::remove_file(real_path).await;
+            }
+            return Err(e);
+        }
+        drop(guards);
+
+        for version in staged.iter() {
+            self.vault
+                .audit(
+                    &version.member,
+                    AuditOperation::UpdateVirtualFile,
+                    &version.id,
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Move the staged instances in place and record them in the metadata
+    ///
+    /// Collects the moved instances and the metadata before its first change, to undo them
+    async fn store<'s>(
+        &self,
+        staged: &'s [StagedVersion],
+        stored: &mut Vec<PathBuf>,
+        backups: &mut Vec<(&'s VirtualFileId, VirtualFileMeta)>,
+    ) -> Result<(), std::io::Error> {
+        // Move every instance in place first, a version taken concurrently fails the batch
+        for version in staged {
+            let real_path = self
+                .vault
+                .virtual_file_real_path(&version.id, &version.received.version);
+            move_new_instance(&version.received.receive_path, &real_path).await?;
+            stored.push(real_path);
+        }
+
+        // Update metadata, re-read to keep concurrent updates of other versions
+        for version in staged {
+            let mut meta = self.vault.virtual_file_meta(&version.id).await?;
+            if !backups.iter().any(|(id, _)| *id == &version.id) {
+                backups.push((&version.id, meta.clone()));
+            }
+            meta.push_version(
+                version.received.version.clone(),
+                version.received.description(),
+                version.received.hash.clone(),
+            );
+            VirtualFileMeta::write_to(&meta, self.vault.virtual_file_meta_path(&version.id))
+                .await?;
+        }
+        Ok(())
+    }
+}
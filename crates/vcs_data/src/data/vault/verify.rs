@@ -0,0 +1,166 @@
+use std::{collections::HashSet, io::Error, path::PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::{
+    constants::SERVER_NAME_VF_META,
+    data::{
+        sheet::{SheetName, SheetPathBuf},
+        vault::{
+            Vault,
+            virtual_file::{VirtualFileId, VirtualFileVersion},
+        },
+    },
+};
+
+/// Extension of virtual file version instances
+const VERSION_INSTANCE_EXTENSION: &str = "rf";
+
+/// Result of `Vault::verify`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VaultVerifyReport {
+    /// Sheet mappings pointing to a virtual file that does not exist
+    pub dangling_mappings: Vec<(SheetName, SheetPathBuf, VirtualFileId)>,
+
+    /// Sheets whose id mapping does not match their mapping
+    pub id_mapping_mismatches: Vec<SheetName>,
+
+    /// Versions in the histories of a virtual file whose instance file is missing
+    pub missing_instances: Vec<(VirtualFileId, VirtualFileVersion)>,
+
+    /// Virtual files whose metadata is unreadable or contradicts itself, with the reason
+    pub inconsistent_meta: Vec<(VirtualFileId, String)>,
+
+    /// Files in the storage not referred to by any virtual file metadata
+    pub orphaned_storage: Vec<PathBuf>,
+}
+
+impl VaultVerifyReport {
+    /// Check if no problem was found
+    pub fn is_clean(&self) -> bool {
+        self.dangling_mappings.is_empty()
+            && self.id_mapping_mismatches.is_empty()
+            && self.missing_instances.is_empty()
+            && self.inconsistent_meta.is_empty()
+            && self.orphaned_storage.is_empty()
+    }
+}
+
+impl Vault {
+    /// Check that the on-disk state of the vault is self-consistent
+    ///
+    /// Nothing is repaired, every problem found is listed in the report.
+    /// Hashes of version instances are not checked, see `verify_and_compact_file`
+    pub async fn verify(&self) -> Result<VaultVerifyReport, std::io::Error> {
+        let mut report = VaultVerifyReport::default();
+        let ids: HashSet<VirtualFileId> = self.virtual_file_ids()?.into_iter().collect();
+
+        // Sheets
+        for sheet in self.sheets().await? {
+            let mut mapping: Vec<_> = sheet.mapping().iter().collect();
+            mapping.sort_by(|a, b| a.0.cmp(b.0));
+            for (path, metadata) in mapping {
+                if !ids.contains(&metadata.id) {
+                    report.dangling_mappings.push((
+                        sheet.name().clone(),
+                        path.clone(),
+                        metadata.id.clone(),
+                    ));
+                }
+            }
+
+            if let Some(id_mapping) = sheet.id_mapping() {
+                let matches = id_mapping.len() == sheet.mapping().len()
+                    && id_mapping
+                        .iter()
+                        .all(|(id, path)| sheet.mapping().get(path).is_some_and(|m| &m.id == id));
+                if !matches {
+                    report.id_mapping_mismatches.push(sheet.name().clone());
+                }
+            }
+        }
+
+        // Virtual files
+        let mut known_instances: HashSet<PathBuf> = HashSet::new();
+        let mut sorted_ids: Vec<&VirtualFileId> = ids.iter().collect();
+        sorted_ids.sort();
+        for id in sorted_ids {
+            let meta = match self.virtual_file_meta(id).await {
+                Ok(meta) => meta,
+                Err(e) => {
+                    report
+                        .inconsistent_meta
+                        .push((id.clone(), format!("Unreadable metadata: {}", e)));
+                    continue;
+                }
+            };
+
+            if meta.versions().is_empty() {
+                report
+                    .inconsistent_meta
+                    .push((id.clone(), "Empty histories".to_string()));
+            } else if !meta.version_exists(meta.current_version()) {
+                report.inconsistent_meta.push((
+                    id.clone(),
+                    format!(
+                        "Current version `{}` is not in the histories",
+                        meta.current_version()
+                    ),
+                ));
+            }
+
+            let mut described: Vec<&VirtualFileVersion> = meta
+                .version_descriptions()
+                .keys()
+                .filter(|v| !meta.version_exists(v))
+                .collect();
+            described.sort();
+            for version in described {
+                report.inconsistent_meta.push((
+                    id.clone(),
+                    format!("Description of unknown version `{}`", version),
+                ));
+            }
+
+            let mut checked = HashSet::new();
+            for version in meta.versions() {
+                if !checked.insert(version) {
+                    continue;
+                }
+                let real_path = self.virtual_file_real_path(id, version);
+                if !real_path.exists() {
+                    report.missing_instances.push((id.clone(), version.clone()));
+                }
+                known_instances.insert(real_path);
+            }
+        }
+
+        // Storage
+        let storage_dir = self.virtual_file_storage_dir();
+        if storage_dir.exists() {
+            for entry in WalkDir::new(&storage_dir) {
+                let entry = entry.map_err(Error::other)?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let path = entry.path();
+                let is_meta = entry.file_name() == SERVER_NAME_VF_META;
+                let is_known_meta = is_meta
+                    && path
+                        .parent()
+                        .and_then(|p| p.file_name())
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|id| path == self.virtual_file_meta_path(&id.to_string()));
+                let is_known_instance = path.extension().and_then(|e| e.to_str())
+                    == Some(VERSION_INSTANCE_EXTENSION)
+                    && known_instances.contains(path);
+                if !is_known_meta && !is_known_instance {
+                    report.orphaned_storage.push(path.to_path_buf());
+                }
+            }
+        }
+        report.orphaned_storage.sort();
+
+        Ok(report)
+    }
+}
@@ -1,22 +1,37 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::{Error, ErrorKind},
-    path::PathBuf,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
 };
 
 use cfg_file::{ConfigFile, config::ConfigFile};
+use futures::{StreamExt, stream};
 use serde::{Deserialize, Serialize};
-use string_proc::{dot_case, snake_case};
+use sha1_hash::{calc_sha1_default, calc_sha1_string};
+use string_proc::dot_case;
 use tcp_connection::instance::ConnectionInstance;
-use tokio::fs;
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, BufReader},
+    sync::{Mutex, Semaphore},
+    task::JoinSet,
+};
 use uuid::Uuid;
+use walkdir::WalkDir;
 
 use crate::{
     constants::{
-        SERVER_FILE_VF_META, SERVER_FILE_VF_VERSION_INSTANCE, SERVER_PATH_VF_ROOT,
-        SERVER_PATH_VF_STORAGE, SERVER_PATH_VF_TEMP,
+        SERVER_FILE_VF_META, SERVER_FILE_VF_VERSION_INSTANCE, SERVER_NAME_VF_META,
+        SERVER_PATH_VF_ROOT, SERVER_PATH_VF_STORAGE, SERVER_PATH_VF_TEMP, VAULT_HOST_NAME,
+    },
+    data::{
+        keyed_lock::{KeyedLockGuard, KeyedLocks},
+        member::{MemberId, MemberRole},
+        vault::{Vault, audit::AuditOperation},
     },
-    data::{member::MemberId, vault::Vault},
 };
 
 pub type VirtualFileId = String;
@@ -28,6 +43,24 @@ const ID_INDEX: &str = "{vf_index}";
 const VERSION_PARAM: &str = "{vf_version}";
 const TEMP_NAME: &str = "{temp_name}";
 
+const ACTIVE_HOLDS_CONCURRENCY: usize = 16;
+const EDIT_RIGHTS_BATCH_CONCURRENCY: usize = 16;
+
+/// Virtual files whose metadata changed more recently than this are skipped by `Vault::gc`,
+/// they may have just been created and not yet been mapped into a sheet
+pub const VIRTUAL_FILE_GC_GRACE: Duration = Duration::from_secs(10 * 60);
+
+/// Chunk size used by `Vault::virtual_file_version_diff`
+pub const VIRTUAL_FILE_DIFF_CHUNK_SIZE: usize = 4096;
+
+/// Serializes deletion of virtual file storage
+static STORAGE_DELETE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// In-process locks of virtual file metadata, keyed by metadata path
+///
+/// Held around every read-modify-write of the metadata, so no change is written back stale
+static META_LOCKS: KeyedLocks<PathBuf> = KeyedLocks::new();
+
 pub struct VirtualFile<'a> {
     /// Unique identifier for the virtual file
     id: VirtualFileId,
@@ -53,6 +86,67 @@ pub struct VirtualFileMeta {
     /// Histories
     #[serde(rename = "histories")]
     histories: Vec<VirtualFileVersion>,
+
+    /// SHA1 hash of each version, recorded when the version is received
+    #[serde(rename = "hashes", default)]
+    version_hashes: HashMap<VirtualFileVersion, String>,
+
+    /// Creation time of each version, missing for versions created before it was recorded
+    #[serde(rename = "times", default)]
+    version_created_at: HashMap<VirtualFileVersion, SystemTime>,
+}
+
+/// Result of `Vault::verify_and_compact_file`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VirtualFileCompactReport {
+    /// Versions whose instance matches the recorded hash
+    pub verified: Vec<VirtualFileVersion>,
+
+    /// Versions without a recorded hash, only checked for existence
+    pub unrecorded: Vec<VirtualFileVersion>,
+
+    /// Versions whose instance is missing or does not match the recorded hash
+    pub corrupted: Vec<VirtualFileVersion>,
+
+    /// Versions removed by compaction
+    pub compacted: Vec<VirtualFileVersion>,
+}
+
+impl VirtualFileCompactReport {
+    /// Whether compaction was skipped because corruption was found
+    pub fn compaction_skipped(&self) -> bool {
+        !self.corrupted.is_empty()
+    }
+}
+
+/// Result of `Vault::gc`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VirtualFileGcReport {
+    /// Orphaned virtual files whose storage was removed
+    pub freed: Vec<VirtualFileId>,
+
+    /// Orphaned virtual files kept because they changed within the grace period
+    pub skipped: Vec<VirtualFileId>,
+}
+
+/// Result of `Vault::virtual_file_version_diff`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VirtualFileVersionDiff {
+    /// Size of the compared chunks in bytes
+    pub chunk_size: usize,
+
+    /// Indices of the chunks whose content differs, ascending
+    pub changed_chunks: Vec<usize>,
+
+    /// Changed byte ranges, only filled by `Vault::virtual_file_version_diff_with_ranges`
+    pub changed_ranges: Option<Vec<Range<u64>>>,
+}
+
+impl VirtualFileVersionDiff {
+    /// Check if the two versions have the same content
+    pub fn is_empty(&self) -> bool {
+        self.changed_chunks.is_empty()
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -64,6 +158,10 @@ pub struct VirtualFileVersionDescription {
     /// The description of this version
     #[serde(rename = "desc")]
     pub description: String,
+
+    /// The version name as the creator typed it, if normalizing changed it
+    #[serde(rename = "display", default)]
+    pub display_name: Option<String>,
 }
 
 impl VirtualFileVersionDescription {
@@ -72,7 +170,62 @@ impl VirtualFileVersionDescription {
         Self {
             creator,
             description,
+            display_name: None,
+        }
+    }
+}
+
+/// A new version received into the temp directory by `Vault::receive_version_to_temp`
+///
+/// The received file is removed when dropped, unless it was moved in place
+pub(crate) struct ReceivedVersion {
+    /// Version name as the member typed it
+    typed_version: String,
+
+    /// Normalized version name, before any suffix
+    requested_version: VirtualFileVersion,
+
+    /// Suffix of `version`, 1 if not suffixed
+    suffix: usize,
+
+    /// Version name to store
+    pub(crate) version: VirtualFileVersion,
+
+    description: VirtualFileVersionDescription,
+
+    /// SHA1 hash of the received file
+    pub(crate) hash: String,
+
+    /// Path of the received file in the temp directory
+    pub(crate) receive_path: PathBuf,
+}
+
+impl ReceivedVersion {
+    /// Move on to the next suffixed version name, `1.0.0_2`, `1.0.0_3` and so on
+    pub(crate) fn next_suffix(&mut self) {
+        self.suffix += 1;
+        self.version = format!("{}_{}", self.requested_version, self.suffix);
+    }
+
+    /// Description to store, keeping the name as typed with the same suffix as the stored one
+    pub(crate) fn description(&self) -> VirtualFileVersionDescription {
+        let mut description = self.description.clone();
+        let display_name = format!(
+            "{}{}",
+            self.typed_version,
+            &self.version[self.requested_version.len()..]
+        );
+        if display_name != self.version {
+            description.display_name = Some(display_name);
         }
+        description
+    }
+}
+
+impl Drop for ReceivedVersion {
+    fn drop(&mut self) {
+        // Gone once the version is moved in place
+        let _ = std::fs::remove_file(&self.receive_path);
     }
 }
 
@@ -91,50 +244,31 @@ impl Vault {
     }
 
     /// Get the directory where a specific virtual file is stored
-    pub fn virtual_file_dir(&self, id: &VirtualFileId) -> Result<PathBuf, std::io::Error> {
-        Ok(self.vault_path().join(
+    pub fn virtual_file_dir(&self, id: &VirtualFileId) -> PathBuf {
+        self.vault_path().join(
             SERVER_PATH_VF_STORAGE
                 .replace(ID_PARAM, &id.to_string())
-                .replace(ID_INDEX, &Self::vf_index(id)?),
-        ))
+                .replace(ID_INDEX, &Self::vf_index(id)),
+        )
     }
 
     // Generate index path of virtual file
-    fn vf_index(id: &VirtualFileId) -> Result<String, std::io::Error> {
+    //
+    // Ids whose first hyphen-separated part has at least 4 characters are sharded by them,
+    // the layout existing vaults are stored in.
+    // Any other id, which used to be rejected, by the first 4 hex characters of its SHA1
+    fn vf_index(id: &VirtualFileId) -> String {
         // Remove VF_PREFIX if present
-        let id_str = if let Some(stripped) = id.strip_prefix(VF_PREFIX) {
-            stripped
-        } else {
-            id
-        };
+        let id_str = id.strip_prefix(VF_PREFIX).unwrap_or(id);
 
-        // Extract the first part before the first hyphen
-        let first_part = id_str.split('-').next().ok_or_else(|| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid virtual file ID format: no hyphen found",
-            )
-        })?;
-
-        // Ensure the first part has at least 4 characters
-        if first_part.len() < 4 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "Invalid virtual file ID format: first part must have at least 4 characters",
-            ))?;
-        }
-
-        // Take only the first 4 characters and split into two 2-character chunks
-        let first_four = &first_part[0..4];
-        let mut path = String::new();
-        for i in (0..first_four.len()).step_by(2) {
-            if i > 0 {
-                path.push('/');
-            }
-            path.push_str(&first_four[i..i + 2]);
-        }
+        let first_part = id_str.split('-').next().unwrap_or_default();
+        let shard = match first_part.get(0..4) {
+            Some(first_four) if first_four.is_char_boundary(2) => first_four.to_string(),
+            _ => calc_sha1_string(id)[0..4].to_string(),
+        };
 
-        Ok(path)
+        // Split into two 2-character levels
+        format!("{}/{}", &shard[0..2], &shard[2..4])
     }
 
     /// Get the directory where a specific virtual file's metadata is stored
@@ -146,24 +280,103 @@ impl Vault {
         self.vault_path().join(
             SERVER_FILE_VF_VERSION_INSTANCE
                 .replace(ID_PARAM, &id.to_string())
-                .replace(ID_INDEX, &Self::vf_index(id).unwrap_or_default())
+                .replace(ID_INDEX, &Self::vf_index(id))
                 .replace(VERSION_PARAM, &version.to_string()),
         )
     }
 
+    /// Get the real path of a specific version of a virtual file, ensuring the version exists
+    pub async fn virtual_file_version_path(
+        &self,
+        id: &VirtualFileId,
+        version: &VirtualFileVersion,
+    ) -> Result<PathBuf, std::io::Error> {
+        let meta = self.virtual_file_meta(id).await?;
+        let real_path = self.virtual_file_real_path(id, version);
+        if !meta.version_exists(version) || !real_path.exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Version `{}` of virtual file `{}` not found!", version, id),
+            ));
+        }
+        Ok(real_path)
+    }
+
     /// Get the directory where a specific virtual file's metadata is stored
     pub fn virtual_file_meta_path(&self, id: &VirtualFileId) -> PathBuf {
         self.vault_path().join(
             SERVER_FILE_VF_META
                 .replace(ID_PARAM, &id.to_string())
-                .replace(ID_INDEX, &Self::vf_index(id).unwrap_or_default()),
+                .replace(ID_INDEX, &Self::vf_index(id)),
         )
     }
 
+    /// List the IDs of all virtual files in the vault
+    ///
+    /// The complexity of this operation is proportional to the number of virtual files
+    pub fn virtual_file_ids(&self) -> Result<Vec<VirtualFileId>, std::io::Error> {
+        let storage_dir = self.virtual_file_storage_dir();
+        if !storage_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids = Vec::new();
+        for entry in WalkDir::new(storage_dir) {
+            let entry = entry.map_err(Error::other)?;
+            if entry.file_type().is_file()
+                && entry.file_name() == SERVER_NAME_VF_META
+                && let Some(id) = entry
+                    .path()
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .and_then(|n| n.to_str())
+            {
+                ids.push(id.to_string());
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// List all virtual files currently held and the member holding them
+    ///
+    /// Metadata files are read concurrently, at most `ACTIVE_HOLDS_CONCURRENCY` at a time.
+    /// The third element is the expiry time of the hold,
+    /// holds currently have no lease and never expire, so it is always `None`.
+    pub async fn active_holds(
+        &self,
+    ) -> Result<Vec<(VirtualFileId, MemberId, Option<SystemTime>)>, std::io::Error> {
+        let semaphore = Arc::new(Semaphore::new(ACTIVE_HOLDS_CONCURRENCY));
+        let mut tasks = JoinSet::new();
+
+        for id in self.virtual_file_ids()? {
+            let meta_path = self.virtual_file_meta_path(&id);
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.map_err(Error::other)?;
+                let meta = VirtualFileMeta::read_from(meta_path).await?;
+                Ok::<_, std::io::Error>((id, meta.hold_member))
+            });
+        }
+
+        let mut holds = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            let (id, holder) = result.map_err(Error::other)??;
+            if !holder.is_empty() {
+                holds.push((id, holder, None));
+            }
+        }
+
+        // Keep the result stable
+        holds.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(holds)
+    }
+
     /// Get the virtual file with the given ID
     pub fn virtual_file(&self, id: &VirtualFileId) -> Result<VirtualFile<'_>, std::io::Error> {
         let dir = self.virtual_file_dir(id);
-        if dir?.exists() {
+        if dir.exists() {
             Ok(VirtualFile {
                 id: id.clone(),
                 current_vault: self,
@@ -176,6 +389,36 @@ impl Vault {
         }
     }
 
+    /// Calculate the SHA1 hash of a specific version of a virtual file
+    pub async fn virtual_file_hash(
+        &self,
+        id: &VirtualFileId,
+        version: &VirtualFileVersion,
+    ) -> Result<String, std::io::Error> {
+        let real_path = self.virtual_file_real_path(id, version);
+        if !real_path.exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Version `{}` of virtual file `{}` not found!", version, id),
+            ));
+        }
+        let result = calc_sha1_default(real_path).await.map_err(Error::other)?;
+        Ok(result.hash)
+    }
+
+    /// Normalize a version name the way it is stored
+    ///
+    /// With `VaultConfig::normalize_version_names` (the default), names are converted to dot case,
+    /// so `1.0.0`, `1-0-0` and `1_0_0` all name the same version.
+    /// Otherwise the name is only trimmed
+    pub fn normalize_version_name(&self, version: &str) -> VirtualFileVersion {
+        if self.config().normalize_version_names() {
+            dot_case!(version.to_string())
+        } else {
+            version.trim().to_string()
+        }
+    }
+
     /// Get the meta data of the virtual file with the given ID
     pub async fn virtual_file_meta(
         &self,
@@ -186,12 +429,24 @@ impl Vault {
         Ok(metadata)
     }
 
+    /// Lock the meta data of the virtual file with the given ID
+    ///
+    /// Hold the guard from reading the meta data until the changed meta data is written
+    pub(crate) async fn lock_virtual_file_meta(
+        &self,
+        id: &VirtualFileId,
+    ) -> KeyedLockGuard<PathBuf> {
+        META_LOCKS.lock(self.virtual_file_meta_path(id)).await
+    }
+
     /// Write the meta data of the virtual file with the given ID
     pub async fn write_virtual_file_meta(
         &self,
         id: &VirtualFileId,
         meta: &VirtualFileMeta,
     ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
         let dir = self.virtual_file_meta_path(id);
         VirtualFileMeta::write_to(meta, dir).await?;
         Ok(())
@@ -211,7 +466,18 @@ impl Vault {
         instance: &mut ConnectionInstance,
         member_id: &MemberId,
     ) -> Result<VirtualFileId, std::io::Error> {
+        self.check_writable()?;
+
         const FIRST_VERSION: &str = "0.1.0";
+
+        // Readers cannot track files
+        if !self.member_role(member_id).await?.can_edit() {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("Member `{}` is a reader and cannot track files", member_id),
+            ));
+        }
+
         let receive_path = self.virtual_file_temp_path();
         let new_id = format!("{}{}", VF_PREFIX, Uuid::new_v4());
         let move_path = self.virtual_file_real_path(&new_id, &FIRST_VERSION.to_string());
@@ -219,15 +485,17 @@ impl Vault {
         match instance.read_file(receive_path.clone()).await {
             Ok(_) => {
                 // Read successful, create virtual file
+                let hash = calc_sha1_default(&receive_path)
+                    .await
+                    .map_err(Error::other)?
+                    .hash;
+
                 // Create default version description
                 let mut version_description =
                     HashMap::<VirtualFileVersion, VirtualFileVersionDescription>::new();
                 version_description.insert(
                     FIRST_VERSION.to_string(),
-                    VirtualFileVersionDescription {
-                        creator: member_id.clone(),
-                        description: "Track".to_string(),
-                    },
+                    VirtualFileVersionDescription::new(member_id.clone(), "Track".to_string()),
                 );
                 // Create metadata
                 let mut meta = VirtualFileMeta {
@@ -235,10 +503,15 @@ impl Vault {
                     hold_member: member_id.clone(), // The holder of the newly created virtual file is the creator by default
                     version_description,
                     histories: Vec::default(),
+                    version_hashes: HashMap::new(),
+                    version_created_at: HashMap::new(),
                 };
 
                 // Add first version
                 meta.histories.push(FIRST_VERSION.to_string());
+                meta.version_hashes.insert(FIRST_VERSION.to_string(), hash);
+                meta.version_created_at
+                    .insert(FIRST_VERSION.to_string(), SystemTime::now());
 
                 // Write metadata to file
                 VirtualFileMeta::write_to(&meta, self.virtual_file_meta_path(&new_id)).await?;
@@ -251,6 +524,9 @@ impl Vault {
                 }
                 fs::rename(receive_path, move_path).await?;
 
+                self.audit(member_id, AuditOperation::CreateVirtualFile, &new_id)
+                    .await;
+
                 Ok(new_id)
             }
             Err(e) => {
@@ -264,6 +540,45 @@ impl Vault {
         }
     }
 
+    /// Create a virtual file from a connection instance, at most once per token
+    ///
+    /// Works like `create_virtual_file_from_connection`, but if the member already created
+    /// a virtual file with the same token during this session and it is still at its first version,
+    /// the received file is discarded and the existing virtual file id is returned.
+    ///
+    /// Lets a retried create (e.g. with the content hash of the file as token) avoid duplicates
+    pub async fn create_virtual_file_from_connection_once(
+        &self,
+        instance: &mut ConnectionInstance,
+        member_id: &MemberId,
+        token: &str,
+    ) -> Result<VirtualFileId, std::io::Error> {
+        self.check_writable()?;
+
+        let key = (member_id.clone(), token.to_string());
+        let existing = self.create_tokens.lock().await.get(&key).cloned();
+
+        if let Some(id) = existing
+            && let Ok(meta) = self.virtual_file_meta(&id).await
+            && meta.versions().len() == 1
+        {
+            // The client still sends the file, receive and drop it
+            let receive_path = self.virtual_file_temp_path();
+            let result = instance.read_file(receive_path.clone()).await;
+            if receive_path.exists() {
+                fs::remove_file(receive_path).await?;
+            }
+            result.map_err(Error::other)?;
+            return Ok(id);
+        }
+
+        let id = self
+            .create_virtual_file_from_connection(instance, member_id)
+            .await?;
+        self.create_tokens.lock().await.insert(key, id.clone());
+        Ok(id)
+    }
+
     /// Update a virtual file from a connection instance
     ///
     /// It's the only way to update virtual files!
@@ -277,6 +592,9 @@ impl Vault {
     ///    otherwise the file reception will not be allowed.
     ///
     /// Make sure to obtain the edit right of the file before calling this function.
+    ///
+    /// The version name is stored as `normalize_version_name` returns it,
+    ///    the name as typed is kept in `VirtualFileVersionDescription::display_name`.
     pub async fn update_virtual_file_from_connection(
         &self,
         instance: &mut ConnectionInstance,
@@ -285,52 +603,172 @@ impl Vault {
         new_version: &VirtualFileVersion,
         description: VirtualFileVersionDescription,
     ) -> Result<(), std::io::Error> {
-        let new_version = dot_case!(new_version.clone());
-        let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+        self.receive_new_version(
+            instance,
+            member,
+            virtual_file_id,
+            new_version,
+            description,
+            false,
+        )
+        .await?;
+        Ok(())
+    }
 
-        // Check if the member has edit right
-        self.check_virtual_file_edit_right(member, virtual_file_id)
+    /// Update virtual file from connection, suffixing the version name if it is already taken
+    ///
+    /// Works like `update_virtual_file_from_connection`, but instead of failing with
+    ///    `ErrorKind::AlreadyExists`, `1.0.0` is stored as `1.0.0_2`, `1.0.0_3` and so on,
+    ///    so an upload racing another one of the same version is not wasted.
+    ///
+    /// Returns the version name that was actually used.
+    pub async fn update_virtual_file_from_connection_auto_suffix(
+        &self,
+        instance: &mut ConnectionInstance,
+        member: &MemberId,
+        virtual_file_id: &VirtualFileId,
+        new_version: &VirtualFileVersion,
+        description: VirtualFileVersionDescription,
+    ) -> Result<VirtualFileVersion, std::io::Error> {
+        self.receive_new_version(
+            instance,
+            member,
+            virtual_file_id,
+            new_version,
+            description,
+            true,
+        )
+        .await
+    }
+
+    /// Receive a new version of a virtual file, see `update_virtual_file_from_connection`
+    ///
+    /// With `auto_suffix`, a taken version name is suffixed instead of rejected
+    async fn receive_new_version(
+        &self,
+        instance: &mut ConnectionInstance,
+        member: &MemberId,
+        virtual_file_id: &VirtualFileId,
+        new_version: &VirtualFileVersion,
+        description: VirtualFileVersionDescription,
+        auto_suffix: bool,
+    ) -> Result<VirtualFileVersion, std::io::Error> {
+        let mut received = self
+            .receive_version_to_temp(
+                instance,
+                member,
+                virtual_file_id,
+                new_version,
+                description,
+                auto_suffix,
+                |_| false,
+            )
             .await?;
 
-        // Check if the new version already exists
-        if meta.version_description.contains_key(&new_version) {
-            return Err(Error::new(
-                ErrorKind::AlreadyExists,
-                format!(
-                    "Version `{}` already exists for virtual file `{}`",
-                    new_version, virtual_file_id
-                ),
-            ));
+        // Another upload of the same version may have finished in the meantime
+        loop {
+            let move_path = self.virtual_file_real_path(virtual_file_id, &received.version);
+            match move_new_instance(&received.receive_path, &move_path).await {
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists && auto_suffix => {
+                    received.next_suffix();
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    return Err(Error::new(
+                        ErrorKind::AlreadyExists,
+                        format!(
+                            "Version `{}` of virtual file `{}` was created concurrently",
+                            received.version, virtual_file_id
+                        ),
+                    ));
+                }
+                Err(e) => return Err(e),
+            }
         }
 
-        // Verify success
-        let receive_path = self.virtual_file_temp_path();
-        let move_path = self.virtual_file_real_path(virtual_file_id, &new_version);
+        // Update metadata, re-read to keep concurrent updates of other versions
+        {
+            let _guard = self.lock_virtual_file_meta(virtual_file_id).await;
+            let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+            meta.push_version(
+                received.version.clone(),
+                received.description(),
+                received.hash.clone(),
+            );
+            VirtualFileMeta::write_to(&meta, self.virtual_file_meta_path(virtual_file_id)).await?;
+        }
 
-        match instance.read_file(receive_path.clone()).await {
-            Ok(_) => {
-                // Read success, move temp file to real path.
-                fs::rename(receive_path, move_path).await?;
+        self.audit(member, AuditOperation::UpdateVirtualFile, virtual_file_id)
+            .await;
 
-                // Update metadata
-                meta.current_version = new_version.clone();
-                meta.version_description
-                    .insert(new_version.clone(), description);
-                meta.histories.push(new_version);
-                VirtualFileMeta::write_to(&meta, self.virtual_file_meta_path(virtual_file_id))
-                    .await?;
+        Ok(received.version.clone())
+    }
 
-                Ok(())
-            }
-            Err(e) => {
-                // Read failed, remove temp file.
-                if receive_path.exists() {
-                    fs::remove_file(receive_path).await?;
-                }
+    /// Check a new version of a virtual file and receive it into the temp directory
+    ///
+    /// The checks of every update: the member must hold the file
+    ///    and the version name must be neither stored nor `taken` by the caller,
+    ///    with `auto_suffix` the name is suffixed until it is free instead of rejected.
+    ///
+    /// The received version is not stored, it is removed when dropped unless moved in place.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn receive_version_to_temp(
+        &self,
+        instance: &mut ConnectionInstance,
+        member: &MemberId,
+        virtual_file_id: &VirtualFileId,
+        new_version: &VirtualFileVersion,
+        description: VirtualFileVersionDescription,
+        auto_suffix: bool,
+        taken: impl Fn(&VirtualFileVersion) -> bool,
+    ) -> Result<ReceivedVersion, std::io::Error> {
+        self.check_writable()?;
 
-                Err(Error::other(e))
+        let requested_version = self.normalize_version_name(new_version);
+        let meta = self.virtual_file_meta(virtual_file_id).await?;
+
+        // Check if the member has edit right
+        self.check_virtual_file_edit_right(member, virtual_file_id)
+            .await?;
+
+        let mut received = ReceivedVersion {
+            typed_version: new_version.trim().to_string(),
+            version: requested_version.clone(),
+            requested_version,
+            suffix: 1,
+            description,
+            hash: String::default(),
+            receive_path: self.virtual_file_temp_path(),
+        };
+
+        // Check if the new version already exists
+        while meta.version_exists(&received.version)
+            || meta.version_description.contains_key(&received.version)
+            || taken(&received.version)
+        {
+            if !auto_suffix {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!(
+                        "Version `{}` already exists for virtual file `{}`",
+                        received.version, virtual_file_id
+                    ),
+                ));
             }
+            received.next_suffix();
         }
+
+        // Receive into the temp directory, removed by the drop of `received` on failure
+        instance
+            .read_file(received.receive_path.clone())
+            .await
+            .map_err(Error::other)?;
+        received.hash = calc_sha1_default(&received.receive_path)
+            .await
+            .map_err(Error::other)?
+            .hash;
+
+        Ok(received)
     }
 
     /// Update virtual file from existing version
@@ -344,7 +782,10 @@ impl Vault {
         virtual_file_id: &VirtualFileId,
         old_version: &VirtualFileVersion,
     ) -> Result<(), std::io::Error> {
-        let old_version = snake_case!(old_version.clone());
+        self.check_writable()?;
+
+        let old_version = self.normalize_version_name(old_version);
+        let meta_guard = self.lock_virtual_file_meta(virtual_file_id).await;
         let mut meta = self.virtual_file_meta(virtual_file_id).await?;
 
         // Check if the member has edit right
@@ -371,28 +812,470 @@ impl Vault {
         meta.current_version = old_version.clone();
         meta.histories.push(old_version);
         VirtualFileMeta::write_to(&meta, self.virtual_file_meta_path(virtual_file_id)).await?;
+        drop(meta_guard);
+
+        self.audit(member, AuditOperation::UpdateVirtualFile, virtual_file_id)
+            .await;
 
         Ok(())
     }
 
+    /// Rewrite the description of an existing version, the creator stays unchanged
+    ///
+    /// Only the creator of the version or the current holder of the file may amend it,
+    /// other members get PermissionDenied. A released file can only be amended by the creator
+    pub async fn amend_version_description(
+        &self,
+        virtual_file_id: &VirtualFileId,
+        version: &VirtualFileVersion,
+        new_description: String,
+        requesting_member: &MemberId,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        {
+            // The holder must not change between the check and the write
+            let _guard = self.lock_virtual_file_meta(virtual_file_id).await;
+            let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+            let holder = meta.hold_member.clone();
+            let Some(description) = meta.version_description.get_mut(version) else {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Version `{}` not found!", version),
+                ));
+            };
+
+            // An unheld file has no holder to amend it
+            let is_holder = !holder.is_empty() && holder == *requesting_member;
+            if description.creator != *requesting_member && !is_holder {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!(
+                        "Member `{}` neither created version `{}` nor holds virtual file `{}`",
+                        requesting_member, version, virtual_file_id
+                    ),
+                ));
+            }
+
+            description.description = new_description;
+            self.write_virtual_file_meta(virtual_file_id, &meta).await?;
+        }
+
+        self.audit(
+            requesting_member,
+            AuditOperation::AmendVersionDescription,
+            virtual_file_id,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Compare the instances of two versions of a virtual file chunk by chunk
+    ///
+    /// Chunks are compared byte for byte, a chunk past the end of the shorter version counts as changed
+    pub async fn virtual_file_version_diff(
+        &self,
+        id: &VirtualFileId,
+        from: &VirtualFileVersion,
+        to: &VirtualFileVersion,
+    ) -> Result<VirtualFileVersionDiff, std::io::Error> {
+        self.diff_virtual_file_versions(id, from, to, false).await
+    }
+
+    /// Compare two versions of a virtual file like `virtual_file_version_diff`,
+    /// also reporting the changed byte ranges
+    pub async fn virtual_file_version_diff_with_ranges(
+        &self,
+        id: &VirtualFileId,
+        from: &VirtualFileVersion,
+        to: &VirtualFileVersion,
+    ) -> Result<VirtualFileVersionDiff, std::io::Error> {
+        self.diff_virtual_file_versions(id, from, to, true).await
+    }
+
+    async fn diff_virtual_file_versions(
+        &self,
+        id: &VirtualFileId,
+        from: &VirtualFileVersion,
+        to: &VirtualFileVersion,
+        with_ranges: bool,
+    ) -> Result<VirtualFileVersionDiff, std::io::Error> {
+        let chunk_size = VIRTUAL_FILE_DIFF_CHUNK_SIZE;
+        let from_path = self.virtual_file_version_path(id, from).await?;
+        let to_path = self.virtual_file_version_path(id, to).await?;
+        let mut from_reader = BufReader::new(fs::File::open(from_path).await?);
+        let mut to_reader = BufReader::new(fs::File::open(to_path).await?);
+
+        let mut diff = VirtualFileVersionDiff {
+            chunk_size,
+            changed_chunks: Vec::new(),
+            changed_ranges: with_ranges.then(Vec::new),
+        };
+        let mut from_chunk = vec![0u8; chunk_size];
+        let mut to_chunk = vec![0u8; chunk_size];
+
+        for index in 0usize.. {
+            let from_len = read_chunk(&mut from_reader, &mut from_chunk).await?;
+            let to_len = read_chunk(&mut to_reader, &mut to_chunk).await?;
+            if from_len == 0 && to_len == 0 {
+                break;
+            }
+
+            if from_chunk[..from_len] == to_chunk[..to_len] {
+                continue;
+            }
+            diff.changed_chunks.push(index);
+
+            // Collect the differing bytes, merging neighbours into ranges
+            let Some(ranges) = diff.changed_ranges.as_mut() else {
+                continue;
+            };
+            let chunk_start = (index * chunk_size) as u64;
+            for i in 0..from_len.max(to_len) {
+                if i < from_len && i < to_len && from_chunk[i] == to_chunk[i] {
+                    continue;
+                }
+                let offset = chunk_start + i as u64;
+                match ranges.last_mut() {
+                    Some(last) if last.end == offset => last.end += 1,
+                    _ => ranges.push(offset..offset + 1),
+                }
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// Verify every version of a virtual file, then compact its old versions
+    ///
+    /// Each version in the history is checked against its recorded hash.
+    /// Only if all versions pass, the versions older than the last `keep_last` distinct versions
+    /// are pruned with `prune_virtual_file_versions`, so the current version
+    /// and versions pinned by a sheet mapping or pending share are always kept.
+    ///
+    /// If any version is missing or corrupted, nothing is removed
+    /// and the failing versions are listed in the report.
+    pub async fn verify_and_compact_file(
+        &self,
+        id: &VirtualFileId,
+        keep_last: usize,
+    ) -> Result<VirtualFileCompactReport, std::io::Error> {
+        self.check_writable()?;
+
+        let meta = self.virtual_file_meta(id).await?;
+        let mut report = VirtualFileCompactReport::default();
+
+        // Distinct versions, from oldest to newest by their last appearance
+        let mut versions: Vec<VirtualFileVersion> = Vec::new();
+        for version in meta.histories.iter() {
+            versions.retain(|v| v != version);
+            versions.push(version.clone());
+        }
+
+        // Verify
+        for version in versions.iter() {
+            let Ok(hash) = self.virtual_file_hash(id, version).await else {
+                report.corrupted.push(version.clone());
+                continue;
+            };
+            match meta.version_hashes.get(version) {
+                Some(recorded) if recorded == &hash => report.verified.push(version.clone()),
+                Some(_) => report.corrupted.push(version.clone()),
+                None => report.unrecorded.push(version.clone()),
+            }
+        }
+
+        // Abort compaction when corruption is found
+        if report.compaction_skipped() {
+            return Ok(report);
+        }
+
+        // Compact
+        report.compacted = self
+            .prune_virtual_file_versions(id, keep_last.max(1))
+            .await?;
+
+        Ok(report)
+    }
+
+    /// Collect the IDs of all virtual files mapped by any sheet or pending share
+    pub async fn referenced_virtual_file_ids(
+        &self,
+    ) -> Result<HashSet<VirtualFileId>, std::io::Error> {
+        let mut ids = HashSet::new();
+        for sheet in self.sheets().await? {
+            ids.extend(sheet.mapping().values().map(|m| m.id.clone()));
+            if let Some(id_mapping) = sheet.id_mapping() {
+                ids.extend(id_mapping.keys().cloned());
+            }
+            for share in sheet.get_shares().await? {
+                ids.extend(share.mappings.values().map(|m| m.id.clone()));
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Collect the versions of a virtual file pinned by any sheet mapping or pending share
+    pub async fn pinned_virtual_file_versions(
+        &self,
+        id: &VirtualFileId,
+    ) -> Result<HashSet<VirtualFileVersion>, std::io::Error> {
+        let mut versions = HashSet::new();
+        for sheet in self.sheets().await? {
+            let shares = sheet.get_shares().await?;
+            let mappings = sheet
+                .mapping()
+                .values()
+                .chain(shares.iter().flat_map(|share| share.mappings.values()));
+            for mapping in mappings {
+                if &mapping.id == id {
+                    versions.insert(mapping.version.clone());
+                }
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Delete the oldest version instances of a virtual file
+    ///
+    /// The current version, the most recent `keep_latest` distinct versions
+    /// and every version pinned by a sheet mapping or pending share are kept.
+    /// Removed versions are dropped from the histories and descriptions before
+    /// their instance files are deleted. Returns the removed versions, oldest first.
+    pub async fn prune_virtual_file_versions(
+        &self,
+        id: &VirtualFileId,
+        keep_latest: usize,
+    ) -> Result<Vec<VirtualFileVersion>, std::io::Error> {
+        self.check_writable()?;
+
+        let _guard = STORAGE_DELETE_LOCK.lock().await;
+        let meta_guard = self.lock_virtual_file_meta(id).await;
+
+        let mut meta = self.virtual_file_meta(id).await?;
+        let pinned = self.pinned_virtual_file_versions(id).await?;
+
+        // Distinct versions, from oldest to newest by their last appearance
+        let mut versions: Vec<VirtualFileVersion> = Vec::new();
+        for version in meta.histories.iter() {
+            versions.retain(|v| v != version);
+            versions.push(version.clone());
+        }
+
+        let remove_count = versions.len().saturating_sub(keep_latest);
+        let removed: Vec<VirtualFileVersion> = versions
+            .into_iter()
+            .take(remove_count)
+            .filter(|v| v != &meta.current_version && !pinned.contains(v))
+            .collect();
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+
+        meta.histories.retain(|v| !removed.contains(v));
+        for version in removed.iter() {
+            meta.version_description.remove(version);
+            meta.version_hashes.remove(version);
+            meta.version_created_at.remove(version);
+        }
+        self.write_virtual_file_meta(id, &meta).await?;
+        drop(meta_guard);
+
+        // Remove instances only after the metadata no longer refers to them
+        for version in removed.iter() {
+            let real_path = self.virtual_file_real_path(id, version);
+            if real_path.exists() {
+                fs::remove_file(real_path).await?;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Delete a virtual file, removing its storage directory and metadata
+    ///
+    /// Refuses to delete a virtual file that is still mapped by any sheet or pending share.
+    pub async fn delete_virtual_file(&self, id: &VirtualFileId) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        let _guard = STORAGE_DELETE_LOCK.lock().await;
+
+        let dir = self.virtual_file_dir(id);
+        if !dir.exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Virtual file `{}` not found!", id),
+            ));
+        }
+
+        if self.referenced_virtual_file_ids().await?.contains(id) {
+            return Err(Error::new(
+                ErrorKind::ResourceBusy,
+                format!("Virtual file `{}` is still mapped by a sheet", id),
+            ));
+        }
+
+        fs::remove_dir_all(dir).await?;
+        Ok(())
+    }
+
+    /// Remove the storage of all virtual files no longer mapped by any sheet or pending share
+    ///
+    /// Virtual files changed within `VIRTUAL_FILE_GC_GRACE` are kept,
+    /// see `gc_with_grace` to use a different grace period.
+    pub async fn gc(&self) -> Result<VirtualFileGcReport, std::io::Error> {
+        self.gc_with_grace(VIRTUAL_FILE_GC_GRACE).await
+    }
+
+    /// Remove the storage of all orphaned virtual files
+    /// whose metadata has not changed within `grace`
+    ///
+    /// Only one deletion runs at a time, and references are collected
+    /// after acquiring the lock, so a file mapped in the meantime is not removed.
+    pub async fn gc_with_grace(
+        &self,
+        grace: Duration,
+    ) -> Result<VirtualFileGcReport, std::io::Error> {
+        self.check_writable()?;
+
+        let _guard = STORAGE_DELETE_LOCK.lock().await;
+
+        let referenced = self.referenced_virtual_file_ids().await?;
+        let now = SystemTime::now();
+        let mut report = VirtualFileGcReport::default();
+
+        for id in self.virtual_file_ids()? {
+            if referenced.contains(&id) {
+                continue;
+            }
+
+            let modified = fs::metadata(self.virtual_file_meta_path(&id))
+                .await?
+                .modified()?;
+            let age = now.duration_since(modified).unwrap_or_default();
+            if age < grace {
+                report.skipped.push(id);
+                continue;
+            }
+
+            fs::remove_dir_all(self.virtual_file_dir(&id)).await?;
+            report.freed.push(id);
+        }
+
+        // Keep the result stable
+        report.freed.sort();
+        report.skipped.sort();
+
+        Ok(report)
+    }
+
     /// Grant a member the edit right for a virtual file
     /// This operation takes effect immediately upon success
+    ///
+    /// Readers can never hold a file
     pub async fn grant_virtual_file_edit_right(
         &self,
         member_id: &MemberId,
         virtual_file_id: &VirtualFileId,
     ) -> Result<(), std::io::Error> {
-        let mut meta = self.virtual_file_meta(virtual_file_id).await?;
-        meta.hold_member = member_id.clone();
-        self.write_virtual_file_meta(virtual_file_id, &meta).await
+        self.check_writable()?;
+
+        if !self.member_role(member_id).await?.can_edit() {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("Member `{}` is a reader and cannot hold files", member_id),
+            ));
+        }
+
+        {
+            let _guard = self.lock_virtual_file_meta(virtual_file_id).await;
+            let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+            meta.hold_member = member_id.clone();
+            self.write_virtual_file_meta(virtual_file_id, &meta).await?;
+        }
+
+        self.audit(member_id, AuditOperation::GrantEditRight, virtual_file_id)
+            .await;
+        Ok(())
+    }
+
+    /// Hand the edit right for a virtual file from its holder to another member
+    ///
+    /// The holder is checked and replaced in a single read-modify-write,
+    /// so no other member can take the file in between.
+    /// Fails with PermissionDenied if `from_member` does not hold the file
+    /// or `to_member` is a reader
+    pub async fn transfer_edit_right(
+        &self,
+        from_member: &MemberId,
+        to_member: &MemberId,
+        virtual_file_id: &VirtualFileId,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        if !self.member_role(to_member).await?.can_edit() {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("Member `{}` is a reader and cannot hold files", to_member),
+            ));
+        }
+
+        {
+            let _guard = self.lock_virtual_file_meta(virtual_file_id).await;
+            let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+            if meta.hold_member != *from_member {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!(
+                        "Member `{}` does not hold virtual file `{}`",
+                        from_member, virtual_file_id
+                    ),
+                ));
+            }
+            meta.hold_member = to_member.clone();
+            self.write_virtual_file_meta(virtual_file_id, &meta).await?;
+        }
+
+        self.audit(
+            from_member,
+            AuditOperation::TransferEditRight,
+            virtual_file_id,
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Grant a member the edit right for several virtual files
+    ///
+    /// Files are processed concurrently, at most `EDIT_RIGHTS_BATCH_CONCURRENCY` at a time.
+    /// Each file is updated on its own, the result of every id is returned in input order
+    pub async fn grant_edit_rights(
+        &self,
+        member_id: &MemberId,
+        virtual_file_ids: &[VirtualFileId],
+    ) -> Vec<(VirtualFileId, Result<(), std::io::Error>)> {
+        stream::iter(virtual_file_ids)
+            .map(|id| async move {
+                let result = self.grant_virtual_file_edit_right(member_id, id).await;
+                (id.clone(), result)
+            })
+            .buffered(EDIT_RIGHTS_BATCH_CONCURRENCY)
+            .collect()
+            .await
     }
 
     /// Check if a member has the edit right for a virtual file
+    ///
+    /// The member must hold the file, and readers never have the edit right
     pub async fn has_virtual_file_edit_right(
         &self,
         member_id: &MemberId,
         virtual_file_id: &VirtualFileId,
     ) -> Result<bool, std::io::Error> {
+        if !self.member_role(member_id).await?.can_edit() {
+            return Ok(false);
+        }
+
         let meta = self.virtual_file_meta(virtual_file_id).await?;
         Ok(meta.hold_member.eq(member_id))
     }
@@ -421,13 +1304,81 @@ impl Vault {
 
     /// Revoke the edit right for a virtual file from the current holder
     /// This operation takes effect immediately upon success
+    ///
+    /// The audit log records it as done by the host
     pub async fn revoke_virtual_file_edit_right(
         &self,
         virtual_file_id: &VirtualFileId,
     ) -> Result<(), std::io::Error> {
-        let mut meta = self.virtual_file_meta(virtual_file_id).await?;
-        meta.hold_member = String::default();
-        self.write_virtual_file_meta(virtual_file_id, &meta).await
+        self.check_writable()?;
+
+        self.clear_virtual_file_hold(&VAULT_HOST_NAME.to_string(), virtual_file_id)
+            .await
+    }
+
+    /// Revoke the edit right for several virtual files on behalf of a member
+    ///
+    /// Same rules as `revoke_virtual_file_edit_right_as` for each file,
+    /// the result of every id is returned in input order
+    pub async fn revoke_edit_rights(
+        &self,
+        member_id: &MemberId,
+        virtual_file_ids: &[VirtualFileId],
+    ) -> Vec<(VirtualFileId, Result<(), std::io::Error>)> {
+        stream::iter(virtual_file_ids)
+            .map(|id| async move {
+                let result = self.revoke_virtual_file_edit_right_as(member_id, id).await;
+                (id.clone(), result)
+            })
+            .buffered(EDIT_RIGHTS_BATCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    // Clear the holder of a virtual file on behalf of a member
+    async fn clear_virtual_file_hold(
+        &self,
+        member_id: &MemberId,
+        virtual_file_id: &VirtualFileId,
+    ) -> Result<(), std::io::Error> {
+        {
+            let _guard = self.lock_virtual_file_meta(virtual_file_id).await;
+            let mut meta = self.virtual_file_meta(virtual_file_id).await?;
+            meta.hold_member = String::default();
+            self.write_virtual_file_meta(virtual_file_id, &meta).await?;
+        }
+
+        self.audit(member_id, AuditOperation::RevokeEditRight, virtual_file_id)
+            .await;
+        Ok(())
+    }
+
+    /// Revoke the edit right for a virtual file on behalf of a member
+    ///
+    /// Allowed for the current holder and for admins, who can revoke any hold
+    pub async fn revoke_virtual_file_edit_right_as(
+        &self,
+        member_id: &MemberId,
+        virtual_file_id: &VirtualFileId,
+    ) -> Result<(), std::io::Error> {
+        self.check_writable()?;
+
+        let is_admin = self.member_role(member_id).await? == MemberRole::Admin;
+        if !is_admin
+            && !self
+                .has_virtual_file_edit_right(member_id, virtual_file_id)
+                .await?
+        {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!(
+                    "Member `{}` not allowed to revoke the edit right of virtual file `{}`",
+                    member_id, virtual_file_id
+                ),
+            ));
+        }
+        self.clear_virtual_file_hold(member_id, virtual_file_id)
+            .await
     }
 }
 
@@ -444,6 +1395,27 @@ impl<'a> VirtualFile<'a> {
 }
 
 impl VirtualFileMeta {
+    /// Record a newly received version as the current one
+    pub(crate) fn push_version(
+        &mut self,
+        version: VirtualFileVersion,
+        description: VirtualFileVersionDescription,
+        hash: String,
+    ) {
+        self.current_version = version.clone();
+        self.version_description
+            .insert(version.clone(), description);
+        self.version_hashes.insert(version.clone(), hash);
+        self.version_created_at
+            .insert(version.clone(), SystemTime::now());
+        self.histories.push(version);
+    }
+
+    /// Get the current version of the virtual file
+    pub fn current_version(&self) -> &VirtualFileVersion {
+        &self.current_version
+    }
+
     /// Get all versions of the virtual file
     pub fn versions(&self) -> &Vec<VirtualFileVersion> {
         &self.histories
@@ -483,6 +1455,18 @@ impl VirtualFileMeta {
         self.histories.get(version_num as usize).cloned()
     }
 
+    /// Get the recorded SHA1 hash of a given version
+    /// Returns None if no hash was recorded for the version
+    pub fn version_hash(&self, version: &VirtualFileVersion) -> Option<&String> {
+        self.version_hashes.get(version)
+    }
+
+    /// Get the creation time of a given version
+    /// Returns None if no time was recorded for the version
+    pub fn version_created_at(&self, version: &VirtualFileVersion) -> Option<SystemTime> {
+        self.version_created_at.get(version).copied()
+    }
+
     /// Get the member who holds the edit right of the file
     pub fn hold_member(&self) -> &MemberId {
         &self.hold_member
@@ -495,6 +1479,12 @@ impl VirtualFileMeta {
         &self.version_description
     }
 
+    /// Get the name of a version as its creator typed it, falls back to the stored name
+    pub fn version_display_name(&self, version: &VirtualFileVersion) -> Option<String> {
+        let desc = self.version_description.get(version)?;
+        Some(desc.display_name.clone().unwrap_or_else(|| version.clone()))
+    }
+
     /// Get the version description for a given version
     pub fn version_description(
         &self,
@@ -504,3 +1494,41 @@ impl VirtualFileMeta {
         desc.get(&version)
     }
 }
+
+/// Move a received file to the real path of a new version instance, never overwriting
+///
+/// Fails with `ErrorKind::AlreadyExists` if the instance already exists,
+/// the received file is then kept so it can be moved to another version
+pub(crate) async fn move_new_instance(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    if let Some(parent) = to.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent).await?;
+    }
+
+    // Linking fails atomically if the destination exists
+    match fs::hard_link(from, to).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => return Err(e),
+        Err(_) if to.exists() => return Err(Error::from(ErrorKind::AlreadyExists)),
+        Err(_) => return fs::rename(from, to).await,
+    };
+    let _ = fs::remove_file(from).await;
+    Ok(())
+}
+
+/// Fill the buffer from the reader, returns fewer bytes only at the end of the input
+async fn read_chunk(
+    reader: &mut (impl AsyncRead + Unpin),
+    buffer: &mut [u8],
+) -> Result<usize, std::io::Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let n = reader.read(&mut buffer[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
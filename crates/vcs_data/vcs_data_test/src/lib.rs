@@ -17,6 +17,105 @@ pub mod test_sheet_creation_management_and_persistence;
 #[cfg(test)]
 pub mod test_sheet_share_creation_and_management;
 
+#[cfg(test)]
+pub mod test_virtual_file_active_holds;
+
+#[cfg(test)]
+pub mod test_virtual_file_verify_and_compact;
+
+#[cfg(test)]
+pub mod test_vault_uuid_round_trip;
+
+#[cfg(test)]
+pub mod test_workspace_analyzer;
+
+#[cfg(test)]
+pub mod test_virtual_file_gc;
+
+#[cfg(test)]
+pub mod test_virtual_file_prune;
+
+#[cfg(test)]
+pub mod test_member_roles;
+
+#[cfg(test)]
+pub mod test_vault_audit;
+
+#[cfg(test)]
+pub mod test_virtual_file_diff;
+
+#[cfg(test)]
+pub mod test_virtual_file_index;
+
+#[cfg(test)]
+pub mod test_find_members;
+
+#[cfg(test)]
+pub mod test_virtual_file_batch_edit_rights;
+
+#[cfg(test)]
+pub mod test_virtual_file_transfer_edit_right;
+
+#[cfg(test)]
+pub mod test_local_sheet_erased;
+
+#[cfg(test)]
+pub mod test_vault_verify;
+
+#[cfg(test)]
+pub mod test_local_sheet_modified;
+
+#[cfg(test)]
+pub mod test_virtual_file_created_at;
+
+#[cfg(test)]
+pub mod test_virtual_file_create_once;
+
+#[cfg(test)]
+pub mod test_virtual_file_concurrent_update;
+
+#[cfg(test)]
+pub mod test_vault_read_only;
+
+#[cfg(test)]
+pub mod test_vault_sheets_stream;
+
+#[cfg(test)]
+pub mod test_sheet_diff;
+
+#[cfg(test)]
+pub mod test_ref_sheet_guard;
+
+#[cfg(test)]
+pub mod test_vault_backup;
+
+#[cfg(test)]
+pub mod test_cached_sheet_apply_diff;
+
+#[cfg(test)]
+pub mod test_local_sheet_tree_hash;
+
+#[cfg(test)]
+pub mod test_virtual_file_auto_suffix;
+
+#[cfg(test)]
+pub mod test_local_sheet_validate_moves;
+
+#[cfg(test)]
+pub mod test_local_files_patterns;
+
+#[cfg(test)]
+pub mod test_workspace_ignore;
+
+#[cfg(test)]
+pub mod test_virtual_file_amend_description;
+
+#[cfg(test)]
+pub mod test_virtual_file_version_names;
+
+#[cfg(test)]
+pub mod test_vault_transaction_rollback;
+
 pub async fn get_test_dir(area: &str) -> Result<PathBuf, std::io::Error> {
     let dir = current_dir()?.join(".temp").join("test").join(area);
     if !dir.exists() {
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::data::{local::cached_sheet::CachedSheet, sheet::SheetData};
+
+use tokio::fs;
+
+use crate::get_test_dir;
+
+/// Build sheet data with the given write count through its on-disk format
+async fn sheet_data(
+    dir: &Path,
+    write_count: i32,
+    mappings: &[(&str, &str, &str)],
+) -> Result<SheetData, std::io::Error> {
+    let mut content = format!("v = {}\n", write_count);
+    content.push_str("[map]\n");
+    for (path, id, version) in mappings {
+        content.push_str(&format!(
+            "\"{}\" = {{ id = \"{}\", ver = \"{}\" }}\n",
+            path, id, version
+        ));
+    }
+    content.push_str("[id_map]\n");
+    for (path, id, _) in mappings {
+        content.push_str(&format!("\"{}\" = \"{}\"\n", id, path));
+    }
+
+    let path = dir.join("source.toml");
+    fs::write(&path, content).await?;
+    SheetData::read_from(&path).await
+}
+
+#[tokio::test]
+async fn test_cached_sheet_apply_single_mapping_diff() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("cached_sheet_apply_diff").await?;
+    let path = dir.join("main.toml");
+
+    let cached = sheet_data(
+        &dir,
+        3,
+        &[
+            ("a.txt", "vf-a", "0.1.0"),
+            ("b.txt", "vf-b", "0.1.0"),
+            ("c/d.txt", "vf-d", "0.1.0"),
+        ],
+    )
+    .await?;
+    SheetData::write_to(&cached, &path).await?;
+
+    // Remote only bumped one file
+    let latest = sheet_data(
+        &dir,
+        4,
+        &[
+            ("a.txt", "vf-a", "0.1.0"),
+            ("b.txt", "vf-b", "0.2.0"),
+            ("c/d.txt", "vf-d", "0.1.0"),
+        ],
+    )
+    .await?;
+    let diff = cached.diff(&latest);
+    assert_eq!(diff.version_changed, vec![PathBuf::from("b.txt")]);
+
+    let patched = CachedSheet::apply_diff_at(&path, cached.clone(), &diff, &latest).await?;
+    let on_disk = SheetData::read_from(&path).await?;
+    assert_eq!(patched.write_count(), 4);
+    assert_eq!(on_disk.write_count(), 4);
+
+    // Only the changed entry differs from the cached sheet
+    let b = &on_disk.mapping()[&PathBuf::from("b.txt")];
+    assert_eq!(b.version, "0.2.0");
+    for path in ["a.txt", "c/d.txt"] {
+        let path = PathBuf::from(path);
+        assert_eq!(on_disk.mapping()[&path], cached.mapping()[&path]);
+    }
+    assert_eq!(on_disk.mapping().len(), 3);
+    assert_eq!(on_disk.id_mapping(), cached.id_mapping());
+    assert!(on_disk.diff(&latest).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_sheet_apply_diff_keeps_id_mapping() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("cached_sheet_apply_diff_ids").await?;
+    let path = dir.join("main.toml");
+
+    let cached = sheet_data(
+        &dir,
+        1,
+        &[("a.txt", "vf-a", "0.1.0"), ("b.txt", "vf-b", "0.1.0")],
+    )
+    .await?;
+    SheetData::write_to(&cached, &path).await?;
+
+    // `b.txt` is gone, `a.txt` moved to `moved/a.txt` and `c.txt` is new
+    let latest = sheet_data(
+        &dir,
+        2,
+        &[("moved/a.txt", "vf-a", "0.1.0"), ("c.txt", "vf-c", "0.1.0")],
+    )
+    .await?;
+    let diff = cached.diff(&latest);
+    CachedSheet::apply_diff_at(&path, cached, &diff, &latest).await?;
+
+    let on_disk = SheetData::read_from(&path).await?;
+    assert!(on_disk.diff(&latest).is_empty());
+    assert_eq!(on_disk.id_mapping(), latest.id_mapping());
+    assert_eq!(on_disk.write_count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_sheet_apply_empty_diff_skips_write() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("cached_sheet_apply_diff_empty").await?;
+    let path = dir.join("main.toml");
+
+    let cached = sheet_data(&dir, 5, &[("a.txt", "vf-a", "0.1.0")]).await?;
+    let latest = sheet_data(&dir, 5, &[("a.txt", "vf-a", "0.1.0")]).await?;
+    let diff = cached.diff(&latest);
+    assert!(diff.is_empty());
+
+    // Nothing changed, the file is not written
+    let patched = CachedSheet::apply_diff_at(&path, cached, &diff, &latest).await?;
+    assert_eq!(patched.write_count(), 5);
+    assert!(!path.exists());
+
+    // A bumped write count alone is still recorded
+    let latest = sheet_data(&dir, 6, &[("a.txt", "vf-a", "0.1.0")]).await?;
+    CachedSheet::apply_diff_at(&path, patched, &diff, &latest).await?;
+    assert_eq!(SheetData::read_from(&path).await?.write_count(), 6);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::io::Error;
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::{SERVER_FILE_VAULT, VAULT_HOST_NAME},
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_find_members() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("find_members").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    // Register members
+    for id in ["alice", "alicia", "albert", "bob"] {
+        vault.register_member_to_vault(Member::new(id)).await?;
+    }
+
+    // Prefix lookups
+    assert_eq!(vault.find_members("ali", false), vec!["alice", "alicia"]);
+    assert_eq!(
+        vault.find_members("al", false),
+        vec!["albert", "alice", "alicia"]
+    );
+    assert_eq!(vault.find_members("bob", false), vec!["bob"]);
+    assert_eq!(vault.find_members("alice", false), vec!["alice"]);
+
+    // Case-insensitive lookups
+    assert!(vault.find_members("ALI", false).is_empty());
+    assert_eq!(vault.find_members("ALI", true), vec!["alice", "alicia"]);
+    assert_eq!(vault.find_members("Bo", true), vec!["bob"]);
+
+    // An empty prefix lists every member, including the host
+    let all = vault.find_members("", false);
+    assert_eq!(all.len(), 5);
+    assert!(all.contains(&VAULT_HOST_NAME.to_string()));
+
+    // No match is not an error
+    assert!(vault.find_members("carol", true).is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use vcs_data::data::local::{LocalWorkspace, local_files::get_relative_paths_by_patterns};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_relative_paths_by_patterns() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("local_files_patterns").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+
+    for file in [
+        "src/main.rs",
+        "src/lib.rs",
+        "src/data/sheet.rs",
+        "src/data/README.md",
+        "assets/src/fake.rs",
+        "build.rs",
+    ] {
+        let path = dir.join(file);
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+        tokio::fs::write(&path, file).await?;
+    }
+
+    let expand = |patterns: &[&str]| -> Result<Vec<PathBuf>, std::io::Error> {
+        let patterns = patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        Ok(get_relative_paths_by_patterns(&dir, &patterns)?
+            .into_iter()
+            .collect())
+    };
+    let paths = |paths: &[&str]| paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+    // `**` crosses directories, only a subset of the files match
+    assert_eq!(
+        expand(&["src/**/*.rs"])?,
+        paths(&["src/data/sheet.rs", "src/lib.rs", "src/main.rs"])
+    );
+
+    // `*` stays within one directory
+    assert_eq!(
+        expand(&["src/*.rs"])?,
+        paths(&["src/lib.rs", "src/main.rs"])
+    );
+
+    // Patterns are combined, overlaps are not duplicated
+    assert_eq!(
+        expand(&["*.rs", "src/*.rs", "src/lib.rs"])?,
+        paths(&["build.rs", "src/lib.rs", "src/main.rs"])
+    );
+
+    // Workspace files are never selected
+    assert!(
+        expand(&["**/*"])?
+            .iter()
+            .all(|path| !path.to_string_lossy().contains(".jv"))
+    );
+    assert!(expand(&["**/*"])?.contains(&PathBuf::from("assets/src/fake.rs")));
+
+    // Nothing matched is not an error, a malformed pattern is
+    assert!(expand(&["docs/**"])?.is_empty());
+    assert_eq!(
+        expand(&["src/[.rs"]).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+
+    Ok(())
+}
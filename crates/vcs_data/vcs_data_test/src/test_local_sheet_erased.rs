@@ -0,0 +1,83 @@
+use std::{io::Error, path::PathBuf};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::CLIENT_FILE_WORKSPACE,
+    data::{
+        local::{LocalWorkspace, config::LocalConfig, local_sheet::LocalMappingMetadata},
+        sheet::{SheetData, SheetMappingMetadata},
+    },
+};
+
+use crate::get_test_dir;
+
+fn mapping(id: &str) -> LocalMappingMetadata {
+    let mut mapping = LocalMappingMetadata::default();
+    mapping.set_mapping_vfid(id.to_string());
+    mapping
+}
+
+#[tokio::test]
+async fn test_local_sheet_erased_cleanup() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("local_sheet_erased").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+    let config = LocalConfig::read_from(dir.join(CLIENT_FILE_WORKSPACE)).await?;
+    let Some(workspace) = LocalWorkspace::init(config, &dir) else {
+        return Err(Error::new(
+            std::io::ErrorKind::NotFound,
+            "Local workspace not found!",
+        ));
+    };
+
+    let member = "erased_member".to_string();
+    let sheet = "erased_sheet".to_string();
+    let kept = PathBuf::from("kept.txt");
+    let unmapped = PathBuf::from("unmapped.txt");
+    let deleted = PathBuf::from("deleted.txt");
+    for path in [&kept, &unmapped, &deleted] {
+        tokio::fs::write(dir.join(path), "content").await?;
+    }
+
+    let mut local_sheet = workspace.local_sheet(&member, &sheet).await?;
+    local_sheet.add_mapping(&kept, mapping("vf_kept"))?;
+    local_sheet.add_mapping(&unmapped, mapping("vf_unmapped"))?;
+    local_sheet.add_mapping(&deleted, mapping("vf_deleted"))?;
+    local_sheet.write().await?;
+
+    // The cached sheet dropped two of the three mappings
+    let mut cached_sheet = SheetData::default();
+    cached_sheet.mapping_mut().insert(
+        kept.clone(),
+        SheetMappingMetadata {
+            id: "vf_kept".to_string(),
+            version: "1.0.0".to_string(),
+        },
+    );
+    let erased = local_sheet.erased_paths(&cached_sheet);
+    assert_eq!(erased.len(), 2);
+    assert!(erased.contains(&unmapped) && erased.contains(&deleted));
+
+    // Only unmap, the file stays on disk
+    let removed = local_sheet.remove_erased([&unmapped], false).await?;
+    assert_eq!(removed, vec![unmapped.clone()]);
+    assert!(dir.join(&unmapped).exists());
+
+    // Unmap and delete
+    let removed = local_sheet.remove_erased([&deleted], true).await?;
+    assert_eq!(removed, vec![deleted.clone()]);
+    assert!(!dir.join(&deleted).exists());
+
+    // Already removed entries are ignored
+    assert!(local_sheet.remove_erased(&erased, true).await?.is_empty());
+    local_sheet.write().await?;
+
+    // The persisted local sheet only keeps the mapping still upstream
+    let local_sheet = workspace.local_sheet(&member, &sheet).await?;
+    assert!(local_sheet.mapping_data(&kept).is_ok());
+    assert!(local_sheet.mapping_data(&unmapped).is_err());
+    assert!(local_sheet.mapping_data(&deleted).is_err());
+    assert!(local_sheet.erased_paths(&cached_sheet).is_empty());
+    assert!(dir.join(&kept).exists());
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::HashSet,
+    io::Error,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use cfg_file::config::ConfigFile;
+use sha1_hash::calc_sha1;
+use vcs_data::{
+    constants::CLIENT_FILE_WORKSPACE,
+    data::local::{LocalWorkspace, config::LocalConfig, local_sheet::LocalMappingMetadata},
+};
+
+use crate::get_test_dir;
+
+async fn mapping_of(file: &Path, id: &str) -> Result<LocalMappingMetadata, std::io::Error> {
+    let hash = calc_sha1(file, 2048).await.map_err(Error::other)?.hash;
+    let metadata = tokio::fs::metadata(file).await?;
+    let mut mapping = LocalMappingMetadata::default();
+    mapping.set_mapping_vfid(id.to_string());
+    mapping.set_hash_when_updated(hash);
+    mapping.set_size_when_updated(metadata.len());
+    mapping.set_last_modifiy_check_time(metadata.modified()?);
+    mapping.set_last_modifiy_check_result(false);
+    Ok(mapping)
+}
+
+#[tokio::test]
+async fn test_local_sheet_is_modified() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("local_sheet_modified").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+    let config = LocalConfig::read_from(dir.join(CLIENT_FILE_WORKSPACE)).await?;
+    let Some(workspace) = LocalWorkspace::init(config, &dir) else {
+        return Err(Error::new(
+            std::io::ErrorKind::NotFound,
+            "Local workspace not found!",
+        ));
+    };
+    let mut local_sheet = workspace
+        .local_sheet(
+            &"modified_member".to_string(),
+            &"modified_sheet".to_string(),
+        )
+        .await?;
+
+    let unchanged = PathBuf::from("unchanged.txt");
+    let touched = PathBuf::from("touched.txt");
+    let changed = PathBuf::from("changed.txt");
+    for path in [&unchanged, &touched, &changed] {
+        tokio::fs::write(dir.join(path), "original").await?;
+        let mapping = mapping_of(&dir.join(path), path.to_str().unwrap()).await?;
+        local_sheet.add_mapping(path, mapping)?;
+    }
+
+    // Unchanged: mtime and size match, the cached result is used without hashing,
+    // so a bogus recorded hash is never noticed
+    local_sheet
+        .mapping_data_mut(&unchanged)?
+        .set_hash_when_updated("not_a_hash".to_string());
+    assert!(!local_sheet.is_modified(&unchanged).await?);
+    assert!(
+        local_sheet
+            .mapping_data(&unchanged)?
+            .last_modifiy_check_hash()
+            .is_none()
+    );
+
+    // Touched but same content: the mtime differs, hashing finds no change
+    local_sheet
+        .mapping_data_mut(&touched)?
+        .set_last_modifiy_check_time(SystemTime::UNIX_EPOCH);
+    assert!(!local_sheet.is_modified(&touched).await?);
+    let touched_data = local_sheet.mapping_data(&touched)?;
+    assert_eq!(
+        touched_data.last_modifiy_check_hash().as_ref(),
+        Some(touched_data.hash_when_updated())
+    );
+    assert_ne!(
+        touched_data.last_modifiy_check_time(),
+        &SystemTime::UNIX_EPOCH
+    );
+
+    // Genuinely changed, with the same size
+    tokio::fs::write(dir.join(&changed), "modified").await?;
+    local_sheet
+        .mapping_data_mut(&changed)?
+        .set_last_modifiy_check_time(SystemTime::UNIX_EPOCH);
+    assert!(local_sheet.is_modified(&changed).await?);
+    assert!(
+        local_sheet
+            .mapping_data(&changed)?
+            .last_modifiy_check_result()
+    );
+
+    // The cached result is reused afterwards
+    assert!(local_sheet.is_modified(&changed).await?);
+
+    // Unmapped paths are an error
+    assert!(
+        local_sheet
+            .is_modified(&PathBuf::from("unmapped.txt"))
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_local_sheet_modified_paths_batch() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("local_sheet_modified_batch").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+    let config = LocalConfig::read_from(dir.join(CLIENT_FILE_WORKSPACE)).await?;
+    let Some(workspace) = LocalWorkspace::init(config, &dir) else {
+        return Err(Error::new(
+            std::io::ErrorKind::NotFound,
+            "Local workspace not found!",
+        ));
+    };
+    let mut local_sheet = workspace
+        .local_sheet(&"batch_member".to_string(), &"batch_sheet".to_string())
+        .await?;
+
+    // Every third file is untouched, the others need hashing and half of those really changed
+    let mut paths = Vec::new();
+    let mut expected = HashSet::new();
+    for i in 0..96 {
+        let path = PathBuf::from(format!("files/{:02}.txt", i));
+        tokio::fs::create_dir_all(dir.join("files")).await?;
+        tokio::fs::write(dir.join(&path), format!("original {:02}", i)).await?;
+        let mapping = mapping_of(&dir.join(&path), &format!("vf-{}", i)).await?;
+        local_sheet.add_mapping(&path, mapping)?;
+
+        if i % 3 != 0 {
+            if i % 2 == 0 {
+                tokio::fs::write(dir.join(&path), format!("changed  {:02}", i)).await?;
+                expected.insert(path.clone());
+            }
+            local_sheet
+                .mapping_data_mut(&path)?
+                .set_last_modifiy_check_time(SystemTime::UNIX_EPOCH);
+        }
+        paths.push(path);
+    }
+    paths.push(PathBuf::from("unmapped.txt"));
+
+    let modified = local_sheet.modified_paths(&paths).await?;
+    assert_eq!(modified, expected);
+
+    for (i, path) in paths.iter().take(96).enumerate() {
+        let data = local_sheet.mapping_data(path)?;
+        if i % 3 == 0 {
+            // Cached results are reused without hashing
+            assert!(data.last_modifiy_check_hash().is_none());
+        } else {
+            // The batch recorded the hash of every candidate
+            let hash = calc_sha1(dir.join(path), 2048)
+                .await
+                .map_err(Error::other)?
+                .hash;
+            assert_eq!(data.last_modifiy_check_hash().as_ref(), Some(&hash));
+            assert_eq!(data.last_modifiy_check_result(), expected.contains(path));
+        }
+
+        // The single file check agrees with the batch
+        assert_eq!(
+            local_sheet.is_modified(path).await?,
+            expected.contains(path)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::{io::Error, path::PathBuf};
+
+use cfg_file::config::ConfigFile;
+use sha1_hash::calc_sha1_default;
+use vcs_data::{
+    constants::CLIENT_FILE_WORKSPACE,
+    data::local::{
+        LocalWorkspace,
+        config::LocalConfig,
+        local_sheet::{LocalMappingMetadata, tree_hash},
+    },
+};
+
+use crate::get_test_dir;
+
+fn entries(pairs: &[(&str, &str)]) -> Vec<(PathBuf, String)> {
+    pairs
+        .iter()
+        .map(|(path, hash)| (PathBuf::from(path), hash.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_tree_hash_is_canonical() {
+    let sorted = entries(&[("a.txt", "h1"), ("b/c.txt", "h2"), ("d.txt", "h3")]);
+    let shuffled = entries(&[("d.txt", "h3"), ("a.txt", "h1"), ("b/c.txt", "h2")]);
+    assert_eq!(tree_hash(sorted.clone()), tree_hash(shuffled));
+
+    // Platform separators do not change the root
+    let windows = entries(&[("a.txt", "h1"), ("b\\c.txt", "h2"), ("d.txt", "h3")]);
+    assert_eq!(tree_hash(sorted.clone()), tree_hash(windows));
+
+    // Any changed hash or path changes the root
+    let changed_hash = entries(&[("a.txt", "h1"), ("b/c.txt", "hX"), ("d.txt", "h3")]);
+    let changed_path = entries(&[("a.txt", "h1"), ("b/e.txt", "h2"), ("d.txt", "h3")]);
+    let swapped = entries(&[("a.txt", "h3"), ("b/c.txt", "h2"), ("d.txt", "h1")]);
+    for other in [changed_hash, changed_path, swapped] {
+        assert_ne!(tree_hash(sorted.clone()), tree_hash(other));
+    }
+
+    // Empty and single-entry trees are still distinct
+    assert_ne!(
+        tree_hash(Vec::new()),
+        tree_hash(entries(&[("a.txt", "h1")]))
+    );
+}
+
+#[tokio::test]
+async fn test_workspace_tree_hash_follows_content() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("local_sheet_tree_hash").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+    let config = LocalConfig::read_from(dir.join(CLIENT_FILE_WORKSPACE)).await?;
+    let Some(workspace) = LocalWorkspace::init(config, &dir) else {
+        return Err(Error::new(
+            std::io::ErrorKind::NotFound,
+            "Local workspace not found!",
+        ));
+    };
+    let mut local_sheet = workspace
+        .local_sheet(&"tree_member".to_string(), &"tree_sheet".to_string())
+        .await?;
+
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        let path = PathBuf::from(name);
+        tokio::fs::write(dir.join(&path), name).await?;
+        let mut mapping = LocalMappingMetadata::default();
+        mapping.set_mapping_vfid(name.to_string());
+        mapping.set_hash_when_updated(
+            calc_sha1_default(dir.join(&path))
+                .await
+                .map_err(Error::other)?
+                .hash,
+        );
+        local_sheet.add_mapping(&path, mapping)?;
+    }
+
+    // Freshly updated files match what was recorded
+    let before = local_sheet.workspace_tree_hash().await?;
+    assert_eq!(before, local_sheet.recorded_tree_hash());
+    assert_eq!(before, local_sheet.workspace_tree_hash().await?);
+
+    // One changed file changes the workspace root, but not the recorded one
+    tokio::fs::write(dir.join("b.txt"), "changed").await?;
+    let after = local_sheet.workspace_tree_hash().await?;
+    assert_ne!(before, after);
+    assert_eq!(before, local_sheet.recorded_tree_hash());
+
+    // Restoring the content restores the root
+    tokio::fs::write(dir.join("b.txt"), "b.txt").await?;
+    assert_eq!(before, local_sheet.workspace_tree_hash().await?);
+
+    Ok(())
+}
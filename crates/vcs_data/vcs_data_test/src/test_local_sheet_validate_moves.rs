@@ -0,0 +1,178 @@
+use std::{io::Error, path::PathBuf};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::CLIENT_FILE_WORKSPACE,
+    data::local::{
+        LocalWorkspace,
+        config::LocalConfig,
+        local_sheet::{LocalMappingMetadata, LocalSheet, MoveConflict},
+    },
+};
+
+use crate::get_test_dir;
+
+fn moves(pairs: &[(&str, &str)]) -> Vec<(PathBuf, PathBuf)> {
+    pairs
+        .iter()
+        .map(|(from, to)| (PathBuf::from(from), PathBuf::from(to)))
+        .collect()
+}
+
+async fn workspace_in(area: &str) -> Result<LocalWorkspace, std::io::Error> {
+    let dir = get_test_dir(area).await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+    let config = LocalConfig::read_from(dir.join(CLIENT_FILE_WORKSPACE)).await?;
+    LocalWorkspace::init(config, &dir).ok_or(Error::new(
+        std::io::ErrorKind::NotFound,
+        "Local workspace not found!",
+    ))
+}
+
+#[tokio::test]
+async fn test_validate_moves_clean_batch() -> Result<(), std::io::Error> {
+    let workspace = workspace_in("local_sheet_validate_moves_clean").await?;
+    let mut local_sheet = workspace
+        .local_sheet(&"moves_member".to_string(), &"moves_sheet".to_string())
+        .await?;
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        let mut mapping = LocalMappingMetadata::default();
+        mapping.set_mapping_vfid(name.to_string());
+        local_sheet.add_mapping(&PathBuf::from(name), mapping)?;
+    }
+
+    // Later moves may use paths freed or taken by earlier ones
+    let batch = moves(&[
+        ("a.txt", "dir/a.txt"),
+        ("b.txt", "a.txt"),
+        ("dir/a.txt", "dir/renamed.txt"),
+    ]);
+    assert!(local_sheet.validate_moves(&batch)?.is_empty());
+
+    // Validation alone changes nothing
+    assert_eq!(
+        local_sheet
+            .mapping_data(&PathBuf::from("a.txt"))?
+            .mapping_vfid(),
+        "a.txt"
+    );
+    assert!(
+        local_sheet
+            .mapping_data(&PathBuf::from("dir/a.txt"))
+            .is_err()
+    );
+
+    // A validated batch applies completely
+    for (from, to) in batch.iter() {
+        local_sheet.move_mapping(from, to)?;
+    }
+    assert_eq!(
+        local_sheet
+            .mapping_data(&PathBuf::from("dir/renamed.txt"))?
+            .mapping_vfid(),
+        "a.txt"
+    );
+    assert_eq!(
+        local_sheet
+            .mapping_data(&PathBuf::from("a.txt"))?
+            .mapping_vfid(),
+        "b.txt"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_validate_moves_destination_collision() -> Result<(), std::io::Error> {
+    let workspace = workspace_in("local_sheet_validate_moves_collision").await?;
+    let mut local_sheet = workspace
+        .local_sheet(&"moves_member".to_string(), &"moves_sheet".to_string())
+        .await?;
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt", "taken.txt"] {
+        let mut mapping = LocalMappingMetadata::default();
+        mapping.set_mapping_vfid(name.to_string());
+        local_sheet.add_mapping(&PathBuf::from(name), mapping)?;
+    }
+
+    // The fifth move collides with an existing mapping
+    let batch = moves(&[
+        ("a.txt", "moved/a.txt"),
+        ("b.txt", "moved/b.txt"),
+        ("c.txt", "moved/c.txt"),
+        ("d.txt", "moved/d.txt"),
+        ("e.txt", "taken.txt"),
+        ("missing.txt", "moved/missing.txt"),
+    ]);
+    let conflicts = local_sheet.validate_moves(&batch)?;
+    assert_eq!(
+        conflicts,
+        vec![
+            MoveConflict::DestinationExists(PathBuf::from("taken.txt")),
+            MoveConflict::SourceNotFound(PathBuf::from("missing.txt")),
+        ]
+    );
+
+    // Nothing was applied
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+        assert_eq!(
+            local_sheet
+                .mapping_data(&PathBuf::from(name))?
+                .mapping_vfid(),
+            name
+        );
+    }
+    assert!(
+        local_sheet
+            .mapping_data(&PathBuf::from("moved/a.txt"))
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_moves_chained_renames() -> Result<(), std::io::Error> {
+    let workspace = workspace_in("local_sheet_order_moves_chained").await?;
+    let mut local_sheet = workspace
+        .local_sheet(&"moves_member".to_string(), &"moves_sheet".to_string())
+        .await?;
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        let mut mapping = LocalMappingMetadata::default();
+        mapping.set_mapping_vfid(name.to_string());
+        local_sheet.add_mapping(&PathBuf::from(name), mapping)?;
+    }
+
+    // `c -> d`, `b -> c`, `a -> b`, whatever order they were collected in
+    let chained = moves(&[("a.txt", "b.txt"), ("b.txt", "c.txt"), ("c.txt", "d.txt")]);
+    let ordered = moves(&[("c.txt", "d.txt"), ("b.txt", "c.txt"), ("a.txt", "b.txt")]);
+    assert!(!local_sheet.validate_moves(&chained)?.is_empty());
+    for batch in [chained.clone(), chained.iter().rev().cloned().collect()] {
+        assert_eq!(LocalSheet::order_moves(batch), ordered);
+    }
+    assert!(local_sheet.validate_moves(&ordered)?.is_empty());
+
+    for (from, to) in ordered.iter() {
+        local_sheet.move_mapping(from, to)?;
+    }
+    for (name, vfid) in [("b.txt", "a.txt"), ("c.txt", "b.txt"), ("d.txt", "c.txt")] {
+        assert_eq!(
+            local_sheet
+                .mapping_data(&PathBuf::from(name))?
+                .mapping_vfid(),
+            vfid
+        );
+    }
+    assert!(local_sheet.mapping_data(&PathBuf::from("a.txt")).is_err());
+
+    // A swap cannot be ordered, it is still reported
+    let swap = LocalSheet::order_moves(moves(&[("b.txt", "c.txt"), ("c.txt", "b.txt")]));
+    assert_eq!(
+        local_sheet.validate_moves(&swap)?,
+        vec![
+            MoveConflict::DestinationExists(PathBuf::from("c.txt")),
+            MoveConflict::DestinationExists(PathBuf::from("b.txt")),
+        ]
+    );
+
+    Ok(())
+}
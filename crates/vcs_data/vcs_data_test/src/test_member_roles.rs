@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::{SERVER_FILE_VAULT, VAULT_HOST_NAME},
+    data::{
+        member::{Member, MemberRole},
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileVersionDescription},
+    },
+};
+
+use crate::get_test_dir;
+
+struct MemberRolesClientHandle;
+struct MemberRolesServerHandle;
+
+impl ClientHandle<MemberRolesServerHandle> for MemberRolesClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("member_roles_client").await.unwrap();
+
+        // Send the file for virtual file creation
+        let file_path = dir.join("file.txt");
+        tokio::fs::write(&file_path, "Content").await.unwrap();
+        instance.write_file(&file_path).await.unwrap();
+    }
+}
+
+impl ServerHandle<MemberRolesClientHandle> for MemberRolesServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("member_roles").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        // Register members, the role is persisted
+        let admin = "admin_member".to_string();
+        let writer = "writer_member".to_string();
+        let other_writer = "other_writer_member".to_string();
+        let reader = "reader_member".to_string();
+        for (id, role) in [
+            (&admin, MemberRole::Admin),
+            (&writer, MemberRole::Writer),
+            (&other_writer, MemberRole::Writer),
+            (&reader, MemberRole::Reader),
+        ] {
+            vault
+                .register_member_to_vault(Member::with_role(id, role))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            vault.member(&reader).await.unwrap().role(),
+            MemberRole::Reader
+        );
+        assert_eq!(vault.member_role(&admin).await.unwrap(), MemberRole::Admin);
+
+        // The host is registered as an admin
+        assert_eq!(
+            vault
+                .member(&VAULT_HOST_NAME.to_string())
+                .await
+                .unwrap()
+                .role(),
+            MemberRole::Admin
+        );
+
+        // The writer tracks a file and holds it
+        let id = vault
+            .create_virtual_file_from_connection(&mut instance, &writer)
+            .await
+            .unwrap();
+
+        // A reader never gets the edit right, so it cannot update the file
+        assert!(
+            vault
+                .grant_virtual_file_edit_right(&reader, &id)
+                .await
+                .is_err()
+        );
+        assert!(
+            !vault
+                .has_virtual_file_edit_right(&reader, &id)
+                .await
+                .unwrap()
+        );
+        let err = vault
+            .update_virtual_file_from_connection(
+                &mut instance,
+                &reader,
+                &id,
+                &"0.2.0".to_string(),
+                VirtualFileVersionDescription::new(reader.clone(), "Update".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+        // Another writer cannot revoke the hold, an admin can
+        assert!(
+            vault
+                .revoke_virtual_file_edit_right_as(&other_writer, &id)
+                .await
+                .is_err()
+        );
+        vault
+            .revoke_virtual_file_edit_right_as(&admin, &id)
+            .await
+            .unwrap();
+        let meta = vault.virtual_file_meta(&id).await.unwrap();
+        assert!(meta.hold_member().is_empty());
+
+        // Demoting a member takes the edit right away
+        vault
+            .grant_virtual_file_edit_right(&writer, &id)
+            .await
+            .unwrap();
+        vault
+            .set_member_role(&writer, MemberRole::Reader)
+            .await
+            .unwrap();
+        assert!(
+            !vault
+                .has_virtual_file_edit_right(&writer, &id)
+                .await
+                .unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_member_roles() -> Result<(), std::io::Error> {
+    let host = "localhost:5042";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<MemberRolesClientHandle, MemberRolesServerHandle>::from_domain(host)
+            .await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<MemberRolesClientHandle, MemberRolesServerHandle>::from_domain(host)
+            .await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::io::{Error, ErrorKind};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::{REF_SHEET_NAME, SERVER_FILE_VAULT},
+    data::{
+        member::{Member, MemberId},
+        sheet::{SheetName, SheetPathBuf},
+        vault::{
+            Vault, config::VaultConfig, sheet_share::ShareMergeMode, virtual_file::VirtualFileId,
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_ref_sheet_guard() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("ref_sheet_guard").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    let member_id: MemberId = "guard_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    // Members cannot create the reference sheet, whatever the spelling
+    for name in ["ref", "Ref", "REF"] {
+        let result = vault.create_sheet(&name.to_string(), &member_id).await;
+        assert_eq!(
+            result.map(|_| ()).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+    assert!(
+        vault
+            .sheet(&REF_SHEET_NAME.to_string())
+            .await?
+            .holder()
+            .is_some()
+    );
+
+    // A normal sheet still works
+    let sheet_name: SheetName = "main".to_string();
+    vault.create_sheet(&sheet_name, &member_id).await?;
+    let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+    let path = SheetPathBuf::from("file.txt");
+    sheet
+        .add_mapping(
+            path.clone(),
+            VirtualFileId::from("guard_file"),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet.persist().await?;
+
+    // Share the mapping into the reference sheet
+    vault
+        .sheet(&sheet_name)
+        .await?
+        .share_mappings(
+            &REF_SHEET_NAME.to_string(),
+            vec![path.clone()],
+            &member_id,
+            "Into ref".to_string(),
+        )
+        .await?;
+    let ref_sheet = vault.sheet(&REF_SHEET_NAME.to_string()).await?;
+    let share = ref_sheet.get_shares().await?.remove(0);
+
+    // Merging into the reference sheet must be allowed explicitly
+    let result = ref_sheet
+        .merge_share(share.clone(), ShareMergeMode::Safe)
+        .await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+    assert!(
+        !vault
+            .sheet(&REF_SHEET_NAME.to_string())
+            .await?
+            .mapping()
+            .contains_key(&path)
+    );
+
+    vault
+        .sheet(&REF_SHEET_NAME.to_string())
+        .await?
+        .merge_share_allow_ref(share, ShareMergeMode::Safe, true)
+        .await?;
+    assert!(
+        vault
+            .sheet(&REF_SHEET_NAME.to_string())
+            .await?
+            .mapping()
+            .contains_key(&path)
+    );
+
+    Ok(())
+}
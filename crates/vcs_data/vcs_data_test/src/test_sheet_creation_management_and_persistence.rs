@@ -1,12 +1,16 @@
-use std::io::Error;
+use std::{io::Error, sync::Arc, time::Duration};
 
 use cfg_file::config::ConfigFile;
 use vcs_data::{
     constants::{SERVER_FILE_SHEET, SERVER_FILE_VAULT},
     data::{
         member::{Member, MemberId},
-        sheet::SheetName,
-        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileId},
+        sheet::{RemoveMappingResult, SheetName, SheetPathBuf},
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
     },
 };
 
@@ -96,10 +100,55 @@ async fn test_sheet_creation_management_and_persistence() -> Result<(), std::io:
 
     // Test 5: Remove mapping entry
     let mut sheet_for_removal = vault.sheet(&sheet_name).await?;
-    let _removed_virtual_file_id = sheet_for_removal.remove_mapping(&mapping_path).await;
-    // Don't check the return value since it depends on virtual file existence
+    // The virtual file doesn't exist in the vault
+    let result = sheet_for_removal.remove_mapping(&mapping_path).await;
+    assert!(matches!(result, RemoveMappingResult::RemovedOrphan));
     assert_eq!(sheet_for_removal.mapping().len(), 2);
 
+    // Virtual files held by the holder and by nobody
+    let held_id = VirtualFileId::from("held_id");
+    let unheld_id = VirtualFileId::from("unheld_id");
+    for (id, holder) in [(&held_id, member_id.as_str()), (&unheld_id, "")] {
+        let meta_source = dir.join(format!("{}.toml", id));
+        tokio::fs::write(
+            &meta_source,
+            format!(
+                "ver = \"1.0.0\"\nholder = \"{}\"\nhistories = [\"1.0.0\"]\n[descs]\n",
+                holder
+            ),
+        )
+        .await?;
+        let meta = VirtualFileMeta::read_from(&meta_source).await?;
+        vault.write_virtual_file_meta(id, &meta).await?;
+    }
+
+    let held_path = SheetPathBuf::from("held.txt");
+    let unheld_path = SheetPathBuf::from("unheld.txt");
+    sheet_for_removal
+        .add_mapping(held_path.clone(), held_id.clone(), "1.0.0".to_string())
+        .await?;
+    sheet_for_removal
+        .add_mapping(unheld_path.clone(), unheld_id.clone(), "1.0.0".to_string())
+        .await?;
+
+    // A held file keeps its mapping
+    let result = sheet_for_removal.remove_mapping(&held_path).await;
+    assert!(matches!(result, RemoveMappingResult::NotRemovedHeld));
+    assert!(!result.removed());
+    assert!(sheet_for_removal.mapping().contains_key(&held_path));
+
+    // An unheld file is released
+    let result = sheet_for_removal.remove_mapping(&unheld_path).await;
+    match result {
+        RemoveMappingResult::RemovedReleased(metadata) => assert_eq!(metadata.id, unheld_id),
+        other => panic!("Expected RemovedReleased, got {:?}", other),
+    }
+    assert!(!sheet_for_removal.mapping().contains_key(&unheld_path));
+
+    // A missing mapping is reported as an error
+    let result = sheet_for_removal.remove_mapping(&unheld_path).await;
+    assert!(matches!(result, RemoveMappingResult::Error(_)));
+
     // Test 6: List all sheets in vault
     let sheet_names = vault.sheet_names()?;
     assert_eq!(sheet_names.len(), 2);
@@ -273,3 +322,340 @@ async fn test_sheet_data_serialization() -> Result<(), std::io::Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_sheet_path_limits() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("sheet_path_limits").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+
+    // Get vault with custom path limits
+    let mut config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    config.set_sheet_path_max_length(Some(64));
+    config.set_sheet_path_max_depth(Some(4));
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    // Add a member to use as sheet holder
+    let member_id: MemberId = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let sheet_name: SheetName = "test_sheet".to_string();
+    let mut sheet = vault.create_sheet(&sheet_name, &member_id).await?;
+
+    // Test 1: Path within limits is accepted
+    let normal_path = vcs_data::data::sheet::SheetPathBuf::from("src/main.rs");
+    sheet
+        .add_mapping(
+            normal_path.clone(),
+            VirtualFileId::new(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    assert!(sheet.mapping().contains_key(&normal_path));
+
+    // Test 2: Over-long path is rejected
+    let long_path = vcs_data::data::sheet::SheetPathBuf::from(format!("{}.txt", "a".repeat(80)));
+    let result = sheet
+        .add_mapping(long_path.clone(), VirtualFileId::new(), "1.0.0".to_string())
+        .await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::InvalidInput)
+    );
+    assert!(!sheet.mapping().contains_key(&long_path));
+
+    // Test 3: Over-deep path is rejected
+    let deep_path = vcs_data::data::sheet::SheetPathBuf::from("a/b/c/d/e/f.txt");
+    let result = sheet
+        .add_mapping(deep_path.clone(), VirtualFileId::new(), "1.0.0".to_string())
+        .await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::InvalidInput)
+    );
+    assert!(!sheet.mapping().contains_key(&deep_path));
+
+    // Clean up
+    vault.remove_member_from_vault(&member_id)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sheet_move_subtree() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("sheet_move_subtree").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    let member_id: MemberId = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let sheet_name: SheetName = "test_sheet".to_string();
+    let mut sheet = vault.create_sheet(&sheet_name, &member_id).await?;
+
+    // Files under `src/old`, plus neighbours which must not move
+    let moved_files = ["a.rs", "b.rs", "nested/c.rs"];
+    for (i, file) in moved_files.iter().enumerate() {
+        sheet
+            .add_mapping(
+                SheetPathBuf::from("src/old").join(file),
+                format!("vf-old-{}", i),
+                format!("{}.0.0", i),
+            )
+            .await?;
+    }
+    sheet
+        .add_mapping(
+            SheetPathBuf::from("src/older/d.rs"),
+            "vf-older".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet
+        .add_mapping(
+            SheetPathBuf::from("src/new/b.rs"),
+            "vf-conflict".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+
+    // Test 1: Fails when a target already exists, nothing changes
+    let before = sheet.mapping().clone();
+    let result = sheet.move_subtree(&"src/old".into(), &"src/new".into());
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::AlreadyExists)
+    );
+    assert_eq!(sheet.mapping(), &before);
+
+    // Test 2: Fails when nothing is under the prefix
+    let result = sheet.move_subtree(&"src/missing".into(), &"src/new".into());
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::NotFound)
+    );
+
+    // Test 3: Move to a free prefix, ids and versions are preserved
+    sheet
+        .mapping_mut()
+        .remove(&SheetPathBuf::from("src/new/b.rs"));
+    let count = sheet.move_subtree(&"src/old".into(), &"src/new".into())?;
+    assert_eq!(count, moved_files.len());
+
+    for (i, file) in moved_files.iter().enumerate() {
+        let old_path = SheetPathBuf::from("src/old").join(file);
+        let new_path = SheetPathBuf::from("src/new").join(file);
+        assert!(!sheet.mapping().contains_key(&old_path));
+        let meta = sheet.mapping().get(&new_path).unwrap();
+        assert_eq!(meta.id, format!("vf-old-{}", i));
+        assert_eq!(meta.version, format!("{}.0.0", i));
+    }
+
+    // `src/older` only shares a string prefix, it stays in place
+    assert!(
+        sheet
+            .mapping()
+            .contains_key(&SheetPathBuf::from("src/older/d.rs"))
+    );
+
+    // Persist and reload
+    sheet.persist().await?;
+    let reloaded = vault.sheet(&sheet_name).await?;
+    assert_eq!(reloaded.mapping().len(), moved_files.len() + 1);
+    assert_eq!(
+        reloaded
+            .mapping()
+            .get(&SheetPathBuf::from("src/new/nested/c.rs"))
+            .map(|m| m.id.clone()),
+        Some("vf-old-2".to_string())
+    );
+
+    // Clean up
+    vault.remove_member_from_vault(&member_id)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sheet_snapshot_and_rollback() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("sheet_snapshot_rollback").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    let member_id: MemberId = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let sheet_name: SheetName = "test_sheet".to_string();
+    vault.create_sheet(&sheet_name, &member_id).await?;
+
+    // Mutate, then snapshot
+    let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+    sheet
+        .add_mapping(
+            SheetPathBuf::from("scenes/level.scn"),
+            "vf-level".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet.persist().await?;
+
+    let snapshot = vault.snapshot_sheet(&sheet_name).await?;
+    assert_eq!(snapshot, 1);
+    assert_eq!(vault.sheet_snapshots(&sheet_name)?, vec![snapshot]);
+    let snapshot_mapping = vault.sheet(&sheet_name).await?.mapping().clone();
+
+    // Mutate again, like a bad merge
+    let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+    sheet
+        .mapping_mut()
+        .remove(&SheetPathBuf::from("scenes/level.scn"));
+    sheet
+        .add_mapping(
+            SheetPathBuf::from("scenes/broken.scn"),
+            "vf-broken".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet.persist().await?;
+    assert_ne!(vault.sheet(&sheet_name).await?.mapping(), &snapshot_mapping);
+
+    // Roll back
+    vault.rollback_sheet(&sheet_name, snapshot).await?;
+    let sheet = vault.sheet(&sheet_name).await?;
+    assert_eq!(sheet.mapping(), &snapshot_mapping);
+    assert_eq!(sheet.holder(), Some(&member_id));
+    assert_eq!(
+        sheet.id_mapping().as_ref().unwrap().get("vf-level"),
+        Some(&SheetPathBuf::from("scenes/level.scn"))
+    );
+
+    // The write count keeps increasing so clients refresh their cache
+    assert_eq!(sheet.write_count(), 3);
+
+    // Unknown snapshots cannot be restored
+    let result = vault.rollback_sheet(&sheet_name, 42).await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::NotFound)
+    );
+
+    // Snapshots do not show up as sheets
+    assert!(!vault.sheet_names()?.iter().any(|name| name == "history"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sheet_concurrent_updates() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("sheet_concurrent_updates").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+    let vault = Arc::new(vault);
+
+    let member_id: MemberId = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let sheet_name: SheetName = "test_sheet".to_string();
+    vault.create_sheet(&sheet_name, &member_id).await?;
+
+    // Two tasks each add a distinct mapping, pausing between read and write
+    let mut tasks = Vec::new();
+    for i in 0..2 {
+        let vault = vault.clone();
+        let sheet_name = sheet_name.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+            sheet
+                .add_mapping(
+                    SheetPathBuf::from(format!("task_{}.txt", i)),
+                    format!("vf-task-{}", i),
+                    "1.0.0".to_string(),
+                )
+                .await?;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            sheet.persist().await
+        }));
+    }
+    for task in tasks {
+        task.await.map_err(Error::other)??;
+    }
+
+    let sheet = vault.sheet(&sheet_name).await?;
+    assert_eq!(sheet.mapping().len(), 2);
+    assert!(
+        sheet
+            .mapping()
+            .contains_key(&SheetPathBuf::from("task_0.txt"))
+    );
+    assert!(
+        sheet
+            .mapping()
+            .contains_key(&SheetPathBuf::from("task_1.txt"))
+    );
+    assert_eq!(sheet.write_count(), 2);
+
+    // A sheet read without the lock does not overwrite a change made since it was read
+    let mut stale = vault.sheet(&sheet_name).await?;
+    let mut sheet = vault.sheet_for_update(&sheet_name).await?;
+    sheet
+        .add_mapping(
+            SheetPathBuf::from("locked.txt"),
+            "vf-locked".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet.persist().await?;
+    stale
+        .add_mapping(
+            SheetPathBuf::from("stale.txt"),
+            "vf-stale".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    assert!(stale.persist().await.is_err());
+
+    let sheet = vault.sheet(&sheet_name).await?;
+    assert_eq!(sheet.write_count(), 3);
+    assert!(
+        sheet
+            .mapping()
+            .contains_key(&SheetPathBuf::from("locked.txt"))
+    );
+    assert!(
+        !sheet
+            .mapping()
+            .contains_key(&SheetPathBuf::from("stale.txt"))
+    );
+
+    // Unchanged since it was read, it persists
+    vault.sheet(&sheet_name).await?.persist().await?;
+    assert_eq!(vault.sheet(&sheet_name).await?.write_count(), 4);
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use vcs_data::data::sheet::{SheetData, SheetDiff, SheetMappingMetadata};
+
+fn sheet_data(mappings: &[(&str, &str, &str)]) -> SheetData {
+    let mut data = SheetData::default();
+    for (path, id, version) in mappings {
+        data.mapping_mut().insert(
+            PathBuf::from(path),
+            SheetMappingMetadata {
+                id: id.to_string(),
+                version: version.to_string(),
+            },
+        );
+    }
+    data
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn test_sheet_diff_identical() {
+    let data = sheet_data(&[("a.txt", "vf-a", "0.1.0"), ("b.txt", "vf-b", "0.1.0")]);
+    let diff = data.diff(&data.clone());
+    assert!(diff.is_empty());
+    assert_eq!(diff, SheetDiff::default());
+}
+
+#[test]
+fn test_sheet_diff_additions_and_removals() {
+    let old = sheet_data(&[("a.txt", "vf-a", "0.1.0"), ("b.txt", "vf-b", "0.1.0")]);
+    let new = sheet_data(&[
+        ("a.txt", "vf-a", "0.1.0"),
+        ("d/c.txt", "vf-c", "0.1.0"),
+        ("c.txt", "vf-d", "0.1.0"),
+    ]);
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, paths(&["c.txt", "d/c.txt"]));
+    assert_eq!(diff.removed, paths(&["b.txt"]));
+    assert!(diff.version_changed.is_empty());
+
+    // The reverse direction swaps additions and removals
+    let reverse = new.diff(&old);
+    assert_eq!(reverse.added, diff.removed);
+    assert_eq!(reverse.removed, diff.added);
+}
+
+#[test]
+fn test_sheet_diff_version_bumps() {
+    let old = sheet_data(&[
+        ("a.txt", "vf-a", "0.1.0"),
+        ("b.txt", "vf-b", "0.1.0"),
+        ("c.txt", "vf-c", "0.1.0"),
+    ]);
+    let new = sheet_data(&[
+        ("a.txt", "vf-a", "0.2.0"),
+        ("b.txt", "vf-b", "0.1.0"),
+        ("c.txt", "vf-other", "0.1.0"),
+    ]);
+
+    let diff = old.diff(&new);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.version_changed, paths(&["a.txt", "c.txt"]));
+    assert!(!diff.is_empty());
+}
@@ -1,16 +1,16 @@
-use std::io::Error;
+use std::{collections::HashMap, io::Error, time::Duration};
 
 use cfg_file::config::ConfigFile;
 use vcs_data::{
-    constants::SERVER_FILE_VAULT,
+    constants::{SERVER_FILE_VAULT, SERVER_PATH_SHEET_HISTORY},
     data::{
         member::{Member, MemberId},
-        sheet::{SheetName, SheetPathBuf},
+        sheet::{SheetMappingMetadata, SheetName, SheetPathBuf},
         vault::{
             Vault,
             config::VaultConfig,
-            sheet_share::{Share, ShareMergeMode, SheetShareId},
-            virtual_file::VirtualFileId,
+            sheet_share::{Share, ShareMergeConflict, ShareMergeMode, SheetShareId},
+            virtual_file::{VirtualFileId, VirtualFileMeta},
         },
     },
 };
@@ -295,6 +295,12 @@ async fn test_share_merge_conflicts() -> Result<(), std::io::Error> {
         .await;
 
     assert!(result.is_err(), "Safe mode should fail with conflicts");
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    let conflicts =
+        ShareMergeConflict::from_error(&err).expect("Safe mode error should carry the conflicts");
+    assert_eq!(conflicts.duplicate_mapping, vec![conflicting_path.clone()]);
+    assert!(conflicts.duplicate_file.is_empty());
 
     // Test 6: Overwrite mode merge with conflict (should succeed)
     let target_sheet_clone = vault.sheet(&target_sheet_name).await?;
@@ -592,6 +598,39 @@ async fn test_share_error_conditions() -> Result<(), std::io::Error> {
 
     assert!(result.is_err());
 
+    // Test 10.1: Share to the sheet itself should fail
+    let result = source_sheet
+        .share_mappings(
+            &"Source".to_string(),
+            vec![file_path.clone()],
+            &sharer_id,
+            "Test".to_string(),
+        )
+        .await;
+
+    assert_eq!(
+        result.map(|_| ()).unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+
+    // Test 10.2: Share without mappings should fail and write no share
+    let result = source_sheet
+        .share_mappings(&target_sheet_name, vec![], &sharer_id, "Test".to_string())
+        .await;
+
+    assert_eq!(
+        result.map(|_| ()).unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    assert!(
+        vault
+            .sheet(&target_sheet_name)
+            .await?
+            .get_shares()
+            .await?
+            .is_empty()
+    );
+
     // Test 11: Merge non-existent share should fail
     let target_sheet = vault.sheet(&target_sheet_name).await?;
     let non_existent_share_id: SheetShareId = "non_existent_share".to_string();
@@ -623,9 +662,348 @@ async fn test_share_id_generation() -> Result<(), std::io::Error> {
     assert_ne!(id2, id3);
 
     // IDs should start with sharer name
-    assert!(id1.starts_with(&format!("test_sharer@")));
-    assert!(id2.starts_with(&format!("test_sharer@")));
-    assert!(id3.starts_with(&format!("test_sharer@")));
+    assert!(id1.starts_with("test_sharer@"));
+    assert!(id2.starts_with("test_sharer@"));
+    assert!(id3.starts_with("test_sharer@"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_share_id_generation_with_seed() -> Result<(), std::io::Error> {
+    let sharer_id: MemberId = "test_sharer".to_string();
+    let description = "Seeded share".to_string();
+
+    let mut mappings = HashMap::new();
+    mappings.insert(
+        SheetPathBuf::from("src/main.rs"),
+        SheetMappingMetadata {
+            id: "vf-0001".to_string(),
+            version: "1.0.0".to_string(),
+        },
+    );
+
+    // Same seed and content yield the same ID
+    let id1 = Share::gen_share_id_with_seed(&sharer_id, "ci", &description, &mappings);
+    let id2 = Share::gen_share_id_with_seed(&sharer_id, "ci", &description, &mappings);
+    assert_eq!(id1, id2);
+    assert!(id1.starts_with("test_sharer@"));
+
+    // Different content yields a different ID
+    let mut other_mappings = mappings.clone();
+    other_mappings.insert(
+        SheetPathBuf::from("src/lib.rs"),
+        SheetMappingMetadata {
+            id: "vf-0002".to_string(),
+            version: "1.0.0".to_string(),
+        },
+    );
+    let id3 = Share::gen_share_id_with_seed(&sharer_id, "ci", &description, &other_mappings);
+    assert_ne!(id1, id3);
+
+    // Different seed yields a different ID
+    let id4 = Share::gen_share_id_with_seed(&sharer_id, "other", &description, &mappings);
+    assert_ne!(id1, id4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sheet_rename_and_delete_with_shares() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("sheet_rename_and_delete").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    let member_id: MemberId = "member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let source_sheet_name: SheetName = "source_sheet".to_string();
+    let target_sheet_name: SheetName = "target_sheet".to_string();
+    let other_sheet_name: SheetName = "other_sheet".to_string();
+    vault.create_sheet(&source_sheet_name, &member_id).await?;
+    vault.create_sheet(&target_sheet_name, &member_id).await?;
+    vault.create_sheet(&other_sheet_name, &member_id).await?;
+
+    // Share a mapping from the source sheet to the target sheet
+    let mut source_sheet = vault.sheet(&source_sheet_name).await?;
+    let asset_path = SheetPathBuf::from("assets/logo.png");
+    source_sheet
+        .add_mapping(
+            asset_path.clone(),
+            VirtualFileId::from("logo_id"),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    source_sheet.persist().await?;
+    vault
+        .sheet(&source_sheet_name)
+        .await?
+        .share_mappings(
+            &target_sheet_name,
+            vec![asset_path.clone()],
+            &member_id,
+            "Logo".to_string(),
+        )
+        .await?;
+
+    // Test 1: Rename the target sheet, its shares follow
+    let renamed_target: SheetName = "renamed_target".to_string();
+    vault
+        .rename_sheet(&target_sheet_name, &renamed_target)
+        .await?;
+    assert!(vault.sheet(&target_sheet_name).await.is_err());
+    let shares = vault.sheet(&renamed_target).await?.get_shares().await?;
+    assert_eq!(shares.len(), 1);
+    assert!(shares[0].mappings.contains_key(&asset_path));
+
+    // Test 2: Rename the source sheet, shares exported from it are updated
+    let renamed_source: SheetName = "renamed_source".to_string();
+    vault
+        .rename_sheet(&source_sheet_name, &renamed_source)
+        .await?;
+    let shares = vault.sheet(&renamed_target).await?.get_shares().await?;
+    assert_eq!(shares[0].from_sheet, renamed_source);
+    assert_eq!(
+        vault.sheet(&renamed_source).await?.mapping().len(),
+        1,
+        "Mappings are kept after renaming"
+    );
+
+    // Test 3: Collisions and the reserved reference sheet are rejected
+    let result = vault.rename_sheet(&renamed_source, &other_sheet_name).await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::AlreadyExists)
+    );
+    let result = vault
+        .rename_sheet(&other_sheet_name, &"Ref".to_string())
+        .await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::InvalidInput)
+    );
+    let result = vault
+        .rename_sheet(&"ref".to_string(), &"main".to_string())
+        .await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::InvalidInput)
+    );
+    let result = vault
+        .rename_sheet(&"missing_sheet".to_string(), &"main".to_string())
+        .await;
+    assert_eq!(
+        result.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::NotFound)
+    );
+
+    // Test 4: A rename that fails midway moves everything back
+    let snapshot = vault.snapshot_sheet(&renamed_target).await?;
+    let blocked: SheetName = "blocked_sheet".to_string();
+    let blocked_history = dir.join(SERVER_PATH_SHEET_HISTORY.replace("{sheet_name}", &blocked));
+    tokio::fs::create_dir_all(&blocked_history).await?;
+    tokio::fs::write(blocked_history.join("junk"), "junk").await?;
+    assert!(vault.rename_sheet(&renamed_target, &blocked).await.is_err());
+    assert!(vault.sheet_names()?.contains(&renamed_target));
+    assert!(!vault.sheet_names()?.contains(&blocked));
+    assert_eq!(vault.share_file_paths(&renamed_target).await.len(), 1);
+    assert!(vault.share_file_paths(&blocked).await.is_empty());
+    assert_eq!(vault.sheet_snapshots(&renamed_target)?, vec![snapshot]);
+
+    // Test 5: Deleting a sheet waits for its writers, and clears its shares and snapshots
+    let share_paths = vault.share_file_paths(&renamed_target).await;
+    assert_eq!(share_paths.len(), 1);
+    let sheet = vault.sheet_for_update(&renamed_target).await?;
+    let sheet_path = sheet.sheet_path();
+    let (deleted, _) = tokio::join!(vault.delete_sheet(&renamed_target), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(sheet_path.exists());
+        sheet.persist().await
+    });
+    deleted?;
+    assert!(!sheet_path.exists());
+    assert!(!share_paths[0].exists());
+    assert!(vault.share_file_paths(&renamed_target).await.is_empty());
+    assert!(vault.sheet_snapshots(&renamed_target)?.is_empty());
+    assert!(!vault.sheet_names()?.contains(&renamed_target));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_share_prefer_newer_version_mode() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("share_prefer_newer_version").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+
+    // Get vault
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    // Add members
+    let sharer_id: MemberId = "sharer_member".to_string();
+    let target_member_id: MemberId = "target_member".to_string();
+
+    vault
+        .register_member_to_vault(Member::new(&sharer_id))
+        .await?;
+    vault
+        .register_member_to_vault(Member::new(&target_member_id))
+        .await?;
+
+    // Create sheets
+    let source_sheet_name: SheetName = "source_sheet".to_string();
+    let target_sheet_name: SheetName = "target_sheet".to_string();
+    vault.create_sheet(&source_sheet_name, &sharer_id).await?;
+    vault
+        .create_sheet(&target_sheet_name, &target_member_id)
+        .await?;
+
+    // Virtual files with two versions each
+    let incoming_newer_id = VirtualFileId::from("incoming_newer_id");
+    let local_newer_id = VirtualFileId::from("local_newer_id");
+    let equal_id = VirtualFileId::from("equal_id");
+    let source_file_id = VirtualFileId::from("source_file_id");
+    let target_file_id = VirtualFileId::from("target_file_id");
+    let unknown_version_id = VirtualFileId::from("unknown_version_id");
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"2.0.0\"\nholder = \"\"\nhistories = [\"1.0.0\", \"2.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    for id in [
+        &incoming_newer_id,
+        &local_newer_id,
+        &equal_id,
+        &source_file_id,
+        &target_file_id,
+        &unknown_version_id,
+    ] {
+        vault.write_virtual_file_meta(id, &meta).await?;
+    }
+
+    // Conflicting mappings on both sides
+    let incoming_newer_path = SheetPathBuf::from("incoming_newer.txt");
+    let local_newer_path = SheetPathBuf::from("local_newer.txt");
+    let equal_path = SheetPathBuf::from("equal.txt");
+    let other_file_path = SheetPathBuf::from("other_file.txt");
+    let unknown_version_path = SheetPathBuf::from("unknown_version.txt");
+
+    let mut source_sheet = vault.sheet(&source_sheet_name).await?;
+    let mut target_sheet = vault.sheet(&target_sheet_name).await?;
+    for (path, source_id, target_id, source_version, target_version) in [
+        (
+            &incoming_newer_path,
+            &incoming_newer_id,
+            &incoming_newer_id,
+            "2.0.0",
+            "1.0.0",
+        ),
+        (
+            &local_newer_path,
+            &local_newer_id,
+            &local_newer_id,
+            "1.0.0",
+            "2.0.0",
+        ),
+        (&equal_path, &equal_id, &equal_id, "1.0.0", "1.0.0"),
+        // Different files, their version numbers are not comparable
+        (
+            &other_file_path,
+            &source_file_id,
+            &target_file_id,
+            "2.0.0",
+            "1.0.0",
+        ),
+        // A version the file does not know
+        (
+            &unknown_version_path,
+            &unknown_version_id,
+            &unknown_version_id,
+            "1.0.0",
+            "3.0.0",
+        ),
+    ] {
+        source_sheet
+            .add_mapping(path.clone(), source_id.clone(), source_version.to_string())
+            .await?;
+        target_sheet
+            .add_mapping(path.clone(), target_id.clone(), target_version.to_string())
+            .await?;
+    }
+    source_sheet.persist().await?;
+    target_sheet.persist().await?;
+
+    // Share all mappings
+    let source_sheet = vault.sheet(&source_sheet_name).await?;
+    source_sheet
+        .share_mappings(
+            &target_sheet_name,
+            vec![
+                incoming_newer_path.clone(),
+                local_newer_path.clone(),
+                equal_path.clone(),
+                other_file_path.clone(),
+                unknown_version_path.clone(),
+            ],
+            &sharer_id,
+            "Versioned share".to_string(),
+        )
+        .await?;
+
+    let target_sheet = vault.sheet(&target_sheet_name).await?;
+    let shares = target_sheet.get_shares().await?;
+    assert_eq!(shares.len(), 1);
+    let share = shares[0].clone();
+
+    // Merge, the tie and the conflicts without comparable versions are reported
+    let mut unresolved = target_sheet
+        .merge_share(share, ShareMergeMode::PreferNewerVersion)
+        .await?;
+    unresolved.duplicate_mapping.sort();
+    assert_eq!(
+        unresolved.duplicate_mapping,
+        vec![
+            equal_path.clone(),
+            other_file_path.clone(),
+            unknown_version_path.clone()
+        ]
+    );
+    assert!(unresolved.duplicate_file.is_empty());
+
+    // The newer version wins, ties keep the local mapping
+    let updated_target_sheet = vault.sheet(&target_sheet_name).await?;
+    let version_of = |path: &SheetPathBuf| {
+        updated_target_sheet
+            .mapping()
+            .get(path)
+            .map(|m| m.version.clone())
+    };
+    assert_eq!(version_of(&incoming_newer_path), Some("2.0.0".to_string()));
+    assert_eq!(version_of(&local_newer_path), Some("2.0.0".to_string()));
+    assert_eq!(version_of(&equal_path), Some("1.0.0".to_string()));
+    assert_eq!(version_of(&unknown_version_path), Some("3.0.0".to_string()));
+    assert_eq!(
+        updated_target_sheet.mapping()[&other_file_path].id,
+        target_file_id
+    );
+    assert!(updated_target_sheet.get_shares().await?.is_empty());
+
+    // Clean up
+    vault.remove_member_from_vault(&sharer_id)?;
+    vault.remove_member_from_vault(&target_member_id)?;
 
     Ok(())
 }
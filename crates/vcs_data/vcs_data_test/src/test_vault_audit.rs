@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            audit::{AuditFilter, AuditOperation},
+            config::VaultConfig,
+            virtual_file::VirtualFileVersionDescription,
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+struct AuditClientHandle;
+struct AuditServerHandle;
+
+impl ClientHandle<AuditServerHandle> for AuditClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("vault_audit_client").await.unwrap();
+
+        // Send the first version, then an update
+        for i in 0..2 {
+            let file_path = dir.join(format!("file_{}.txt", i));
+            tokio::fs::write(&file_path, format!("Version {}", i))
+                .await
+                .unwrap();
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<AuditClientHandle> for AuditServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("vault_audit").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "audit_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+
+        // Create and update a virtual file
+        let id = vault
+            .create_virtual_file_from_connection(&mut instance, &member_id)
+            .await
+            .unwrap();
+        vault
+            .update_virtual_file_from_connection(
+                &mut instance,
+                &member_id,
+                &id,
+                &"0.2.0".to_string(),
+                VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string()),
+            )
+            .await
+            .unwrap();
+
+        // The update is recorded
+        let updates = vault
+            .audit_entries(&AuditFilter {
+                operation: Some(AuditOperation::UpdateVirtualFile),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].member, member_id);
+        assert_eq!(updates[0].target, id);
+        assert!(updates[0].timestamp > 0);
+
+        // Entries of the file are kept in order
+        let entries = vault
+            .audit_entries(&AuditFilter {
+                target: Some(id.clone()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let operations: Vec<_> = entries.iter().map(|e| e.operation).collect();
+        assert_eq!(
+            operations,
+            vec![
+                AuditOperation::CreateVirtualFile,
+                AuditOperation::UpdateVirtualFile
+            ]
+        );
+
+        // Sheet writes are recorded as well
+        let sheet = vault.sheet(&"ref".to_string()).await.unwrap();
+        sheet.persist().await.unwrap();
+        let sheet_writes = vault
+            .audit_entries(&AuditFilter {
+                operation: Some(AuditOperation::PersistSheet),
+                target: Some("ref".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(sheet_writes.len(), 1);
+        assert_eq!(sheet_writes[0].member, "host");
+    }
+}
+
+#[tokio::test]
+async fn test_vault_audit() -> Result<(), std::io::Error> {
+    let host = "localhost:5043";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<AuditClientHandle, AuditServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<AuditClientHandle, AuditServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::{
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_vault_backup_round_trip() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("vault_backup").await?;
+    let restore_dir = get_test_dir("vault_backup_restore").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    let member_id = "backup_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    // A virtual file with an old, a pinned and a latest version
+    let id = VirtualFileId::from("vf_backup");
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"2.0.0\"\nholder = \"backup_member\"\nhistories = [\"0.5.0\", \"1.0.0\", \"2.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+    for version in ["0.5.0", "1.0.0", "2.0.0"] {
+        tokio::fs::write(
+            vault.virtual_file_real_path(&id, &version.to_string()),
+            format!("Content at {}", version),
+        )
+        .await?;
+    }
+
+    // A sheet pinning the middle version
+    let sheet_name = "backup_sheet".to_string();
+    let mut sheet = vault.create_sheet(&sheet_name, &member_id).await?;
+    sheet
+        .add_mapping(PathBuf::from("file.txt"), id.clone(), "1.0.0".to_string())
+        .await?;
+    sheet.persist().await?;
+
+    // Back up and restore
+    let mut archive = Vec::new();
+    vault.export_backup(&mut archive).await?;
+    Vault::import_backup(archive.as_slice(), &restore_dir).await?;
+
+    let Some(restored) = Vault::init(
+        VaultConfig::read_from(restore_dir.join(SERVER_FILE_VAULT)).await?,
+        &restore_dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Restored vault not found!"));
+    };
+
+    // Sheets and members survive
+    assert_eq!(restored.config().vault_uuid(), vault.config().vault_uuid());
+    assert!(restored.member_ids()?.contains(&member_id));
+    let restored_sheet = restored.sheet(&sheet_name).await?;
+    assert_eq!(
+        restored_sheet.mapping().get(&PathBuf::from("file.txt")),
+        vault
+            .sheet(&sheet_name)
+            .await?
+            .mapping()
+            .get(&PathBuf::from("file.txt"))
+    );
+
+    // The virtual file survives with its pinned and latest versions
+    let restored_meta = restored.virtual_file_meta(&id).await?;
+    assert_eq!(restored_meta.versions(), meta.versions());
+    for version in ["1.0.0", "2.0.0"] {
+        let content =
+            tokio::fs::read_to_string(restored.virtual_file_real_path(&id, &version.to_string()))
+                .await?;
+        assert_eq!(content, format!("Content at {}", version));
+    }
+    assert!(
+        !restored
+            .virtual_file_real_path(&id, &"0.5.0".to_string())
+            .exists()
+    );
+
+    // Restoring needs an empty directory and a valid archive
+    let result = Vault::import_backup(archive.as_slice(), &restore_dir).await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::DirectoryNotEmpty);
+    let garbage_dir = get_test_dir("vault_backup_garbage").await?;
+    let result = Vault::import_backup(&b"not a backup at all"[..], &garbage_dir).await;
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+
+    Ok(())
+}
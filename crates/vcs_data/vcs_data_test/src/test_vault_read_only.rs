@@ -0,0 +1,74 @@
+use std::io::{Error, ErrorKind};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_vault_read_only() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("vault_read_only").await?;
+
+    // Setup vault with a member and a sheet
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    let member_id = "read_only_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+    let sheet_name = "main".to_string();
+    vault.create_sheet(&sheet_name, &member_id).await?;
+    assert!(!vault.is_read_only());
+
+    // Open the same vault read-only
+    let Some(readonly) = Vault::init_readonly(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    assert!(readonly.is_read_only());
+
+    // Reads work
+    assert!(readonly.member_ids()?.contains(&member_id));
+    let sheet = readonly.sheet(&sheet_name).await?;
+    assert_eq!(sheet.holder(), Some(&member_id));
+
+    // Writes are rejected
+    let denied = |result: Result<(), Error>| {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+    };
+    denied(sheet.persist().await);
+    denied(
+        readonly
+            .register_member_to_vault(Member::new("other_member"))
+            .await,
+    );
+    denied(
+        readonly
+            .create_sheet(&"other".to_string(), &member_id)
+            .await
+            .map(|_| ()),
+    );
+    denied(readonly.delete_sheet(&sheet_name).await);
+    denied(readonly.lock());
+
+    // Nothing changed on disk
+    assert!(!vault.member_ids()?.contains(&"other_member".to_string()));
+    assert!(vault.sheet(&"other".to_string()).await.is_err());
+    assert!(vault.sheet(&sheet_name).await.is_ok());
+    assert!(!vault.is_locked());
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind},
+    pin::pin,
+};
+
+use cfg_file::config::ConfigFile;
+use futures::StreamExt;
+use vcs_data::{
+    constants::{REF_SHEET_NAME, SERVER_FILE_VAULT},
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+const SHEET_COUNT: usize = 100;
+
+#[tokio::test]
+async fn test_vault_sheets_stream() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("vault_sheets_stream").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    let member_id = "stream_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let mut expected = HashSet::from([REF_SHEET_NAME.to_string()]);
+    for i in 0..SHEET_COUNT {
+        let sheet_name = format!("sheet_{}", i);
+        vault.create_sheet(&sheet_name, &member_id).await?;
+        expected.insert(sheet_name);
+    }
+
+    // Sheets are read one at a time, only their names are kept here
+    let mut seen = HashSet::new();
+    let mut sheets = pin!(vault.sheets_stream()?);
+    while let Some(sheet) = sheets.next().await {
+        let sheet = sheet?;
+        assert!(seen.insert(sheet.name().clone()));
+    }
+    assert_eq!(seen, expected);
+
+    // Members stream the same way
+    let mut members = pin!(vault.members_stream()?);
+    let mut member_ids = Vec::new();
+    while let Some(member) = members.next().await {
+        member_ids.push(member.id());
+    }
+    assert!(member_ids.contains(&member_id));
+    assert_eq!(member_ids.len(), vault.member_ids()?.len());
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileVersionDescription},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+/// Open a connection pair on the listener, returns (server side, client side)
+async fn connection_pair(listener: &TcpListener) -> (ConnectionInstance, ConnectionInstance) {
+    let addr = listener.local_addr().unwrap();
+    let (client, accepted) = join!(TcpStream::connect(addr), listener.accept());
+    (
+        ConnectionInstance::from(accepted.unwrap().0),
+        ConnectionInstance::from(client.unwrap()),
+    )
+}
+
+/// Stage a new version of every file and commit, the sabotage runs right before the commit
+async fn commit_batch(
+    vault: &Vault,
+    listener: &TcpListener,
+    ids: &[VirtualFileId],
+    file: &Path,
+    sabotage: impl AsyncFnOnce(&Path) -> Result<(), std::io::Error>,
+) -> Result<Result<(), std::io::Error>, std::io::Error> {
+    let member_id = "test_member".to_string();
+    let new_version = "0.2.0".to_string();
+    let mut transaction = vault.transaction(&"main".to_string()).await?;
+    for id in ids {
+        let (mut server, mut client) = connection_pair(listener).await;
+        let (received, sent) = join!(
+            transaction.receive_version(
+                &mut server,
+                &member_id,
+                id,
+                &new_version,
+                VirtualFileVersionDescription::new(member_id.clone(), "Batch".to_string()),
+                false,
+            ),
+            client.write_file(file)
+        );
+        received?;
+        sent.map_err(std::io::Error::other)?;
+    }
+    sabotage(&transaction.sheet().sheet_path()).await?;
+    Ok(transaction.commit().await)
+}
+
+#[tokio::test]
+async fn test_vault_transaction_rollback() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("vault_transaction_rollback").await?;
+    let listener = TcpListener::bind("localhost:5072").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        panic!("No vault found!");
+    };
+    let member_id = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+    vault.create_sheet(&"main".to_string(), &member_id).await?;
+
+    let files = get_test_dir("vault_transaction_rollback_client").await?;
+    let file = files.join("file.txt");
+    tokio::fs::write(&file, "First").await?;
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let (mut server, mut client) = connection_pair(&listener).await;
+        let (id, sent) = join!(
+            vault.create_virtual_file_from_connection(&mut server, &member_id),
+            client.write_file(&file)
+        );
+        sent.map_err(std::io::Error::other)?;
+        ids.push(id?);
+    }
+    tokio::fs::write(&file, "Second").await?;
+
+    let assert_untouched = async || -> Result<(), std::io::Error> {
+        for id in ids.iter() {
+            let meta = vault.virtual_file_meta(id).await?;
+            assert_eq!(meta.versions(), &vec!["0.1.0".to_string()]);
+            assert_eq!(meta.current_version(), "0.1.0");
+            assert!(
+                !vault
+                    .virtual_file_real_path(id, &"0.2.0".to_string())
+                    .exists()
+            );
+        }
+        Ok(())
+    };
+
+    // The metadata of the second file cannot be read, the first one is already written
+    let second_meta = vault.virtual_file_meta_path(&ids[1]);
+    let result = commit_batch(&vault, &listener, &ids, &file, async |_| {
+        tokio::fs::rename(&second_meta, second_meta.with_extension("bak")).await?;
+        tokio::fs::create_dir(&second_meta).await
+    })
+    .await?;
+    assert!(result.is_err());
+    tokio::fs::remove_dir(&second_meta).await?;
+    tokio::fs::rename(second_meta.with_extension("bak"), &second_meta).await?;
+    assert_untouched().await?;
+
+    // The sheet cannot be persisted once every metadata is written
+    let sheet_backup = dir.join("main.bak");
+    let result = commit_batch(&vault, &listener, &ids, &file, async |sheet_path| {
+        tokio::fs::rename(sheet_path, &sheet_backup).await?;
+        tokio::fs::create_dir(sheet_path).await
+    })
+    .await?;
+    assert!(result.is_err());
+    assert_untouched().await?;
+
+    Ok(())
+}
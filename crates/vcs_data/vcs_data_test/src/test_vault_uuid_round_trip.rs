@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use tcp_connection::instance::{ConnectionInstance, MessageFormat};
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use uuid::Uuid;
+use vcs_data::data::vault::config::VaultUuid;
+
+// Fixed so that both sides know the expected value
+const VAULT_UUID: &str = "6f9a1c2e-3b4d-4e5f-8a7b-9c0d1e2f3a4b";
+
+struct UuidClientHandle;
+struct UuidServerHandle;
+
+impl ClientHandle<UuidServerHandle> for UuidClientHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        let expected: VaultUuid = Uuid::parse_str(VAULT_UUID).unwrap();
+
+        // Default format (MessagePack), as `set_upstream_vault_action` does
+        assert_eq!(instance.config().message_format, MessageFormat::MsgPack);
+        let vault_uuid = instance.read::<VaultUuid>().await.unwrap();
+        assert_eq!(vault_uuid, expected);
+
+        // JSON format for debugging
+        instance.config_mut().message_format = MessageFormat::Json;
+        let vault_uuid = instance.read::<VaultUuid>().await.unwrap();
+        assert_eq!(vault_uuid, expected);
+    }
+}
+
+impl ServerHandle<UuidClientHandle> for UuidServerHandle {
+    async fn process(mut instance: ConnectionInstance) {
+        let vault_uuid: VaultUuid = Uuid::parse_str(VAULT_UUID).unwrap();
+
+        instance.write(vault_uuid).await.unwrap();
+
+        instance.config_mut().message_format = MessageFormat::Json;
+        instance.write(vault_uuid).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_vault_uuid_round_trip() -> Result<(), std::io::Error> {
+    let host = "localhost:5019";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<UuidClientHandle, UuidServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<UuidClientHandle, UuidServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(10);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
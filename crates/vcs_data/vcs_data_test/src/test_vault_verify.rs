@@ -0,0 +1,99 @@
+use std::{
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_vault_verify() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("vault_verify").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+    let member_id = "verify_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    // A virtual file with two versions
+    let id = VirtualFileId::from("vf_verify");
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.1\"\nholder = \"\"\nhistories = [\"1.0.0\", \"1.0.1\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+    for version in ["1.0.0", "1.0.1"] {
+        tokio::fs::write(
+            vault.virtual_file_real_path(&id, &version.to_string()),
+            version,
+        )
+        .await?;
+    }
+
+    // A sheet mapping the virtual file
+    let mut sheet = vault
+        .create_sheet(&"verify_sheet".to_string(), &member_id)
+        .await?;
+    sheet
+        .add_mapping(PathBuf::from("file.txt"), id.clone(), "1.0.1".to_string())
+        .await?;
+    sheet.persist().await?;
+
+    assert!(vault.verify().await?.is_clean());
+
+    // Delete a version instance and tamper with the storage and sheet
+    let missing = vault.virtual_file_real_path(&id, &"1.0.0".to_string());
+    tokio::fs::remove_file(&missing).await?;
+    let stray = vault.virtual_file_real_path(&id, &"9.9.9".to_string());
+    tokio::fs::write(&stray, "stray").await?;
+    let mut sheet = vault.sheet_for_update(&"verify_sheet".to_string()).await?;
+    sheet
+        .add_mapping(
+            PathBuf::from("gone.txt"),
+            "vf_gone".to_string(),
+            "1.0.0".to_string(),
+        )
+        .await?;
+    sheet.persist().await?;
+
+    let report = vault.verify().await?;
+    assert!(!report.is_clean());
+    assert_eq!(
+        report.missing_instances,
+        vec![(id.clone(), "1.0.0".to_string())]
+    );
+    assert_eq!(report.orphaned_storage, vec![stray]);
+    assert_eq!(
+        report.dangling_mappings,
+        vec![(
+            "verify_sheet".to_string(),
+            PathBuf::from("gone.txt"),
+            "vf_gone".to_string()
+        )]
+    );
+    assert!(report.id_mapping_mismatches.is_empty());
+    assert!(report.inconsistent_meta.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+const FILE_COUNT: usize = 3;
+
+struct ActiveHoldsClientHandle;
+struct ActiveHoldsServerHandle;
+
+impl ClientHandle<ActiveHoldsServerHandle> for ActiveHoldsClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_active_holds_client")
+            .await
+            .unwrap();
+
+        // Send files for virtual file creation
+        for i in 0..FILE_COUNT {
+            let file_path = dir.join(format!("file_{}.txt", i));
+            tokio::fs::write(&file_path, format!("Content of file {}", i))
+                .await
+                .unwrap();
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<ActiveHoldsClientHandle> for ActiveHoldsServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_active_holds").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        // Register members
+        let member_a = "member_a".to_string();
+        let member_b = "member_b".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_a))
+            .await
+            .unwrap();
+        vault
+            .register_member_to_vault(Member::new(&member_b))
+            .await
+            .unwrap();
+
+        // Create virtual files, held by the creator by default
+        let mut ids = Vec::new();
+        for _ in 0..FILE_COUNT {
+            let id = vault
+                .create_virtual_file_from_connection(&mut instance, &member_a)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        // File 0: held by member_a
+        // File 1: held by member_b
+        // File 2: not held
+        vault
+            .grant_virtual_file_edit_right(&member_b, &ids[1])
+            .await
+            .unwrap();
+        vault.revoke_virtual_file_edit_right(&ids[2]).await.unwrap();
+
+        let holds = vault.active_holds().await.unwrap();
+
+        let mut expected = vec![
+            (ids[0].clone(), member_a.clone(), None),
+            (ids[1].clone(), member_b.clone(), None),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(holds, expected);
+        assert!(!holds.iter().any(|(id, _, _)| id == &ids[2]));
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_active_holds() -> Result<(), std::io::Error> {
+    let host = "localhost:5016";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<ActiveHoldsClientHandle, ActiveHoldsServerHandle>::from_domain(host)
+            .await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<ActiveHoldsClientHandle, ActiveHoldsServerHandle>::from_domain(host)
+            .await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::io::{Error, ErrorKind};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            audit::{AuditFilter, AuditOperation},
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+async fn setup(area: &str) -> Result<(Vault, VirtualFileId), std::io::Error> {
+    let dir = get_test_dir(area).await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    for id in ["alice", "bob", "carol"] {
+        vault.register_member_to_vault(Member::new(id)).await?;
+    }
+
+    // A version created by alice, the file is now held by bob
+    let id = VirtualFileId::from("vf_amend");
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.0\"\nholder = \"bob\"\nhistories = [\"1.0.0\"]\n\
+         [descs.\"1.0.0\"]\ncreator = \"alice\"\ndesc = \"Fix teh typo\"\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+
+    Ok((vault, id))
+}
+
+#[tokio::test]
+async fn test_amend_version_description() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("amend_version_description").await?;
+    let version = "1.0.0".to_string();
+
+    // The creator may amend
+    vault
+        .amend_version_description(&id, &version, "Fix the typo".to_string(), &"alice".into())
+        .await?;
+    let meta = vault.virtual_file_meta(&id).await?;
+    let desc = meta.version_description(version.clone()).unwrap();
+    assert_eq!(desc.description, "Fix the typo");
+    assert_eq!(desc.creator, "alice");
+
+    // So may the holder, without becoming the creator
+    vault
+        .amend_version_description(&id, &version, "Fix typos".to_string(), &"bob".into())
+        .await?;
+    let meta = vault.virtual_file_meta(&id).await?;
+    let desc = meta.version_description(version.clone()).unwrap();
+    assert_eq!(desc.description, "Fix typos");
+    assert_eq!(desc.creator, "alice");
+
+    let entries = vault
+        .audit_entries(&AuditFilter {
+            operation: Some(AuditOperation::AmendVersionDescription),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(entries.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_amend_version_description_rejected() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("amend_version_description_rejected").await?;
+    let version = "1.0.0".to_string();
+
+    // Carol neither created the version nor holds the file
+    let err = vault
+        .amend_version_description(&id, &version, "Mine now".to_string(), &"carol".into())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    let meta = vault.virtual_file_meta(&id).await?;
+    let desc = meta.version_description(version).unwrap();
+    assert_eq!(desc.description, "Fix teh typo");
+    assert_eq!(desc.creator, "alice");
+
+    // Unknown versions are reported as such
+    let err = vault
+        .amend_version_description(&id, &"2.0.0".to_string(), "?".to_string(), &"alice".into())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_amend_version_description_unheld() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("amend_version_description_unheld").await?;
+    let version = "1.0.0".to_string();
+
+    // Release the file, the holder is empty
+    let meta_source = vault.vault_path().join("unheld_meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.0\"\nholder = \"\"\nhistories = [\"1.0.0\"]\n\
+         [descs.\"1.0.0\"]\ncreator = \"alice\"\ndesc = \"Fix teh typo\"\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+
+    // An empty member does not pass as the holder
+    let err = vault
+        .amend_version_description(&id, &version, "Nobody's".to_string(), &"".into())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    let meta = vault.virtual_file_meta(&id).await?;
+    let desc = meta.version_description(version.clone()).unwrap();
+    assert_eq!(desc.description, "Fix teh typo");
+
+    // The creator still may amend
+    vault
+        .amend_version_description(&id, &version, "Fix the typo".to_string(), &"alice".into())
+        .await?;
+    let meta = vault.virtual_file_meta(&id).await?;
+    let desc = meta.version_description(version).unwrap();
+    assert_eq!(desc.description, "Fix the typo");
+
+    Ok(())
+}
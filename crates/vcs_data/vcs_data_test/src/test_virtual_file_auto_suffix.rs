@@ -0,0 +1,130 @@
+use std::io::ErrorKind;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileVersionDescription},
+    },
+};
+
+use crate::get_test_dir;
+
+/// Open a connection pair on the listener, returns (server side, client side)
+async fn connection_pair(listener: &TcpListener) -> (ConnectionInstance, ConnectionInstance) {
+    let addr = listener.local_addr().unwrap();
+    let (client, accepted) = join!(TcpStream::connect(addr), listener.accept());
+    (
+        ConnectionInstance::from(accepted.unwrap().0),
+        ConnectionInstance::from(client.unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_virtual_file_auto_suffix() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_auto_suffix").await?;
+    let listener = TcpListener::bind("localhost:5063").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        panic!("No vault found!");
+    };
+    let member_id = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let files = get_test_dir("virtual_file_auto_suffix_client").await?;
+    let paths = ["first", "second", "third", "fourth"].map(|name| {
+        let path = files.join(format!("{}.txt", name));
+        std::fs::write(&path, name).unwrap();
+        path
+    });
+
+    // Create the virtual file and its 0.2.0 version
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (id, sent) = join!(
+        vault.create_virtual_file_from_connection(&mut server, &member_id),
+        client.write_file(&paths[0])
+    );
+    let id = id?;
+    sent.unwrap();
+
+    let new_version = "0.2.0".to_string();
+    let description = VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string());
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (updated, sent) = join!(
+        vault.update_virtual_file_from_connection_auto_suffix(
+            &mut server,
+            &member_id,
+            &id,
+            &new_version,
+            description.clone(),
+        ),
+        client.write_file(&paths[1])
+    );
+    sent.unwrap();
+    assert_eq!(updated?, "0.2.0");
+
+    // Colliding uploads are stored under derived names
+    for (path, expected) in [(&paths[2], "0.2.0_2"), (&paths[3], "0.2.0_3")] {
+        let (mut server, mut client) = connection_pair(&listener).await;
+        let (updated, sent) = join!(
+            vault.update_virtual_file_from_connection_auto_suffix(
+                &mut server,
+                &member_id,
+                &id,
+                &new_version,
+                description.clone(),
+            ),
+            client.write_file(path)
+        );
+        sent.unwrap();
+        let used = updated?;
+        assert_eq!(used, expected);
+
+        let content = tokio::fs::read(vault.virtual_file_real_path(&id, &used)).await?;
+        assert_eq!(content, tokio::fs::read(path).await?);
+    }
+
+    // The original version is untouched
+    let content = tokio::fs::read(vault.virtual_file_real_path(&id, &new_version)).await?;
+    assert_eq!(content, b"second");
+
+    let meta = vault.virtual_file_meta(&id).await?;
+    assert_eq!(
+        meta.versions(),
+        &vec![
+            "0.1.0".to_string(),
+            "0.2.0".to_string(),
+            "0.2.0_2".to_string(),
+            "0.2.0_3".to_string()
+        ]
+    );
+    assert_eq!(meta.version_latest(), "0.2.0_3");
+
+    // The strict default still rejects the collision, before anything is received
+    let (mut server, _client) = connection_pair(&listener).await;
+    let strict = vault
+        .update_virtual_file_from_connection(
+            &mut server,
+            &member_id,
+            &id,
+            &new_version,
+            description.clone(),
+        )
+        .await;
+    assert_eq!(strict.unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::io::{Error, ErrorKind};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::{Member, MemberRole},
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_virtual_file_batch_edit_rights() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_batch_edit_rights").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    let writer = "writer_member".to_string();
+    let other_writer = "other_writer_member".to_string();
+    let reader = "reader_member".to_string();
+    vault.register_member_to_vault(Member::new(&writer)).await?;
+    vault
+        .register_member_to_vault(Member::new(&other_writer))
+        .await?;
+    vault
+        .register_member_to_vault(Member::with_role(&reader, MemberRole::Reader))
+        .await?;
+
+    // A directory of unheld assets
+    let ids: Vec<VirtualFileId> = (0..20).map(|i| format!("vf_asset_{:02}", i)).collect();
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.0\"\nholder = \"\"\nhistories = [\"1.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    for id in &ids {
+        vault.write_virtual_file_meta(id, &meta).await?;
+    }
+
+    // Grant all of them, results keep the input order
+    let results = vault.grant_edit_rights(&writer, &ids).await;
+    assert_eq!(results.len(), ids.len());
+    for ((id, result), expected) in results.iter().zip(&ids) {
+        assert_eq!(id, expected);
+        assert!(result.is_ok());
+        assert_eq!(vault.virtual_file_meta(id).await?.hold_member(), &writer);
+    }
+
+    // Partial failure: a missing file is reported, the others still succeed
+    let mut with_missing = ids[..3].to_vec();
+    with_missing.insert(1, "vf_missing".to_string());
+    let results = vault.grant_edit_rights(&other_writer, &with_missing).await;
+    let failed: Vec<&VirtualFileId> = results
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(failed, vec!["vf_missing"]);
+    for id in &ids[..3] {
+        assert_eq!(
+            vault.virtual_file_meta(id).await?.hold_member(),
+            &other_writer
+        );
+    }
+
+    // Readers are rejected for every file
+    let results = vault.grant_edit_rights(&reader, &ids[3..6]).await;
+    assert!(results.iter().all(|(_, result)| {
+        result
+            .as_ref()
+            .is_err_and(|e| e.kind() == ErrorKind::PermissionDenied)
+    }));
+
+    // Revoke: only the files held by the member are released
+    let results = vault.revoke_edit_rights(&writer, &ids[..6]).await;
+    for (i, (id, result)) in results.iter().enumerate() {
+        let holder = vault.virtual_file_meta(id).await?.hold_member().clone();
+        if i < 3 {
+            assert!(result.is_err());
+            assert_eq!(holder, other_writer);
+        } else {
+            assert!(result.is_ok());
+            assert!(holder.is_empty());
+        }
+    }
+
+    Ok(())
+}
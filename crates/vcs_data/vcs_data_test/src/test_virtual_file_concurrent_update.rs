@@ -0,0 +1,117 @@
+use std::{io::ErrorKind, time::Duration};
+
+use cfg_file::config::ConfigFile;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileVersionDescription},
+    },
+};
+
+use crate::get_test_dir;
+
+/// Open a connection pair on the listener, returns (server side, client side)
+async fn connection_pair(listener: &TcpListener) -> (ConnectionInstance, ConnectionInstance) {
+    let addr = listener.local_addr().unwrap();
+    let (client, accepted) = join!(TcpStream::connect(addr), listener.accept());
+    (
+        ConnectionInstance::from(accepted.unwrap().0),
+        ConnectionInstance::from(client.unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_virtual_file_concurrent_update() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_concurrent_update").await?;
+    let listener = TcpListener::bind("localhost:5059").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        panic!("No vault found!");
+    };
+    let member_id = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let files = get_test_dir("virtual_file_concurrent_update_client").await?;
+    let first_path = files.join("first.txt");
+    let a_path = files.join("a.txt");
+    let b_path = files.join("b.txt");
+    tokio::fs::write(&first_path, "First").await?;
+    tokio::fs::write(&a_path, "Upload A").await?;
+    tokio::fs::write(&b_path, "Upload B").await?;
+
+    // Create the virtual file
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (id, sent) = join!(
+        vault.create_virtual_file_from_connection(&mut server, &member_id),
+        client.write_file(&first_path)
+    );
+    let id = id?;
+    sent.unwrap();
+
+    // Two uploads of the same new version, both pass the version check before receiving
+    let (mut server_a, mut client_a) = connection_pair(&listener).await;
+    let (mut server_b, mut client_b) = connection_pair(&listener).await;
+    let new_version = "0.2.0".to_string();
+    let description = VirtualFileVersionDescription::new(member_id.clone(), "Race".to_string());
+
+    let race = async {
+        join!(
+            vault.update_virtual_file_from_connection(
+                &mut server_a,
+                &member_id,
+                &id,
+                &new_version,
+                description.clone(),
+            ),
+            vault.update_virtual_file_from_connection(
+                &mut server_b,
+                &member_id,
+                &id,
+                &new_version,
+                description.clone(),
+            ),
+            async {
+                sleep(Duration::from_millis(200)).await;
+                client_a.write_file(&a_path).await.unwrap();
+            },
+            async {
+                sleep(Duration::from_millis(200)).await;
+                client_b.write_file(&b_path).await.unwrap();
+            },
+        )
+    };
+    let (result_a, result_b, _, _) = timeout(Duration::from_secs(10), race)
+        .await
+        .map_err(|_| std::io::Error::new(ErrorKind::TimedOut, "Race timed out"))?;
+
+    // Exactly one upload wins, the other gets a conflict
+    let (winner, conflict) = match (result_a, result_b) {
+        (Ok(_), Err(e)) => ("Upload A", e),
+        (Err(e), Ok(_)) => ("Upload B", e),
+        other => panic!("Expected exactly one conflict, got {:?}", other),
+    };
+    assert_eq!(conflict.kind(), ErrorKind::AlreadyExists);
+
+    let content =
+        tokio::fs::read_to_string(vault.virtual_file_real_path(&id, &new_version)).await?;
+    assert_eq!(content, winner);
+
+    let meta = vault.virtual_file_meta(&id).await?;
+    assert_eq!(meta.versions(), &vec!["0.1.0".to_string(), new_version]);
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig},
+    },
+};
+
+use crate::get_test_dir;
+
+struct CreateOnceClientHandle;
+struct CreateOnceServerHandle;
+
+impl ClientHandle<CreateOnceServerHandle> for CreateOnceClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_create_once_client")
+            .await
+            .unwrap();
+
+        let file_path = dir.join("file.txt");
+        tokio::fs::write(&file_path, "Tracked content")
+            .await
+            .unwrap();
+
+        // The first create is retried, then another file is created
+        for _ in 0..3 {
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<CreateOnceClientHandle> for CreateOnceServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_create_once").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "test_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+
+        let first = vault
+            .create_virtual_file_from_connection_once(&mut instance, &member_id, "file.txt:hash")
+            .await
+            .unwrap();
+        let retried = vault
+            .create_virtual_file_from_connection_once(&mut instance, &member_id, "file.txt:hash")
+            .await
+            .unwrap();
+        assert_eq!(first, retried);
+        assert_eq!(vault.virtual_file_ids().unwrap(), vec![first.clone()]);
+
+        // Another token creates another virtual file
+        let other = vault
+            .create_virtual_file_from_connection_once(&mut instance, &member_id, "other.txt:hash")
+            .await
+            .unwrap();
+        assert_ne!(first, other);
+        assert_eq!(vault.virtual_file_ids().unwrap().len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_create_once() -> Result<(), std::io::Error> {
+    let host = "localhost:5058";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<CreateOnceClientHandle, CreateOnceServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<CreateOnceClientHandle, CreateOnceServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
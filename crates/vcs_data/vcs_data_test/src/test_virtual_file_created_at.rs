@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileMeta, VirtualFileVersionDescription},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+const VERSIONS: [&str; 3] = ["0.1.0", "0.2.0", "0.3.0"];
+
+struct CreatedAtClientHandle;
+struct CreatedAtServerHandle;
+
+impl ClientHandle<CreatedAtServerHandle> for CreatedAtClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_created_at_client")
+            .await
+            .unwrap();
+
+        for version in VERSIONS {
+            let file_path = dir.join(format!("file_{}.txt", version));
+            tokio::fs::write(&file_path, format!("File at {}", version))
+                .await
+                .unwrap();
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<CreatedAtClientHandle> for CreatedAtServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_created_at").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "test_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+
+        // Create a virtual file and update it twice
+        let id = vault
+            .create_virtual_file_from_connection(&mut instance, &member_id)
+            .await
+            .unwrap();
+        for version in VERSIONS.iter().skip(1) {
+            sleep(Duration::from_millis(20)).await;
+            vault
+                .update_virtual_file_from_connection(
+                    &mut instance,
+                    &member_id,
+                    &id,
+                    &version.to_string(),
+                    VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string()),
+                )
+                .await
+                .unwrap();
+        }
+
+        let meta = vault.virtual_file_meta(&id).await.unwrap();
+        let times: Vec<_> = VERSIONS
+            .iter()
+            .map(|version| meta.version_created_at(&version.to_string()).unwrap())
+            .collect();
+        assert!(times[0] < times[1]);
+        assert!(times[1] < times[2]);
+        assert!(meta.version_created_at(&"9.9.9".to_string()).is_none());
+
+        // Metadata written before timestamps were recorded still loads
+        let legacy_path = dir.join("legacy_meta.toml");
+        tokio::fs::write(
+            &legacy_path,
+            "ver = \"1.0.0\"\nholder = \"test_member\"\nhistories = [\"1.0.0\"]\n[descs]\n",
+        )
+        .await
+        .unwrap();
+        let legacy = VirtualFileMeta::read_from(&legacy_path).await.unwrap();
+        assert!(legacy.version_created_at(&"1.0.0".to_string()).is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_created_at() -> Result<(), std::io::Error> {
+    let host = "localhost:5057";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<CreatedAtClientHandle, CreatedAtServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<CreatedAtClientHandle, CreatedAtServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
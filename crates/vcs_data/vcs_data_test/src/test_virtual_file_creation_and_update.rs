@@ -97,10 +97,7 @@ impl ServerHandle<VirtualFileCreateClientHandle> for VirtualFileCreateServerHand
                 &member_id.to_string(),
                 &virtual_file_id,
                 &"2".to_string(),
-                VirtualFileVersionDescription {
-                    creator: member_id.to_string(),
-                    description: "Update".to_string(),
-                },
+                VirtualFileVersionDescription::new(member_id.to_string(), "Update".to_string()),
             )
             .await
             .unwrap();
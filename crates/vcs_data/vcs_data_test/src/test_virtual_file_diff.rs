@@ -0,0 +1,78 @@
+use std::io::Error;
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::vault::{
+        Vault,
+        config::VaultConfig,
+        virtual_file::{VIRTUAL_FILE_DIFF_CHUNK_SIZE, VirtualFileId, VirtualFileMeta},
+    },
+};
+
+use crate::get_test_dir;
+
+#[tokio::test]
+async fn test_virtual_file_version_diff() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_diff").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    // A virtual file with two versions
+    let id = VirtualFileId::from("vf_diff");
+    let from = "1.0.0".to_string();
+    let to = "2.0.0".to_string();
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"2.0.0\"\nholder = \"\"\nhistories = [\"1.0.0\", \"2.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+
+    // Four chunks, the second version edits bytes inside the third chunk
+    let chunk = VIRTUAL_FILE_DIFF_CHUNK_SIZE;
+    let original: Vec<u8> = (0..chunk * 4).map(|i| (i % 251) as u8).collect();
+    let mut edited = original.clone();
+    let edit_start = chunk * 2 + 10;
+    for byte in &mut edited[edit_start..edit_start + 5] {
+        *byte = byte.wrapping_add(1);
+    }
+    tokio::fs::write(vault.virtual_file_real_path(&id, &from), &original).await?;
+    tokio::fs::write(vault.virtual_file_real_path(&id, &to), &edited).await?;
+
+    // Only the edited chunk is reported
+    let diff = vault.virtual_file_version_diff(&id, &from, &to).await?;
+    assert_eq!(diff.chunk_size, chunk);
+    assert_eq!(diff.changed_chunks, vec![2]);
+    assert!(diff.changed_ranges.is_none());
+
+    // The byte range is the edited region
+    let diff = vault
+        .virtual_file_version_diff_with_ranges(&id, &from, &to)
+        .await?;
+    assert_eq!(diff.changed_chunks, vec![2]);
+    let ranges = diff.changed_ranges.unwrap_or_default();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0], edit_start as u64..(edit_start + 5) as u64);
+
+    // A version compared with itself has no difference
+    let diff = vault.virtual_file_version_diff(&id, &from, &from).await?;
+    assert!(diff.is_empty());
+
+    // Unknown versions are rejected
+    assert!(
+        vault
+            .virtual_file_version_diff(&id, &from, &"3.0.0".to_string())
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::{path::PathBuf, time::Duration};
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileId},
+    },
+};
+
+use crate::get_test_dir;
+
+const FILE_COUNT: usize = 2;
+
+struct GcClientHandle;
+struct GcServerHandle;
+
+impl ClientHandle<GcServerHandle> for GcClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_gc_client").await.unwrap();
+
+        for i in 0..FILE_COUNT {
+            let file_path = dir.join(format!("file_{}.txt", i));
+            tokio::fs::write(&file_path, format!("File {}", i))
+                .await
+                .unwrap();
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<GcClientHandle> for GcServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_gc").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "test_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+        let sheet_name = "main".to_string();
+        vault.create_sheet(&sheet_name, &member_id).await.unwrap();
+
+        // Create virtual files and map them into the sheet
+        let mut ids: Vec<VirtualFileId> = Vec::new();
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        for i in 0..FILE_COUNT {
+            let id = vault
+                .create_virtual_file_from_connection(&mut instance, &member_id)
+                .await
+                .unwrap();
+            sheet
+                .add_mapping(
+                    PathBuf::from(format!("file_{}.txt", i)),
+                    id.clone(),
+                    "0.1.0".to_string(),
+                )
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        sheet.persist().await.unwrap();
+
+        // Mapped files are neither deleted nor collected
+        assert!(vault.delete_virtual_file(&ids[0]).await.is_err());
+        let report = vault.gc_with_grace(Duration::ZERO).await.unwrap();
+        assert!(report.freed.is_empty());
+        assert!(report.skipped.is_empty());
+
+        // Unmap the first file
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        sheet.mapping_mut().remove(&PathBuf::from("file_0.txt"));
+        sheet.persist().await.unwrap();
+
+        // Recently created files are kept by the default grace period
+        let report = vault.gc().await.unwrap();
+        assert!(report.freed.is_empty());
+        assert_eq!(report.skipped, vec![ids[0].clone()]);
+        assert!(vault.virtual_file(&ids[0]).is_ok());
+
+        // Orphaned file is collected
+        let report = vault.gc_with_grace(Duration::ZERO).await.unwrap();
+        assert_eq!(report.freed, vec![ids[0].clone()]);
+        assert!(report.skipped.is_empty());
+        assert!(vault.virtual_file(&ids[0]).is_err());
+        assert!(!vault.virtual_file_meta_path(&ids[0]).exists());
+        assert!(vault.virtual_file(&ids[1]).is_ok());
+
+        // Unmapped files can be deleted directly
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        sheet.mapping_mut().remove(&PathBuf::from("file_1.txt"));
+        sheet.persist().await.unwrap();
+        vault.delete_virtual_file(&ids[1]).await.unwrap();
+        assert!(vault.virtual_file(&ids[1]).is_err());
+        assert!(vault.delete_virtual_file(&ids[1]).await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_gc() -> Result<(), std::io::Error> {
+    let host = "localhost:5040";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<GcClientHandle, GcServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<GcClientHandle, GcServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::{io::Error, path::Path};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::vault::{
+        Vault,
+        config::VaultConfig,
+        virtual_file::{VirtualFileId, VirtualFileMeta},
+    },
+};
+
+use crate::get_test_dir;
+
+/// Get the two index levels between the storage dir and the virtual file dir
+fn index_of(vault: &Vault, dir: &Path) -> Vec<String> {
+    dir.strip_prefix(vault.virtual_file_storage_dir())
+        .unwrap()
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .take(2)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_virtual_file_index() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_index").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(std::io::ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    // UUID ids are indexed by their first characters
+    let uuid_id = VirtualFileId::from("vf-1a2b3c4d-0000-4000-8000-000000000000");
+    let uuid_dir = vault.virtual_file_dir(&uuid_id);
+    assert_eq!(index_of(&vault, &uuid_dir), vec!["1a", "2b"]);
+    assert!(uuid_dir.ends_with(&uuid_id));
+
+    // Non-UUID ids keep the layout of their first characters
+    for (id, expected) in [("vf-legacyfile", ["le", "ga"]), ("legacyid", ["le", "ga"])] {
+        let id = VirtualFileId::from(id);
+        assert_eq!(index_of(&vault, &vault.virtual_file_dir(&id)), expected);
+    }
+
+    // Short ids, which used to be rejected, map to a stable hashed directory
+    for id in ["vf-ab", "x"] {
+        let id = VirtualFileId::from(id);
+        let first = vault.virtual_file_dir(&id);
+        assert_eq!(first, vault.virtual_file_dir(&id));
+        assert!(first.ends_with(&id));
+
+        let index = index_of(&vault, &first);
+        assert_eq!(index.len(), 2);
+        for level in index {
+            assert_eq!(level.len(), 2);
+            assert!(level.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
+
+    // A virtual file with a short id can be stored and found
+    let short_id = VirtualFileId::from("vf-ab");
+    assert!(vault.virtual_file(&short_id).is_err());
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.0\"\nholder = \"\"\nhistories = [\"1.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&short_id, &meta).await?;
+    assert!(vault.virtual_file(&short_id).is_ok());
+    assert!(vault.virtual_file_ids()?.contains(&short_id));
+
+    // A non-UUID id stored in the old layout is still found
+    let legacy_id = VirtualFileId::from("vf-legacyfile");
+    let legacy_meta = dir.join("storage/le/ga/vf-legacyfile/meta.vf");
+    tokio::fs::create_dir_all(legacy_meta.parent().unwrap()).await?;
+    VirtualFileMeta::write_to(&meta, &legacy_meta).await?;
+    assert!(vault.virtual_file(&legacy_id).is_ok());
+    assert_eq!(
+        vault.virtual_file_meta(&legacy_id).await?.version_latest(),
+        "1.0.0"
+    );
+
+    Ok(())
+}
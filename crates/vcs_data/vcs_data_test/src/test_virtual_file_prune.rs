@@ -0,0 +1,190 @@
+use std::{path::PathBuf, time::Duration};
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileVersionDescription},
+    },
+};
+
+use crate::get_test_dir;
+
+const VERSIONS: [&str; 4] = ["0.1.0", "0.2.0", "0.3.0", "0.4.0"];
+
+struct PruneClientHandle;
+struct PruneServerHandle;
+
+impl ClientHandle<PruneServerHandle> for PruneClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_prune_client").await.unwrap();
+
+        for version in VERSIONS {
+            let file_path = dir.join(format!("file_{}.txt", version));
+            tokio::fs::write(&file_path, format!("File at {}", version))
+                .await
+                .unwrap();
+            instance.write_file(&file_path).await.unwrap();
+        }
+    }
+}
+
+impl ServerHandle<PruneClientHandle> for PruneServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_prune").await.unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "test_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+        let sheet_name = "main".to_string();
+        vault.create_sheet(&sheet_name, &member_id).await.unwrap();
+
+        // Create a virtual file with four versions
+        let id = vault
+            .create_virtual_file_from_connection(&mut instance, &member_id)
+            .await
+            .unwrap();
+        for version in VERSIONS.iter().skip(1) {
+            vault
+                .update_virtual_file_from_connection(
+                    &mut instance,
+                    &member_id,
+                    &id,
+                    &version.to_string(),
+                    VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string()),
+                )
+                .await
+                .unwrap();
+        }
+
+        // The sheet still pins the first version
+        let mapping_path = PathBuf::from("file.txt");
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        sheet
+            .add_mapping(mapping_path.clone(), id.clone(), VERSIONS[0].to_string())
+            .await
+            .unwrap();
+        sheet.persist().await.unwrap();
+
+        let removed = vault.prune_virtual_file_versions(&id, 2).await.unwrap();
+        assert_eq!(removed, vec![VERSIONS[1].to_string()]);
+
+        let meta = vault.virtual_file_meta(&id).await.unwrap();
+        assert_eq!(
+            meta.versions(),
+            &vec![
+                VERSIONS[0].to_string(),
+                VERSIONS[2].to_string(),
+                VERSIONS[3].to_string()
+            ]
+        );
+        assert_eq!(meta.version_latest(), VERSIONS[3].to_string());
+        assert!(meta.version_description(VERSIONS[1].to_string()).is_none());
+        assert!(meta.version_hash(&VERSIONS[1].to_string()).is_none());
+        assert!(
+            !vault
+                .virtual_file_real_path(&id, &VERSIONS[1].to_string())
+                .exists()
+        );
+        for version in meta.versions() {
+            assert!(vault.virtual_file_version_path(&id, version).await.is_ok());
+        }
+
+        // Once unpinned, the first version is pruned as well
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        sheet
+            .add_mapping(mapping_path, id.clone(), VERSIONS[3].to_string())
+            .await
+            .unwrap();
+        sheet.persist().await.unwrap();
+
+        let removed = vault.prune_virtual_file_versions(&id, 2).await.unwrap();
+        assert_eq!(removed, vec![VERSIONS[0].to_string()]);
+
+        let meta = vault.virtual_file_meta(&id).await.unwrap();
+        assert_eq!(
+            meta.versions(),
+            &vec![VERSIONS[2].to_string(), VERSIONS[3].to_string()]
+        );
+        assert!(
+            !vault
+                .virtual_file_real_path(&id, &VERSIONS[0].to_string())
+                .exists()
+        );
+
+        // Nothing more to prune
+        let removed = vault.prune_virtual_file_versions(&id, 2).await.unwrap();
+        assert!(removed.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_prune() -> Result<(), std::io::Error> {
+    let host = "localhost:5041";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<PruneClientHandle, PruneServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<PruneClientHandle, PruneServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
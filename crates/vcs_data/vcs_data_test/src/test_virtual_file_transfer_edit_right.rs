@@ -0,0 +1,147 @@
+use std::io::{Error, ErrorKind};
+
+use cfg_file::config::ConfigFile;
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::{Member, MemberRole},
+        vault::{
+            Vault,
+            audit::{AuditFilter, AuditOperation},
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileMeta},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+async fn setup(area: &str) -> Result<(Vault, VirtualFileId), std::io::Error> {
+    let dir = get_test_dir(area).await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    let Some(vault) = Vault::init(config, &dir) else {
+        return Err(Error::new(ErrorKind::NotFound, "Vault not found!"));
+    };
+
+    for (id, role) in [
+        ("alice", MemberRole::Writer),
+        ("bob", MemberRole::Writer),
+        ("carol", MemberRole::Writer),
+        ("reader", MemberRole::Reader),
+    ] {
+        vault
+            .register_member_to_vault(Member::with_role(id, role))
+            .await?;
+    }
+
+    // A virtual file held by alice
+    let id = VirtualFileId::from("vf_transfer");
+    let meta_source = dir.join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.0\"\nholder = \"alice\"\nhistories = [\"1.0.0\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+    vault.write_virtual_file_meta(&id, &meta).await?;
+
+    Ok((vault, id))
+}
+
+#[tokio::test]
+async fn test_transfer_edit_right() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("transfer_edit_right").await?;
+    let alice = "alice".to_string();
+    let bob = "bob".to_string();
+
+    vault.transfer_edit_right(&alice, &bob, &id).await?;
+    assert_eq!(vault.virtual_file_meta(&id).await?.hold_member(), &bob);
+    assert!(vault.has_virtual_file_edit_right(&bob, &id).await?);
+    assert!(!vault.has_virtual_file_edit_right(&alice, &id).await?);
+
+    // The hand-off is recorded once, on behalf of the previous holder
+    let entries = vault
+        .audit_entries(&AuditFilter {
+            operation: Some(AuditOperation::TransferEditRight),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].member, alice);
+    assert_eq!(entries[0].target, id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_edit_right_rejected() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("transfer_edit_right_rejected").await?;
+    let alice = "alice".to_string();
+    let bob = "bob".to_string();
+    let carol = "carol".to_string();
+
+    // Bob does not hold the file
+    let err = vault
+        .transfer_edit_right(&bob, &carol, &id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    assert_eq!(vault.virtual_file_meta(&id).await?.hold_member(), &alice);
+
+    // Readers cannot receive the file
+    let err = vault
+        .transfer_edit_right(&alice, &"reader".to_string(), &id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    assert_eq!(vault.virtual_file_meta(&id).await?.hold_member(), &alice);
+
+    // An unheld file cannot be transferred
+    vault.revoke_virtual_file_edit_right(&id).await?;
+    let err = vault
+        .transfer_edit_right(&alice, &bob, &id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    assert!(vault.virtual_file_meta(&id).await?.hold_member().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_edit_right_during_prune() -> Result<(), std::io::Error> {
+    let (vault, id) = setup("transfer_edit_right_during_prune").await?;
+    let alice = "alice".to_string();
+    let bob = "bob".to_string();
+
+    let meta_source = get_test_dir("transfer_edit_right_during_prune_meta")
+        .await?
+        .join("meta.toml");
+    tokio::fs::write(
+        &meta_source,
+        "ver = \"1.0.2\"\nholder = \"alice\"\nhistories = [\"1.0.0\", \"1.0.1\", \"1.0.2\"]\n[descs]\n",
+    )
+    .await?;
+    let meta = VirtualFileMeta::read_from(&meta_source).await?;
+
+    for _ in 0..8 {
+        vault.write_virtual_file_meta(&id, &meta).await?;
+
+        // The prune must not write back the holder it read before the transfer
+        let (pruned, transferred) = tokio::join!(
+            vault.prune_virtual_file_versions(&id, 1),
+            vault.transfer_edit_right(&alice, &bob, &id)
+        );
+        assert_eq!(pruned?.len(), 2);
+        transferred?;
+
+        let meta = vault.virtual_file_meta(&id).await?;
+        assert_eq!(meta.hold_member(), &bob);
+        assert_eq!(meta.versions(), &vec!["1.0.2".to_string()]);
+    }
+
+    Ok(())
+}
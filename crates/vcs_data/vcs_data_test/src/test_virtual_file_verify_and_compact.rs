@@ -0,0 +1,224 @@
+use std::{path::PathBuf, time::Duration};
+
+use cfg_file::config::ConfigFile;
+use tcp_connection_test::{
+    handle::{ClientHandle, ServerHandle},
+    target::TcpServerTarget,
+    target_configure::ServerTargetConfig,
+};
+use tokio::{
+    join,
+    time::{sleep, timeout},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{
+            Vault,
+            config::VaultConfig,
+            virtual_file::{VirtualFileId, VirtualFileVersionDescription},
+        },
+    },
+};
+
+use crate::get_test_dir;
+
+// Three files, three versions each
+const FILE_COUNT: usize = 3;
+const VERSIONS: [&str; 3] = ["0.1.0", "0.2.0", "0.3.0"];
+
+struct CompactClientHandle;
+struct CompactServerHandle;
+
+impl ClientHandle<CompactServerHandle> for CompactClientHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_verify_and_compact_client")
+            .await
+            .unwrap();
+
+        for i in 0..FILE_COUNT {
+            for version in VERSIONS {
+                let file_path = dir.join(format!("file_{}_{}.txt", i, version));
+                tokio::fs::write(&file_path, format!("File {} at {}", i, version))
+                    .await
+                    .unwrap();
+                instance.write_file(&file_path).await.unwrap();
+            }
+        }
+    }
+}
+
+impl ServerHandle<CompactClientHandle> for CompactServerHandle {
+    async fn process(mut instance: tcp_connection::instance::ConnectionInstance) {
+        let dir = get_test_dir("virtual_file_verify_and_compact")
+            .await
+            .unwrap();
+
+        // Setup vault
+        Vault::setup_vault(dir.clone(), "TestVault").await.unwrap();
+        let Some(vault) = Vault::init(
+            VaultConfig::read_from(dir.join(SERVER_FILE_VAULT))
+                .await
+                .unwrap(),
+            &dir,
+        ) else {
+            panic!("No vault found!");
+        };
+
+        let member_id = "test_member".to_string();
+        vault
+            .register_member_to_vault(Member::new(&member_id))
+            .await
+            .unwrap();
+
+        // Create virtual files with their versions
+        let mut ids: Vec<VirtualFileId> = Vec::new();
+        for _ in 0..FILE_COUNT {
+            let id = vault
+                .create_virtual_file_from_connection(&mut instance, &member_id)
+                .await
+                .unwrap();
+            for version in VERSIONS.iter().skip(1) {
+                vault
+                    .update_virtual_file_from_connection(
+                        &mut instance,
+                        &member_id,
+                        &id,
+                        &version.to_string(),
+                        VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string()),
+                    )
+                    .await
+                    .unwrap();
+            }
+            ids.push(id);
+        }
+
+        // Hashes are recorded on receive
+        let meta = vault.virtual_file_meta(&ids[0]).await.unwrap();
+        for version in VERSIONS {
+            assert!(meta.version_hash(&version.to_string()).is_some());
+        }
+
+        // Corrupt the oldest version of the first file
+        let corrupted_path = vault.virtual_file_real_path(&ids[0], &VERSIONS[0].to_string());
+        tokio::fs::write(&corrupted_path, "Corrupted content")
+            .await
+            .unwrap();
+
+        let report = vault.verify_and_compact_file(&ids[0], 1).await.unwrap();
+        assert!(report.compaction_skipped());
+        assert_eq!(report.corrupted, vec![VERSIONS[0].to_string()]);
+        assert_eq!(report.verified.len(), 2);
+        assert!(report.compacted.is_empty());
+
+        // Nothing removed
+        let meta = vault.virtual_file_meta(&ids[0]).await.unwrap();
+        assert_eq!(meta.version_len(), VERSIONS.len() as i32);
+        for version in VERSIONS {
+            assert!(
+                vault
+                    .virtual_file_real_path(&ids[0], &version.to_string())
+                    .exists()
+            );
+        }
+
+        // The intact file is compacted down to the last version
+        let report = vault.verify_and_compact_file(&ids[1], 1).await.unwrap();
+        assert!(!report.compaction_skipped());
+        assert_eq!(report.verified.len(), VERSIONS.len());
+        assert_eq!(
+            report.compacted,
+            vec![VERSIONS[0].to_string(), VERSIONS[1].to_string()]
+        );
+
+        let meta = vault.virtual_file_meta(&ids[1]).await.unwrap();
+        assert_eq!(meta.versions(), &vec![VERSIONS[2].to_string()]);
+        assert!(
+            !vault
+                .virtual_file_real_path(&ids[1], &VERSIONS[0].to_string())
+                .exists()
+        );
+        assert!(
+            vault
+                .virtual_file_real_path(&ids[1], &VERSIONS[2].to_string())
+                .exists()
+        );
+
+        // Versions still mapped by a sheet survive the compaction
+        let sheet_name = "main".to_string();
+        vault.create_sheet(&sheet_name, &member_id).await.unwrap();
+        let mut sheet = vault.sheet_for_update(&sheet_name).await.unwrap();
+        sheet
+            .add_mapping(
+                PathBuf::from("file.txt"),
+                ids[2].clone(),
+                VERSIONS[0].to_string(),
+            )
+            .await
+            .unwrap();
+        sheet.persist().await.unwrap();
+
+        let report = vault.verify_and_compact_file(&ids[2], 1).await.unwrap();
+        assert_eq!(report.compacted, vec![VERSIONS[1].to_string()]);
+        let meta = vault.virtual_file_meta(&ids[2]).await.unwrap();
+        assert_eq!(
+            meta.versions(),
+            &vec![VERSIONS[0].to_string(), VERSIONS[2].to_string()]
+        );
+        assert!(
+            vault
+                .virtual_file_version_path(&ids[2], &VERSIONS[0].to_string())
+                .await
+                .is_ok()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_virtual_file_verify_and_compact() -> Result<(), std::io::Error> {
+    let host = "localhost:5018";
+
+    // Server setup
+    let Ok(server_target) =
+        TcpServerTarget::<CompactClientHandle, CompactServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    // Client setup
+    let Ok(client_target) =
+        TcpServerTarget::<CompactClientHandle, CompactServerHandle>::from_domain(host).await
+    else {
+        panic!("Test target built failed from a domain named `{}`", host);
+    };
+
+    let future_server = async move {
+        // Only process once
+        let configured_server = server_target.server_cfg(ServerTargetConfig::default().once());
+
+        // Listen here
+        let _ = configured_server.listen().await;
+    };
+
+    let future_client = async move {
+        // Wait for server start
+        let _ = sleep(Duration::from_secs_f32(1.5)).await;
+
+        // Connect here
+        let _ = client_target.connect().await;
+    };
+
+    let test_timeout = Duration::from_secs(15);
+
+    timeout(test_timeout, async { join!(future_client, future_server) })
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Test timed out after {:?}", test_timeout),
+            )
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::io::ErrorKind;
+
+use cfg_file::config::ConfigFile;
+use tcp_connection::instance::ConnectionInstance;
+use tokio::{
+    join,
+    net::{TcpListener, TcpStream},
+};
+use vcs_data::{
+    constants::SERVER_FILE_VAULT,
+    data::{
+        member::Member,
+        vault::{Vault, config::VaultConfig, virtual_file::VirtualFileVersionDescription},
+    },
+};
+
+use crate::get_test_dir;
+
+/// Open a connection pair on the listener, returns (server side, client side)
+async fn connection_pair(listener: &TcpListener) -> (ConnectionInstance, ConnectionInstance) {
+    let addr = listener.local_addr().unwrap();
+    let (client, accepted) = join!(TcpStream::connect(addr), listener.accept());
+    (
+        ConnectionInstance::from(accepted.unwrap().0),
+        ConnectionInstance::from(client.unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_virtual_file_version_names() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_version_names").await?;
+    let listener = TcpListener::bind("localhost:5066").await?;
+
+    // Setup vault
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let Some(vault) = Vault::init(
+        VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?,
+        &dir,
+    ) else {
+        panic!("No vault found!");
+    };
+    let member_id = "test_member".to_string();
+    vault
+        .register_member_to_vault(Member::new(&member_id))
+        .await?;
+
+    let files = get_test_dir("virtual_file_version_names_client").await?;
+    let paths = ["first", "second", "third"].map(|name| {
+        let path = files.join(format!("{}.txt", name));
+        std::fs::write(&path, name).unwrap();
+        path
+    });
+
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (id, sent) = join!(
+        vault.create_virtual_file_from_connection(&mut server, &member_id),
+        client.write_file(&paths[0])
+    );
+    let id = id?;
+    sent.unwrap();
+
+    let description = VirtualFileVersionDescription::new(member_id.clone(), "Update".to_string());
+
+    // Names differing only in separators are the same version
+    assert_eq!(
+        vault.normalize_version_name("1-0-0"),
+        vault.normalize_version_name("1.0.0")
+    );
+    let version = "1.0.0".to_string();
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (updated, sent) = join!(
+        vault.update_virtual_file_from_connection(
+            &mut server,
+            &member_id,
+            &id,
+            &version,
+            description.clone(),
+        ),
+        client.write_file(&paths[1])
+    );
+    sent.unwrap();
+    updated?;
+
+    let (mut server, _client) = connection_pair(&listener).await;
+    let duplicate = vault
+        .update_virtual_file_from_connection(
+            &mut server,
+            &member_id,
+            &id,
+            &"1-0-0".to_string(),
+            description.clone(),
+        )
+        .await;
+    assert_eq!(duplicate.unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    // A name changed by normalizing keeps the original for display
+    let typed = "Release 2.0".to_string();
+    let stored = vault.normalize_version_name(&typed);
+    assert_ne!(stored, typed);
+    let (mut server, mut client) = connection_pair(&listener).await;
+    let (updated, sent) = join!(
+        vault.update_virtual_file_from_connection(
+            &mut server,
+            &member_id,
+            &id,
+            &typed,
+            description.clone(),
+        ),
+        client.write_file(&paths[2])
+    );
+    sent.unwrap();
+    updated?;
+
+    let meta = vault.virtual_file_meta(&id).await?;
+    assert_eq!(meta.version_latest(), stored);
+    assert_eq!(meta.version_display_name(&stored), Some(typed.clone()));
+    let desc = meta.version_description(stored.clone()).unwrap();
+    assert_eq!(desc.display_name.as_deref(), Some(typed.as_str()));
+
+    // Unchanged names store no display name
+    assert_eq!(meta.version_display_name(&version), Some(version.clone()));
+    assert!(
+        meta.version_description(version)
+            .unwrap()
+            .display_name
+            .is_none()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_virtual_file_version_names_unnormalized() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("virtual_file_version_names_unnormalized").await?;
+
+    Vault::setup_vault(dir.clone(), "TestVault").await?;
+    let mut config = VaultConfig::read_from(dir.join(SERVER_FILE_VAULT)).await?;
+    config.set_normalize_version_names(Some(false));
+    let Some(vault) = Vault::init(config, &dir) else {
+        panic!("No vault found!");
+    };
+
+    // Only surrounding whitespace is removed
+    assert_eq!(vault.normalize_version_name(" Release 2.0 "), "Release 2.0");
+    assert_ne!(
+        vault.normalize_version_name("1-0-0"),
+        vault.normalize_version_name("1.0.0")
+    );
+
+    Ok(())
+}
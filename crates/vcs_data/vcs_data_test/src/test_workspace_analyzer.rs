@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use vcs_data::data::local::workspace_analyzer::{FuzzyMoveCandidate, fuzzy_match_moved};
+
+#[test]
+fn test_fuzzy_match_detects_rename_with_edit() {
+    let lost = vec![
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Assets/hero_sword.png"),
+            size: 10_000,
+        },
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Audio/theme.wav"),
+            size: 2_000_000,
+        },
+    ];
+    let created = vec![
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Assets/Weapons/hero_sword_v2.png"),
+            size: 10_240,
+        },
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Audio/Music/theme.wav"),
+            size: 2_050_000,
+        },
+    ];
+
+    let mut pairs = fuzzy_match_moved(&lost, &created, 0.8);
+    pairs.sort();
+    assert_eq!(
+        pairs,
+        vec![
+            (
+                PathBuf::from("Assets/hero_sword.png"),
+                PathBuf::from("Assets/Weapons/hero_sword_v2.png"),
+            ),
+            (
+                PathBuf::from("Audio/theme.wav"),
+                PathBuf::from("Audio/Music/theme.wav"),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_fuzzy_match_keeps_unrelated_files_apart() {
+    let lost = vec![
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Models/tree.fbx"),
+            size: 5_000,
+        },
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Audio/explosion.wav"),
+            size: 1_000_000,
+        },
+    ];
+    let created = vec![
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Docs/tree.md"),
+            size: 5_000,
+        },
+        FuzzyMoveCandidate {
+            path: PathBuf::from("Audio/footstep.wav"),
+            size: 20_000,
+        },
+    ];
+
+    assert!(fuzzy_match_moved(&lost, &created, 0.8).is_empty());
+}
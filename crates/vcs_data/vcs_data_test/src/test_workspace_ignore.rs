@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use vcs_data::data::local::{
+    LocalWorkspace, ignore::IgnoreRules, workspace_analyzer::scan_local_files,
+};
+
+use crate::get_test_dir;
+
+#[test]
+fn test_ignore_rules_matching() -> Result<(), std::io::Error> {
+    let rules = IgnoreRules::parse(
+        "# Build output\n\
+         target/\n\
+         *.log\n\
+         \n\
+         /docs/*.tmp\n\
+         assets/**/cache\n\
+         !keep.log\n",
+    )?;
+
+    // Directory rules only match directories, at any depth
+    assert!(rules.is_ignored(Path::new("target"), true));
+    assert!(rules.is_ignored(Path::new("sub/target"), true));
+    assert!(!rules.is_ignored(Path::new("target"), false));
+
+    // Name rules match at any depth, later negations win
+    assert!(rules.is_ignored(Path::new("debug.log"), false));
+    assert!(rules.is_ignored(Path::new("a/b/debug.log"), false));
+    assert!(!rules.is_ignored(Path::new("a/keep.log"), false));
+
+    // Path rules are anchored to the workspace root
+    assert!(rules.is_ignored(Path::new("docs/draft.tmp"), false));
+    assert!(!rules.is_ignored(Path::new("other/docs/draft.tmp"), false));
+    assert!(!rules.is_ignored(Path::new("docs/nested/draft.tmp"), false));
+    assert!(rules.is_ignored(Path::new("assets/cache"), true));
+    assert!(rules.is_ignored(Path::new("assets/models/cache"), true));
+
+    assert!(!rules.is_ignored(Path::new("src/main.rs"), false));
+    assert!(IgnoreRules::parse("src/[").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_local_files_with_jvignore() -> Result<(), std::io::Error> {
+    let dir = get_test_dir("workspace_ignore").await?;
+    LocalWorkspace::setup_local_workspace(dir.clone()).await?;
+
+    for file in [
+        "src/main.rs",
+        "build/output.bin",
+        "build/nested/cache.bin",
+        "build/tracked.bin",
+        "notes.log",
+    ] {
+        let path = dir.join(file);
+        tokio::fs::create_dir_all(path.parent().unwrap()).await?;
+        tokio::fs::write(&path, file).await?;
+    }
+    tokio::fs::write(dir.join(".jvignore"), "build/\n*.log\n").await?;
+
+    // Without a mapping, nothing below the ignored directory is scanned
+    let rules = IgnoreRules::read_from_workspace(&dir).await?;
+    let scanned = scan_local_files(&dir, &rules, &[]);
+    assert!(scanned.contains(&PathBuf::from("src/main.rs")));
+    assert!(scanned.iter().all(|path| !path.starts_with("build")));
+    assert!(!scanned.contains(&PathBuf::from("notes.log")));
+    assert!(!scanned.contains(&PathBuf::from(".jvignore")));
+
+    // Already mapped files stay visible, so they are never reported lost
+    let tracked = PathBuf::from("build/tracked.bin");
+    let scanned = scan_local_files(&dir, &rules, &[&tracked]);
+    assert!(scanned.contains(&tracked));
+    assert!(!scanned.contains(&PathBuf::from("build/output.bin")));
+    assert!(!scanned.contains(&PathBuf::from("build/nested/cache.bin")));
+
+    // Without an ignore file every file is scanned
+    tokio::fs::remove_file(dir.join(".jvignore")).await?;
+    let rules = IgnoreRules::read_from_workspace(&dir).await?;
+    assert!(rules.is_empty());
+    let scanned = scan_local_files(&dir, &rules, &[]);
+    assert!(scanned.contains(&PathBuf::from("build/nested/cache.bin")));
+    assert!(scanned.contains(&PathBuf::from("notes.log")));
+
+    Ok(())
+}